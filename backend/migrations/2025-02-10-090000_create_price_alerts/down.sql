@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS price_alerts;
+ALTER TABLE users DROP COLUMN price_alert_threshold;
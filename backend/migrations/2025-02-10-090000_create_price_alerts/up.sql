@@ -0,0 +1,19 @@
+-- Per-user threshold (in percent) above which a price change raises an alert.
+ALTER TABLE users ADD COLUMN price_alert_threshold INTEGER NOT NULL DEFAULT 20;
+
+-- Alerts recorded when a new product price deviates from the rolling median.
+CREATE TABLE price_alerts (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    product_price_id INTEGER NOT NULL,
+    median_price INTEGER NOT NULL,          -- rolling median before the change (cents)
+    new_price INTEGER NOT NULL,             -- the price that triggered the alert (cents)
+    change_percent DOUBLE PRECISION NOT NULL,
+    dismissed BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products (id),
+    FOREIGN KEY (product_price_id) REFERENCES product_prices (id),
+    UNIQUE (product_price_id)
+);
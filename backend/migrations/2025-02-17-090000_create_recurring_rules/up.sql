@@ -0,0 +1,16 @@
+-- Rules describing transactions that repeat on a fixed cadence (subscriptions, rent, salary...).
+CREATE TABLE recurring_rules (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    description TEXT,
+    amount INTEGER NOT NULL,                -- store as an integer (cents)
+    transaction_type TEXT NOT NULL,         -- "income" or "expense"
+    cadence TEXT NOT NULL,                  -- "weekly", "monthly" or "yearly"
+    next_due_date DATE NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products (id),
+    UNIQUE (user_id, product_id, cadence)
+);
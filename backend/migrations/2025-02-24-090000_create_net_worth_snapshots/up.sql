@@ -0,0 +1,11 @@
+-- Manually entered values of assets (house, savings account...) and liabilities (mortgage, loans...).
+CREATE TABLE net_worth_snapshots (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,                     -- "asset" or "liability"
+    value BIGINT NOT NULL,                  -- store as an integer (cents), always positive
+    snapshot_date DATE NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    UNIQUE (user_id, name, snapshot_date)
+);
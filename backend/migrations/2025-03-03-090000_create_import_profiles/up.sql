@@ -0,0 +1,21 @@
+-- Saved column mappings for importing CSV bank statements.
+CREATE TABLE import_profiles (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    delimiter TEXT NOT NULL DEFAULT ',',
+    has_header BOOLEAN NOT NULL DEFAULT TRUE,
+    skip_rows INTEGER NOT NULL DEFAULT 0,   -- preamble lines before the header
+    date_column TEXT NOT NULL,              -- header name or zero-based column index
+    date_format TEXT NOT NULL DEFAULT '%Y-%m-%d',
+    decimal_separator TEXT NOT NULL DEFAULT '.',
+    amount_column TEXT NOT NULL,
+    sign_column TEXT,                       -- optional debit/credit indicator column
+    income_indicator TEXT,                  -- value of sign_column that marks income
+    invert_sign BOOLEAN NOT NULL DEFAULT FALSE,
+    description_column TEXT,
+    product_column TEXT,
+    tags_column TEXT,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    UNIQUE (user_id, name)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS transactions_user_external_id;
+ALTER TABLE transactions DROP COLUMN IF EXISTS external_id;
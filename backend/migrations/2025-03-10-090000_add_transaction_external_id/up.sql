@@ -0,0 +1,7 @@
+-- Identifier of the transaction in the bank's own export (e.g. OFX FITID),
+-- so re-importing a statement never creates the same transaction twice.
+ALTER TABLE transactions ADD COLUMN external_id TEXT;
+
+CREATE UNIQUE INDEX transactions_user_external_id
+    ON transactions (user_id, external_id)
+    WHERE external_id IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Bank statement details kept from imports: the account the money moved on
+-- and the value date (the transaction date itself is the booking date).
+ALTER TABLE transactions ADD COLUMN account TEXT;
+ALTER TABLE transactions ADD COLUMN value_date DATE;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS duplicate_reviews;
+DROP INDEX IF EXISTS transactions_user_import_hash;
+ALTER TABLE transactions DROP COLUMN IF EXISTS import_hash;
+ALTER TABLE transactions
+    ADD CONSTRAINT transactions_user_id_product_id_date_transaction_type_key
+    UNIQUE (user_id, product_id, date, transaction_type);
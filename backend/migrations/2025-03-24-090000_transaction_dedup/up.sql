@@ -0,0 +1,28 @@
+-- Same-day purchases of the same product are legitimate; duplicates are
+-- found by external id / import hash and the fuzzy duplicate finder instead.
+ALTER TABLE transactions
+    DROP CONSTRAINT transactions_user_id_product_id_date_transaction_type_key;
+
+-- Hash of an imported statement line without a bank id (account, date,
+-- amount, text and its occurrence in the file), so re-imports are idempotent.
+ALTER TABLE transactions ADD COLUMN import_hash TEXT;
+
+CREATE UNIQUE INDEX transactions_user_import_hash
+    ON transactions (user_id, import_hash)
+    WHERE import_hash IS NOT NULL;
+
+-- Decisions on duplicate candidates. A merged duplicate is deleted; its
+-- external id and import hash are kept here so re-imports still skip it.
+CREATE TABLE duplicate_reviews (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    transaction_id INTEGER NOT NULL,        -- the transaction that was kept
+    duplicate_id INTEGER NOT NULL,          -- no FK: deleted when merged
+    action TEXT NOT NULL,                   -- "merged" or "dismissed"
+    external_id TEXT,
+    import_hash TEXT,
+    reviewed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (transaction_id) REFERENCES transactions (id) ON DELETE CASCADE,
+    UNIQUE (transaction_id, duplicate_id)
+);
@@ -0,0 +1,30 @@
+-- User-defined rules that categorize, tag and describe transactions when they
+-- are created or imported. Every condition that is set must match.
+CREATE TABLE rules (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,    -- lower runs first
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    description_pattern TEXT,               -- regex on the description
+    product_name TEXT,                      -- exact product name, any case
+    merchant TEXT,                          -- text in the product name or description
+    account TEXT,                           -- bank account, any case
+    min_amount INTEGER,                     -- cents, inclusive
+    max_amount INTEGER,                     -- cents, inclusive
+    set_category_id INTEGER,                -- applied to the transaction's product
+    set_description TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (set_category_id) REFERENCES categories (id) ON DELETE SET NULL,
+    UNIQUE (user_id, name)
+);
+
+-- Tags a rule adds.
+CREATE TABLE rule_tags (
+    rule_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (rule_id, tag_id),
+    FOREIGN KEY (rule_id) REFERENCES rules (id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags (id) ON DELETE CASCADE
+);
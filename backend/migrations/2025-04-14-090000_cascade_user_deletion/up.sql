@@ -0,0 +1,29 @@
+-- Deleting a user removes everything they own. The newer tables already
+-- cascade; these are the original ones.
+ALTER TABLE categories
+    DROP CONSTRAINT categories_user_id_fkey,
+    ADD CONSTRAINT categories_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+
+ALTER TABLE products
+    DROP CONSTRAINT products_user_id_fkey,
+    ADD CONSTRAINT products_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+
+ALTER TABLE product_prices
+    DROP CONSTRAINT product_prices_product_id_fkey,
+    ADD CONSTRAINT product_prices_product_id_fkey
+        FOREIGN KEY (product_id) REFERENCES products (id) ON DELETE CASCADE;
+
+ALTER TABLE transactions
+    DROP CONSTRAINT transactions_user_id_fkey,
+    ADD CONSTRAINT transactions_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+
+ALTER TABLE transaction_tags
+    DROP CONSTRAINT transaction_tags_transaction_id_fkey,
+    ADD CONSTRAINT transaction_tags_transaction_id_fkey
+        FOREIGN KEY (transaction_id) REFERENCES transactions (id) ON DELETE CASCADE,
+    DROP CONSTRAINT transaction_tags_tag_id_fkey,
+    ADD CONSTRAINT transaction_tags_tag_id_fkey
+        FOREIGN KEY (tag_id) REFERENCES tags (id) ON DELETE CASCADE;
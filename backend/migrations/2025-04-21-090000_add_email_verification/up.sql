@@ -0,0 +1,26 @@
+ALTER TABLE users ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Accounts from before verification existed keep every feature.
+UPDATE users SET email_verified = TRUE;
+
+-- Sign-up and login now trim and lowercase addresses. Bring existing ones in
+-- line unless that would make two accounts collide.
+UPDATE users u
+SET email = lower(btrim(u.email))
+WHERE u.email <> lower(btrim(u.email))
+  AND NOT EXISTS (
+      SELECT 1 FROM users o
+      WHERE o.id <> u.id AND lower(btrim(o.email)) = lower(btrim(u.email))
+  );
+
+-- Emailed verification tokens; like password reset tokens only the SHA-256
+-- is kept and each works once.
+CREATE TABLE email_verification_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+-- Failed login counters, keyed by "account:<email>" or "ip:<address>".
+-- Unknown emails get a row too, so lockouts do not reveal which accounts
+-- exist.
+CREATE TABLE login_throttles (
+    key TEXT PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMP
+);
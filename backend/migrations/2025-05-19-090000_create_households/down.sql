@@ -0,0 +1,57 @@
+-- Data goes back to the first owner of its household. Rows of households
+-- without an owner cannot be kept.
+CREATE TEMPORARY TABLE household_owner AS
+    SELECT DISTINCT ON (household_id) household_id, user_id
+    FROM household_members
+    WHERE role = 'owner'
+    ORDER BY household_id, joined_at, user_id;
+
+DROP INDEX transactions_household_id_idx;
+DROP INDEX transactions_household_import_hash;
+DROP INDEX transactions_household_external_id;
+
+DELETE FROM transactions t
+    WHERE NOT EXISTS (SELECT 1 FROM household_owner o WHERE o.household_id = t.household_id);
+UPDATE transactions t SET user_id = o.user_id
+    FROM household_owner o WHERE o.household_id = t.household_id;
+ALTER TABLE transactions
+    DROP CONSTRAINT transactions_user_id_fkey,
+    DROP CONSTRAINT transactions_household_id_fkey,
+    DROP COLUMN household_id,
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD CONSTRAINT transactions_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+
+CREATE UNIQUE INDEX transactions_user_external_id
+    ON transactions (user_id, external_id)
+    WHERE external_id IS NOT NULL;
+CREATE UNIQUE INDEX transactions_user_import_hash
+    ON transactions (user_id, import_hash)
+    WHERE import_hash IS NOT NULL;
+
+DO $$
+DECLARE
+    t TEXT;
+BEGIN
+    FOREACH t IN ARRAY ARRAY[
+        'rules', 'duplicate_reviews', 'import_profiles', 'net_worth_snapshots',
+        'recurring_rules', 'price_alerts', 'tags', 'products', 'categories'
+    ] LOOP
+        EXECUTE format('ALTER TABLE %I DROP CONSTRAINT %I', t, t || '_household_id_fkey');
+        EXECUTE format(
+            'DELETE FROM %I x WHERE NOT EXISTS '
+            '(SELECT 1 FROM household_owner o WHERE o.household_id = x.household_id)', t);
+        EXECUTE format(
+            'UPDATE %I x SET household_id = o.user_id '
+            'FROM household_owner o WHERE o.household_id = x.household_id', t);
+        EXECUTE format('ALTER TABLE %I RENAME COLUMN household_id TO user_id', t);
+        EXECUTE format(
+            'ALTER TABLE %I ADD CONSTRAINT %I FOREIGN KEY (user_id) '
+            'REFERENCES users (id) ON DELETE CASCADE', t, t || '_user_id_fkey');
+    END LOOP;
+END $$;
+
+DROP TABLE household_owner;
+DROP TABLE household_invitations;
+DROP TABLE household_members;
+DROP TABLE households;
@@ -0,0 +1,115 @@
+-- Households (shared ledgers) own the financial data instead of single
+-- users. Members have a role: owners manage members, editors change data,
+-- viewers only read.
+CREATE TABLE households (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE household_members (
+    household_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    joined_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (household_id, user_id),
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX household_members_user_id_idx ON household_members (user_id);
+
+-- Single-use invitations. Only the SHA-256 of the token is kept.
+CREATE TABLE household_invitations (
+    id SERIAL PRIMARY KEY,
+    household_id INTEGER NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    token_hash TEXT NOT NULL UNIQUE,
+    created_by INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users (id) ON DELETE CASCADE
+);
+
+-- Every existing user gets a personal household with the same id, so the
+-- data can be handed over by renaming the owner column.
+INSERT INTO households (id, name) SELECT id, 'Personal' FROM users;
+SELECT setval(
+    pg_get_serial_sequence('households', 'id'),
+    COALESCE((SELECT MAX(id) FROM households), 0) + 1,
+    false
+);
+INSERT INTO household_members (household_id, user_id, role)
+    SELECT id, id, 'owner' FROM users;
+
+ALTER TABLE categories DROP CONSTRAINT categories_user_id_fkey;
+ALTER TABLE categories RENAME COLUMN user_id TO household_id;
+ALTER TABLE categories ADD CONSTRAINT categories_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE products DROP CONSTRAINT products_user_id_fkey;
+ALTER TABLE products RENAME COLUMN user_id TO household_id;
+ALTER TABLE products ADD CONSTRAINT products_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE tags DROP CONSTRAINT tags_user_id_fkey;
+ALTER TABLE tags RENAME COLUMN user_id TO household_id;
+ALTER TABLE tags ADD CONSTRAINT tags_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE price_alerts DROP CONSTRAINT price_alerts_user_id_fkey;
+ALTER TABLE price_alerts RENAME COLUMN user_id TO household_id;
+ALTER TABLE price_alerts ADD CONSTRAINT price_alerts_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE recurring_rules DROP CONSTRAINT recurring_rules_user_id_fkey;
+ALTER TABLE recurring_rules RENAME COLUMN user_id TO household_id;
+ALTER TABLE recurring_rules ADD CONSTRAINT recurring_rules_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE net_worth_snapshots DROP CONSTRAINT net_worth_snapshots_user_id_fkey;
+ALTER TABLE net_worth_snapshots RENAME COLUMN user_id TO household_id;
+ALTER TABLE net_worth_snapshots ADD CONSTRAINT net_worth_snapshots_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE import_profiles DROP CONSTRAINT import_profiles_user_id_fkey;
+ALTER TABLE import_profiles RENAME COLUMN user_id TO household_id;
+ALTER TABLE import_profiles ADD CONSTRAINT import_profiles_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE duplicate_reviews DROP CONSTRAINT duplicate_reviews_user_id_fkey;
+ALTER TABLE duplicate_reviews RENAME COLUMN user_id TO household_id;
+ALTER TABLE duplicate_reviews ADD CONSTRAINT duplicate_reviews_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+ALTER TABLE rules DROP CONSTRAINT rules_user_id_fkey;
+ALTER TABLE rules RENAME COLUMN user_id TO household_id;
+ALTER TABLE rules ADD CONSTRAINT rules_household_id_fkey
+    FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE;
+
+-- Transactions belong to a household and remember which member entered
+-- them. The member may leave; the transaction stays.
+ALTER TABLE transactions ADD COLUMN household_id INTEGER;
+UPDATE transactions SET household_id = user_id;
+ALTER TABLE transactions
+    ALTER COLUMN household_id SET NOT NULL,
+    ADD CONSTRAINT transactions_household_id_fkey
+        FOREIGN KEY (household_id) REFERENCES households (id) ON DELETE CASCADE,
+    ALTER COLUMN user_id DROP NOT NULL,
+    DROP CONSTRAINT transactions_user_id_fkey,
+    ADD CONSTRAINT transactions_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE SET NULL;
+
+DROP INDEX transactions_user_external_id;
+CREATE UNIQUE INDEX transactions_household_external_id
+    ON transactions (household_id, external_id)
+    WHERE external_id IS NOT NULL;
+
+DROP INDEX transactions_user_import_hash;
+CREATE UNIQUE INDEX transactions_household_import_hash
+    ON transactions (household_id, import_hash)
+    WHERE import_hash IS NOT NULL;
+
+CREATE INDEX transactions_household_id_idx ON transactions (household_id);
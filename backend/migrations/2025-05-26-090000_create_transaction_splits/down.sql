@@ -0,0 +1,2 @@
+DROP TABLE split_shares;
+DROP TABLE transaction_splits;
@@ -0,0 +1,24 @@
+-- How a household transaction is shared: who paid it and what each
+-- participant owes of it, in cents. Settlements are transfers split the
+-- same way, with the receiver owing the whole amount. A member who deletes
+-- their account leaves blanks behind, so the others keep their history.
+CREATE TABLE transaction_splits (
+    transaction_id INTEGER PRIMARY KEY,
+    paid_by INTEGER,
+    method TEXT NOT NULL CHECK (method IN ('equal', 'shares', 'exact', 'percentages')),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (transaction_id) REFERENCES transactions (id) ON DELETE CASCADE,
+    FOREIGN KEY (paid_by) REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE TABLE split_shares (
+    id SERIAL PRIMARY KEY,
+    transaction_id INTEGER NOT NULL,
+    user_id INTEGER,
+    amount INTEGER NOT NULL CHECK (amount >= 0),
+    UNIQUE (transaction_id, user_id),
+    FOREIGN KEY (transaction_id) REFERENCES transaction_splits (transaction_id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE INDEX split_shares_user_id_idx ON split_shares (user_id);
@@ -0,0 +1,4 @@
+DROP INDEX recurring_rules_product_cadence_description_key;
+
+ALTER TABLE recurring_rules ADD CONSTRAINT recurring_rules_user_id_product_id_cadence_key
+    UNIQUE (household_id, product_id, cadence);
@@ -0,0 +1,6 @@
+-- One product can carry several recurring payments told apart by their
+-- description, such as rent and the gym both paid by bank transfer.
+ALTER TABLE recurring_rules DROP CONSTRAINT recurring_rules_user_id_product_id_cadence_key;
+
+CREATE UNIQUE INDEX recurring_rules_product_cadence_description_key
+    ON recurring_rules (household_id, product_id, cadence, COALESCE(description, ''));
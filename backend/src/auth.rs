@@ -1,44 +1,118 @@
-use axum::extract::Request;
+use axum::extract::{Request, State};
 use axum::http::StatusCode;
 use axum::middleware::Next;
-use axum::response::Response;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use backend::ErrorResponse;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, DecodingKey, Validation};
-use jsonwebtoken::{encode, EncodingKey, Header};
+use diesel::prelude::*;
+use jsonwebtoken::{decode, decode_header, Validation};
+use jsonwebtoken::{encode, Header};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::config::JwtConfig;
+use crate::domain::access_tokens::models::{Access, ApiArea, Scope};
+use crate::domain::access_tokens::services::{authenticate_access_token, ACCESS_TOKEN_PREFIX};
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::households::services::{default_household, member_role, HOUSEHOLD_HEADER};
+use crate::domain::sessions::services::touch_session;
+use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: i32,   // subject (user_id)
-    exp: usize, // expiration
+pub struct Claims {
+    pub sub: i32,   // subject (user_id)
+    pub sid: i32,   // session the token was issued for
+    pub exp: usize, // expiration
+    pub iat: usize, // issued at
+    pub iss: String,
+    pub aud: String,
 }
 
-pub fn generate_jwt(user_id: i32) -> String {
-    // Typically you'd load this from env or config
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "CHANGE_ME".to_string());
+/// The session behind the current request, inserted next to the user id by
+/// `require_auth`.
+#[derive(Clone, Copy, Debug)]
+pub struct CurrentSession(pub i32);
+
+/// How the current request was authenticated, inserted by `require_auth`.
+#[derive(Clone, Debug)]
+pub enum Credential {
+    /// A JWT from a login session; allowed everywhere.
+    Session,
+    /// A personal access token; limited to routes its scopes cover.
+    AccessToken(Vec<Scope>),
+}
+
+fn forbidden(message: impl ToString) -> Response {
+    let body = ErrorResponse {
+        error: message.to_string(),
+    };
+    (StatusCode::FORBIDDEN, Json(body)).into_response()
+}
 
-    // Set expiration (e.g. 24 hours)
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+/// Signs an access token with the current key; its `kid` header names the
+/// key so it can be checked after the key was rotated out.
+pub fn generate_jwt(jwt: &JwtConfig, user_id: i32, session_id: i32) -> String {
+    let now = Utc::now();
+    let expiration = now
+        .checked_add_signed(Duration::minutes(jwt.access_token_minutes))
         .expect("valid timestamp")
         .timestamp() as usize;
 
     let claims = Claims {
         sub: user_id,
+        sid: session_id,
         exp: expiration,
+        iat: now.timestamp() as usize,
+        iss: jwt.issuer.clone(),
+        aud: jwt.audience.clone(),
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
-    )
-    .expect("JWT encode should not fail")
+    let mut header = Header::new(jwt.algorithm);
+    header.kid = Some(jwt.current_kid.clone());
+    encode(&header, &claims, jwt.encoding_key()).expect("JWT encode should not fail")
 }
 
-pub async fn require_auth(mut req: Request, next: Next) -> Result<Response, StatusCode> {
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "CHANGE_ME".to_string());
+/// Checks an access token's signature, `kid`, expiry, issuer and audience.
+pub fn verify_jwt(jwt: &JwtConfig, token: &str) -> Option<Claims> {
+    let header = decode_header(token).ok()?;
+    if header.alg != jwt.algorithm {
+        return None;
+    }
+    let key = jwt.decoding_key(header.kid.as_deref()?)?;
 
+    let mut validation = Validation::new(jwt.algorithm);
+    validation.set_issuer(&[&jwt.issuer]);
+    validation.set_audience(&[&jwt.audience]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+    decode::<Claims>(token, key, &validation)
+        .ok()
+        .map(|data| data.claims)
+}
+
+/// A fresh 256-bit secret, hex encoded, for tokens that are stored hashed.
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// SHA-256 of a random token. The tokens carry enough entropy that a fast
+/// hash is fine, and it lets us look them up directly.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub async fn require_auth(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
     // Extract Authorization header
     let auth_header = match req.headers().get(axum::http::header::AUTHORIZATION) {
         Some(hv) => hv.to_str().map_err(|_| StatusCode::UNAUTHORIZED)?,
@@ -53,19 +127,153 @@ pub async fn require_auth(mut req: Request, next: Next) -> Result<Response, Stat
     // Extract the token portion
     let token = &auth_header["Bearer ".len()..];
 
-    // Decode & validate the token
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::default(),
-    )
-    .map_err(|_| StatusCode::UNAUTHORIZED)?;
+    // The pooled connection is only needed for the checks below; it goes back
+    // to the pool before the handler, which takes its own, runs.
+    {
+        let mut conn = state
+            .pool
+            .get()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Scripts authenticate with personal access tokens instead of a session.
+        if token.starts_with(ACCESS_TOKEN_PREFIX) {
+            let access_token = authenticate_access_token(&mut conn, token)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .ok_or(StatusCode::UNAUTHORIZED)?;
+            let scopes = access_token
+                .scopes
+                .iter()
+                .filter_map(|s| Scope::parse(s))
+                .collect();
+            req.extensions_mut().insert(access_token.user_id);
+            req.extensions_mut().insert(Credential::AccessToken(scopes));
+        } else {
+            // Decode & validate the token
+            let claims = verify_jwt(&state.jwt, token).ok_or(StatusCode::UNAUTHORIZED)?;
+
+            // A valid signature is not enough: the session may have been revoked.
+            let active = touch_session(&mut conn, claims.sid, claims.sub)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            if !active {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+
+            // Store the user id in request extensions so handlers know who is calling
+            req.extensions_mut().insert(claims.sub);
+            req.extensions_mut().insert(CurrentSession(claims.sid));
+            req.extensions_mut().insert(Credential::Session);
+        }
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Lets only users with a confirmed email address through. Must run inside
+/// `require_auth`, which provides the user id.
+pub async fn require_verified_email(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    use crate::schema::users::dsl as u;
+
+    let verified = {
+        let mut conn = state
+            .pool
+            .get()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        u::users
+            .filter(u::id.eq(logged_in_user_id))
+            .select(u::email_verified)
+            .first::<bool>(&mut conn)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?
+    };
+    if !verified {
+        return Ok(forbidden("Verify your email address to use this feature"));
+    }
+
+    Ok(next.run(req).await)
+}
 
-    // Optionally, store `token_data.claims.sub` (user_id) in request extensions
-    // so future handlers can figure out who is calling
-    let logged_in_user_id = token_data.claims.sub;
-    print!("user id: {}", logged_in_user_id);
-    req.extensions_mut().insert(logged_in_user_id);
+/// Checks that a personal access token has a scope for `area`: reads need
+/// `read:<area>` and everything else `write:<area>`. Sessions pass.
+pub async fn require_scope(
+    State(area): State<ApiArea>,
+    Extension(credential): Extension<Credential>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Credential::AccessToken(scopes) = &credential {
+        let access = if req.method().is_safe() {
+            Access::Read
+        } else {
+            Access::Write
+        };
+        let required = Scope { access, area };
+        if !scopes.iter().any(|scope| scope.covers(required)) {
+            return forbidden(format!("This token lacks the '{required}' scope"));
+        }
+    }
+    next.run(req).await
+}
+
+/// Keeps personal access tokens away from account and security settings.
+pub async fn require_session(
+    Extension(credential): Extension<Credential>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Credential::AccessToken(_) = credential {
+        return forbidden("Personal access tokens cannot be used here");
+    }
+    next.run(req).await
+}
+
+/// Picks the household a request works on: the one in the `X-Household-Id`
+/// header, or else the user's first. Viewers only get through with reads.
+/// Must run inside `require_auth`, which provides the user id.
+pub async fn require_household(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let requested = match req.headers().get(HOUSEHOLD_HEADER) {
+        Some(value) => Some(
+            value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .ok_or(StatusCode::BAD_REQUEST)?,
+        ),
+        None => None,
+    };
+
+    let membership = {
+        let mut conn = state
+            .pool
+            .get()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        match requested {
+            Some(id) => {
+                member_role(&mut conn, id, logged_in_user_id).map(|r| r.map(|role| (id, role)))
+            }
+            None => default_household(&mut conn, logged_in_user_id),
+        }
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    };
+    let Some((id, role)) = membership else {
+        return Ok(forbidden("You are not a member of this household"));
+    };
+    if !role.can_write() && !req.method().is_safe() {
+        return Ok(forbidden("Viewers cannot change household data"));
+    }
 
+    req.extensions_mut().insert(ActiveHousehold {
+        id,
+        user_id: logged_in_user_id,
+        role,
+    });
     Ok(next.run(req).await)
 }
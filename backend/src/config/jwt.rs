@@ -0,0 +1,229 @@
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use std::collections::HashMap;
+use std::{env, fmt, fs};
+
+/// The secret used when none is configured; only accepted in dev mode.
+pub const DEV_SECRET: &str = "CHANGE_ME";
+
+/// Shortest HS256 secret accepted outside dev mode.
+const MIN_SECRET_BYTES: usize = 32;
+
+/// Longest accepted `ACCESS_TOKEN_MINUTES`: a day.
+const MAX_ACCESS_TOKEN_MINUTES: i64 = 24 * 60;
+
+/// Longest accepted `SESSION_DAYS`: a year.
+const MAX_SESSION_DAYS: i64 = 365;
+
+/// How access tokens are signed and checked, and how long tokens live.
+#[derive(Clone)]
+pub struct JwtConfig {
+    pub algorithm: Algorithm,
+    /// Goes into the `kid` header of every new token.
+    pub current_kid: String,
+    encoding_key: EncodingKey,
+    /// Keys tokens are accepted with, by `kid`: the current one plus keys
+    /// rotated out whose tokens have not expired yet.
+    decoding_keys: HashMap<String, DecodingKey>,
+    pub issuer: String,
+    pub audience: String,
+    pub access_token_minutes: i64,
+    /// How long a session lasts without being refreshed.
+    pub session_days: i64,
+}
+
+impl fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut kids: Vec<_> = self.decoding_keys.keys().collect();
+        kids.sort();
+        f.debug_struct("JwtConfig")
+            .field("algorithm", &self.algorithm)
+            .field("current_kid", &self.current_kid)
+            .field("accepted_kids", &kids)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("access_token_minutes", &self.access_token_minutes)
+            .field("session_days", &self.session_days)
+            .finish()
+    }
+}
+
+/// Reads a key for `algorithm`: the secret itself for HS256, otherwise the
+/// path of a PEM file.
+fn decoding_key(algorithm: Algorithm, value: &str) -> Result<DecodingKey, String> {
+    match algorithm {
+        Algorithm::HS256 => Ok(DecodingKey::from_secret(value.as_bytes())),
+        Algorithm::RS256 => DecodingKey::from_rsa_pem(&read_pem(value)?)
+            .map_err(|e| format!("Invalid RSA public key in {value}: {e}")),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(&read_pem(value)?)
+            .map_err(|e| format!("Invalid Ed25519 public key in {value}: {e}")),
+        other => Err(format!("Unsupported JWT algorithm {other:?}")),
+    }
+}
+
+/// Outside dev mode, refuses the dev secret and secrets too short to be safe.
+fn check_secret(name: &str, secret: &str, dev_mode: bool) -> Result<(), String> {
+    if dev_mode {
+        return Ok(());
+    }
+    if secret == DEV_SECRET {
+        return Err(format!(
+            "{name} must not be the dev secret outside dev mode"
+        ));
+    }
+    if secret.len() < MIN_SECRET_BYTES {
+        return Err(format!(
+            "{name} must be at least {MIN_SECRET_BYTES} bytes long"
+        ));
+    }
+    Ok(())
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("Failed to read key file {path}: {e}"))
+}
+
+impl JwtConfig {
+    fn new(
+        algorithm: Algorithm,
+        kid: &str,
+        encoding_key: EncodingKey,
+        decoding_key: DecodingKey,
+    ) -> Self {
+        Self {
+            algorithm,
+            current_kid: kid.to_string(),
+            encoding_key,
+            decoding_keys: HashMap::from([(kid.to_string(), decoding_key)]),
+            issuer: "rusty-fin".to_string(),
+            audience: "rusty-fin-api".to_string(),
+            access_token_minutes: 15,
+            session_days: 30,
+        }
+    }
+
+    /// HS256 with a shared secret and default settings.
+    pub fn hs256(kid: &str, secret: &str) -> Self {
+        Self::new(
+            Algorithm::HS256,
+            kid,
+            EncodingKey::from_secret(secret.as_bytes()),
+            DecodingKey::from_secret(secret.as_bytes()),
+        )
+    }
+
+    /// RS256 or EdDSA with a PEM key pair and default settings.
+    pub fn from_pem_files(
+        algorithm: Algorithm,
+        kid: &str,
+        private_key_file: &str,
+        public_key_file: &str,
+    ) -> Result<Self, String> {
+        let pem = read_pem(private_key_file)?;
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&pem),
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(&pem),
+            other => return Err(format!("{other:?} does not use key files")),
+        }
+        .map_err(|e| format!("Invalid private key in {private_key_file}: {e}"))?;
+        let decoding_key = decoding_key(algorithm, public_key_file)?;
+        Ok(Self::new(algorithm, kid, encoding_key, decoding_key))
+    }
+
+    /// Keeps accepting tokens signed with a key that was rotated out. The
+    /// key uses the current algorithm; see `decoding_key` for `value`.
+    pub fn accept_previous_key(mut self, kid: &str, value: &str) -> Result<Self, String> {
+        if kid == self.current_kid {
+            return Err(format!("Previous key id '{kid}' is the current key id"));
+        }
+        let key = decoding_key(self.algorithm, value)?;
+        self.decoding_keys.insert(kid.to_string(), key);
+        Ok(self)
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    pub fn decoding_key(&self, kid: &str) -> Option<&DecodingKey> {
+        self.decoding_keys.get(kid)
+    }
+
+    /// Reads the `JWT_*` variables. Outside dev mode a missing, default or
+    /// short HS256 secret is an error rather than a warning, for previous
+    /// keys as much as for the current one.
+    ///
+    /// - `JWT_ALGORITHM`: `HS256` (default), `RS256` or `EdDSA`
+    /// - `JWT_SECRET`, or `JWT_PRIVATE_KEY_FILE` and `JWT_PUBLIC_KEY_FILE`
+    /// - `JWT_KEY_ID`: `kid` of the current key, default `default`
+    /// - `JWT_PREVIOUS_KEYS`: `kid=secret-or-public-key-file`, comma separated,
+    ///   so these secrets cannot contain commas
+    /// - `JWT_ISSUER`, `JWT_AUDIENCE`, `ACCESS_TOKEN_MINUTES`, `SESSION_DAYS`
+    pub fn from_env(dev_mode: bool) -> Result<Self, String> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let algorithm = match var("JWT_ALGORITHM").as_deref().unwrap_or("HS256") {
+            "HS256" => Algorithm::HS256,
+            "RS256" => Algorithm::RS256,
+            "EdDSA" => Algorithm::EdDSA,
+            other => {
+                return Err(format!(
+                    "JWT_ALGORITHM must be HS256, RS256 or EdDSA, not {other}"
+                ))
+            }
+        };
+        let kid = var("JWT_KEY_ID").unwrap_or_else(|| "default".to_string());
+
+        let mut config = if algorithm == Algorithm::HS256 {
+            let secret = match var("JWT_SECRET") {
+                Some(secret) if secret != DEV_SECRET => secret,
+                _ if dev_mode => {
+                    println!("Warning: JWT_SECRET is not set; using the insecure dev secret");
+                    DEV_SECRET.to_string()
+                }
+                _ => return Err("JWT_SECRET must be set outside dev mode".to_string()),
+            };
+            check_secret("JWT_SECRET", &secret, dev_mode)?;
+            Self::hs256(&kid, &secret)
+        } else {
+            let private_key_file =
+                var("JWT_PRIVATE_KEY_FILE").ok_or("JWT_PRIVATE_KEY_FILE must be set")?;
+            let public_key_file =
+                var("JWT_PUBLIC_KEY_FILE").ok_or("JWT_PUBLIC_KEY_FILE must be set")?;
+            Self::from_pem_files(algorithm, &kid, &private_key_file, &public_key_file)?
+        };
+
+        for entry in var("JWT_PREVIOUS_KEYS").iter().flat_map(|v| v.split(',')) {
+            let (previous_kid, value) = entry
+                .trim()
+                .split_once('=')
+                .ok_or_else(|| format!("JWT_PREVIOUS_KEYS entry '{entry}' is not kid=key"))?;
+            if algorithm == Algorithm::HS256 {
+                let name = format!("JWT_PREVIOUS_KEYS secret of '{previous_kid}'");
+                check_secret(&name, value, dev_mode)?;
+            }
+            config = config.accept_previous_key(previous_kid, value)?;
+        }
+
+        if let Some(issuer) = var("JWT_ISSUER") {
+            config.issuer = issuer;
+        }
+        if let Some(audience) = var("JWT_AUDIENCE") {
+            config.audience = audience;
+        }
+        // Bounded so that token lifetimes are sane and cannot overflow the
+        // date arithmetic at login.
+        let number = |name: &str, default: i64, max: i64| match var(name) {
+            Some(v) => match v.parse::<i64>() {
+                Ok(n) if (1..=max).contains(&n) => Ok(n),
+                _ => Err(format!("{name} must be a number from 1 to {max}")),
+            },
+            None => Ok(default),
+        };
+        config.access_token_minutes = number(
+            "ACCESS_TOKEN_MINUTES",
+            config.access_token_minutes,
+            MAX_ACCESS_TOKEN_MINUTES,
+        )?;
+        config.session_days = number("SESSION_DAYS", config.session_days, MAX_SESSION_DAYS)?;
+        Ok(config)
+    }
+}
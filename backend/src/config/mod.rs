@@ -1,10 +1,27 @@
 use dotenvy::dotenv;
 use std::env;
 
+use crate::rate_limit::RateLimitConfig;
+
+pub mod jwt;
+
+pub use jwt::JwtConfig;
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub database_url: String,
     pub address: String, // or store host + port separately if you prefer
+    /// Directory emails are dropped into. Required outside development mode;
+    /// in development, emails are logged when it is unset.
+    pub mail_dir: Option<String>,
+    /// Requests per client and window; `None` turns rate limiting off.
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Whether to take client addresses from `X-Forwarded-For`. Only safe
+    /// behind a proxy that sets it.
+    pub trust_proxy_headers: bool,
+    /// `APP_ENV=development`; allows insecure defaults such as the JWT secret.
+    pub dev_mode: bool,
+    pub jwt: JwtConfig,
 }
 
 impl AppConfig {
@@ -21,9 +38,39 @@ impl AppConfig {
         // or default to "127.0.0.1:3000"
         let address = env::var("SERVER_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8000".to_string());
 
+        let mail_dir = env::var("MAIL_DIR").ok().filter(|d| !d.is_empty());
+
+        // RATE_LIMIT_REQUESTS=0 disables the limit.
+        let requests: u32 = env::var("RATE_LIMIT_REQUESTS")
+            .map(|v| v.parse())
+            .unwrap_or(Ok(300))?;
+        let window_seconds: u64 = env::var("RATE_LIMIT_WINDOW_SECONDS")
+            .map(|v| v.parse())
+            .unwrap_or(Ok(60))?;
+        let rate_limit = (requests > 0 && window_seconds > 0).then_some(RateLimitConfig {
+            requests,
+            window_seconds,
+        });
+
+        let trust_proxy_headers = env::var("TRUST_PROXY_HEADERS")
+            .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let dev_mode = matches!(env::var("APP_ENV").as_deref(), Ok("development" | "dev"));
+        // Logged emails carry reset and verification tokens in plain text.
+        if mail_dir.is_none() && !dev_mode {
+            return Err("MAIL_DIR must be set outside development mode".into());
+        }
+        let jwt = JwtConfig::from_env(dev_mode)?;
+
         Ok(Self {
             database_url,
             address,
+            mail_dir,
+            rate_limit,
+            trust_proxy_headers,
+            dev_mode,
+            jwt,
         })
     }
 }
@@ -0,0 +1,88 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{AccessTokenDto, CreateAccessTokenRequest, CreatedAccessToken};
+use super::services::{
+    active_access_tokens, create_access_token, parse_scopes, revoke_access_token, MAX_EXPIRY_DAYS,
+};
+
+/// Handler for POST /access-tokens.
+/// Creates a named token for scripts; the secret is only returned here.
+#[debug_handler]
+pub async fn create_access_token_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<CreateAccessTokenRequest>,
+) -> JsonResult<CreatedAccessToken> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(error_response("A token needs a name"));
+    }
+    let scopes = parse_scopes(&payload.scopes).map_err(error_response)?;
+    if let Some(days) = payload.expires_in_days {
+        if !(1..=MAX_EXPIRY_DAYS).contains(&days) {
+            return Err(error_response(format!(
+                "expires_in_days must be between 1 and {MAX_EXPIRY_DAYS}"
+            )));
+        }
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let (token, created) = create_access_token(
+        &mut conn,
+        logged_in_user_id,
+        name,
+        &scopes,
+        payload.expires_in_days,
+    )
+    .map_err(|e| error_response(format!("Failed to create access token: {e}")))?;
+
+    Ok(Json(CreatedAccessToken {
+        token,
+        details: created.into(),
+    }))
+}
+
+/// Handler for GET /access-tokens.
+#[debug_handler]
+pub async fn list_access_tokens(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+) -> JsonResult<Vec<AccessTokenDto>> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let tokens = active_access_tokens(&mut conn, logged_in_user_id)
+        .map_err(|e| error_response(format!("Error loading access tokens: {e}")))?;
+    Ok(Json(tokens.into_iter().map(AccessTokenDto::from).collect()))
+}
+
+/// Handler for DELETE /access-tokens/{id}.
+#[debug_handler]
+pub async fn delete_access_token(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Path(token_id): Path<i32>,
+) -> JsonResult<AccessTokenDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    revoke_access_token(&mut conn, logged_in_user_id, token_id)
+        .map_err(|e| error_response(format!("Failed to revoke access token: {e}")))?
+        .map(|token| Json(token.into()))
+        .ok_or_else(|| error_response("Access token not found"))
+}
@@ -0,0 +1,162 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::schema::personal_access_tokens;
+
+/// A group of routes that personal access tokens get scopes for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiArea {
+    /// Transactions, tags, imports, duplicates, rules and suggestions.
+    Transactions,
+    /// Categories, products, prices and price alerts.
+    Catalog,
+    /// Reports, net worth and subscriptions.
+    Analytics,
+    Exports,
+    /// Downloading and restoring backups.
+    Backups,
+}
+
+impl ApiArea {
+    pub const ALL: [ApiArea; 5] = [
+        ApiArea::Transactions,
+        ApiArea::Catalog,
+        ApiArea::Analytics,
+        ApiArea::Exports,
+        ApiArea::Backups,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ApiArea::Transactions => "transactions",
+            ApiArea::Catalog => "catalog",
+            ApiArea::Analytics => "analytics",
+            ApiArea::Exports => "exports",
+            ApiArea::Backups => "backups",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// What a personal access token may do, written `read:<area>` or
+/// `write:<area>`. Write access includes read access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scope {
+    pub access: Access,
+    pub area: ApiArea,
+}
+
+impl Scope {
+    pub fn parse(raw: &str) -> Option<Scope> {
+        let (access, area) = raw.split_once(':')?;
+        let access = match access {
+            "read" => Access::Read,
+            "write" => Access::Write,
+            _ => return None,
+        };
+        let area = ApiArea::ALL.into_iter().find(|a| a.name() == area)?;
+        Some(Scope { access, area })
+    }
+
+    /// Whether a token with this scope may make a request needing `required`.
+    pub fn covers(self, required: Scope) -> bool {
+        self.area == required.area
+            && (self.access == Access::Write || required.access == Access::Read)
+    }
+
+    /// Every scope there is, for error messages.
+    pub fn all() -> Vec<Scope> {
+        ApiArea::ALL
+            .into_iter()
+            .flat_map(|area| [Access::Read, Access::Write].map(|access| Scope { access, area }))
+            .collect()
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+        };
+        write!(f, "{}:{}", access, self.area.name())
+    }
+}
+
+/// A token from `personal_access_tokens`, without its hash.
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = personal_access_tokens)]
+pub struct PersonalAccessToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// Used when inserting a token.
+#[derive(Insertable)]
+#[diesel(table_name = personal_access_tokens)]
+pub struct NewPersonalAccessToken {
+    pub user_id: i32,
+    pub name: String,
+    pub token_prefix: String,
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// Body of POST /access-tokens.
+#[derive(Deserialize)]
+pub struct CreateAccessTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Days until the token stops working; it never expires without one.
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+}
+
+/// DTO for listing tokens.
+#[derive(Serialize)]
+pub struct AccessTokenDto {
+    pub id: i32,
+    pub name: String,
+    /// The start of the token, to recognise it by.
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl From<PersonalAccessToken> for AccessTokenDto {
+    fn from(token: PersonalAccessToken) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            scopes: token.scopes,
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+            expires_at: token.expires_at,
+        }
+    }
+}
+
+/// Returned by POST /access-tokens. The token is shown only this once.
+#[derive(Serialize)]
+pub struct CreatedAccessToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub details: AccessTokenDto,
+}
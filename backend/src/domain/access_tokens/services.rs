@@ -0,0 +1,135 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::auth::{hash_token, random_token};
+
+use super::models::{NewPersonalAccessToken, PersonalAccessToken, Scope};
+
+/// Every personal access token starts with this, which is how `require_auth`
+/// tells them from JWTs.
+pub const ACCESS_TOKEN_PREFIX: &str = "rfpat_";
+
+/// Characters of a token kept in the clear for display.
+const DISPLAY_PREFIX_LENGTH: usize = 12;
+
+/// Longest expiry that can be asked for.
+pub const MAX_EXPIRY_DAYS: i64 = 366;
+
+/// `last_used_at` is only written when it is older than this.
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// Parses requested scopes, dropping duplicates.
+pub fn parse_scopes(raw: &[String]) -> Result<Vec<Scope>, String> {
+    if raw.is_empty() {
+        return Err("A token needs at least one scope".to_string());
+    }
+    let mut scopes = Vec::new();
+    for name in raw {
+        let scope = Scope::parse(name.trim()).ok_or_else(|| {
+            let known: Vec<String> = Scope::all().iter().map(Scope::to_string).collect();
+            format!(
+                "Unknown scope '{name}'; expected one of {}",
+                known.join(", ")
+            )
+        })?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    Ok(scopes)
+}
+
+/// Stores a new token and returns it along with the secret to hand out.
+pub fn create_access_token(
+    conn: &mut PgConnection,
+    user_id: i32,
+    name: &str,
+    scopes: &[Scope],
+    expires_in_days: Option<i64>,
+) -> QueryResult<(String, PersonalAccessToken)> {
+    use crate::schema::personal_access_tokens::dsl as p;
+
+    let token = format!("{ACCESS_TOKEN_PREFIX}{}", random_token());
+    let created = diesel::insert_into(p::personal_access_tokens)
+        .values(&NewPersonalAccessToken {
+            user_id,
+            name: name.to_string(),
+            token_prefix: token[..DISPLAY_PREFIX_LENGTH].to_string(),
+            token_hash: hash_token(&token),
+            scopes: scopes.iter().map(Scope::to_string).collect(),
+            expires_at: expires_in_days.map(|days| now() + Duration::days(days)),
+        })
+        .returning(PersonalAccessToken::as_returning())
+        .get_result(conn)?;
+    Ok((token, created))
+}
+
+/// The user's tokens that are neither revoked nor expired, newest first.
+pub fn active_access_tokens(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> QueryResult<Vec<PersonalAccessToken>> {
+    use crate::schema::personal_access_tokens::dsl as p;
+
+    p::personal_access_tokens
+        .filter(p::user_id.eq(user_id))
+        .filter(p::revoked_at.is_null())
+        .filter(p::expires_at.is_null().or(p::expires_at.gt(now())))
+        .order(p::id.desc())
+        .select(PersonalAccessToken::as_select())
+        .load(conn)
+}
+
+/// Revokes one of the user's tokens. Returns `None` if there is no such
+/// active token.
+pub fn revoke_access_token(
+    conn: &mut PgConnection,
+    user_id: i32,
+    token_id: i32,
+) -> QueryResult<Option<PersonalAccessToken>> {
+    use crate::schema::personal_access_tokens::dsl as p;
+
+    diesel::update(
+        p::personal_access_tokens
+            .filter(p::id.eq(token_id))
+            .filter(p::user_id.eq(user_id))
+            .filter(p::revoked_at.is_null()),
+    )
+    .set(p::revoked_at.eq(now()))
+    .returning(PersonalAccessToken::as_returning())
+    .get_result(conn)
+    .optional()
+}
+
+/// Looks up a token presented to the API and records that it was used.
+/// Returns `None` if it is unknown, revoked or expired.
+pub fn authenticate_access_token(
+    conn: &mut PgConnection,
+    token: &str,
+) -> QueryResult<Option<PersonalAccessToken>> {
+    use crate::schema::personal_access_tokens::dsl as p;
+
+    let found = p::personal_access_tokens
+        .filter(p::token_hash.eq(hash_token(token)))
+        .filter(p::revoked_at.is_null())
+        .filter(p::expires_at.is_null().or(p::expires_at.gt(now())))
+        .select(PersonalAccessToken::as_select())
+        .first(conn)
+        .optional()?;
+    let Some(found) = found else {
+        return Ok(None);
+    };
+    let stale = found
+        .last_used_at
+        .is_none_or(|at| at < now() - Duration::seconds(TOUCH_INTERVAL_SECONDS));
+    if stale {
+        diesel::update(p::personal_access_tokens.filter(p::id.eq(found.id)))
+            .set(p::last_used_at.eq(now()))
+            .execute(conn)?;
+    }
+    Ok(Some(found))
+}
@@ -1,22 +1,31 @@
 use crate::domain::analytics::models::{
-    CategorySpending, ProductPriceData, SpendingTimeSeriesEntry,
+    AnomalyQuery, CategorySpending, DayOfMonthCell, ForecastQuery, HeatmapQuery, MonthlySavings,
+    ProductPriceData, ReportRangeQuery, SpendingAnomalies, SpendingForecast, SpendingHeatmap,
+    SpendingTimeSeriesEntry, WeekdayHourCell,
 };
+use crate::domain::analytics::services::{
+    cents_to_dollars, find_day_anomalies, find_transaction_anomalies, fold_monthly_totals,
+    forecast_month, month_start, monthly_savings, ExpenseRow, MonthlyTotals, FORECAST_HISTORY_DAYS,
+    RECURRING_MONTHS,
+};
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::transactions::models::TransactionType;
 use crate::{error_response, AppState, JsonResult};
 use axum::{
     debug_handler,
     extract::{Extension, Query, State},
     Json,
 };
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use diesel::dsl::sql;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Date, Nullable};
+use diesel::sql_types::{BigInt, Date, Integer, Nullable, Text};
 use std::sync::Arc;
 
 #[debug_handler]
 pub async fn spending_time_series(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
 ) -> JsonResult<Vec<SpendingTimeSeriesEntry>> {
     use crate::schema::product_prices::dsl as pp;
     use crate::schema::transactions::dsl as tx;
@@ -28,7 +37,7 @@ pub async fn spending_time_series(
 
     // Group by only the date portion (ignoring the time) so that transactions on the same day are aggregated.
     let query = tx::transactions
-        .filter(tx::user_id.eq(logged_in_user_id))
+        .filter(tx::household_id.eq(household.id))
         .inner_join(pp::product_prices.on(pp::id.eq(tx::product_price_id)))
         .select((
             sql::<Date>("DATE(transactions.date)"),
@@ -61,7 +70,7 @@ pub async fn spending_time_series(
 #[debug_handler]
 pub async fn category_spending(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
 ) -> JsonResult<Vec<CategorySpending>> {
     use crate::schema::categories::dsl as cat;
     use crate::schema::product_prices::dsl as pp;
@@ -74,7 +83,7 @@ pub async fn category_spending(
     };
 
     let query = tx::transactions
-        .filter(tx::user_id.eq(logged_in_user_id))
+        .filter(tx::household_id.eq(household.id))
         .inner_join(pr::products.on(pr::id.eq(tx::product_id)))
         .filter(pr::category_id.is_not_null())
         .inner_join(cat::categories.on(pr::category_id.eq(cat::id.nullable())))
@@ -149,3 +158,295 @@ pub async fn product_price_data(
 
     Ok(Json(data))
 }
+
+/// Default number of MADs a value must exceed to be reported.
+const DEFAULT_ANOMALY_THRESHOLD: f64 = 3.0;
+/// Default minimum group size before anomalies are reported.
+const DEFAULT_ANOMALY_MIN_SAMPLES: usize = 5;
+
+/// GET /spending-anomalies
+/// Flags unusually large expenses and days with unusual total spending.
+#[debug_handler]
+pub async fn spending_anomalies(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<AnomalyQuery>,
+) -> JsonResult<SpendingAnomalies> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::transactions::dsl as tx;
+
+    let threshold = query.threshold.unwrap_or(DEFAULT_ANOMALY_THRESHOLD);
+    if threshold <= 0.0 {
+        return Err(error_response("threshold must be positive"));
+    }
+    let min_samples = query
+        .min_samples
+        .unwrap_or(DEFAULT_ANOMALY_MIN_SAMPLES)
+        .max(2);
+
+    let mut conn = match state.pool.get() {
+        Ok(c) => c,
+        Err(_) => return Err(error_response("Failed to fetch connection from pool")),
+    };
+
+    let expenses = load_expense_rows(&mut conn, household.id, None)
+        .map_err(|e| error_response(format!("Query error: {}", e)))?;
+
+    // Same per-day aggregation as `spending_time_series`, restricted to expenses.
+    let daily_totals = tx::transactions
+        .filter(tx::household_id.eq(household.id))
+        .filter(tx::transaction_type.eq(TransactionType::Expense))
+        .inner_join(pp::product_prices.on(pp::id.eq(tx::product_price_id)))
+        .select((
+            sql::<Date>("DATE(transactions.date)"),
+            sql::<Nullable<BigInt>>("SUM(product_prices.price)"),
+        ))
+        .group_by(sql::<Date>("DATE(transactions.date)"))
+        .order(sql::<Date>("DATE(transactions.date)"))
+        .load::<(NaiveDate, Option<i64>)>(&mut conn)
+        .map_err(|e| error_response(format!("Query error: {}", e)))?
+        .into_iter()
+        .map(|(date, total)| (date, total.unwrap_or(0)))
+        .collect::<Vec<_>>();
+
+    Ok(Json(SpendingAnomalies {
+        transactions: find_transaction_anomalies(&expenses, threshold, min_samples),
+        days: find_day_anomalies(&daily_totals, threshold, min_samples),
+    }))
+}
+
+/// Loads the household's expenses, optionally limited to `[from, until)`, flattened
+/// with product and category names.
+fn load_expense_rows(
+    conn: &mut PgConnection,
+    household_id: i32,
+    range: Option<(NaiveDateTime, NaiveDateTime)>,
+) -> QueryResult<Vec<ExpenseRow>> {
+    use crate::schema::categories::dsl as cat;
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transactions::dsl as tx;
+
+    let mut query = tx::transactions
+        .filter(tx::household_id.eq(household_id))
+        .filter(tx::transaction_type.eq(TransactionType::Expense))
+        .inner_join(pr::products.on(pr::id.eq(tx::product_id)))
+        .left_join(cat::categories.on(pr::category_id.eq(cat::id.nullable())))
+        .inner_join(pp::product_prices.on(pp::id.eq(tx::product_price_id)))
+        .select((
+            tx::id,
+            tx::date,
+            pr::id,
+            pr::name,
+            cat::name.nullable(),
+            pp::price,
+        ))
+        .into_boxed();
+    if let Some((from, until)) = range {
+        query = query.filter(tx::date.ge(from)).filter(tx::date.lt(until));
+    }
+
+    let rows = query.load::<(i32, NaiveDateTime, i32, String, Option<String>, i32)>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(transaction_id, date, product_id, product_name, category_name, price)| ExpenseRow {
+                transaction_id,
+                date: date.date(),
+                product_id,
+                product_name,
+                category_name,
+                amount: price as i64,
+            },
+        )
+        .collect())
+}
+
+/// GET /spending-forecast
+/// Projects end-of-month spending per category with a confidence band.
+#[debug_handler]
+pub async fn spending_forecast(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ForecastQuery>,
+) -> JsonResult<SpendingForecast> {
+    let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut conn = match state.pool.get() {
+        Ok(c) => c,
+        Err(_) => return Err(error_response("Failed to fetch connection from pool")),
+    };
+
+    let history_start = month_start(as_of, 0) - Duration::days(FORECAST_HISTORY_DAYS);
+    let from = history_start
+        .min(month_start(as_of, RECURRING_MONTHS))
+        .and_time(NaiveTime::MIN);
+    let until = (as_of + Duration::days(1)).and_time(NaiveTime::MIN);
+
+    let expenses = load_expense_rows(&mut conn, household.id, Some((from, until)))
+        .map_err(|e| error_response(format!("Query error: {}", e)))?;
+
+    Ok(Json(forecast_month(&expenses, as_of)))
+}
+
+/// Loads income and expense totals per month for the household.
+pub fn load_monthly_totals(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> QueryResult<MonthlyTotals> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::transactions::dsl as tx;
+
+    let month = "DATE(date_trunc('month', transactions.date))";
+    let rows = tx::transactions
+        .filter(tx::household_id.eq(household_id))
+        .inner_join(pp::product_prices.on(pp::id.eq(tx::product_price_id)))
+        .select((
+            sql::<Date>(month),
+            sql::<Text>("transactions.transaction_type"),
+            sql::<Nullable<BigInt>>("SUM(product_prices.price)"),
+        ))
+        .group_by((
+            sql::<Date>(month),
+            sql::<Text>("transactions.transaction_type"),
+        ))
+        .load::<(NaiveDate, TransactionType, Option<i64>)>(conn)?;
+
+    let rows: Vec<_> = rows
+        .into_iter()
+        .map(|(m, kind, total)| (m, kind, total.unwrap_or(0)))
+        .collect();
+    Ok(fold_monthly_totals(&rows))
+}
+
+/// GET /savings-report
+/// Monthly income, expense, savings amount and savings rate.
+#[debug_handler]
+pub async fn savings_report(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ReportRangeQuery>,
+) -> JsonResult<Vec<MonthlySavings>> {
+    let mut conn = match state.pool.get() {
+        Ok(c) => c,
+        Err(_) => return Err(error_response("Failed to fetch connection from pool")),
+    };
+
+    let totals = load_monthly_totals(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Query error: {}", e)))?;
+
+    let from = query.from.unwrap_or(NaiveDate::MIN);
+    let to = query.to.unwrap_or(NaiveDate::MAX);
+    if from > to {
+        return Err(error_response("from must not be after to"));
+    }
+
+    Ok(Json(monthly_savings(&totals, from, to)))
+}
+
+/// One aggregated bucket of the heatmap queries.
+#[derive(QueryableByName)]
+struct HeatmapRow {
+    #[diesel(sql_type = Integer)]
+    bucket: i32,
+    #[diesel(sql_type = Integer)]
+    hour: i32,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    total: Option<i64>,
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// GET /spending-heatmap
+/// Expense totals and counts by (weekday, hour) and by day of month.
+#[debug_handler]
+pub async fn spending_heatmap(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<HeatmapQuery>,
+) -> JsonResult<SpendingHeatmap> {
+    let mut conn = match state.pool.get() {
+        Ok(c) => c,
+        Err(_) => return Err(error_response("Failed to fetch connection from pool")),
+    };
+
+    // Both aggregations share the same filtered set of expenses; `$2`/`$3` are
+    // NULL when no category/tag filter was requested.
+    let filtered = "FROM transactions
+        INNER JOIN products ON products.id = transactions.product_id
+        INNER JOIN product_prices ON product_prices.id = transactions.product_price_id
+        WHERE transactions.household_id = $1
+          AND transactions.transaction_type = 'expense'
+          AND ($2::int IS NULL OR products.category_id = $2)
+          AND ($3::int IS NULL OR EXISTS (
+              SELECT 1 FROM transaction_tags
+              WHERE transaction_tags.transaction_id = transactions.id
+                AND transaction_tags.tag_id = $3))";
+
+    let hourly = diesel::sql_query(format!(
+        "SELECT EXTRACT(ISODOW FROM transactions.date)::int AS bucket,
+                EXTRACT(HOUR FROM transactions.date)::int AS hour,
+                SUM(product_prices.price) AS total,
+                COUNT(*) AS count
+         {filtered}
+         GROUP BY 1, 2"
+    ))
+    .bind::<Integer, _>(household.id)
+    .bind::<Nullable<Integer>, _>(query.category_id)
+    .bind::<Nullable<Integer>, _>(query.tag_id)
+    .load::<HeatmapRow>(&mut conn)
+    .map_err(|e| error_response(format!("Query error: {}", e)))?;
+
+    let daily = diesel::sql_query(format!(
+        "SELECT EXTRACT(DAY FROM transactions.date)::int AS bucket,
+                0 AS hour,
+                SUM(product_prices.price) AS total,
+                COUNT(*) AS count
+         {filtered}
+         GROUP BY 1"
+    ))
+    .bind::<Integer, _>(household.id)
+    .bind::<Nullable<Integer>, _>(query.category_id)
+    .bind::<Nullable<Integer>, _>(query.tag_id)
+    .load::<HeatmapRow>(&mut conn)
+    .map_err(|e| error_response(format!("Query error: {}", e)))?;
+
+    // Fill every slot so clients can draw a full grid without gaps.
+    let by_weekday_hour = (1..=7)
+        .flat_map(|wd| (0..24).map(move |h| (wd, h)))
+        .map(|(wd, h)| {
+            let (total, count) = hourly
+                .iter()
+                .find(|r| r.bucket == wd && r.hour == h)
+                .map(|r| (r.total.unwrap_or(0), r.count))
+                .unwrap_or((0, 0));
+            WeekdayHourCell {
+                weekday: wd,
+                hour: h,
+                total_spending: cents_to_dollars(total as f64),
+                count,
+            }
+        })
+        .collect();
+
+    let by_day_of_month = (1..=31)
+        .map(|d| {
+            let (total, count) = daily
+                .iter()
+                .find(|r| r.bucket == d)
+                .map(|r| (r.total.unwrap_or(0), r.count))
+                .unwrap_or((0, 0));
+            DayOfMonthCell {
+                day: d,
+                total_spending: cents_to_dollars(total as f64),
+                count,
+            }
+        })
+        .collect();
+
+    Ok(Json(SpendingHeatmap {
+        by_weekday_hour,
+        by_day_of_month,
+    }))
+}
@@ -1,4 +1,5 @@
-use serde::Serialize;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
 pub struct SpendingTimeSeriesEntry {
@@ -19,3 +20,137 @@ pub struct ProductPriceData {
     pub date: String,
     pub price: f64,
 }
+
+/// Query parameters for GET /spending-anomalies.
+#[derive(Debug, Deserialize)]
+pub struct AnomalyQuery {
+    /// How many median absolute deviations away from the norm count as unusual.
+    pub threshold: Option<f64>,
+    /// Minimum number of data points a group needs before it is judged.
+    pub min_samples: Option<usize>,
+}
+
+/// A transaction whose amount is far above its product's or category's norm.
+#[derive(Debug, Serialize)]
+pub struct TransactionAnomaly {
+    pub transaction_id: i32,
+    /// Date in "YYYY-MM-DD" format
+    pub date: String,
+    pub product_name: String,
+    pub category_name: Option<String>,
+    pub amount: f64,
+    /// The median amount the transaction was compared against.
+    pub typical_amount: f64,
+    /// Distance from the median, in median absolute deviations.
+    pub score: f64,
+    pub reason: String,
+}
+
+/// A day whose total spending falls outside the typical daily band.
+#[derive(Debug, Serialize)]
+pub struct DayAnomaly {
+    /// Date in "YYYY-MM-DD" format
+    pub date: String,
+    pub total_spending: f64,
+    pub typical_low: f64,
+    pub typical_high: f64,
+    pub score: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingAnomalies {
+    pub transactions: Vec<TransactionAnomaly>,
+    pub days: Vec<DayAnomaly>,
+}
+
+/// Query parameters for GET /spending-forecast.
+#[derive(Debug, Deserialize)]
+pub struct ForecastQuery {
+    /// The day the forecast is made on; defaults to today. The forecast covers
+    /// the month this date falls into.
+    pub as_of: Option<NaiveDate>,
+}
+
+/// Projected end-of-month spending for a single category.
+#[derive(Debug, Serialize)]
+pub struct CategoryForecast {
+    pub category_name: String,
+    pub month_to_date: f64,
+    /// Expected spending on recurring items that haven't shown up yet this month.
+    pub expected_recurring: f64,
+    /// Expected spending on everything else for the rest of the month.
+    pub expected_variable: f64,
+    pub projected_total: f64,
+    /// Lower bound of the 95% confidence band.
+    pub low: f64,
+    /// Upper bound of the 95% confidence band.
+    pub high: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingForecast {
+    /// Month in "YYYY-MM" format
+    pub month: String,
+    /// Date in "YYYY-MM-DD" format
+    pub as_of: String,
+    pub days_remaining: i64,
+    pub categories: Vec<CategoryForecast>,
+    pub total: CategoryForecast,
+}
+
+/// Optional date range for the monthly reports.
+#[derive(Debug, Deserialize)]
+pub struct ReportRangeQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Income, expense and savings for one month.
+#[derive(Debug, Serialize)]
+pub struct MonthlySavings {
+    /// Month in "YYYY-MM" format
+    pub month: String,
+    pub income: f64,
+    pub expense: f64,
+    pub savings: f64,
+    /// Savings as a percentage of income; `None` for months without income.
+    pub savings_rate: Option<f64>,
+}
+
+/// Query parameters for GET /spending-heatmap.
+#[derive(Debug, Deserialize)]
+pub struct HeatmapQuery {
+    /// Only count expenses whose product is in this category.
+    pub category_id: Option<i32>,
+    /// Only count expenses carrying this tag.
+    pub tag_id: Option<i32>,
+}
+
+/// Spending in one (weekday, hour) slot.
+#[derive(Debug, Serialize)]
+pub struct WeekdayHourCell {
+    /// ISO weekday: 1 = Monday ... 7 = Sunday
+    pub weekday: i32,
+    /// Hour of day, 0-23
+    pub hour: i32,
+    pub total_spending: f64,
+    pub count: i64,
+}
+
+/// Spending on one day of the month.
+#[derive(Debug, Serialize)]
+pub struct DayOfMonthCell {
+    /// Day of month, 1-31
+    pub day: i32,
+    pub total_spending: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingHeatmap {
+    /// All 7 x 24 slots, Monday 00:00 first.
+    pub by_weekday_hour: Vec<WeekdayHourCell>,
+    /// All 31 days, the 1st first.
+    pub by_day_of_month: Vec<DayOfMonthCell>,
+}
@@ -0,0 +1,425 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{
+    CategoryForecast, DayAnomaly, MonthlySavings, SpendingForecast, TransactionAnomaly,
+};
+
+/// Returns the median of `values`, or `None` if the slice is empty.
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// Median and median absolute deviation of `values`.
+///
+/// When more than half of the values are identical the MAD collapses to zero;
+/// in that case the mean absolute deviation is used instead so a single
+/// outlier can still be measured against the rest.
+pub fn median_and_mad(values: &[f64]) -> Option<(f64, f64)> {
+    let med = median(values)?;
+    let deviations: Vec<f64> = values.iter().map(|v| (v - med).abs()).collect();
+    let mut mad = median(&deviations)?;
+    if mad == 0.0 {
+        mad = deviations.iter().sum::<f64>() / deviations.len() as f64;
+    }
+    Some((med, mad))
+}
+
+/// Converts an amount in cents to dollars, rounded to 2 decimal places.
+pub fn cents_to_dollars(cents: f64) -> f64 {
+    cents.round() / 100.0
+}
+
+/// One expense, flattened with the names needed to explain an anomaly.
+pub struct ExpenseRow {
+    pub transaction_id: i32,
+    pub date: NaiveDate,
+    pub product_id: i32,
+    pub product_name: String,
+    pub category_name: Option<String>,
+    /// Amount in cents.
+    pub amount: i64,
+}
+
+/// Flags expenses that sit more than `threshold` MADs above the median of
+/// their product, or failing that, of their category.
+pub fn find_transaction_anomalies(
+    rows: &[ExpenseRow],
+    threshold: f64,
+    min_samples: usize,
+) -> Vec<TransactionAnomaly> {
+    let mut by_product: HashMap<i32, Vec<f64>> = HashMap::new();
+    let mut by_category: HashMap<&str, Vec<f64>> = HashMap::new();
+    for row in rows {
+        by_product
+            .entry(row.product_id)
+            .or_default()
+            .push(row.amount as f64);
+        if let Some(cat) = &row.category_name {
+            by_category
+                .entry(cat.as_str())
+                .or_default()
+                .push(row.amount as f64);
+        }
+    }
+
+    let stats = |values: &Vec<f64>| {
+        if values.len() < min_samples {
+            None
+        } else {
+            median_and_mad(values).filter(|(_, mad)| *mad > 0.0)
+        }
+    };
+    let product_stats: HashMap<i32, (f64, f64)> = by_product
+        .iter()
+        .filter_map(|(id, values)| stats(values).map(|s| (*id, s)))
+        .collect();
+    let category_stats: HashMap<&str, (f64, f64)> = by_category
+        .iter()
+        .filter_map(|(name, values)| stats(values).map(|s| (*name, s)))
+        .collect();
+
+    let mut anomalies = Vec::new();
+    for row in rows {
+        let amount = row.amount as f64;
+        let product_hit = product_stats
+            .get(&row.product_id)
+            .map(|(med, mad)| (*med, (amount - med) / mad))
+            .filter(|(_, score)| *score > threshold);
+        let category_hit = row
+            .category_name
+            .as_deref()
+            .and_then(|cat| category_stats.get(cat))
+            .map(|(med, mad)| (*med, (amount - med) / mad))
+            .filter(|(_, score)| *score > threshold);
+
+        let (typical, score, reason) = match (product_hit, category_hit) {
+            (Some((med, score)), _) => (
+                med,
+                score,
+                format!(
+                    "${:.2} is {:.1} MADs above the usual ${:.2} for \"{}\"",
+                    amount / 100.0,
+                    score,
+                    med / 100.0,
+                    row.product_name
+                ),
+            ),
+            (None, Some((med, score))) => (
+                med,
+                score,
+                format!(
+                    "${:.2} is {:.1} MADs above the usual ${:.2} for category \"{}\"",
+                    amount / 100.0,
+                    score,
+                    med / 100.0,
+                    row.category_name.as_deref().unwrap_or_default()
+                ),
+            ),
+            (None, None) => continue,
+        };
+
+        anomalies.push(TransactionAnomaly {
+            transaction_id: row.transaction_id,
+            date: row.date.format("%Y-%m-%d").to_string(),
+            product_name: row.product_name.clone(),
+            category_name: row.category_name.clone(),
+            amount: cents_to_dollars(amount),
+            typical_amount: cents_to_dollars(typical),
+            score: (score * 100.0).round() / 100.0,
+            reason,
+        });
+    }
+
+    anomalies.sort_by(|a, b| b.score.total_cmp(&a.score));
+    anomalies
+}
+
+/// Flags days whose total spending (in cents) falls outside
+/// `median ± threshold * MAD` of all daily totals.
+pub fn find_day_anomalies(
+    daily_totals: &[(NaiveDate, i64)],
+    threshold: f64,
+    min_samples: usize,
+) -> Vec<DayAnomaly> {
+    if daily_totals.len() < min_samples {
+        return Vec::new();
+    }
+    let values: Vec<f64> = daily_totals.iter().map(|(_, t)| *t as f64).collect();
+    let Some((med, mad)) = median_and_mad(&values) else {
+        return Vec::new();
+    };
+    if mad == 0.0 {
+        return Vec::new();
+    }
+    let low = (med - threshold * mad).max(0.0);
+    let high = med + threshold * mad;
+
+    daily_totals
+        .iter()
+        .filter_map(|(date, total)| {
+            let total = *total as f64;
+            let score = (total - med) / mad;
+            let reason = if total > high {
+                format!(
+                    "Spent ${:.2}, {:.1} MADs above the typical ${:.2} per day",
+                    total / 100.0,
+                    score,
+                    med / 100.0
+                )
+            } else if total < low {
+                format!(
+                    "Spent ${:.2}, {:.1} MADs below the typical ${:.2} per day",
+                    total / 100.0,
+                    -score,
+                    med / 100.0
+                )
+            } else {
+                return None;
+            };
+            Some(DayAnomaly {
+                date: date.format("%Y-%m-%d").to_string(),
+                total_spending: cents_to_dollars(total),
+                typical_low: cents_to_dollars(low),
+                typical_high: cents_to_dollars(high),
+                score: (score * 100.0).round() / 100.0,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// How far back the weekday pattern for forecasts looks (26 whole weeks).
+pub const FORECAST_HISTORY_DAYS: i64 = 182;
+
+/// A product counts as recurring when it was bought once in each of this many
+/// months right before the forecast month.
+pub const RECURRING_MONTHS: u32 = 3;
+
+/// z-score for a 95% confidence band.
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// Name used for expenses whose product has no category.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// First day of the month `months_back` months before the month of `date`.
+pub fn month_start(date: NaiveDate, months_back: u32) -> NaiveDate {
+    let first = date.with_day(1).expect("day 1 always exists");
+    first - Months::new(months_back)
+}
+
+/// Last day of the month `date` falls in.
+pub fn month_end(date: NaiveDate) -> NaiveDate {
+    month_start(date, 0) + Months::new(1) - Duration::days(1)
+}
+
+#[derive(Default)]
+struct CategoryProjection {
+    month_to_date: f64,
+    recurring: f64,
+    variable: f64,
+    variance: f64,
+}
+
+impl CategoryProjection {
+    fn into_forecast(self, category_name: String) -> CategoryForecast {
+        let projected = self.month_to_date + self.recurring + self.variable;
+        let spread = CONFIDENCE_Z * self.variance.sqrt();
+        let floor = self.month_to_date + self.recurring;
+        CategoryForecast {
+            category_name,
+            month_to_date: cents_to_dollars(self.month_to_date),
+            expected_recurring: cents_to_dollars(self.recurring),
+            expected_variable: cents_to_dollars(self.variable),
+            projected_total: cents_to_dollars(projected),
+            low: cents_to_dollars((projected - spread).max(floor)),
+            high: cents_to_dollars(projected + spread),
+        }
+    }
+}
+
+/// Projects end-of-month spending per category from the expenses seen so far.
+///
+/// `expenses` must cover at least `FORECAST_HISTORY_DAYS` before the start of
+/// the month of `as_of`, up to and including `as_of`. The projection is
+/// month-to-date + recurring products not yet bought this month + the mean
+/// spending for each remaining weekday, with a band from the weekday variance.
+pub fn forecast_month(expenses: &[ExpenseRow], as_of: NaiveDate) -> SpendingForecast {
+    let start = month_start(as_of, 0);
+    let end = month_end(as_of);
+    let history_start = start - Duration::days(FORECAST_HISTORY_DAYS);
+    let recurring_start = month_start(as_of, RECURRING_MONTHS);
+
+    let category_of = |row: &ExpenseRow| {
+        row.category_name
+            .clone()
+            .unwrap_or_else(|| UNCATEGORIZED.to_string())
+    };
+
+    // 1) Recurring products: bought exactly once in every one of the last
+    //    RECURRING_MONTHS months (so weekly purchases don't qualify).
+    let mut monthly_by_product: HashMap<i32, BTreeMap<NaiveDate, Vec<f64>>> = HashMap::new();
+    for row in expenses
+        .iter()
+        .filter(|r| r.date >= recurring_start && r.date < start)
+    {
+        monthly_by_product
+            .entry(row.product_id)
+            .or_default()
+            .entry(month_start(row.date, 0))
+            .or_default()
+            .push(row.amount as f64);
+    }
+    let recurring: HashMap<i32, f64> = monthly_by_product
+        .into_iter()
+        .filter(|(_, months)| months.len() == RECURRING_MONTHS as usize)
+        .filter(|(_, months)| months.values().all(|amounts| amounts.len() == 1))
+        .filter_map(|(product_id, months)| {
+            let amounts: Vec<f64> = months.into_values().flatten().collect();
+            median(&amounts).map(|m| (product_id, m))
+        })
+        .collect();
+
+    let mut projections: BTreeMap<String, CategoryProjection> = BTreeMap::new();
+    let mut seen_this_month: HashSet<i32> = HashSet::new();
+
+    // 2) Month-to-date totals.
+    for row in expenses
+        .iter()
+        .filter(|r| r.date >= start && r.date <= as_of)
+    {
+        projections
+            .entry(category_of(row))
+            .or_default()
+            .month_to_date += row.amount as f64;
+        seen_this_month.insert(row.product_id);
+    }
+
+    // 3) Recurring items still outstanding this month.
+    for row in expenses
+        .iter()
+        .filter(|r| r.date >= recurring_start && r.date < start)
+    {
+        if let Some(amount) = recurring.get(&row.product_id) {
+            if seen_this_month.insert(row.product_id) {
+                projections.entry(category_of(row)).or_default().recurring += amount;
+            }
+        }
+    }
+
+    // 4) Weekday pattern of the non-recurring spending in the history window.
+    let mut daily: HashMap<String, HashMap<NaiveDate, f64>> = HashMap::new();
+    for row in expenses
+        .iter()
+        .filter(|r| r.date >= history_start && r.date < start)
+        .filter(|r| !recurring.contains_key(&r.product_id))
+    {
+        *daily
+            .entry(category_of(row))
+            .or_default()
+            .entry(row.date)
+            .or_default() += row.amount as f64;
+    }
+
+    let remaining: Vec<NaiveDate> = as_of
+        .succ_opt()
+        .map(|first| first.iter_days().take_while(|d| *d <= end).collect())
+        .unwrap_or_default();
+    let history_days: Vec<NaiveDate> = history_start
+        .iter_days()
+        .take_while(|d| *d < start)
+        .collect();
+
+    for (category, totals) in daily {
+        let projection = projections.entry(category).or_default();
+        for weekday in remaining.iter().map(|d| d.weekday()) {
+            let samples: Vec<f64> = history_days
+                .iter()
+                .filter(|d| d.weekday() == weekday)
+                .map(|d| totals.get(d).copied().unwrap_or(0.0))
+                .collect();
+            let n = samples.len() as f64;
+            if n < 2.0 {
+                continue;
+            }
+            let mean = samples.iter().sum::<f64>() / n;
+            let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            projection.variable += mean;
+            projection.variance += variance;
+        }
+    }
+
+    let mut total = CategoryProjection::default();
+    let categories = projections
+        .into_iter()
+        .map(|(name, p)| {
+            total.month_to_date += p.month_to_date;
+            total.recurring += p.recurring;
+            total.variable += p.variable;
+            total.variance += p.variance;
+            p.into_forecast(name)
+        })
+        .collect();
+
+    SpendingForecast {
+        month: start.format("%Y-%m").to_string(),
+        as_of: as_of.format("%Y-%m-%d").to_string(),
+        days_remaining: remaining.len() as i64,
+        categories,
+        total: total.into_forecast("Total".to_string()),
+    }
+}
+
+/// Income and expense per month (in cents), keyed by the first day of the month.
+pub type MonthlyTotals = BTreeMap<NaiveDate, (i64, i64)>;
+
+/// Folds `(month, type, sum)` rows into per-month `(income, expense)` totals.
+/// Transfers count as neither.
+pub fn fold_monthly_totals(rows: &[(NaiveDate, TransactionType, i64)]) -> MonthlyTotals {
+    let mut totals = MonthlyTotals::new();
+    for (month, kind, sum) in rows {
+        let entry = totals.entry(*month).or_default();
+        match kind {
+            TransactionType::Income => entry.0 += sum,
+            TransactionType::Expense => entry.1 += sum,
+            // Transfers move money inside the household.
+            TransactionType::Transfer => {}
+        }
+    }
+    totals
+}
+
+/// Builds the monthly savings report for the months in `[from, to]`.
+pub fn monthly_savings(
+    totals: &MonthlyTotals,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<MonthlySavings> {
+    totals
+        .range(month_start(from, 0)..=to)
+        .map(|(month, (income, expense))| {
+            let savings = income - expense;
+            let savings_rate = (*income > 0)
+                .then(|| ((savings as f64 / *income as f64) * 10_000.0).round() / 100.0);
+            MonthlySavings {
+                month: month.format("%Y-%m").to_string(),
+                income: cents_to_dollars(*income as f64),
+                expense: cents_to_dollars(*expense as f64),
+                savings: cents_to_dollars(savings as f64),
+                savings_rate,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use std::sync::Arc;
+
+use crate::domain::households::models::{ActiveHousehold, Role};
+use crate::{error_response, AppState, JsonResult};
+use backend::ErrorResponse;
+
+use super::models::{BackupArchive, RestoreSummary};
+use super::services::{load_backup, restore_archive, validate_archive};
+
+/// Handler for GET /backup.
+/// Downloads the active household's ledger as a versioned JSON archive; see
+/// [`BackupArchive`] for what it holds.
+#[debug_handler]
+pub async fn download_backup(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    // One snapshot, so rows added meanwhile cannot leave dangling references.
+    let archive = conn
+        .build_transaction()
+        .repeatable_read()
+        .read_only()
+        .run(|txn_conn| load_backup(txn_conn, household))
+        .map_err(|e| error_response(format!("Failed to create backup: {e}")))?;
+
+    let filename = format!(
+        "rusty-fin-backup-{}.json",
+        archive.created_at.format("%Y-%m-%d")
+    );
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )],
+        Json(archive),
+    )
+        .into_response())
+}
+
+/// Handler for POST /restore.
+/// Restores an archive from GET /backup into the active household, which may
+/// be empty or already hold data; all or nothing. Only owners may restore.
+#[debug_handler]
+pub async fn restore_backup(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(archive): Json<BackupArchive>,
+) -> JsonResult<RestoreSummary> {
+    if household.role != Role::Owner {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Only owners can restore a backup".to_string(),
+            }),
+        ));
+    }
+    validate_archive(&archive).map_err(error_response)?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let summary = conn
+        .transaction::<RestoreSummary, DieselError, _>(|txn_conn| {
+            restore_archive(txn_conn, household, &archive)
+        })
+        .map_err(|e| error_response(format!("Failed to restore backup: {e}")))?;
+
+    Ok(Json(summary))
+}
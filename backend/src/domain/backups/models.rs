@@ -0,0 +1,106 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::transactions::models::TransactionType;
+
+/// Marks a JSON document as one of our backups.
+pub const BACKUP_FORMAT: &str = "rusty-fin-backup";
+
+/// Archive layout version; bumped whenever a table or column is added to
+/// the archive.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// The household's ledger: categories, products and their prices,
+/// transactions and tags. Ids are the ones in the source database and are
+/// only used to link rows within the archive.
+///
+/// Everything else is left out: rules, recurring rules, price alerts, net
+/// worth snapshots, import profiles, and transaction splits, which involve
+/// other members' accounts. A restored transaction is no longer split.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupArchive {
+    pub format: String,
+    pub version: u32,
+    pub created_at: NaiveDateTime,
+    pub user: BackupUser,
+    pub categories: Vec<BackupCategory>,
+    pub products: Vec<BackupProduct>,
+    pub product_prices: Vec<BackupProductPrice>,
+    pub transactions: Vec<BackupTransaction>,
+    pub tags: Vec<BackupTag>,
+    pub transaction_tags: Vec<BackupTransactionTag>,
+}
+
+/// The user's settings. Credentials are never part of a backup.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupUser {
+    pub email: String,
+    pub price_alert_threshold: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupCategory {
+    pub id: i32,
+    pub parent_category_id: Option<i32>,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupProduct {
+    pub id: i32,
+    pub category_id: Option<i32>,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupProductPrice {
+    pub id: i32,
+    pub product_id: i32,
+    /// In cents.
+    pub price: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupTransaction {
+    pub id: i32,
+    pub product_id: i32,
+    pub product_price_id: i32,
+    pub transaction_type: TransactionType,
+    pub description: Option<String>,
+    pub date: NaiveDateTime,
+    pub external_id: Option<String>,
+    pub account: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    pub import_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupTag {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupTransactionTag {
+    pub transaction_id: i32,
+    pub tag_id: i32,
+}
+
+/// Rows of one table that a restore inserted or matched to existing ones.
+#[derive(Serialize, Default, Debug)]
+pub struct RestoreCount {
+    pub created: usize,
+    pub existing: usize,
+}
+
+/// Outcome of POST /restore.
+#[derive(Serialize, Default, Debug)]
+pub struct RestoreSummary {
+    pub categories: RestoreCount,
+    pub products: RestoreCount,
+    pub product_prices: RestoreCount,
+    pub transactions: RestoreCount,
+    pub tags: RestoreCount,
+    pub transaction_tags: RestoreCount,
+}
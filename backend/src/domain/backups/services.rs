@@ -0,0 +1,545 @@
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use crate::domain::categories::models::{Category, NewCategory};
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::product_prices::models::{NewProductPrice, ProductPrice};
+use crate::domain::products::models::{NewProduct, Product};
+use crate::domain::tags::models::{NewTag, Tag};
+use crate::domain::transactions::models::{NewTransaction, Transaction, TransactionType};
+
+use super::models::{
+    BackupArchive, BackupCategory, BackupProduct, BackupProductPrice, BackupTag, BackupTransaction,
+    BackupTransactionTag, BackupUser, RestoreSummary, BACKUP_FORMAT, BACKUP_VERSION,
+};
+
+/// Reads the household's ledger into an archive, each table ordered by id. The user section describes the member taking the backup.
+pub fn load_backup(
+    conn: &mut PgConnection,
+    household: ActiveHousehold,
+) -> QueryResult<BackupArchive> {
+    use crate::schema::categories::dsl as cat;
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::tags::dsl as tg;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+    use crate::schema::users::dsl as u;
+
+    let (email, price_alert_threshold) = u::users
+        .filter(u::id.eq(household.user_id))
+        .select((u::email, u::price_alert_threshold))
+        .first::<(String, i32)>(conn)?;
+
+    let categories = cat::categories
+        .filter(cat::household_id.eq(household.id))
+        .order(cat::id.asc())
+        .load::<Category>(conn)?;
+    let products = pr::products
+        .filter(pr::household_id.eq(household.id))
+        .order(pr::id.asc())
+        .load::<Product>(conn)?;
+    let product_prices = pp::product_prices
+        .inner_join(pr::products.on(pr::id.eq(pp::product_id)))
+        .filter(pr::household_id.eq(household.id))
+        .order(pp::id.asc())
+        .select(ProductPrice::as_select())
+        .load::<ProductPrice>(conn)?;
+    let transactions = tx::transactions
+        .filter(tx::household_id.eq(household.id))
+        .order(tx::id.asc())
+        .load::<Transaction>(conn)?;
+    let tags = tg::tags
+        .filter(tg::household_id.eq(household.id))
+        .order(tg::id.asc())
+        .load::<Tag>(conn)?;
+    let transaction_tags = tt::transaction_tags
+        .inner_join(tx::transactions.on(tx::id.eq(tt::transaction_id)))
+        .filter(tx::household_id.eq(household.id))
+        .order((tt::transaction_id.asc(), tt::tag_id.asc()))
+        .select((tt::transaction_id, tt::tag_id))
+        .load::<(i32, i32)>(conn)?;
+
+    Ok(BackupArchive {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now().naive_utc(),
+        user: BackupUser {
+            email,
+            price_alert_threshold,
+        },
+        categories: categories
+            .into_iter()
+            .map(|c| BackupCategory {
+                id: c.id,
+                parent_category_id: c.parent_category_id,
+                name: c.name,
+            })
+            .collect(),
+        products: products
+            .into_iter()
+            .map(|p| BackupProduct {
+                id: p.id,
+                category_id: p.category_id,
+                name: p.name,
+            })
+            .collect(),
+        product_prices: product_prices
+            .into_iter()
+            .map(|p| BackupProductPrice {
+                id: p.id,
+                product_id: p.product_id,
+                price: p.price,
+                created_at: p.created_at,
+            })
+            .collect(),
+        transactions: transactions
+            .into_iter()
+            .map(|t| BackupTransaction {
+                id: t.id,
+                product_id: t.product_id,
+                product_price_id: t.product_price_id,
+                transaction_type: t.transaction_type,
+                description: t.description,
+                date: t.date,
+                external_id: t.external_id,
+                account: t.account,
+                value_date: t.value_date,
+                import_hash: t.import_hash,
+            })
+            .collect(),
+        tags: tags
+            .into_iter()
+            .map(|t| BackupTag {
+                id: t.id,
+                name: t.name,
+            })
+            .collect(),
+        transaction_tags: transaction_tags
+            .into_iter()
+            .map(|(transaction_id, tag_id)| BackupTransactionTag {
+                transaction_id,
+                tag_id,
+            })
+            .collect(),
+    })
+}
+
+/// Collects ids and checks they are unique within their table.
+fn unique_ids(table: &str, ids: impl Iterator<Item = i32>) -> Result<HashSet<i32>, String> {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            return Err(format!("Duplicate id {id} in {table}"));
+        }
+    }
+    Ok(seen)
+}
+
+fn unique_names<'a>(table: &str, names: impl Iterator<Item = &'a String>) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(format!("Duplicate name {name:?} in {table}"));
+        }
+    }
+    Ok(())
+}
+
+fn check_reference(
+    table: &str,
+    column: &str,
+    id: i32,
+    targets: &HashSet<i32>,
+) -> Result<(), String> {
+    if targets.contains(&id) {
+        Ok(())
+    } else {
+        Err(format!("{table}.{column} refers to unknown id {id}"))
+    }
+}
+
+/// Categories ordered so every parent comes before its children, otherwise
+/// keeping id order so restored ids sort the same way as the originals.
+/// Categories in a parent cycle are left out.
+fn parents_first(categories: &[BackupCategory]) -> Vec<&BackupCategory> {
+    let mut children: HashMap<i32, Vec<&BackupCategory>> = HashMap::new();
+    let mut ready: BinaryHeap<Reverse<(i32, usize)>> = BinaryHeap::new();
+    for (idx, category) in categories.iter().enumerate() {
+        match category.parent_category_id {
+            Some(parent) => children.entry(parent).or_default().push(category),
+            None => ready.push(Reverse((category.id, idx))),
+        }
+    }
+    let index: HashMap<i32, usize> = categories
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| (c.id, idx))
+        .collect();
+
+    let mut ordered = Vec::with_capacity(categories.len());
+    while let Some(Reverse((id, idx))) = ready.pop() {
+        ordered.push(&categories[idx]);
+        for child in children.remove(&id).unwrap_or_default() {
+            ready.push(Reverse((child.id, index[&child.id])));
+        }
+    }
+    ordered
+}
+
+/// Checks that an archive is ours, not from a newer version, and internally
+/// consistent, so a restore cannot fail half-way on a bad reference.
+pub fn validate_archive(archive: &BackupArchive) -> Result<(), String> {
+    if archive.format != BACKUP_FORMAT {
+        return Err(format!(
+            "Not a backup archive: format is {:?}",
+            archive.format
+        ));
+    }
+    if archive.version == 0 || archive.version > BACKUP_VERSION {
+        return Err(format!(
+            "Unsupported backup version {} (this server reads up to {BACKUP_VERSION})",
+            archive.version
+        ));
+    }
+
+    let categories = unique_ids("categories", archive.categories.iter().map(|c| c.id))?;
+    let products = unique_ids("products", archive.products.iter().map(|p| p.id))?;
+    let prices = unique_ids(
+        "product_prices",
+        archive.product_prices.iter().map(|p| p.id),
+    )?;
+    let transactions = unique_ids("transactions", archive.transactions.iter().map(|t| t.id))?;
+    let tags = unique_ids("tags", archive.tags.iter().map(|t| t.id))?;
+    unique_names("categories", archive.categories.iter().map(|c| &c.name))?;
+    unique_names("products", archive.products.iter().map(|p| &p.name))?;
+    unique_names("tags", archive.tags.iter().map(|t| &t.name))?;
+
+    for category in &archive.categories {
+        if let Some(parent) = category.parent_category_id {
+            check_reference("categories", "parent_category_id", parent, &categories)?;
+        }
+    }
+    if parents_first(&archive.categories).len() != archive.categories.len() {
+        return Err("Categories contain a parent cycle".to_string());
+    }
+    for product in &archive.products {
+        if let Some(category) = product.category_id {
+            check_reference("products", "category_id", category, &categories)?;
+        }
+    }
+    let mut price_keys = HashSet::new();
+    for price in &archive.product_prices {
+        check_reference("product_prices", "product_id", price.product_id, &products)?;
+        if !price_keys.insert((price.product_id, price.price, price.created_at)) {
+            return Err(format!(
+                "Duplicate price in product_prices (id {})",
+                price.id
+            ));
+        }
+    }
+    let mut bank_ids = HashSet::new();
+    for transaction in &archive.transactions {
+        check_reference(
+            "transactions",
+            "product_id",
+            transaction.product_id,
+            &products,
+        )?;
+        check_reference(
+            "transactions",
+            "product_price_id",
+            transaction.product_price_id,
+            &prices,
+        )?;
+        for bank_id in [&transaction.external_id, &transaction.import_hash]
+            .into_iter()
+            .flatten()
+        {
+            if !bank_ids.insert(bank_id) {
+                return Err(format!(
+                    "Duplicate bank id {bank_id:?} in transactions (id {})",
+                    transaction.id
+                ));
+            }
+        }
+    }
+    let mut pairs = HashSet::new();
+    for link in &archive.transaction_tags {
+        check_reference(
+            "transaction_tags",
+            "transaction_id",
+            link.transaction_id,
+            &transactions,
+        )?;
+        check_reference("transaction_tags", "tag_id", link.tag_id, &tags)?;
+        if !pairs.insert((link.transaction_id, link.tag_id)) {
+            return Err(format!(
+                "Duplicate transaction_tags row ({}, {})",
+                link.transaction_id, link.tag_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Identifies a transaction by everything but its id.
+type TransactionKey = (
+    i32,
+    i32,
+    TransactionType,
+    Option<String>,
+    NaiveDateTime,
+    Option<String>,
+    Option<String>,
+    Option<NaiveDate>,
+    Option<String>,
+);
+
+/// Restores a validated archive into the household, remapping ids. The user
+/// settings go to the member restoring it.
+///
+/// Rows that already exist are matched instead of duplicated: categories,
+/// products and tags by name, prices by product, amount and time, and
+/// transactions by their content or bank id. Restoring the same archive
+/// twice therefore changes nothing the second time. Existing rows keep their
+/// values (an existing product keeps its category); the user's settings are
+/// taken from the archive. Runs inside the caller's database transaction.
+pub fn restore_archive(
+    txn_conn: &mut PgConnection,
+    household: ActiveHousehold,
+    archive: &BackupArchive,
+) -> QueryResult<RestoreSummary> {
+    use crate::schema::categories::dsl as cat;
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::tags::dsl as tg;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+    use crate::schema::users::dsl as u;
+
+    let mut summary = RestoreSummary::default();
+
+    diesel::update(u::users.filter(u::id.eq(household.user_id)))
+        .set(u::price_alert_threshold.eq(archive.user.price_alert_threshold))
+        .execute(txn_conn)?;
+
+    // Categories, parents first so their new ids are known.
+    let mut existing: HashMap<String, i32> = cat::categories
+        .filter(cat::household_id.eq(household.id))
+        .select((cat::name, cat::id))
+        .load::<(String, i32)>(txn_conn)?
+        .into_iter()
+        .collect();
+    let mut category_ids: HashMap<i32, i32> = HashMap::new();
+    for category in parents_first(&archive.categories) {
+        let new_id = match existing.get(&category.name) {
+            Some(id) => {
+                summary.categories.existing += 1;
+                *id
+            }
+            None => {
+                summary.categories.created += 1;
+                diesel::insert_into(cat::categories)
+                    .values(&NewCategory {
+                        household_id: household.id,
+                        parent_category_id: category
+                            .parent_category_id
+                            .and_then(|p| category_ids.get(&p).copied()),
+                        name: category.name.clone(),
+                    })
+                    .returning(cat::id)
+                    .get_result::<i32>(txn_conn)?
+            }
+        };
+        existing.insert(category.name.clone(), new_id);
+        category_ids.insert(category.id, new_id);
+    }
+
+    let existing: HashMap<String, i32> = pr::products
+        .filter(pr::household_id.eq(household.id))
+        .select((pr::name, pr::id))
+        .load::<(String, i32)>(txn_conn)?
+        .into_iter()
+        .collect();
+    let mut product_ids: HashMap<i32, i32> = HashMap::new();
+    for product in &archive.products {
+        let new_id = match existing.get(&product.name) {
+            Some(id) => {
+                summary.products.existing += 1;
+                *id
+            }
+            None => {
+                summary.products.created += 1;
+                diesel::insert_into(pr::products)
+                    .values(&NewProduct {
+                        household_id: household.id,
+                        category_id: product
+                            .category_id
+                            .and_then(|c| category_ids.get(&c).copied()),
+                        name: product.name.clone(),
+                    })
+                    .returning(pr::id)
+                    .get_result::<i32>(txn_conn)?
+            }
+        };
+        product_ids.insert(product.id, new_id);
+    }
+
+    let existing: HashMap<(i32, i32, NaiveDateTime), i32> = pp::product_prices
+        .inner_join(pr::products.on(pr::id.eq(pp::product_id)))
+        .filter(pr::household_id.eq(household.id))
+        .select((pp::product_id, pp::price, pp::created_at, pp::id))
+        .load::<(i32, i32, NaiveDateTime, i32)>(txn_conn)?
+        .into_iter()
+        .map(|(product_id, price, created_at, id)| ((product_id, price, created_at), id))
+        .collect();
+    let mut price_ids: HashMap<i32, i32> = HashMap::new();
+    for price in &archive.product_prices {
+        let product_id = product_ids[&price.product_id];
+        let new_id = match existing.get(&(product_id, price.price, price.created_at)) {
+            Some(id) => {
+                summary.product_prices.existing += 1;
+                *id
+            }
+            None => {
+                summary.product_prices.created += 1;
+                diesel::insert_into(pp::product_prices)
+                    .values(&NewProductPrice {
+                        product_id,
+                        price: price.price,
+                        created_at: price.created_at,
+                    })
+                    .returning(pp::id)
+                    .get_result::<i32>(txn_conn)?
+            }
+        };
+        price_ids.insert(price.id, new_id);
+    }
+
+    let existing: HashMap<String, i32> = tg::tags
+        .filter(tg::household_id.eq(household.id))
+        .select((tg::name, tg::id))
+        .load::<(String, i32)>(txn_conn)?
+        .into_iter()
+        .collect();
+    let mut tag_ids: HashMap<i32, i32> = HashMap::new();
+    for tag in &archive.tags {
+        let new_id = match existing.get(&tag.name) {
+            Some(id) => {
+                summary.tags.existing += 1;
+                *id
+            }
+            None => {
+                summary.tags.created += 1;
+                diesel::insert_into(tg::tags)
+                    .values(&NewTag {
+                        name: tag.name.clone(),
+                        household_id: household.id,
+                    })
+                    .returning(tg::id)
+                    .get_result::<i32>(txn_conn)?
+            }
+        };
+        tag_ids.insert(tag.id, new_id);
+    }
+
+    // Identical transactions may legitimately repeat, so existing ones are
+    // matched one-to-one rather than by set membership.
+    let mut by_content: HashMap<TransactionKey, VecDeque<i32>> = HashMap::new();
+    let mut by_bank_id: HashMap<String, i32> = HashMap::new();
+    let mut matched: HashSet<i32> = HashSet::new();
+    for t in tx::transactions
+        .filter(tx::household_id.eq(household.id))
+        .order(tx::id.asc())
+        .load::<Transaction>(txn_conn)?
+    {
+        for bank_id in [&t.external_id, &t.import_hash].into_iter().flatten() {
+            by_bank_id.insert(bank_id.clone(), t.id);
+        }
+        by_content
+            .entry((
+                t.product_id,
+                t.product_price_id,
+                t.transaction_type,
+                t.description,
+                t.date,
+                t.external_id,
+                t.account,
+                t.value_date,
+                t.import_hash,
+            ))
+            .or_default()
+            .push_back(t.id);
+    }
+    let mut transaction_ids: HashMap<i32, i32> = HashMap::new();
+    for t in &archive.transactions {
+        let new_transaction = NewTransaction {
+            household_id: household.id,
+            user_id: Some(household.user_id),
+            product_id: product_ids[&t.product_id],
+            product_price_id: price_ids[&t.product_price_id],
+            transaction_type: t.transaction_type,
+            description: t.description.clone(),
+            date: t.date,
+            external_id: t.external_id.clone(),
+            account: t.account.clone(),
+            value_date: t.value_date,
+            import_hash: t.import_hash.clone(),
+        };
+        let key: TransactionKey = (
+            new_transaction.product_id,
+            new_transaction.product_price_id,
+            t.transaction_type,
+            t.description.clone(),
+            t.date,
+            t.external_id.clone(),
+            t.account.clone(),
+            t.value_date,
+            t.import_hash.clone(),
+        );
+        let found = by_content
+            .get_mut(&key)
+            .and_then(|ids| ids.pop_front())
+            .or_else(|| {
+                [&t.external_id, &t.import_hash]
+                    .into_iter()
+                    .flatten()
+                    .find_map(|bank_id| by_bank_id.get(bank_id).copied())
+            })
+            .filter(|id| matched.insert(*id));
+        let new_id = match found {
+            Some(id) => {
+                summary.transactions.existing += 1;
+                id
+            }
+            None => {
+                summary.transactions.created += 1;
+                diesel::insert_into(tx::transactions)
+                    .values(&new_transaction)
+                    .returning(tx::id)
+                    .get_result::<i32>(txn_conn)?
+            }
+        };
+        transaction_ids.insert(t.id, new_id);
+    }
+
+    for link in &archive.transaction_tags {
+        let inserted = diesel::insert_into(tt::transaction_tags)
+            .values((
+                tt::transaction_id.eq(transaction_ids[&link.transaction_id]),
+                tt::tag_id.eq(tag_ids[&link.tag_id]),
+            ))
+            .on_conflict_do_nothing()
+            .execute(txn_conn)?;
+        if inserted == 0 {
+            summary.transaction_tags.existing += 1;
+        } else {
+            summary.transaction_tags.created += 1;
+        }
+    }
+
+    Ok(summary)
+}
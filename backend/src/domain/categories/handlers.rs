@@ -1,4 +1,5 @@
 use crate::domain::categories::models::CategoryDto;
+use crate::domain::households::models::ActiveHousehold;
 use axum::{debug_handler, extract::State, Extension, Json};
 use diesel::prelude::*;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
@@ -12,7 +13,7 @@ use crate::{error_response, schema, AppState, JsonResult};
 #[debug_handler]
 pub async fn create_category(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
     Json(payload): Json<CategoryPayload>,
 ) -> JsonResult<CreateCategoryResponse> {
     use schema::categories::dsl;
@@ -31,10 +32,10 @@ pub async fn create_category(
                 None
             } else {
                 use schema::categories::dsl as cat_dsl;
-                // Look for an existing parent category for the user.
+                // Look for an existing parent category in the household.
                 let existing_parent: Option<Category> = cat_dsl::categories
                     .filter(cat_dsl::name.eq(trimmed))
-                    .filter(cat_dsl::user_id.eq(logged_in_user_id))
+                    .filter(cat_dsl::household_id.eq(household.id))
                     .first::<Category>(txn_conn)
                     .optional()?;
                 if let Some(parent) = existing_parent {
@@ -42,7 +43,7 @@ pub async fn create_category(
                 } else {
                     // No existing parent found; create a new one.
                     let new_parent = NewCategory {
-                        user_id: logged_in_user_id,
+                        household_id: household.id,
                         parent_category_id: None,
                         name: trimmed.to_string(),
                     };
@@ -64,7 +65,7 @@ pub async fn create_category(
 
         // Create the new (child) category with the determined parent_id.
         let new_cat = NewCategory {
-            user_id: logged_in_user_id,
+            household_id: household.id,
             parent_category_id: parent_id,
             name: payload.name.clone(),
         };
@@ -107,7 +108,7 @@ pub async fn create_category(
 #[debug_handler]
 pub async fn list_categories(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
 ) -> JsonResult<Vec<Category>> {
     use schema::categories::dsl::*;
     let mut conn = state
@@ -116,7 +117,7 @@ pub async fn list_categories(
         .map_err(|_| error_response("Failed to fetch connection from pool"))?;
 
     let items = categories
-        .filter(user_id.eq(logged_in_user_id))
+        .filter(household_id.eq(household.id))
         .load::<Category>(&mut conn)
         .map_err(|e| error_response(format!("Error loading categories: {e}")))?;
 
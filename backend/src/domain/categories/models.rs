@@ -7,13 +7,13 @@ pub struct Category {
     pub id: i32,
     pub parent_category_id: Option<i32>,
     pub name: String,
-    pub user_id: i32,
+    pub household_id: i32,
 }
 
 #[derive(diesel::Insertable)]
 #[diesel(table_name = categories)]
 pub struct NewCategory {
-    pub user_id: i32,
+    pub household_id: i32,
     pub parent_category_id: Option<i32>,
     pub name: String,
 }
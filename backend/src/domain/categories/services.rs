@@ -0,0 +1,42 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use super::models::Category;
+
+/// Names from the root down to each category, following `parent_category_id`.
+///
+/// A parent that is missing or part of a cycle ends the path there.
+pub fn category_paths(categories: &[Category]) -> HashMap<i32, Vec<String>> {
+    let by_id: HashMap<i32, &Category> = categories.iter().map(|c| (c.id, c)).collect();
+    categories
+        .iter()
+        .map(|category| {
+            let mut path = vec![category.name.clone()];
+            let mut seen = vec![category.id];
+            let mut parent = category.parent_category_id;
+            while let Some(parent_category) = parent.and_then(|id| by_id.get(&id)) {
+                if seen.contains(&parent_category.id) {
+                    break;
+                }
+                seen.push(parent_category.id);
+                path.push(parent_category.name.clone());
+                parent = parent_category.parent_category_id;
+            }
+            path.reverse();
+            (category.id, path)
+        })
+        .collect()
+}
+
+/// Loads the household's categories and returns their paths.
+pub fn load_category_paths(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> QueryResult<HashMap<i32, Vec<String>>> {
+    use crate::schema::categories::dsl;
+
+    let categories = dsl::categories
+        .filter(dsl::household_id.eq(household_id))
+        .load::<Category>(conn)?;
+    Ok(category_paths(&categories))
+}
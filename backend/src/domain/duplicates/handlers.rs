@@ -0,0 +1,220 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Query, State},
+    Json,
+};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::transactions::models::{Transaction, TransactionType};
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{
+    DuplicateAction, DuplicateCandidate, DuplicateQuery, DuplicateReview, DuplicateReviewDto,
+    DuplicateReviewPayload, DuplicateTransaction, NewDuplicateReview,
+};
+use super::services::find_duplicate_candidates;
+
+/// Handler for GET /transactions/duplicates.
+/// Lists pairs of transactions that are probably the same payment.
+#[debug_handler]
+pub async fn list_duplicates(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<DuplicateQuery>,
+) -> JsonResult<Vec<DuplicateCandidate>> {
+    use crate::schema::duplicate_reviews::dsl as dr;
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transactions::dsl as tx;
+
+    let days = query.days.unwrap_or(3);
+    let min_similarity = query.min_similarity.unwrap_or(0.5);
+    if !(0..=31).contains(&days) {
+        return Err(error_response("days must be between 0 and 31"));
+    }
+    if !(0.0..=1.0).contains(&min_similarity) {
+        return Err(error_response("min_similarity must be between 0 and 1"));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let rows = tx::transactions
+        .filter(tx::household_id.eq(household.id))
+        .inner_join(pr::products.on(pr::id.eq(tx::product_id)))
+        .inner_join(pp::product_prices.on(pp::id.eq(tx::product_price_id)))
+        .select((
+            tx::id,
+            pr::name,
+            tx::description,
+            pp::price,
+            tx::transaction_type,
+            tx::date,
+            tx::external_id,
+        ))
+        .load::<(
+            i32,
+            String,
+            Option<String>,
+            i32,
+            TransactionType,
+            NaiveDateTime,
+            Option<String>,
+        )>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading transactions: {e}")))?;
+
+    let transactions: Vec<DuplicateTransaction> = rows
+        .into_iter()
+        .map(
+            |(id, product_name, description, price, transaction_type, date, external_id)| {
+                DuplicateTransaction {
+                    id,
+                    product_name,
+                    description,
+                    amount: price as f64 / 100.0,
+                    transaction_type,
+                    date,
+                    external_id,
+                }
+            },
+        )
+        .collect();
+
+    let dismissed: HashSet<(i32, i32)> = dr::duplicate_reviews
+        .filter(dr::household_id.eq(household.id))
+        .filter(dr::action.eq(DuplicateAction::Dismiss))
+        .select((dr::transaction_id, dr::duplicate_id))
+        .load::<(i32, i32)>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading reviews: {e}")))?
+        .into_iter()
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+
+    Ok(Json(find_duplicate_candidates(
+        &transactions,
+        days,
+        min_similarity,
+        &dismissed,
+    )))
+}
+
+/// Deletes `duplicate` after moving its tags and review history to `kept`.
+/// Bank details the kept transaction lacks are taken over from the duplicate.
+fn merge_transactions(
+    txn_conn: &mut PgConnection,
+    kept: &Transaction,
+    duplicate: &Transaction,
+) -> QueryResult<()> {
+    use crate::schema::duplicate_reviews::dsl as dr;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+
+    let tag_ids = tt::transaction_tags
+        .filter(tt::transaction_id.eq(duplicate.id))
+        .select(tt::tag_id)
+        .load::<i32>(txn_conn)?;
+    for tag_id in tag_ids {
+        diesel::insert_into(tt::transaction_tags)
+            .values((tt::transaction_id.eq(kept.id), tt::tag_id.eq(tag_id)))
+            .on_conflict_do_nothing()
+            .execute(txn_conn)?;
+    }
+    diesel::delete(tt::transaction_tags.filter(tt::transaction_id.eq(duplicate.id)))
+        .execute(txn_conn)?;
+
+    // Earlier merges into the duplicate must keep skipping their bank ids.
+    diesel::update(dr::duplicate_reviews.filter(dr::transaction_id.eq(duplicate.id)))
+        .set(dr::transaction_id.eq(kept.id))
+        .execute(txn_conn)?;
+
+    diesel::delete(tx::transactions.filter(tx::id.eq(duplicate.id))).execute(txn_conn)?;
+
+    diesel::update(tx::transactions.filter(tx::id.eq(kept.id)))
+        .set((
+            tx::description.eq(kept
+                .description
+                .clone()
+                .or_else(|| duplicate.description.clone())),
+            tx::external_id.eq(kept
+                .external_id
+                .clone()
+                .or_else(|| duplicate.external_id.clone())),
+            tx::import_hash.eq(kept
+                .import_hash
+                .clone()
+                .or_else(|| duplicate.import_hash.clone())),
+            tx::account.eq(kept.account.clone().or_else(|| duplicate.account.clone())),
+            tx::value_date.eq(kept.value_date.or(duplicate.value_date)),
+        ))
+        .execute(txn_conn)?;
+    Ok(())
+}
+
+/// Handler for POST /transactions/duplicates/review.
+/// Merges a duplicate into the kept transaction, or dismisses the pair.
+#[debug_handler]
+pub async fn review_duplicate(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(payload): Json<DuplicateReviewPayload>,
+) -> JsonResult<DuplicateReviewDto> {
+    use crate::schema::duplicate_reviews::dsl as dr;
+    use crate::schema::transactions::dsl as tx;
+
+    if payload.transaction_id == payload.duplicate_id {
+        return Err(error_response(
+            "A transaction cannot be a duplicate of itself",
+        ));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let found = tx::transactions
+        .filter(tx::household_id.eq(household.id))
+        .filter(tx::id.eq_any([payload.transaction_id, payload.duplicate_id]))
+        .load::<Transaction>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading transactions: {e}")))?;
+    let find = |id: i32| found.iter().find(|t| t.id == id);
+    let (Some(kept), Some(duplicate)) = (find(payload.transaction_id), find(payload.duplicate_id))
+    else {
+        return Err(error_response("Transaction not found"));
+    };
+
+    // Only a merged duplicate disappears, so only its bank ids need keeping.
+    let merge = payload.action == DuplicateAction::Merge;
+    let new_review = NewDuplicateReview {
+        household_id: household.id,
+        transaction_id: kept.id,
+        duplicate_id: duplicate.id,
+        action: payload.action,
+        external_id: duplicate.external_id.clone().filter(|_| merge),
+        import_hash: duplicate.import_hash.clone().filter(|_| merge),
+    };
+
+    let result = conn.transaction::<DuplicateReview, DieselError, _>(|txn_conn| {
+        if merge {
+            merge_transactions(txn_conn, kept, duplicate)?;
+        }
+        diesel::insert_into(dr::duplicate_reviews)
+            .values(&new_review)
+            .get_result::<DuplicateReview>(txn_conn)
+    });
+
+    match result {
+        Ok(review) => Ok(Json(review.into())),
+        Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            Err(error_response("This pair has already been reviewed"))
+        }
+        Err(e) => Err(error_response(format!("Failed to review duplicate: {e}"))),
+    }
+}
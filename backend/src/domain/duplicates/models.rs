@@ -0,0 +1,131 @@
+use crate::domain::transactions::models::TransactionType;
+use crate::schema::duplicate_reviews;
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::{AsExpression, FromSqlRow, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+// What to do with a duplicate candidate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = Text)]
+pub enum DuplicateAction {
+    /// Keep one transaction and delete the other, moving its tags over.
+    Merge,
+    /// Not a duplicate; never suggest this pair again.
+    Dismiss,
+}
+
+impl ToSql<Text, Pg> for DuplicateAction {
+    fn to_sql(&self, out: &mut Output<'_, '_, Pg>) -> serialize::Result {
+        match self {
+            DuplicateAction::Merge => out.write_all(b"merge")?,
+            DuplicateAction::Dismiss => out.write_all(b"dismiss")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl diesel::deserialize::FromSql<Text, Pg> for DuplicateAction {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        let s = <String as diesel::deserialize::FromSql<Text, Pg>>::from_sql(bytes)?;
+        match s.as_str() {
+            "merge" => Ok(DuplicateAction::Merge),
+            "dismiss" => Ok(DuplicateAction::Dismiss),
+            _ => Err(format!("Invalid duplicate action: {}", s).into()),
+        }
+    }
+}
+
+/// A recorded decision on a duplicate candidate.
+#[derive(Selectable, Queryable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = duplicate_reviews)]
+pub struct DuplicateReview {
+    pub id: i32,
+    pub household_id: i32,
+    pub transaction_id: i32,
+    pub duplicate_id: i32,
+    pub action: DuplicateAction,
+    /// External id and import hash of a merged duplicate, so re-imports skip it.
+    pub external_id: Option<String>,
+    pub import_hash: Option<String>,
+    pub reviewed_at: NaiveDateTime,
+}
+
+/// Used for inserting a new review.
+#[derive(Insertable)]
+#[diesel(table_name = duplicate_reviews)]
+pub struct NewDuplicateReview {
+    pub household_id: i32,
+    pub transaction_id: i32,
+    pub duplicate_id: i32,
+    pub action: DuplicateAction,
+    pub external_id: Option<String>,
+    pub import_hash: Option<String>,
+}
+
+/// Query parameters for GET /transactions/duplicates.
+#[derive(Deserialize)]
+pub struct DuplicateQuery {
+    /// How many days apart two transactions may be (default 3).
+    pub days: Option<i64>,
+    /// Minimum word overlap of product name and description, 0..1 (default 0.5).
+    pub min_similarity: Option<f64>,
+}
+
+/// One side of a duplicate candidate.
+#[derive(Serialize, Debug, Clone)]
+pub struct DuplicateTransaction {
+    pub id: i32,
+    pub product_name: String,
+    pub description: Option<String>,
+    pub amount: f64,
+    pub transaction_type: TransactionType,
+    pub date: NaiveDateTime,
+    pub external_id: Option<String>,
+}
+
+/// Two transactions that look like the same real-world payment.
+#[derive(Serialize, Debug)]
+pub struct DuplicateCandidate {
+    /// The older record, kept when merging.
+    pub transaction: DuplicateTransaction,
+    /// The newer record, deleted when merging.
+    pub duplicate: DuplicateTransaction,
+    pub days_apart: i64,
+    pub similarity: f64,
+}
+
+/// The payload for POST /transactions/duplicates/review.
+#[derive(Deserialize)]
+pub struct DuplicateReviewPayload {
+    pub transaction_id: i32,
+    pub duplicate_id: i32,
+    pub action: DuplicateAction,
+}
+
+/// DTO for returning a review.
+#[derive(Serialize)]
+pub struct DuplicateReviewDto {
+    pub id: i32,
+    pub transaction_id: i32,
+    pub duplicate_id: i32,
+    pub action: DuplicateAction,
+    pub reviewed_at: NaiveDateTime,
+}
+
+impl From<DuplicateReview> for DuplicateReviewDto {
+    fn from(review: DuplicateReview) -> Self {
+        Self {
+            id: review.id,
+            transaction_id: review.transaction_id,
+            duplicate_id: review.duplicate_id,
+            action: review.action,
+            reviewed_at: review.reviewed_at,
+        }
+    }
+}
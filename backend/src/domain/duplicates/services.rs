@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use super::models::{DuplicateCandidate, DuplicateTransaction};
+
+/// Lowercase words of a transaction's product name and description.
+fn words(tx: &DuplicateTransaction) -> HashSet<String> {
+    let text = format!(
+        "{} {}",
+        tx.product_name,
+        tx.description.as_deref().unwrap_or("")
+    );
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Jaccard similarity of two word sets (1.0 for identical text).
+pub fn text_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Finds pairs with the same type and amount, at most `days` apart, whose
+/// text is at least `min_similarity` alike.
+///
+/// Pairs with two different external ids are the bank saying they are
+/// distinct and are never suggested, nor are pairs in `dismissed`
+/// (stored as (lower id, higher id)). The older record comes first.
+pub fn find_duplicate_candidates(
+    transactions: &[DuplicateTransaction],
+    days: i64,
+    min_similarity: f64,
+    dismissed: &HashSet<(i32, i32)>,
+) -> Vec<DuplicateCandidate> {
+    let mut sorted: Vec<&DuplicateTransaction> = transactions.iter().collect();
+    sorted.sort_by_key(|tx| (tx.date, tx.id));
+    let word_sets: Vec<HashSet<String>> = sorted.iter().map(|tx| words(tx)).collect();
+
+    let mut candidates = Vec::new();
+    for (i, a) in sorted.iter().enumerate() {
+        for (j, b) in sorted.iter().enumerate().skip(i + 1) {
+            let days_apart = (b.date.date() - a.date.date()).num_days();
+            if days_apart > days {
+                break;
+            }
+            if a.amount != b.amount || a.transaction_type != b.transaction_type {
+                continue;
+            }
+            if let (Some(x), Some(y)) = (&a.external_id, &b.external_id) {
+                if x != y {
+                    continue;
+                }
+            }
+            let (kept, duplicate) = if a.id < b.id { (a, b) } else { (b, a) };
+            if dismissed.contains(&(kept.id, duplicate.id)) {
+                continue;
+            }
+            let similarity = text_similarity(&word_sets[i], &word_sets[j]);
+            if similarity < min_similarity {
+                continue;
+            }
+            candidates.push(DuplicateCandidate {
+                transaction: (*kept).clone(),
+                duplicate: (*duplicate).clone(),
+                days_apart,
+                similarity: (similarity * 100.0).round() / 100.0,
+            });
+        }
+    }
+    candidates
+}
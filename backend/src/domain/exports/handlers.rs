@@ -0,0 +1,211 @@
+use axum::{
+    body::{Body, Bytes},
+    debug_handler,
+    extract::{Extension, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, PooledConnection};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::domain::categories::services::load_category_paths;
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::transactions::models::TransactionFilter;
+use crate::{error_response, AppState};
+use backend::ErrorResponse;
+
+use super::models::{ExportFormat, ExportQuery, ExportRow, JournalQuery};
+use super::services::{
+    category_page, price_page, product_page, transaction_page, write_export, write_journal,
+};
+
+type ExportResult = Result<Response, (StatusCode, Json<ErrorResponse>)>;
+type Conn = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Streams a file produced on a blocking thread as the response body.
+///
+/// The connection is taken before the response starts so pool errors are
+/// still reported as JSON; later failures abort the download.
+fn stream_export<P>(
+    mut conn: Conn,
+    content_type: &'static str,
+    filename: String,
+    produce: P,
+) -> Response
+where
+    P: FnOnce(&mut PgConnection, &mut dyn FnMut(Vec<u8>) -> bool) -> Result<(), String>
+        + Send
+        + 'static,
+{
+    let (tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(4);
+    tokio::task::spawn_blocking(move || {
+        let mut send = |chunk: Vec<u8>| tx.blocking_send(Ok(Bytes::from(chunk))).is_ok();
+        if let Err(e) = produce(&mut conn, &mut send) {
+            let _ = tx.blocking_send(Err(std::io::Error::other(e)));
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+/// Streams a table export as CSV, JSON Lines or XLSX.
+fn table_export<R, F>(
+    conn: Conn,
+    format: ExportFormat,
+    name: &'static str,
+    load_page: F,
+) -> Response
+where
+    R: ExportRow + 'static,
+    F: FnMut(&mut PgConnection, i32) -> QueryResult<Vec<R>> + Send + 'static,
+{
+    stream_export(
+        conn,
+        format.content_type(),
+        format!("{name}.{}", format.extension()),
+        move |conn, send| write_export(conn, format, name, load_page, send),
+    )
+}
+
+fn pooled_connection(state: &AppState) -> Result<Conn, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))
+}
+
+/// Handler for GET /export/transactions.
+/// Accepts the same filters as GET /transactions.
+#[debug_handler]
+pub async fn export_transactions(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ExportQuery>,
+    Query(filter): Query<TransactionFilter>,
+) -> ExportResult {
+    let mut conn = pooled_connection(&state)?;
+    let paths = load_category_paths(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Error loading categories: {e}")))?;
+    Ok(table_export(
+        conn,
+        query.format,
+        "transactions",
+        move |conn, after_id| transaction_page(conn, household.id, &filter, &paths, after_id),
+    ))
+}
+
+/// Handler for GET /export/products.
+#[debug_handler]
+pub async fn export_products(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ExportQuery>,
+) -> ExportResult {
+    let mut conn = pooled_connection(&state)?;
+    let paths = load_category_paths(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Error loading categories: {e}")))?;
+    Ok(table_export(
+        conn,
+        query.format,
+        "products",
+        move |conn, after_id| product_page(conn, household.id, &paths, after_id),
+    ))
+}
+
+/// Handler for GET /export/prices.
+#[debug_handler]
+pub async fn export_prices(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ExportQuery>,
+) -> ExportResult {
+    let conn = pooled_connection(&state)?;
+    Ok(table_export(
+        conn,
+        query.format,
+        "prices",
+        move |conn, after_id| price_page(conn, household.id, after_id),
+    ))
+}
+
+/// Handler for GET /export/categories.
+#[debug_handler]
+pub async fn export_categories(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ExportQuery>,
+) -> ExportResult {
+    let mut conn = pooled_connection(&state)?;
+    let paths = load_category_paths(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Error loading categories: {e}")))?;
+    Ok(table_export(
+        conn,
+        query.format,
+        "categories",
+        move |conn, after_id| category_page(conn, household.id, &paths, after_id),
+    ))
+}
+
+/// Beancount's commodity syntax, which ledger and hledger also accept unquoted.
+fn valid_commodity(commodity: &str) -> bool {
+    let mut chars = commodity.chars();
+    (1..=24).contains(&commodity.len())
+        && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && commodity
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || "'._-".contains(c))
+        && commodity.ends_with(|c: char| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Handler for GET /export/journal.
+/// Renders transactions as a ledger, hledger or beancount journal; accepts
+/// the same filters as GET /transactions.
+#[debug_handler]
+pub async fn export_journal(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<JournalQuery>,
+    Query(filter): Query<TransactionFilter>,
+) -> ExportResult {
+    let commodity = query.commodity.unwrap_or_else(|| "USD".to_string());
+    if !valid_commodity(&commodity) {
+        return Err(error_response(
+            "commodity must be 1-24 capital letters, digits or '._- and start with a letter",
+        ));
+    }
+
+    let mut conn = pooled_connection(&state)?;
+    let paths = load_category_paths(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Error loading categories: {e}")))?;
+    let format = query.format;
+    Ok(stream_export(
+        conn,
+        "text/plain; charset=utf-8",
+        format!("transactions.{}", format.extension()),
+        move |conn, send| {
+            write_journal(
+                conn,
+                household.id,
+                &filter,
+                &paths,
+                format,
+                &commodity,
+                send,
+            )
+        },
+    ))
+}
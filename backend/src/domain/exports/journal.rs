@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{JournalFormat, TransactionExportRow};
+
+/// Asset account for transactions without a bank account.
+pub const CASH_ACCOUNT: &str = "Assets:Cash";
+
+/// Account transfers between household members post to, whatever their
+/// category.
+pub const TRANSFER_ACCOUNT: &str = "Equity:Transfers";
+
+/// Category account for products without a category.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Beancount metadata key holding the tag list.
+pub const TAGS_KEY: &str = "tags";
+/// Beancount metadata key holding the bank's transaction id.
+pub const EXTERNAL_ID_KEY: &str = "external_id";
+/// Beancount metadata key holding the raw bank account.
+pub const BANK_ACCOUNT_KEY: &str = "bank_account";
+/// Beancount metadata key holding the value date.
+pub const VALUE_DATE_KEY: &str = "value_date";
+
+/// Turns a name into one account component: "eating out" -> "Eating-Out".
+///
+/// Beancount only accepts components that start with a capital letter or a
+/// digit and contain letters, digits and dashes; the other dialects get the
+/// same names so all three exports line up.
+pub fn account_component(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            let first = chars.next().map(|c| c.to_uppercase().collect::<String>());
+            first.unwrap_or_default() + chars.as_str()
+        })
+        .collect();
+    if words.is_empty() {
+        "Unnamed".to_string()
+    } else {
+        words.join("-")
+    }
+}
+
+/// "Expenses:Food:Groceries" for an expense in Food > Groceries.
+pub fn category_account(transaction_type: TransactionType, category_names: &[String]) -> String {
+    let root = match transaction_type {
+        TransactionType::Expense => "Expenses",
+        TransactionType::Income => "Income",
+        TransactionType::Transfer => return TRANSFER_ACCOUNT.to_string(),
+    };
+    let components: Vec<String> = if category_names.is_empty() {
+        vec![UNCATEGORIZED.to_string()]
+    } else {
+        category_names
+            .iter()
+            .map(|n| account_component(n))
+            .collect()
+    };
+    format!("{root}:{}", components.join(":"))
+}
+
+/// "Assets:DE89370400440532013000" for a transaction booked on that account.
+pub fn asset_account(account: Option<&str>) -> String {
+    match account {
+        Some(account) => format!("Assets:{}", account_component(account)),
+        None => CASH_ACCOUNT.to_string(),
+    }
+}
+
+/// Tag names may not contain spaces or colons in ledger and hledger.
+fn tag_name(tag: &str) -> String {
+    tag.split(|c: char| c.is_whitespace() || c == ':' || c == ',')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// "-2.99 USD" for -299 cents.
+fn money(cents: i64, commodity: &str) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02} {commodity}", cents / 100, cents % 100)
+}
+
+/// A beancount string literal.
+fn quoted(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        single_line(text).replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// `open` directives for every account a beancount export may post to.
+/// `Equity:Transfers` is only opened when the export has transfers.
+pub fn beancount_header(
+    commodity: &str,
+    category_paths: &[Vec<String>],
+    bank_accounts: &[String],
+    has_transfers: bool,
+) -> String {
+    let mut accounts = BTreeSet::from([CASH_ACCOUNT.to_string()]);
+    if has_transfers {
+        accounts.insert(TRANSFER_ACCOUNT.to_string());
+    }
+    for transaction_type in [TransactionType::Expense, TransactionType::Income] {
+        accounts.insert(category_account(transaction_type, &[]));
+        for path in category_paths {
+            accounts.insert(category_account(transaction_type, path));
+        }
+    }
+    for account in bank_accounts {
+        accounts.insert(asset_account(Some(account)));
+    }
+
+    let mut out = format!("option \"operating_currency\" \"{commodity}\"\n\n");
+    for account in accounts {
+        let _ = writeln!(out, "1970-01-01 open {account}");
+    }
+    out.push('\n');
+    out
+}
+
+/// Renders one transaction as a balanced two-posting entry.
+///
+/// Expenses debit the category account and credit the asset account; income
+/// credits the category account under `Income:`. Transfers are booked like
+/// expenses against `Equity:Transfers`. Tags become ledger/hledger
+/// tags or a beancount `tags` metadata string.
+pub fn render_entry(row: &TransactionExportRow, format: JournalFormat, commodity: &str) -> String {
+    let category = category_account(row.transaction_type, &row.category_names);
+    let asset = asset_account(row.account.as_deref());
+    let cents = (row.price * 100.0).round() as i64;
+    let amount = money(cents, commodity);
+    let negated = money(-cents, commodity);
+    let (debit, credit) = match row.transaction_type {
+        TransactionType::Expense | TransactionType::Transfer => (category, asset),
+        TransactionType::Income => (asset, category),
+    };
+    let date = row.date.format("%Y-%m-%d");
+    let mut out = String::new();
+
+    match format {
+        JournalFormat::Ledger | JournalFormat::Hledger => {
+            let _ = write!(out, "{date} * {}", single_line(&row.product));
+            if let Some(description) = &row.description {
+                let _ = write!(out, " | {}", single_line(description));
+            }
+            out.push('\n');
+            let tags: Vec<String> = row
+                .tags
+                .iter()
+                .map(|t| tag_name(t))
+                .filter(|t| !t.is_empty())
+                .collect();
+            if !tags.is_empty() {
+                let _ = match format {
+                    JournalFormat::Ledger => writeln!(out, "    ; :{}:", tags.join(":")),
+                    _ => writeln!(out, "    ; {}:", tags.join(":, ")),
+                };
+            }
+            for (key, value) in [
+                (EXTERNAL_ID_KEY, row.external_id.clone()),
+                (VALUE_DATE_KEY, row.value_date.map(|d| d.to_string())),
+            ] {
+                if let Some(value) = value {
+                    let _ = writeln!(out, "    ; {key}: {}", single_line(&value));
+                }
+            }
+        }
+        JournalFormat::Beancount => {
+            let description = row.description.as_deref().unwrap_or("");
+            let _ = writeln!(
+                out,
+                "{date} * {} {}",
+                quoted(&row.product),
+                quoted(description)
+            );
+            if !row.tags.is_empty() {
+                let _ = writeln!(out, "  {TAGS_KEY}: {}", quoted(&row.tags.join(", ")));
+            }
+            if let Some(external_id) = &row.external_id {
+                let _ = writeln!(out, "  {EXTERNAL_ID_KEY}: {}", quoted(external_id));
+            }
+            if let Some(account) = &row.account {
+                let _ = writeln!(out, "  {BANK_ACCOUNT_KEY}: {}", quoted(account));
+            }
+            if let Some(value_date) = row.value_date {
+                let _ = writeln!(out, "  {VALUE_DATE_KEY}: {value_date}");
+            }
+        }
+    }
+
+    let indent = match format {
+        JournalFormat::Beancount => "  ",
+        _ => "    ",
+    };
+    let width = debit.len().max(credit.len()) + 2;
+    let _ = writeln!(out, "{indent}{debit:<width$}{amount:>14}");
+    let _ = writeln!(out, "{indent}{credit:<width$}{negated:>14}");
+    out.push('\n');
+    out
+}
@@ -0,0 +1,4 @@
+pub mod handlers;
+pub mod journal;
+pub mod models;
+pub mod services;
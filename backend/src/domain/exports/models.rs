@@ -0,0 +1,248 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::imports::csv_import::TAG_SEPARATOR;
+use crate::domain::transactions::models::TransactionType;
+
+/// File format of an export.
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Query parameters for the export endpoints (next to the transaction filters).
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Plain-text accounting dialect of a journal export.
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalFormat {
+    #[default]
+    Ledger,
+    Hledger,
+    Beancount,
+}
+
+impl JournalFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            JournalFormat::Ledger => "ledger",
+            JournalFormat::Hledger => "journal",
+            JournalFormat::Beancount => "beancount",
+        }
+    }
+}
+
+/// Query parameters for GET /export/journal (next to the transaction filters).
+#[derive(Deserialize)]
+pub struct JournalQuery {
+    #[serde(default)]
+    pub format: JournalFormat,
+    /// Commodity written after every amount; "USD" by default.
+    pub commodity: Option<String>,
+}
+
+/// A single spreadsheet cell.
+pub enum ExportCell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl ExportCell {
+    fn text(value: impl ToString) -> Self {
+        ExportCell::Text(value.to_string())
+    }
+
+    fn optional(value: Option<impl ToString>) -> Self {
+        value.map(ExportCell::text).unwrap_or(ExportCell::Empty)
+    }
+
+    /// The cell as CSV text; money keeps two decimals.
+    pub fn to_csv(&self) -> String {
+        match self {
+            ExportCell::Text(text) => text.clone(),
+            ExportCell::Number(number) => format!("{number:.2}"),
+            ExportCell::Empty => String::new(),
+        }
+    }
+}
+
+/// A record that can be written as CSV, JSON Lines or XLSX.
+pub trait ExportRow: Serialize {
+    /// Column names, in the order of `cells`.
+    const HEADERS: &'static [&'static str];
+
+    /// Primary key, used to page through the table.
+    fn id(&self) -> i32;
+
+    fn cells(&self) -> Vec<ExportCell>;
+}
+
+/// A transaction with its product, category path, price and tags flattened.
+#[derive(Serialize, Debug)]
+pub struct TransactionExportRow {
+    pub id: i32,
+    pub date: NaiveDateTime,
+    pub transaction_type: TransactionType,
+    pub product_id: i32,
+    pub product: String,
+    /// E.g. "Food > Groceries"; empty for uncategorized products.
+    pub category_path: Option<String>,
+    /// The same path as names, for journal account names.
+    #[serde(skip)]
+    pub category_names: Vec<String>,
+    /// Price as a float (dollars).
+    pub price: f64,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub account: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    pub external_id: Option<String>,
+}
+
+impl ExportRow for TransactionExportRow {
+    const HEADERS: &'static [&'static str] = &[
+        "id",
+        "date",
+        "transaction_type",
+        "product_id",
+        "product",
+        "category_path",
+        "price",
+        "description",
+        "tags",
+        "account",
+        "value_date",
+        "external_id",
+    ];
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn cells(&self) -> Vec<ExportCell> {
+        vec![
+            ExportCell::text(self.id),
+            ExportCell::text(self.date.format("%Y-%m-%dT%H:%M:%S")),
+            ExportCell::text(format!("{:?}", self.transaction_type)),
+            ExportCell::text(self.product_id),
+            ExportCell::text(&self.product),
+            ExportCell::optional(self.category_path.as_ref()),
+            ExportCell::Number(self.price),
+            ExportCell::optional(self.description.as_ref()),
+            ExportCell::text(self.tags.join(&TAG_SEPARATOR.to_string())),
+            ExportCell::optional(self.account.as_ref()),
+            ExportCell::optional(self.value_date),
+            ExportCell::optional(self.external_id.as_ref()),
+        ]
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProductExportRow {
+    pub id: i32,
+    pub name: String,
+    pub category_id: Option<i32>,
+    pub category_path: Option<String>,
+}
+
+impl ExportRow for ProductExportRow {
+    const HEADERS: &'static [&'static str] = &["id", "name", "category_id", "category_path"];
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn cells(&self) -> Vec<ExportCell> {
+        vec![
+            ExportCell::text(self.id),
+            ExportCell::text(&self.name),
+            ExportCell::optional(self.category_id),
+            ExportCell::optional(self.category_path.as_ref()),
+        ]
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct PriceExportRow {
+    pub id: i32,
+    pub product_id: i32,
+    pub product: String,
+    /// Price as a float (dollars).
+    pub price: f64,
+    pub created_at: NaiveDateTime,
+}
+
+impl ExportRow for PriceExportRow {
+    const HEADERS: &'static [&'static str] =
+        &["id", "product_id", "product", "price", "created_at"];
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn cells(&self) -> Vec<ExportCell> {
+        vec![
+            ExportCell::text(self.id),
+            ExportCell::text(self.product_id),
+            ExportCell::text(&self.product),
+            ExportCell::Number(self.price),
+            ExportCell::text(self.created_at.format("%Y-%m-%dT%H:%M:%S")),
+        ]
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct CategoryExportRow {
+    pub id: i32,
+    pub name: String,
+    pub parent_category_id: Option<i32>,
+    pub path: String,
+}
+
+impl ExportRow for CategoryExportRow {
+    const HEADERS: &'static [&'static str] = &["id", "name", "parent_category_id", "path"];
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn cells(&self) -> Vec<ExportCell> {
+        vec![
+            ExportCell::text(self.id),
+            ExportCell::text(&self.name),
+            ExportCell::optional(self.parent_category_id),
+            ExportCell::text(&self.path),
+        ]
+    }
+}
@@ -0,0 +1,369 @@
+use diesel::prelude::*;
+use rust_xlsxwriter::Workbook;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::domain::categories::models::Category;
+use crate::domain::transactions::models::{Transaction, TransactionFilter, TransactionType};
+use crate::domain::transactions::services::filter_transactions;
+
+use super::journal::{beancount_header, render_entry};
+use super::models::{
+    CategoryExportRow, ExportCell, ExportFormat, ExportRow, JournalFormat, PriceExportRow,
+    ProductExportRow, TransactionExportRow,
+};
+
+/// Rows loaded from the database per round trip while exporting.
+pub const PAGE_SIZE: i64 = 500;
+
+/// Size of the chunks an XLSX file is sent in.
+const XLSX_CHUNK: usize = 64 * 1024;
+
+/// Separator between category names in exported paths.
+pub const CATEGORY_PATH_SEPARATOR: &str = " > ";
+
+fn join_path(paths: &HashMap<i32, Vec<String>>, category_id: Option<i32>) -> Option<String> {
+    category_id
+        .and_then(|id| paths.get(&id))
+        .map(|path| path.join(CATEGORY_PATH_SEPARATOR))
+}
+
+/// Loads the next page of transactions after `after_id`, honoring `filter`.
+pub fn transaction_page(
+    conn: &mut PgConnection,
+    household_id: i32,
+    filter: &TransactionFilter,
+    paths: &HashMap<i32, Vec<String>>,
+    after_id: i32,
+) -> QueryResult<Vec<TransactionExportRow>> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::tags::dsl as tg;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+
+    let transactions = filter_transactions(household_id, filter)
+        .filter(tx::id.gt(after_id))
+        .limit(PAGE_SIZE)
+        .load::<Transaction>(conn)?;
+
+    let product_ids: Vec<i32> = transactions.iter().map(|t| t.product_id).collect();
+    let price_ids: Vec<i32> = transactions.iter().map(|t| t.product_price_id).collect();
+    let tx_ids: Vec<i32> = transactions.iter().map(|t| t.id).collect();
+
+    let products: HashMap<i32, (String, Option<i32>)> = pr::products
+        .filter(pr::id.eq_any(&product_ids))
+        .select((pr::id, pr::name, pr::category_id))
+        .load::<(i32, String, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, name, category_id)| (id, (name, category_id)))
+        .collect();
+    let prices: HashMap<i32, i32> = pp::product_prices
+        .filter(pp::id.eq_any(&price_ids))
+        .select((pp::id, pp::price))
+        .load::<(i32, i32)>(conn)?
+        .into_iter()
+        .collect();
+    let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+    for (transaction_id, name) in tt::transaction_tags
+        .inner_join(tg::tags.on(tg::id.eq(tt::tag_id)))
+        .filter(tt::transaction_id.eq_any(&tx_ids))
+        .order(tg::name.asc())
+        .select((tt::transaction_id, tg::name))
+        .load::<(i32, String)>(conn)?
+    {
+        tags.entry(transaction_id).or_default().push(name);
+    }
+
+    Ok(transactions
+        .into_iter()
+        .map(|t| {
+            let (product, category_id) = products.get(&t.product_id).cloned().unwrap_or_default();
+            TransactionExportRow {
+                id: t.id,
+                date: t.date,
+                transaction_type: t.transaction_type,
+                product_id: t.product_id,
+                product,
+                category_path: join_path(paths, category_id),
+                category_names: category_id
+                    .and_then(|id| paths.get(&id))
+                    .cloned()
+                    .unwrap_or_default(),
+                price: prices.get(&t.product_price_id).copied().unwrap_or(0) as f64 / 100.0,
+                description: t.description,
+                tags: tags.remove(&t.id).unwrap_or_default(),
+                account: t.account,
+                value_date: t.value_date,
+                external_id: t.external_id,
+            }
+        })
+        .collect())
+}
+
+/// Loads the next page of products after `after_id`.
+pub fn product_page(
+    conn: &mut PgConnection,
+    household_id: i32,
+    paths: &HashMap<i32, Vec<String>>,
+    after_id: i32,
+) -> QueryResult<Vec<ProductExportRow>> {
+    use crate::schema::products::dsl as pr;
+
+    let rows = pr::products
+        .filter(pr::household_id.eq(household_id))
+        .filter(pr::id.gt(after_id))
+        .order(pr::id.asc())
+        .limit(PAGE_SIZE)
+        .select((pr::id, pr::name, pr::category_id))
+        .load::<(i32, String, Option<i32>)>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, category_id)| ProductExportRow {
+            id,
+            name,
+            category_id,
+            category_path: join_path(paths, category_id),
+        })
+        .collect())
+}
+
+/// Loads the next page of prices of the household's products after `after_id`.
+pub fn price_page(
+    conn: &mut PgConnection,
+    household_id: i32,
+    after_id: i32,
+) -> QueryResult<Vec<PriceExportRow>> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+
+    let rows = pp::product_prices
+        .inner_join(pr::products.on(pr::id.eq(pp::product_id)))
+        .filter(pr::household_id.eq(household_id))
+        .filter(pp::id.gt(after_id))
+        .order(pp::id.asc())
+        .limit(PAGE_SIZE)
+        .select((pp::id, pp::product_id, pr::name, pp::price, pp::created_at))
+        .load::<(i32, i32, String, i32, chrono::NaiveDateTime)>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(id, product_id, product, price, created_at)| PriceExportRow {
+                id,
+                product_id,
+                product,
+                price: price as f64 / 100.0,
+                created_at,
+            },
+        )
+        .collect())
+}
+
+/// Loads the next page of categories after `after_id`.
+pub fn category_page(
+    conn: &mut PgConnection,
+    household_id: i32,
+    paths: &HashMap<i32, Vec<String>>,
+    after_id: i32,
+) -> QueryResult<Vec<CategoryExportRow>> {
+    use crate::schema::categories::dsl as cat;
+
+    let rows = cat::categories
+        .filter(cat::household_id.eq(household_id))
+        .filter(cat::id.gt(after_id))
+        .order(cat::id.asc())
+        .limit(PAGE_SIZE)
+        .load::<Category>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|c| CategoryExportRow {
+            path: join_path(paths, Some(c.id)).unwrap_or_else(|| c.name.clone()),
+            id: c.id,
+            name: c.name,
+            parent_category_id: c.parent_category_id,
+        })
+        .collect())
+}
+
+fn csv_chunk<R: ExportRow>(rows: &[R], with_header: bool) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    if with_header {
+        writer.write_record(R::HEADERS).map_err(|e| e.to_string())?;
+    }
+    for row in rows {
+        writer
+            .write_record(row.cells().iter().map(ExportCell::to_csv))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+fn jsonl_chunk<R: ExportRow>(rows: &[R]) -> Result<Vec<u8>, String> {
+    let mut chunk = Vec::new();
+    for row in rows {
+        serde_json::to_writer(&mut chunk, row).map_err(|e| e.to_string())?;
+        chunk.push(b'\n');
+    }
+    Ok(chunk)
+}
+
+/// Pages through a table with `load_page` and hands the encoded file to
+/// `send` chunk by chunk.
+///
+/// CSV and JSON Lines are sent one page at a time. XLSX rows go to a
+/// constant-memory worksheet backed by temp files and the finished file is
+/// sent from disk, so memory stays flat either way. Stops early when `send`
+/// returns false (the client went away).
+pub fn write_export<R, F, S>(
+    conn: &mut PgConnection,
+    format: ExportFormat,
+    sheet_name: &str,
+    mut load_page: F,
+    mut send: S,
+) -> Result<(), String>
+where
+    R: ExportRow,
+    F: FnMut(&mut PgConnection, i32) -> QueryResult<Vec<R>>,
+    S: FnMut(Vec<u8>) -> bool,
+{
+    let mut after_id = 0;
+    let mut first_page = true;
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet_with_constant_memory();
+    let mut next_row: u32 = 0;
+
+    if format == ExportFormat::Xlsx {
+        worksheet.set_name(sheet_name).map_err(|e| e.to_string())?;
+        for (col, header) in R::HEADERS.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, *header)
+                .map_err(|e| e.to_string())?;
+        }
+        next_row = 1;
+    }
+
+    loop {
+        let rows = load_page(conn, after_id).map_err(|e| format!("Query error: {e}"))?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after_id = last.id();
+
+        match format {
+            ExportFormat::Csv => {
+                if !send(csv_chunk(&rows, first_page)?) {
+                    return Ok(());
+                }
+            }
+            ExportFormat::Jsonl => {
+                if !send(jsonl_chunk(&rows)?) {
+                    return Ok(());
+                }
+            }
+            ExportFormat::Xlsx => {
+                for row in &rows {
+                    for (col, cell) in row.cells().into_iter().enumerate() {
+                        let col = col as u16;
+                        match cell {
+                            ExportCell::Text(text) => worksheet.write_string(next_row, col, text),
+                            ExportCell::Number(number) => {
+                                worksheet.write_number(next_row, col, number)
+                            }
+                            ExportCell::Empty => continue,
+                        }
+                        .map_err(|e| e.to_string())?;
+                    }
+                    next_row += 1;
+                }
+            }
+        }
+        first_page = false;
+        if (rows.len() as i64) < PAGE_SIZE {
+            break;
+        }
+    }
+
+    match format {
+        // An empty CSV export still gets its header row.
+        ExportFormat::Csv if first_page => {
+            send(csv_chunk::<R>(&[], true)?);
+        }
+        ExportFormat::Xlsx => {
+            let mut file = tempfile::tempfile().map_err(|e| e.to_string())?;
+            workbook
+                .save_to_writer(&mut file)
+                .map_err(|e| e.to_string())?;
+            file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+            let mut buf = vec![0; XLSX_CHUNK];
+            loop {
+                let read = file.read(&mut buf).map_err(|e| e.to_string())?;
+                if read == 0 || !send(buf[..read].to_vec()) {
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Pages through the household's filtered transactions and sends them as a
+/// ledger, hledger or beancount journal.
+pub fn write_journal<S>(
+    conn: &mut PgConnection,
+    household_id: i32,
+    filter: &TransactionFilter,
+    paths: &HashMap<i32, Vec<String>>,
+    format: JournalFormat,
+    commodity: &str,
+    mut send: S,
+) -> Result<(), String>
+where
+    S: FnMut(Vec<u8>) -> bool,
+{
+    use crate::schema::transactions::dsl as tx;
+
+    if format == JournalFormat::Beancount {
+        let bank_accounts = tx::transactions
+            .filter(tx::household_id.eq(household_id))
+            .filter(tx::account.is_not_null())
+            .select(tx::account.assume_not_null())
+            .distinct()
+            .load::<String>(conn)
+            .map_err(|e| format!("Query error: {e}"))?;
+        let has_transfers = diesel::select(diesel::dsl::exists(
+            tx::transactions
+                .filter(tx::household_id.eq(household_id))
+                .filter(tx::transaction_type.eq(TransactionType::Transfer)),
+        ))
+        .get_result::<bool>(conn)
+        .map_err(|e| format!("Query error: {e}"))?;
+        let category_paths: Vec<Vec<String>> = paths.values().cloned().collect();
+        let header = beancount_header(commodity, &category_paths, &bank_accounts, has_transfers);
+        if !send(header.into_bytes()) {
+            return Ok(());
+        }
+    }
+
+    let mut after_id = 0;
+    loop {
+        let rows = transaction_page(conn, household_id, filter, paths, after_id)
+            .map_err(|e| format!("Query error: {e}"))?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after_id = last.id;
+
+        let chunk: String = rows
+            .iter()
+            .map(|row| render_entry(row, format, commodity))
+            .collect();
+        if !send(chunk.into_bytes()) || (rows.len() as i64) < PAGE_SIZE {
+            break;
+        }
+    }
+    Ok(())
+}
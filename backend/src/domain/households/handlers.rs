@@ -0,0 +1,249 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use backend::ErrorResponse;
+use diesel::PgConnection;
+use std::sync::Arc;
+
+use crate::domain::users::handlers::MessageResponse;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{
+    CreateHouseholdRequest, CreateInvitationRequest, HouseholdDto, InvitationDto,
+    JoinHouseholdRequest, MemberDto, Role, UpdateMemberRequest,
+};
+use super::services::{
+    accept_invitation, create_household, create_invitation, member_role, members, memberships,
+    remove_member, set_member_role,
+};
+
+type HandlerError = (StatusCode, Json<ErrorResponse>);
+
+/// The caller's role in the household; 404 for households they are not in,
+/// 403 when the household needs an owner.
+fn caller_role(
+    conn: &mut PgConnection,
+    household_id: i32,
+    user_id: i32,
+    owner_only: bool,
+) -> Result<Role, HandlerError> {
+    let role = member_role(conn, household_id, user_id)
+        .map_err(|e| error_response(format!("Error loading household: {e}")))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "Household not found".to_string(),
+                }),
+            )
+        })?;
+    if owner_only && role != Role::Owner {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Only owners can manage this household".to_string(),
+            }),
+        ));
+    }
+    Ok(role)
+}
+
+fn member_dtos(conn: &mut PgConnection, household_id: i32) -> Result<Vec<MemberDto>, HandlerError> {
+    let members = members(conn, household_id)
+        .map_err(|e| error_response(format!("Error loading members: {e}")))?;
+    Ok(members
+        .into_iter()
+        .map(|(user_id, email, role, joined_at)| MemberDto {
+            user_id,
+            email,
+            role,
+            joined_at,
+        })
+        .collect())
+}
+
+/// Handler for GET /households.
+#[debug_handler]
+pub async fn list_households(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+) -> JsonResult<Vec<HouseholdDto>> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let households = memberships(&mut conn, logged_in_user_id)
+        .map_err(|e| error_response(format!("Error loading households: {e}")))?;
+    Ok(Json(
+        households
+            .into_iter()
+            .map(|(household, role)| HouseholdDto {
+                id: household.id,
+                name: household.name,
+                role,
+                created_at: household.created_at,
+            })
+            .collect(),
+    ))
+}
+
+/// Handler for POST /households.
+/// Creates a shared household owned by the caller.
+#[debug_handler]
+pub async fn create_household_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<CreateHouseholdRequest>,
+) -> JsonResult<HouseholdDto> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(error_response("A household needs a name"));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let household = create_household(&mut conn, logged_in_user_id, name)
+        .map_err(|e| error_response(format!("Failed to create household: {e}")))?;
+    Ok(Json(HouseholdDto {
+        id: household.id,
+        name: household.name,
+        role: Role::Owner,
+        created_at: household.created_at,
+    }))
+}
+
+/// Handler for GET /households/{id}/members.
+#[debug_handler]
+pub async fn list_members(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Path(household_id): Path<i32>,
+) -> JsonResult<Vec<MemberDto>> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    caller_role(&mut conn, household_id, logged_in_user_id, false)?;
+    member_dtos(&mut conn, household_id).map(Json)
+}
+
+/// Handler for PUT /households/{id}/members/{user_id}.
+/// Owners change roles; a household always keeps at least one owner.
+#[debug_handler]
+pub async fn update_member(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Path((household_id, user_id)): Path<(i32, i32)>,
+    Json(payload): Json<UpdateMemberRequest>,
+) -> JsonResult<MemberDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    caller_role(&mut conn, household_id, logged_in_user_id, true)?;
+    let updated = set_member_role(&mut conn, household_id, user_id, payload.role)
+        .map_err(|e| error_response(format!("Failed to update member: {e}")))?;
+    if !updated {
+        return Err(error_response("A household needs at least one owner"));
+    }
+    member_dtos(&mut conn, household_id)?
+        .into_iter()
+        .find(|member| member.user_id == user_id)
+        .map(Json)
+        .ok_or_else(|| error_response("Member not found"))
+}
+
+/// Handler for DELETE /households/{id}/members/{user_id}.
+/// Owners remove members; anyone can leave by removing themselves.
+#[debug_handler]
+pub async fn delete_member(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Path((household_id, user_id)): Path<(i32, i32)>,
+) -> JsonResult<MessageResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    caller_role(
+        &mut conn,
+        household_id,
+        logged_in_user_id,
+        user_id != logged_in_user_id,
+    )?;
+    if member_role(&mut conn, household_id, user_id)
+        .map_err(|e| error_response(format!("Error loading household: {e}")))?
+        .is_none()
+    {
+        return Err(error_response("Member not found"));
+    }
+    let removed = remove_member(&mut conn, household_id, user_id)
+        .map_err(|e| error_response(format!("Failed to remove member: {e}")))?;
+    if !removed {
+        return Err(error_response("A household needs at least one owner"));
+    }
+
+    Ok(Json(MessageResponse {
+        message: "Member removed".to_string(),
+    }))
+}
+
+/// Handler for POST /households/{id}/invitations.
+/// Owners invite people with a role; the token is returned only here.
+#[debug_handler]
+pub async fn create_invitation_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Path(household_id): Path<i32>,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> JsonResult<InvitationDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    caller_role(&mut conn, household_id, logged_in_user_id, true)?;
+    let (token, expires_at) =
+        create_invitation(&mut conn, household_id, logged_in_user_id, payload.role)
+            .map_err(|e| error_response(format!("Failed to create invitation: {e}")))?;
+    Ok(Json(InvitationDto {
+        token,
+        role: payload.role,
+        expires_at,
+    }))
+}
+
+/// Handler for POST /households/join.
+/// Redeems an invitation token for the caller.
+#[debug_handler]
+pub async fn join_household(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<JoinHouseholdRequest>,
+) -> JsonResult<HouseholdDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let household = accept_invitation(&mut conn, payload.token.trim(), logged_in_user_id)
+        .map_err(|e| error_response(format!("Failed to join household: {e}")))?
+        .ok_or_else(|| error_response("Invalid or expired invitation"))?;
+    let role = caller_role(&mut conn, household.id, logged_in_user_id, false)?;
+    Ok(Json(HouseholdDto {
+        id: household.id,
+        name: household.name,
+        role,
+        created_at: household.created_at,
+    }))
+}
@@ -0,0 +1,131 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{household_invitations, household_members, households};
+
+/// What a member may do in a household.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Changes data and manages members and invitations.
+    Owner,
+    /// Changes data.
+    Editor,
+    /// Only reads data.
+    Viewer,
+}
+
+impl Role {
+    pub fn parse(raw: &str) -> Option<Role> {
+        match raw {
+            "owner" => Some(Role::Owner),
+            "editor" => Some(Role::Editor),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Editor => "editor",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    pub fn can_write(self) -> bool {
+        self != Role::Viewer
+    }
+}
+
+/// The household a request works on and the member acting in it, inserted
+/// by `require_household`.
+#[derive(Clone, Copy, Debug)]
+pub struct ActiveHousehold {
+    pub id: i32,
+    pub user_id: i32,
+    pub role: Role,
+}
+
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = households)]
+pub struct Household {
+    pub id: i32,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = households)]
+pub struct NewHousehold {
+    pub name: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = household_members)]
+pub struct NewHouseholdMember {
+    pub household_id: i32,
+    pub user_id: i32,
+    pub role: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = household_invitations)]
+pub struct NewHouseholdInvitation {
+    pub household_id: i32,
+    pub role: String,
+    pub token_hash: String,
+    pub created_by: i32,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Body of POST /households.
+#[derive(Deserialize)]
+pub struct CreateHouseholdRequest {
+    pub name: String,
+}
+
+/// Body of POST /households/{id}/invitations.
+#[derive(Deserialize)]
+pub struct CreateInvitationRequest {
+    pub role: Role,
+}
+
+/// Body of POST /households/join.
+#[derive(Deserialize)]
+pub struct JoinHouseholdRequest {
+    pub token: String,
+}
+
+/// Body of PUT /households/{id}/members/{user_id}.
+#[derive(Deserialize)]
+pub struct UpdateMemberRequest {
+    pub role: Role,
+}
+
+/// A household as seen by one of its members.
+#[derive(Serialize)]
+pub struct HouseholdDto {
+    pub id: i32,
+    pub name: String,
+    pub role: Role,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct MemberDto {
+    pub user_id: i32,
+    pub email: String,
+    pub role: Role,
+    pub joined_at: NaiveDateTime,
+}
+
+/// Returned by POST /households/{id}/invitations. The token is shown only
+/// this once.
+#[derive(Serialize)]
+pub struct InvitationDto {
+    pub token: String,
+    pub role: Role,
+    pub expires_at: NaiveDateTime,
+}
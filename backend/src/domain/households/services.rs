@@ -0,0 +1,287 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::auth::{hash_token, random_token};
+
+use super::models::{Household, NewHousehold, NewHouseholdInvitation, NewHouseholdMember, Role};
+
+/// Header naming the household a request works on. Without it, requests go
+/// to the first household the user joined, their personal one.
+pub const HOUSEHOLD_HEADER: &str = "x-household-id";
+
+/// How long an invitation can be redeemed.
+pub const INVITATION_DAYS: i64 = 7;
+
+/// Name of the household every account starts with.
+pub const PERSONAL_HOUSEHOLD_NAME: &str = "Personal";
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// Creates a household with `user_id` as its owner.
+pub fn create_household(
+    conn: &mut PgConnection,
+    user_id: i32,
+    name: &str,
+) -> QueryResult<Household> {
+    use crate::schema::household_members::dsl as m;
+    use crate::schema::households::dsl as h;
+
+    conn.transaction(|conn| {
+        let household = diesel::insert_into(h::households)
+            .values(&NewHousehold {
+                name: name.to_string(),
+            })
+            .returning(Household::as_returning())
+            .get_result(conn)?;
+        diesel::insert_into(m::household_members)
+            .values(&NewHouseholdMember {
+                household_id: household.id,
+                user_id,
+                role: Role::Owner.name().to_string(),
+            })
+            .execute(conn)?;
+        Ok(household)
+    })
+}
+
+/// The user's role in a household, if they are a member.
+pub fn member_role(
+    conn: &mut PgConnection,
+    household_id: i32,
+    user_id: i32,
+) -> QueryResult<Option<Role>> {
+    use crate::schema::household_members::dsl as m;
+
+    let role = m::household_members
+        .filter(m::household_id.eq(household_id))
+        .filter(m::user_id.eq(user_id))
+        .select(m::role)
+        .first::<String>(conn)
+        .optional()?;
+    Ok(role.as_deref().and_then(Role::parse))
+}
+
+/// The household requests go to when they name none: the one the user
+/// joined first.
+pub fn default_household(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> QueryResult<Option<(i32, Role)>> {
+    use crate::schema::household_members::dsl as m;
+
+    let membership = m::household_members
+        .filter(m::user_id.eq(user_id))
+        .order((m::joined_at.asc(), m::household_id.asc()))
+        .select((m::household_id, m::role))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+    Ok(membership.and_then(|(id, role)| Role::parse(&role).map(|role| (id, role))))
+}
+
+/// Every household the user belongs to, in the order they joined.
+pub fn memberships(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<(Household, Role)>> {
+    use crate::schema::household_members::dsl as m;
+    use crate::schema::households::dsl as h;
+
+    let rows = m::household_members
+        .inner_join(h::households)
+        .filter(m::user_id.eq(user_id))
+        .order((m::joined_at.asc(), m::household_id.asc()))
+        .select((Household::as_select(), m::role))
+        .load::<(Household, String)>(conn)?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(household, role)| Role::parse(&role).map(|role| (household, role)))
+        .collect())
+}
+
+/// The members of a household with their email addresses.
+pub fn members(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> QueryResult<Vec<(i32, String, Role, NaiveDateTime)>> {
+    use crate::schema::household_members::dsl as m;
+    use crate::schema::users::dsl as u;
+
+    let rows = m::household_members
+        .inner_join(u::users)
+        .filter(m::household_id.eq(household_id))
+        .order((m::joined_at.asc(), m::user_id.asc()))
+        .select((m::user_id, u::email, m::role, m::joined_at))
+        .load::<(i32, String, String, NaiveDateTime)>(conn)?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, email, role, joined)| Role::parse(&role).map(|r| (id, email, r, joined)))
+        .collect())
+}
+
+/// Owners of the household other than `user_id`.
+fn other_owners(conn: &mut PgConnection, household_id: i32, user_id: i32) -> QueryResult<i64> {
+    use crate::schema::household_members::dsl as m;
+
+    m::household_members
+        .filter(m::household_id.eq(household_id))
+        .filter(m::role.eq(Role::Owner.name()))
+        .filter(m::user_id.ne(user_id))
+        .count()
+        .get_result(conn)
+}
+
+/// Changes a member's role. Returns `Ok(false)` when that would leave the
+/// household without an owner.
+pub fn set_member_role(
+    conn: &mut PgConnection,
+    household_id: i32,
+    user_id: i32,
+    role: Role,
+) -> QueryResult<bool> {
+    use crate::schema::household_members::dsl as m;
+
+    conn.transaction(|conn| {
+        if role != Role::Owner && other_owners(conn, household_id, user_id)? == 0 {
+            return Ok(false);
+        }
+        diesel::update(
+            m::household_members
+                .filter(m::household_id.eq(household_id))
+                .filter(m::user_id.eq(user_id)),
+        )
+        .set(m::role.eq(role.name()))
+        .execute(conn)?;
+        Ok(true)
+    })
+}
+
+/// Removes a member. Returns `Ok(false)` when they are the last owner.
+pub fn remove_member(
+    conn: &mut PgConnection,
+    household_id: i32,
+    user_id: i32,
+) -> QueryResult<bool> {
+    use crate::schema::household_members::dsl as m;
+
+    conn.transaction(|conn| {
+        if member_role(conn, household_id, user_id)? == Some(Role::Owner)
+            && other_owners(conn, household_id, user_id)? == 0
+        {
+            return Ok(false);
+        }
+        diesel::delete(
+            m::household_members
+                .filter(m::household_id.eq(household_id))
+                .filter(m::user_id.eq(user_id)),
+        )
+        .execute(conn)?;
+        Ok(true)
+    })
+}
+
+/// Stores an invitation and returns the token to hand out with its expiry.
+pub fn create_invitation(
+    conn: &mut PgConnection,
+    household_id: i32,
+    created_by: i32,
+    role: Role,
+) -> QueryResult<(String, NaiveDateTime)> {
+    use crate::schema::household_invitations::dsl as i;
+
+    let token = random_token();
+    let expires_at = now() + Duration::days(INVITATION_DAYS);
+    diesel::insert_into(i::household_invitations)
+        .values(&NewHouseholdInvitation {
+            household_id,
+            role: role.name().to_string(),
+            token_hash: hash_token(&token),
+            created_by,
+            expires_at,
+        })
+        .execute(conn)?;
+    Ok((token, expires_at))
+}
+
+/// Redeems an unused, unexpired invitation and adds the user to its
+/// household. Members keep their current role. Returns the household, or
+/// `None` for an unknown token.
+pub fn accept_invitation(
+    conn: &mut PgConnection,
+    token: &str,
+    user_id: i32,
+) -> QueryResult<Option<Household>> {
+    use crate::schema::household_invitations::dsl as i;
+    use crate::schema::household_members::dsl as m;
+    use crate::schema::households::dsl as h;
+
+    conn.transaction(|conn| {
+        let invitation = diesel::update(
+            i::household_invitations
+                .filter(i::token_hash.eq(hash_token(token)))
+                .filter(i::used_at.is_null())
+                .filter(i::expires_at.gt(now())),
+        )
+        .set(i::used_at.eq(now()))
+        .returning((i::household_id, i::role))
+        .get_result::<(i32, String)>(conn)
+        .optional()?;
+        let Some((household_id, role)) = invitation else {
+            return Ok(None);
+        };
+
+        diesel::insert_into(m::household_members)
+            .values(&NewHouseholdMember {
+                household_id,
+                user_id,
+                role,
+            })
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        h::households
+            .find(household_id)
+            .select(Household::as_select())
+            .first(conn)
+            .map(Some)
+    })
+}
+
+/// Hands over the households `user_id` is the only owner of: the member who
+/// joined first after them becomes an owner, and households without anyone
+/// else are deleted with all their data. Called before the account itself is
+/// deleted, so a shared household never loses its data with its owner.
+pub fn release_owned_households(conn: &mut PgConnection, user_id: i32) -> QueryResult<()> {
+    use crate::schema::household_members::dsl as m;
+    use crate::schema::households::dsl as h;
+
+    let owned = m::household_members
+        .filter(m::user_id.eq(user_id))
+        .filter(m::role.eq(Role::Owner.name()))
+        .select(m::household_id)
+        .load::<i32>(conn)?;
+    for household_id in owned {
+        if other_owners(conn, household_id, user_id)? > 0 {
+            continue;
+        }
+        let successor = m::household_members
+            .filter(m::household_id.eq(household_id))
+            .filter(m::user_id.ne(user_id))
+            .order((m::joined_at.asc(), m::user_id.asc()))
+            .select(m::user_id)
+            .first::<i32>(conn)
+            .optional()?;
+        match successor {
+            Some(successor) => {
+                diesel::update(
+                    m::household_members
+                        .filter(m::household_id.eq(household_id))
+                        .filter(m::user_id.eq(successor)),
+                )
+                .set(m::role.eq(Role::Owner.name()))
+                .execute(conn)?;
+            }
+            None => {
+                diesel::delete(h::households.find(household_id)).execute(conn)?;
+            }
+        }
+    }
+    Ok(())
+}
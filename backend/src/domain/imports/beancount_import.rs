@@ -0,0 +1,290 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::HashMap;
+
+use crate::domain::exports::journal::{
+    BANK_ACCOUNT_KEY, EXTERNAL_ID_KEY, TAGS_KEY, TRANSFER_ACCOUNT, VALUE_DATE_KEY,
+};
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{ImportRow, ImportRowError, ParsedStatement};
+use super::services::parse_amount;
+
+/// A transaction directive with its metadata and postings.
+struct Entry {
+    line: usize,
+    date: NaiveDate,
+    payee: Option<String>,
+    narration: Option<String>,
+    tags: Vec<String>,
+    meta: HashMap<String, String>,
+    /// Account and amount in cents; `None` for an elided amount.
+    postings: Vec<(String, Option<i64>)>,
+    error: Option<String>,
+}
+
+/// Reads a double-quoted string with backslash escapes from the start of
+/// `text`, returning it and the rest of the line.
+fn read_string(text: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?.1),
+            '"' => return Some((value, &text[idx + 2..])),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+fn meta_value(raw: &str) -> String {
+    let raw = raw.trim();
+    match read_string(raw) {
+        Some((value, _)) => value,
+        None => raw.to_string(),
+    }
+}
+
+/// Parses `2025-01-08 * "Payee" "Narration" #tag ^link`.
+/// Returns `None` for lines that are not transactions.
+fn parse_header(line: usize, text: &str) -> Option<Result<Entry, String>> {
+    let (date, rest) = text.split_once(char::is_whitespace)?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let mut rest = rest.trim_start();
+    let (flag, after_flag) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if !matches!(flag, "*" | "!" | "txn") {
+        return None;
+    }
+    rest = after_flag.trim_start();
+
+    let mut strings = Vec::new();
+    let mut tags = Vec::new();
+    while !rest.is_empty() && !rest.starts_with(';') {
+        if rest.starts_with('"') {
+            let Some((value, after)) = read_string(rest) else {
+                return Some(Err("Unterminated string".to_string()));
+            };
+            strings.push(value);
+            rest = after.trim_start();
+            continue;
+        }
+        let (token, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if let Some(tag) = token.strip_prefix('#') {
+            tags.push(tag.to_string());
+        }
+        rest = after.trim_start();
+    }
+
+    // A single string is the narration.
+    let (payee, narration) = match strings.len() {
+        0 => (None, None),
+        1 => (None, strings.pop()),
+        2 => {
+            let narration = strings.pop();
+            (strings.pop(), narration)
+        }
+        _ => return Some(Err("Too many strings in transaction header".to_string())),
+    };
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+    Some(Ok(Entry {
+        line,
+        date,
+        payee: non_empty(payee),
+        narration: non_empty(narration),
+        tags,
+        meta: HashMap::new(),
+        postings: Vec::new(),
+        error: None,
+    }))
+}
+
+/// Adds an indented metadata or posting line to `entry`.
+fn parse_entry_line(entry: &mut Entry, text: &str) {
+    let text = text.trim();
+    if text.starts_with(';') {
+        return;
+    }
+    if let Some((key, value)) = text.split_once(':') {
+        if key.starts_with(|c: char| c.is_ascii_lowercase())
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            entry.meta.insert(key.to_string(), meta_value(value));
+            return;
+        }
+    }
+
+    let text = text.split(';').next().unwrap_or("").trim();
+    let text = text
+        .strip_prefix(['*', '!'])
+        .map(str::trim_start)
+        .unwrap_or(text);
+    let mut tokens = text.split_whitespace();
+    let Some(account) = tokens.next() else {
+        return;
+    };
+    let amount = match tokens.next() {
+        Some(number) => match parse_amount(number, '.') {
+            Ok(cents) => Some(cents),
+            Err(message) => {
+                entry.error.get_or_insert(message);
+                return;
+            }
+        },
+        None => None,
+    };
+    entry.postings.push((account.to_string(), amount));
+}
+
+/// Parses a beancount file.
+///
+/// Each transaction that touches `Expenses:` or `Income:` accounts becomes a
+/// row: its net amount on those accounts decides between expense and income.
+/// The payee is the product (the narration when there is no payee), tags come
+/// from `#tags`, `pushtag` and the `tags` metadata written by the journal
+/// export, and its `external_id`, `bank_account` and `value_date` metadata are
+/// read back as well. Categories are not created from account names. Other
+/// directives (open, balance, price, ...) are ignored.
+pub fn parse_beancount(data: &[u8]) -> Result<ParsedStatement, String> {
+    let text = String::from_utf8_lossy(data);
+    let mut parsed = ParsedStatement::default();
+    let mut pushed_tags: Vec<String> = Vec::new();
+    let mut entry: Option<Entry> = None;
+    let mut seen_directive = false;
+
+    for (idx, raw) in text.lines().enumerate() {
+        let line = idx + 1;
+        let indented = raw.starts_with([' ', '\t']);
+        if indented && !raw.trim().is_empty() {
+            if let Some(entry) = entry.as_mut() {
+                parse_entry_line(entry, raw);
+            }
+            continue;
+        }
+
+        if let Some(finished) = entry.take() {
+            push_entry(&mut parsed, finished);
+        }
+        let trimmed = raw.trim();
+        if let Some(tag) = trimmed.strip_prefix("pushtag ") {
+            pushed_tags.push(tag.trim().trim_start_matches('#').to_string());
+        } else if let Some(tag) = trimmed.strip_prefix("poptag ") {
+            let tag = tag.trim().trim_start_matches('#');
+            if let Some(pos) = pushed_tags.iter().rposition(|t| t == tag) {
+                pushed_tags.remove(pos);
+            }
+        }
+        if trimmed.starts_with(|c: char| c.is_ascii_digit()) || trimmed.starts_with("option ") {
+            seen_directive = true;
+        }
+
+        match parse_header(line, trimmed) {
+            Some(Ok(mut new_entry)) => {
+                new_entry.tags.extend(pushed_tags.iter().cloned());
+                entry = Some(new_entry);
+            }
+            Some(Err(message)) => parsed.errors.push(ImportRowError { line, message }),
+            None => {}
+        }
+    }
+    if let Some(finished) = entry.take() {
+        push_entry(&mut parsed, finished);
+    }
+
+    if !seen_directive {
+        return Err("Not a beancount file: no dated directives found".to_string());
+    }
+    Ok(parsed)
+}
+
+fn push_entry(parsed: &mut ParsedStatement, entry: Entry) {
+    let line = entry.line;
+    match beancount_row(entry) {
+        Ok(row) => parsed.rows.push(row),
+        Err(message) => parsed.errors.push(ImportRowError { line, message }),
+    }
+}
+
+fn beancount_row(mut entry: Entry) -> Result<ImportRow, String> {
+    if let Some(message) = entry.error {
+        return Err(message);
+    }
+    let elided = entry.postings.iter().filter(|(_, a)| a.is_none()).count();
+    if elided > 1 {
+        return Err("More than one posting without an amount".to_string());
+    }
+    let balance: i64 = entry.postings.iter().filter_map(|(_, a)| *a).sum();
+    if elided == 0 && balance != 0 {
+        return Err("Postings do not balance".to_string());
+    }
+
+    let mut touches_category = false;
+    let mut transfer = false;
+    let mut net = 0;
+    for (account, amount) in &entry.postings {
+        if account.starts_with("Expenses:") || account.starts_with("Income:") {
+            touches_category = true;
+            net += amount.unwrap_or(-balance);
+        } else if account == TRANSFER_ACCOUNT {
+            transfer = true;
+            net += amount.unwrap_or(-balance);
+        }
+    }
+    // Transfers are exported against Equity:Transfers; mixed with a category
+    // the entry is an ordinary expense or income.
+    let transfer = transfer && !touches_category;
+    if !touches_category && !transfer {
+        return Err(format!(
+            "Only transactions with Expenses, Income or {TRANSFER_ACCOUNT} postings can be imported"
+        ));
+    }
+    if net == 0 {
+        return Err("Amount is zero".to_string());
+    }
+
+    let (product_name, description) = match (entry.payee, entry.narration) {
+        (Some(payee), narration) => (payee, narration),
+        (None, Some(narration)) => (narration, None),
+        (None, None) => return Err("Missing payee and narration".to_string()),
+    };
+    if let Some(tags) = entry.meta.remove(TAGS_KEY) {
+        entry
+            .tags
+            .extend(tags.split(',').map(str::trim).map(str::to_string));
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for tag in entry.tags {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    let value_date = entry
+        .meta
+        .get(VALUE_DATE_KEY)
+        .map(|raw| {
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid {VALUE_DATE_KEY}: {raw:?}"))
+        })
+        .transpose()?;
+
+    Ok(ImportRow {
+        line: entry.line,
+        date: entry.date.and_time(NaiveTime::MIN),
+        amount: net.abs(),
+        transaction_type: if transfer {
+            TransactionType::Transfer
+        } else if net > 0 {
+            TransactionType::Expense
+        } else {
+            TransactionType::Income
+        },
+        product_name,
+        description,
+        tags,
+        external_id: entry.meta.remove(EXTERNAL_ID_KEY),
+        account: entry.meta.remove(BANK_ACCOUNT_KEY),
+        value_date,
+        import_hash: None,
+    })
+}
@@ -0,0 +1,214 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{ImportRow, ImportRowError, ParsedStatement};
+use super::services::parse_amount;
+
+/// Placeholder banks send when a reference is missing.
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+/// Text values of one `<Ntry>`, keyed by their path below the entry
+/// (e.g. "NtryDtls/TxDtls/RmtInf/Ustrd"). Repeated elements keep every value.
+type EntryFields = HashMap<String, Vec<String>>;
+
+fn first<'a>(fields: &'a EntryFields, paths: &[&str]) -> Option<&'a str> {
+    paths.iter().find_map(|p| {
+        fields
+            .get(*p)
+            .and_then(|v| v.first())
+            .map(String::as_str)
+            .filter(|v| !v.is_empty() && *v != NOT_PROVIDED)
+    })
+}
+
+fn path_ends_with(path: &[String], suffix: &[&str]) -> bool {
+    path.len() >= suffix.len()
+        && path[path.len() - suffix.len()..]
+            .iter()
+            .zip(suffix)
+            .all(|(a, b)| a == b)
+}
+
+/// Parses an ISO date or date-time ("2025-01-03" or "2025-01-03T10:15:00+01:00").
+fn parse_iso_date(raw: &str) -> Result<NaiveDateTime, String> {
+    let invalid = || format!("Invalid date: {raw:?}");
+    let date = NaiveDate::parse_from_str(raw.get(..10).ok_or_else(invalid)?, "%Y-%m-%d")
+        .map_err(|_| invalid())?;
+    let time = match raw.get(11..19) {
+        Some(time) => NaiveTime::parse_from_str(time, "%H:%M:%S").map_err(|_| invalid())?,
+        None => NaiveTime::MIN,
+    };
+    Ok(date.and_time(time))
+}
+
+/// Parses an ISO 20022 CAMT.053 bank-to-customer statement.
+///
+/// Each booked `<Ntry>` becomes a row. The counterparty (creditor for debits,
+/// debtor for credits) is the product and the unstructured remittance info
+/// the description. The bank's entry reference, prefixed with the account,
+/// is the external id. Batch entries are imported as one row using the first
+/// transaction's details; pending entries are skipped.
+pub fn parse_camt053(data: &[u8]) -> Result<ParsedStatement, String> {
+    let mut reader = Reader::from_reader(data);
+    reader.config_mut().trim_text(true);
+
+    let mut parsed = ParsedStatement::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut account: Option<String> = None;
+    let mut entry: Option<EntryFields> = None;
+    let mut entry_count = 0;
+    let mut seen_statement = false;
+    let mut buf = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("Invalid XML at byte {}: {e}", reader.buffer_position()))?;
+        match event {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                match name.as_str() {
+                    "Stmt" => {
+                        seen_statement = true;
+                        account = None;
+                    }
+                    "Ntry" => {
+                        entry_count += 1;
+                        entry = Some(EntryFields::new());
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::End(_) => {
+                let closed = path.pop();
+                if let (Some("Ntry"), Some(fields)) = (closed.as_deref(), entry.as_ref()) {
+                    push_entry(&mut parsed, fields, entry_count, account.as_deref());
+                    entry = None;
+                }
+            }
+            Event::Text(e) => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| format!("Invalid XML text: {e}"))?
+                    .trim()
+                    .to_string();
+                if let Some(fields) = entry.as_mut() {
+                    let ntry = path.iter().rposition(|p| p == "Ntry").unwrap_or(0);
+                    fields
+                        .entry(path[ntry + 1..].join("/"))
+                        .or_default()
+                        .push(text);
+                } else if path_ends_with(&path, &["Acct", "Id", "IBAN"])
+                    || path_ends_with(&path, &["Acct", "Id", "Othr", "Id"])
+                {
+                    account.get_or_insert(text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if !seen_statement {
+        return Err("Not a CAMT.053 statement: missing <Stmt> element".to_string());
+    }
+    Ok(parsed)
+}
+
+fn push_entry(
+    parsed: &mut ParsedStatement,
+    fields: &EntryFields,
+    entry: usize,
+    account: Option<&str>,
+) {
+    // Pending entries come back booked with a new reference later.
+    if first(fields, &["Sts", "Sts/Cd"]) == Some("PDNG") {
+        return;
+    }
+    match camt_row(fields, account) {
+        Ok(mut row) => {
+            row.line = entry;
+            parsed.rows.push(row);
+        }
+        Err(message) => parsed.errors.push(ImportRowError {
+            line: entry,
+            message,
+        }),
+    }
+}
+
+fn camt_row(fields: &EntryFields, account: Option<&str>) -> Result<ImportRow, String> {
+    let amount = parse_amount(first(fields, &["Amt"]).ok_or("Missing Amt")?, '.')?;
+    if amount == 0 {
+        return Err("Amount is zero".to_string());
+    }
+    let transaction_type = match first(fields, &["CdtDbtInd"]) {
+        Some("CRDT") => TransactionType::Income,
+        Some("DBIT") => TransactionType::Expense,
+        other => return Err(format!("Invalid CdtDbtInd: {other:?}")),
+    };
+
+    let booking = first(fields, &["BookgDt/Dt", "BookgDt/DtTm"]).ok_or("Missing BookgDt")?;
+    let date = parse_iso_date(booking)?;
+    let value_date = first(fields, &["ValDt/Dt", "ValDt/DtTm"])
+        .map(|raw| parse_iso_date(raw).map(|d| d.date()))
+        .transpose()?;
+
+    let party = match transaction_type {
+        TransactionType::Income => "Dbtr",
+        TransactionType::Expense | TransactionType::Transfer => "Cdtr",
+    };
+    let name_paths = [
+        format!("NtryDtls/TxDtls/RltdPties/{party}/Nm"),
+        format!("NtryDtls/TxDtls/RltdPties/{party}/Pty/Nm"),
+    ];
+    let counterparty = first(fields, &[name_paths[0].as_str(), name_paths[1].as_str()]);
+    let remittance = fields
+        .get("NtryDtls/TxDtls/RmtInf/Ustrd")
+        .map(|lines| lines.join(" "))
+        .or_else(|| {
+            first(fields, &["NtryDtls/TxDtls/RmtInf/Strd/CdtrRefInf/Ref"]).map(str::to_string)
+        })
+        .filter(|r| !r.is_empty());
+    let additional = first(fields, &["AddtlNtryInf"]).map(str::to_string);
+
+    let product_name = counterparty
+        .map(str::to_string)
+        .or_else(|| additional.clone())
+        .or_else(|| remittance.clone())
+        .ok_or("Missing counterparty and remittance information")?;
+
+    let reference = first(
+        fields,
+        &[
+            "AcctSvcrRef",
+            "NtryRef",
+            "NtryDtls/TxDtls/Refs/AcctSvcrRef",
+            "NtryDtls/TxDtls/Refs/EndToEndId",
+        ],
+    );
+    let external_id = reference.map(|r| match account {
+        Some(account) => format!("{account}:{r}"),
+        None => r.to_string(),
+    });
+
+    Ok(ImportRow {
+        line: 0,
+        date,
+        amount: amount.abs(),
+        transaction_type,
+        product_name,
+        description: remittance.or(additional),
+        tags: Vec::new(),
+        external_id,
+        account: account.map(str::to_string),
+        value_date,
+        import_hash: None,
+    })
+}
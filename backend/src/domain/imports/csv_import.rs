@@ -0,0 +1,225 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use csv::{ReaderBuilder, StringRecord};
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{CsvMapping, ImportRow, ImportRowError, ParsedStatement};
+use super::services::parse_amount;
+
+/// Separator between several tag names inside the tags column.
+pub const TAG_SEPARATOR: char = '|';
+
+/// Column positions resolved from a mapping against the header row.
+struct Columns {
+    date: usize,
+    amount: usize,
+    sign: Option<usize>,
+    description: Option<usize>,
+    product: Option<usize>,
+    tags: Option<usize>,
+}
+
+/// Finds `spec` among the headers (case-insensitive) or reads it as an index.
+fn resolve_column(spec: &str, headers: Option<&StringRecord>) -> Result<usize, String> {
+    let spec = spec.trim();
+    if let Some(headers) = headers {
+        if let Some(pos) = headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(spec))
+        {
+            return Ok(pos);
+        }
+    }
+    spec.parse::<usize>()
+        .map_err(|_| format!("Unknown column: {spec}"))
+}
+
+/// Parses a date with `format`, accepting formats with or without a time part.
+pub fn parse_date(raw: &str, format: &str) -> Result<NaiveDateTime, String> {
+    let raw = raw.trim();
+    NaiveDateTime::parse_from_str(raw, format)
+        .or_else(|_| NaiveDate::parse_from_str(raw, format).map(|d| d.and_time(NaiveTime::MIN)))
+        .map_err(|_| format!("Date {raw:?} does not match format {format:?}"))
+}
+
+/// Checks the parts of a mapping that don't depend on the file and returns
+/// the delimiter byte and decimal separator.
+pub fn validate_mapping(mapping: &CsvMapping) -> Result<(u8, char), String> {
+    let delimiter = match mapping.delimiter.as_bytes() {
+        [b] => *b,
+        _ => return Err("Delimiter must be a single character".to_string()),
+    };
+    let decimal_separator = match mapping.decimal_separator.as_str() {
+        "." => '.',
+        "," => ',',
+        _ => return Err("Decimal separator must be \".\" or \",\"".to_string()),
+    };
+    if mapping.date_format.trim().is_empty() {
+        return Err("Date format must not be empty".to_string());
+    }
+    if mapping.skip_rows < 0 {
+        return Err("skip_rows must not be negative".to_string());
+    }
+    Ok((delimiter, decimal_separator))
+}
+
+/// Parses a CSV statement according to `mapping`.
+///
+/// Returns an error for problems with the mapping itself (bad delimiter,
+/// unknown columns); problems with individual lines end up in `errors`.
+pub fn parse_csv(data: &[u8], mapping: &CsvMapping) -> Result<ParsedStatement, String> {
+    let (delimiter, decimal_separator) = validate_mapping(mapping)?;
+
+    // Skip preamble lines before handing the rest to the CSV reader.
+    let skip = mapping.skip_rows as usize;
+    let mut offset = 0;
+    for _ in 0..skip {
+        match data[offset..].iter().position(|b| *b == b'\n') {
+            Some(pos) => offset += pos + 1,
+            None => offset = data.len(),
+        }
+    }
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(mapping.has_header)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(&data[offset..]);
+
+    let headers = if mapping.has_header {
+        Some(
+            reader
+                .headers()
+                .map_err(|e| format!("Could not read header row: {e}"))?
+                .clone(),
+        )
+    } else {
+        None
+    };
+    let optional = |spec: &Option<String>| -> Result<Option<usize>, String> {
+        spec.as_deref()
+            .map(|s| resolve_column(s, headers.as_ref()))
+            .transpose()
+    };
+    let columns = Columns {
+        date: resolve_column(&mapping.date_column, headers.as_ref())?,
+        amount: resolve_column(&mapping.amount_column, headers.as_ref())?,
+        sign: optional(&mapping.sign_column)?,
+        description: optional(&mapping.description_column)?,
+        product: optional(&mapping.product_column)?,
+        tags: optional(&mapping.tags_column)?,
+    };
+    let income_indicator = mapping.income_indicator.as_deref().unwrap_or("CR");
+
+    let mut parsed = ParsedStatement::default();
+    for record in reader.records() {
+        let record = match record {
+            Ok(r) => r,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0) + skip;
+                parsed.errors.push(ImportRowError {
+                    line,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0) + skip;
+        if record.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        match parse_record(
+            &record,
+            &columns,
+            mapping,
+            decimal_separator,
+            income_indicator,
+        ) {
+            Ok(mut row) => {
+                row.line = line;
+                parsed.rows.push(row);
+            }
+            Err(message) => parsed.errors.push(ImportRowError { line, message }),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_record(
+    record: &StringRecord,
+    columns: &Columns,
+    mapping: &CsvMapping,
+    decimal_separator: char,
+    income_indicator: &str,
+) -> Result<ImportRow, String> {
+    let field = |idx: usize| -> Result<&str, String> {
+        record
+            .get(idx)
+            .ok_or_else(|| format!("Missing column {idx}"))
+    };
+    let optional_field = |idx: Option<usize>| -> Option<String> {
+        idx.and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+
+    let date = parse_date(field(columns.date)?, &mapping.date_format)?;
+    let raw_amount = parse_amount(field(columns.amount)?, decimal_separator)?;
+    if raw_amount == 0 {
+        return Err("Amount is zero".to_string());
+    }
+
+    let transaction_type = match columns.sign {
+        Some(idx) => {
+            if field(idx)?.trim().eq_ignore_ascii_case(income_indicator) {
+                TransactionType::Income
+            } else {
+                TransactionType::Expense
+            }
+        }
+        None => {
+            let signed = if mapping.invert_sign {
+                -raw_amount
+            } else {
+                raw_amount
+            };
+            if signed > 0 {
+                TransactionType::Income
+            } else {
+                TransactionType::Expense
+            }
+        }
+    };
+
+    let description = optional_field(columns.description);
+    let product_name = optional_field(columns.product)
+        .or_else(|| description.clone())
+        .ok_or_else(|| "Missing product name and description".to_string())?;
+
+    let tags = optional_field(columns.tags)
+        .map(|raw| {
+            raw.split(TAG_SEPARATOR)
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(ImportRow {
+        line: 0,
+        date,
+        amount: raw_amount.abs(),
+        transaction_type,
+        product_name,
+        description,
+        tags,
+        external_id: None,
+        account: None,
+        value_date: None,
+        import_hash: None,
+    })
+}
@@ -0,0 +1,372 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Multipart, Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::{error_response, AppState, JsonResult};
+
+use super::beancount_import::parse_beancount;
+use super::camt_import::parse_camt053;
+use super::csv_import::{parse_csv, validate_mapping};
+use super::models::{
+    CsvMapping, ImportProfile, ImportProfileDto, ImportProfilePayload, ImportResult, ImportRow,
+    ImportRowPreview, NewImportProfile, ParsedStatement,
+};
+use super::mt940_import::parse_mt940;
+use super::ofx_import::parse_ofx;
+use super::qif_import::parse_qif;
+use super::services::{
+    assign_import_hashes, commit_rows, find_duplicates, reject_out_of_range_amounts,
+};
+
+/// The parts of a statement upload: the file itself plus any text fields.
+pub struct StatementUpload {
+    pub file: Vec<u8>,
+    pub fields: HashMap<String, String>,
+}
+
+impl StatementUpload {
+    /// Imports are dry runs unless `dry_run=false` is sent explicitly.
+    pub fn dry_run(&self) -> bool {
+        self.fields
+            .get("dry_run")
+            .map(|v| !v.trim().eq_ignore_ascii_case("false"))
+            .unwrap_or(true)
+    }
+}
+
+/// Reads a multipart upload with a `file` part and optional text fields.
+pub async fn read_statement_upload(mut multipart: Multipart) -> Result<StatementUpload, String> {
+    let mut file = None;
+    let mut fields = HashMap::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| format!("Invalid multipart body: {e}"))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to read uploaded file: {e}"))?;
+            file = Some(bytes.to_vec());
+        } else {
+            let text = field
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read field {name}: {e}"))?;
+            fields.insert(name, text);
+        }
+    }
+
+    let file = file.ok_or_else(|| "Missing \"file\" part".to_string())?;
+    Ok(StatementUpload { file, fields })
+}
+
+/// Previews a parsed statement, or commits every row in one Diesel transaction.
+///
+/// Shared by all statement importers. A real import is refused while any line
+/// fails to parse, so a dry run should always come first. Rows whose external
+/// id or import hash was imported before are flagged as duplicates and skipped.
+pub fn finish_import(
+    state: &AppState,
+    household: ActiveHousehold,
+    mut parsed: ParsedStatement,
+    dry_run: bool,
+) -> JsonResult<ImportResult> {
+    reject_out_of_range_amounts(&mut parsed);
+    assign_import_hashes(&mut parsed.rows);
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let duplicates = find_duplicates(&mut conn, household.id, &parsed.rows)
+        .map_err(|e| error_response(format!("Error checking for duplicates: {e}")))?;
+    let skipped = duplicates.iter().filter(|d| **d).count();
+    let previews = parsed
+        .rows
+        .iter()
+        .zip(&duplicates)
+        .map(|(row, duplicate)| ImportRowPreview {
+            duplicate: *duplicate,
+            ..ImportRowPreview::from(row)
+        })
+        .collect();
+
+    if dry_run {
+        return Ok(Json(ImportResult {
+            dry_run,
+            imported: 0,
+            skipped,
+            rows: previews,
+            errors: parsed.errors,
+        }));
+    }
+
+    if !parsed.errors.is_empty() {
+        return Err(error_response(format!(
+            "{} line(s) could not be parsed; run a dry run to see them",
+            parsed.errors.len()
+        )));
+    }
+
+    let new_rows: Vec<ImportRow> = parsed
+        .rows
+        .into_iter()
+        .zip(duplicates)
+        .filter(|(_, duplicate)| !duplicate)
+        .map(|(row, _)| row)
+        .collect();
+
+    let result = conn.transaction::<Vec<i32>, DieselError, _>(|txn_conn| {
+        commit_rows(txn_conn, household, &new_rows)
+    });
+
+    match result {
+        Ok(ids) => Ok(Json(ImportResult {
+            dry_run,
+            imported: ids.len(),
+            skipped,
+            rows: previews,
+            errors: Vec::new(),
+        })),
+        Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info)) => Err(
+            error_response(format!("Duplicate transaction entry: {}", info.message())),
+        ),
+        Err(e) => Err(error_response(format!(
+            "Failed to import transactions: {e}"
+        ))),
+    }
+}
+
+/// Handler for POST /import/csv.
+///
+/// Multipart fields: `file` (the CSV), either `profile_id` or `mapping` (a JSON
+/// `CsvMapping`), and `dry_run` ("false" to actually import).
+#[debug_handler]
+pub async fn import_csv(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    multipart: Multipart,
+) -> JsonResult<ImportResult> {
+    use crate::schema::import_profiles::dsl as ip;
+
+    let upload = read_statement_upload(multipart)
+        .await
+        .map_err(error_response)?;
+
+    let mapping: CsvMapping = if let Some(profile_id) = upload.fields.get("profile_id") {
+        let profile_id: i32 = profile_id
+            .trim()
+            .parse()
+            .map_err(|_| error_response("profile_id must be a number"))?;
+        let mut conn = state
+            .pool
+            .get()
+            .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+        let profile = ip::import_profiles
+            .filter(ip::id.eq(profile_id))
+            .filter(ip::household_id.eq(household.id))
+            .first::<ImportProfile>(&mut conn)
+            .optional()
+            .map_err(|e| error_response(format!("Error loading import profile: {e}")))?
+            .ok_or_else(|| error_response("Import profile not found"))?;
+        CsvMapping::from(&profile)
+    } else if let Some(raw) = upload.fields.get("mapping") {
+        serde_json::from_str(raw).map_err(|e| error_response(format!("Invalid mapping: {e}")))?
+    } else {
+        return Err(error_response("Either profile_id or mapping is required"));
+    };
+
+    let parsed = parse_csv(&upload.file, &mapping).map_err(error_response)?;
+    finish_import(&state, household, parsed, upload.dry_run())
+}
+
+/// Handler for POST /import/ofx.
+///
+/// Accepts OFX and QFX files. Multipart fields: `file` and `dry_run`.
+#[debug_handler]
+pub async fn import_ofx(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    multipart: Multipart,
+) -> JsonResult<ImportResult> {
+    let upload = read_statement_upload(multipart)
+        .await
+        .map_err(error_response)?;
+    let parsed = parse_ofx(&upload.file).map_err(error_response)?;
+    finish_import(&state, household, parsed, upload.dry_run())
+}
+
+/// Handler for POST /import/qif.
+///
+/// Multipart fields: `file`, `dry_run`, and optionally `date_format` (chrono
+/// format; month/day/year by default) and `decimal_separator` ("." by default).
+#[debug_handler]
+pub async fn import_qif(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    multipart: Multipart,
+) -> JsonResult<ImportResult> {
+    let upload = read_statement_upload(multipart)
+        .await
+        .map_err(error_response)?;
+    let decimal_separator = match upload.fields.get("decimal_separator").map(|s| s.trim()) {
+        None | Some(".") => '.',
+        Some(",") => ',',
+        Some(_) => return Err(error_response("Decimal separator must be \".\" or \",\"")),
+    };
+    let date_format = upload
+        .fields
+        .get("date_format")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty());
+
+    let parsed = parse_qif(&upload.file, date_format, decimal_separator).map_err(error_response)?;
+    finish_import(&state, household, parsed, upload.dry_run())
+}
+
+/// Handler for POST /import/camt053.
+///
+/// Accepts ISO 20022 CAMT.053 XML. Multipart fields: `file` and `dry_run`.
+#[debug_handler]
+pub async fn import_camt053(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    multipart: Multipart,
+) -> JsonResult<ImportResult> {
+    let upload = read_statement_upload(multipart)
+        .await
+        .map_err(error_response)?;
+    let parsed = parse_camt053(&upload.file).map_err(error_response)?;
+    finish_import(&state, household, parsed, upload.dry_run())
+}
+
+/// Handler for POST /import/mt940.
+///
+/// Multipart fields: `file` and `dry_run`.
+#[debug_handler]
+pub async fn import_mt940(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    multipart: Multipart,
+) -> JsonResult<ImportResult> {
+    let upload = read_statement_upload(multipart)
+        .await
+        .map_err(error_response)?;
+    let parsed = parse_mt940(&upload.file).map_err(error_response)?;
+    finish_import(&state, household, parsed, upload.dry_run())
+}
+
+/// Handler for POST /import/beancount.
+///
+/// Reads transactions back from a beancount journal, such as one written by
+/// GET /export/journal. Multipart fields: `file` and `dry_run`.
+#[debug_handler]
+pub async fn import_beancount(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    multipart: Multipart,
+) -> JsonResult<ImportResult> {
+    let upload = read_statement_upload(multipart)
+        .await
+        .map_err(error_response)?;
+    let parsed = parse_beancount(&upload.file).map_err(error_response)?;
+    finish_import(&state, household, parsed, upload.dry_run())
+}
+
+/// Handler for POST /import-profiles.
+#[debug_handler]
+pub async fn create_import_profile(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(payload): Json<ImportProfilePayload>,
+) -> JsonResult<ImportProfileDto> {
+    use crate::schema::import_profiles::dsl;
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(error_response("Profile name must not be empty"));
+    }
+    validate_mapping(&payload.mapping).map_err(error_response)?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let new_profile = NewImportProfile::new(household.id, name, payload.mapping);
+    let inserted = diesel::insert_into(dsl::import_profiles)
+        .values(&new_profile)
+        .get_result::<ImportProfile>(&mut conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                error_response("An import profile with that name already exists")
+            }
+            e => error_response(format!("Failed to create import profile: {e}")),
+        })?;
+
+    Ok(Json(inserted.into()))
+}
+
+/// Handler for GET /import-profiles.
+#[debug_handler]
+pub async fn list_import_profiles(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> JsonResult<Vec<ImportProfileDto>> {
+    use crate::schema::import_profiles::dsl::*;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let items = import_profiles
+        .filter(household_id.eq(household.id))
+        .order(name.asc())
+        .load::<ImportProfile>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading import profiles: {e}")))?;
+
+    Ok(Json(
+        items.into_iter().map(ImportProfileDto::from).collect(),
+    ))
+}
+
+/// Handler for DELETE /import-profiles/{id}.
+#[debug_handler]
+pub async fn delete_import_profile(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(profile_id): Path<i32>,
+) -> JsonResult<ImportProfileDto> {
+    use crate::schema::import_profiles::dsl::*;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let deleted = diesel::delete(
+        import_profiles
+            .filter(id.eq(profile_id))
+            .filter(household_id.eq(household.id)),
+    )
+    .get_result::<ImportProfile>(&mut conn)
+    .optional()
+    .map_err(|e| error_response(format!("Failed to delete import profile: {e}")))?;
+
+    match deleted {
+        Some(profile) => Ok(Json(profile.into())),
+        None => Err(error_response("Import profile not found")),
+    }
+}
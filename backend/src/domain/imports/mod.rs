@@ -0,0 +1,9 @@
+pub mod beancount_import;
+pub mod camt_import;
+pub mod csv_import;
+pub mod handlers;
+pub mod models;
+pub mod mt940_import;
+pub mod ofx_import;
+pub mod qif_import;
+pub mod services;
@@ -0,0 +1,249 @@
+use crate::domain::transactions::models::TransactionType;
+use crate::schema::import_profiles;
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Column mapping and parsing options for a CSV bank statement.
+///
+/// Columns are referenced by header name (case-insensitive) or by zero-based index.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsvMapping {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: String,
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+    /// Preamble lines to skip before the header (or the first row).
+    #[serde(default)]
+    pub skip_rows: i32,
+    pub date_column: String,
+    /// A chrono format string such as "%d.%m.%Y".
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// Either "." or ",".
+    #[serde(default = "default_decimal_separator")]
+    pub decimal_separator: String,
+    pub amount_column: String,
+    /// Optional column holding a debit/credit indicator; the amount is then unsigned.
+    pub sign_column: Option<String>,
+    /// Value of `sign_column` that marks income (defaults to "CR").
+    pub income_indicator: Option<String>,
+    /// Treat positive amounts as expenses (for banks that list debits as positive).
+    #[serde(default)]
+    pub invert_sign: bool,
+    pub description_column: Option<String>,
+    /// Column used as product name; falls back to the description.
+    pub product_column: Option<String>,
+    /// Column with tag names separated by "|".
+    pub tags_column: Option<String>,
+}
+
+fn default_delimiter() -> String {
+    ",".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_decimal_separator() -> String {
+    ".".to_string()
+}
+
+/// A saved CSV mapping profile, mapped to the `import_profiles` table.
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = import_profiles)]
+pub struct ImportProfile {
+    pub id: i32,
+    pub household_id: i32,
+    pub name: String,
+    pub delimiter: String,
+    pub has_header: bool,
+    pub skip_rows: i32,
+    pub date_column: String,
+    pub date_format: String,
+    pub decimal_separator: String,
+    pub amount_column: String,
+    pub sign_column: Option<String>,
+    pub income_indicator: Option<String>,
+    pub invert_sign: bool,
+    pub description_column: Option<String>,
+    pub product_column: Option<String>,
+    pub tags_column: Option<String>,
+}
+
+impl From<&ImportProfile> for CsvMapping {
+    fn from(p: &ImportProfile) -> Self {
+        Self {
+            delimiter: p.delimiter.clone(),
+            has_header: p.has_header,
+            skip_rows: p.skip_rows,
+            date_column: p.date_column.clone(),
+            date_format: p.date_format.clone(),
+            decimal_separator: p.decimal_separator.clone(),
+            amount_column: p.amount_column.clone(),
+            sign_column: p.sign_column.clone(),
+            income_indicator: p.income_indicator.clone(),
+            invert_sign: p.invert_sign,
+            description_column: p.description_column.clone(),
+            product_column: p.product_column.clone(),
+            tags_column: p.tags_column.clone(),
+        }
+    }
+}
+
+/// For inserting a new import profile.
+#[derive(Insertable)]
+#[diesel(table_name = import_profiles)]
+pub struct NewImportProfile {
+    pub household_id: i32,
+    pub name: String,
+    pub delimiter: String,
+    pub has_header: bool,
+    pub skip_rows: i32,
+    pub date_column: String,
+    pub date_format: String,
+    pub decimal_separator: String,
+    pub amount_column: String,
+    pub sign_column: Option<String>,
+    pub income_indicator: Option<String>,
+    pub invert_sign: bool,
+    pub description_column: Option<String>,
+    pub product_column: Option<String>,
+    pub tags_column: Option<String>,
+}
+
+impl NewImportProfile {
+    pub fn new(household_id: i32, name: String, m: CsvMapping) -> Self {
+        Self {
+            household_id,
+            name,
+            delimiter: m.delimiter,
+            has_header: m.has_header,
+            skip_rows: m.skip_rows,
+            date_column: m.date_column,
+            date_format: m.date_format,
+            decimal_separator: m.decimal_separator,
+            amount_column: m.amount_column,
+            sign_column: m.sign_column,
+            income_indicator: m.income_indicator,
+            invert_sign: m.invert_sign,
+            description_column: m.description_column,
+            product_column: m.product_column,
+            tags_column: m.tags_column,
+        }
+    }
+}
+
+/// Payload received from the client when saving an import profile.
+#[derive(Deserialize)]
+pub struct ImportProfilePayload {
+    pub name: String,
+    #[serde(flatten)]
+    pub mapping: CsvMapping,
+}
+
+/// DTO for returning an import profile.
+#[derive(Serialize)]
+pub struct ImportProfileDto {
+    pub id: i32,
+    pub name: String,
+    #[serde(flatten)]
+    pub mapping: CsvMapping,
+}
+
+impl From<ImportProfile> for ImportProfileDto {
+    fn from(profile: ImportProfile) -> Self {
+        Self {
+            id: profile.id,
+            mapping: CsvMapping::from(&profile),
+            name: profile.name,
+        }
+    }
+}
+
+/// A statement line in the common shape every importer produces.
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    /// Line (or entry) number in the source file, for error messages.
+    pub line: usize,
+    pub date: NaiveDateTime,
+    /// Always positive, in cents; the direction is in `transaction_type`.
+    pub amount: i64,
+    pub transaction_type: TransactionType,
+    pub product_name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// The bank's id for the transaction, if the format has one (e.g. OFX FITID).
+    pub external_id: Option<String>,
+    pub account: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    /// Set by `assign_import_hashes` for rows without an external id.
+    pub import_hash: Option<String>,
+}
+
+/// A line that could not be parsed.
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// A parsed statement: the rows that parsed and the ones that didn't.
+#[derive(Debug, Default)]
+pub struct ParsedStatement {
+    pub rows: Vec<ImportRow>,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// A preview of a single row as it would be imported.
+#[derive(Debug, Serialize)]
+pub struct ImportRowPreview {
+    pub line: usize,
+    pub date: NaiveDateTime,
+    /// Amount as a float (dollars).
+    pub amount: f64,
+    pub transaction_type: TransactionType,
+    pub product_name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub external_id: Option<String>,
+    pub account: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    /// Already imported earlier (or repeated in the file); skipped on import.
+    pub duplicate: bool,
+}
+
+impl From<&ImportRow> for ImportRowPreview {
+    fn from(row: &ImportRow) -> Self {
+        Self {
+            line: row.line,
+            date: row.date,
+            amount: row.amount as f64 / 100.0,
+            transaction_type: row.transaction_type,
+            product_name: row.product_name.clone(),
+            description: row.description.clone(),
+            tags: row.tags.clone(),
+            external_id: row.external_id.clone(),
+            account: row.account.clone(),
+            value_date: row.value_date,
+            duplicate: false,
+        }
+    }
+}
+
+/// The response of an import, for both dry runs and real imports.
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub dry_run: bool,
+    /// Number of transactions created (always 0 for a dry run).
+    pub imported: usize,
+    /// Number of rows skipped because they were imported before.
+    pub skipped: usize,
+    pub rows: Vec<ImportRowPreview>,
+    pub errors: Vec<ImportRowError>,
+}
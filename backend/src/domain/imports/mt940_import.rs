@@ -0,0 +1,249 @@
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{ImportRow, ImportRowError, ParsedStatement};
+use super::services::parse_amount;
+
+/// Customer reference banks use when there is none.
+const NO_REFERENCE: &str = "NONREF";
+
+/// A `:61:` statement line with the `:86:` information that follows it.
+struct StatementLine {
+    line: usize,
+    account: Option<String>,
+    details: String,
+    information: Vec<String>,
+}
+
+/// Splits an MT940 file into `:61:` lines, each with its `:86:` text.
+fn collect_lines(text: &str) -> Result<Vec<StatementLine>, String> {
+    let mut lines = Vec::new();
+    let mut account: Option<String> = None;
+    let mut field: Option<String> = None;
+    let mut seen_statement = false;
+
+    for (idx, raw) in text.lines().enumerate() {
+        let raw = raw.trim_end();
+        // SWIFT envelope blocks and end-of-message markers.
+        let raw = match raw.find("{4:") {
+            Some(pos) => &raw[pos + 3..],
+            None => raw,
+        };
+        if raw.is_empty() || raw.starts_with('{') || raw == "-" || raw == "-}" {
+            continue;
+        }
+
+        if let Some(rest) = raw.strip_prefix(':') {
+            let Some((tag, value)) = rest.split_once(':') else {
+                continue;
+            };
+            field = Some(tag.to_string());
+            match tag {
+                "20" => seen_statement = true,
+                "25" => account = Some(value.trim().to_string()),
+                "61" => lines.push(StatementLine {
+                    line: idx + 1,
+                    account: account.clone(),
+                    details: value.to_string(),
+                    information: Vec::new(),
+                }),
+                "86" => {
+                    if let Some(last) = lines.last_mut() {
+                        last.information.push(value.to_string());
+                    }
+                }
+                _ => {}
+            }
+        } else if field.as_deref() == Some("86") {
+            if let Some(last) = lines.last_mut() {
+                last.information.push(raw.to_string());
+            }
+        }
+    }
+
+    if !seen_statement {
+        return Err("Not an MT940 statement: missing :20: field".to_string());
+    }
+    Ok(lines)
+}
+
+/// Parses an MT940 date "YYMMDD".
+fn parse_yymmdd(raw: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("20{raw}"), "%Y%m%d").ok()
+}
+
+/// The fields of a `:61:` line that the importer uses.
+struct ParsedDetails {
+    value_date: NaiveDate,
+    booking_date: NaiveDate,
+    income: bool,
+    amount: i64,
+    reference: Option<String>,
+}
+
+/// Parses `:61:` such as "2501030103DR42,10NMSCNONREF//B5A03XYZ".
+///
+/// Layout: value date, optional booking MMDD, debit/credit mark (D, C, RD,
+/// RC), optional funds code, amount, 4-char type code, customer reference
+/// and an optional "//" bank reference.
+fn parse_details(raw: &str) -> Result<ParsedDetails, String> {
+    let invalid = |what: &str| format!("Invalid :61: {what}: {raw:?}");
+    let value_date = raw
+        .get(..6)
+        .and_then(parse_yymmdd)
+        .ok_or_else(|| invalid("value date"))?;
+    let mut rest = &raw[6..];
+
+    let booking_date = match rest.get(..4) {
+        Some(mmdd) if mmdd.chars().all(|c| c.is_ascii_digit()) => {
+            rest = &rest[4..];
+            let month: u32 = mmdd[..2].parse().map_err(|_| invalid("booking date"))?;
+            let day: u32 = mmdd[2..].parse().map_err(|_| invalid("booking date"))?;
+            // Booking and value date can straddle the turn of the year.
+            let year = match (month, value_date.month()) {
+                (12, 1) => value_date.year() - 1,
+                (1, 12) => value_date.year() + 1,
+                _ => value_date.year(),
+            };
+            NaiveDate::from_ymd_opt(year, month, day).ok_or_else(|| invalid("booking date"))?
+        }
+        _ => value_date,
+    };
+
+    let (income, mark_len) = if rest.starts_with("RC") {
+        (false, 2)
+    } else if rest.starts_with("RD") {
+        (true, 2)
+    } else if rest.starts_with('C') {
+        (true, 1)
+    } else if rest.starts_with('D') {
+        (false, 1)
+    } else {
+        return Err(invalid("debit/credit mark"));
+    };
+    rest = &rest[mark_len..];
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..];
+    }
+
+    let amount_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == ','))
+        .unwrap_or(rest.len());
+    let amount = parse_amount(&rest[..amount_len], ',')?;
+    rest = &rest[amount_len..];
+
+    let references = rest.get(4..).unwrap_or("");
+    let (customer, bank) = match references.split_once("//") {
+        Some((customer, bank)) => (customer, Some(bank)),
+        None => (references, None),
+    };
+    let reference = bank
+        .map(|b| b.split_whitespace().next().unwrap_or(""))
+        .filter(|b| !b.is_empty())
+        .or(Some(customer.trim()).filter(|c| !c.is_empty() && *c != NO_REFERENCE))
+        .map(str::to_string);
+
+    Ok(ParsedDetails {
+        value_date,
+        booking_date,
+        income,
+        amount,
+        reference,
+    })
+}
+
+/// Splits structured `:86:` text ("166?00GUTSCHRIFT?20Invoice 42?32ACME")
+/// into its `?NN` subfields. Returns `None` for free text.
+fn structured_subfields(text: &str) -> Option<BTreeMap<u32, String>> {
+    let (code, rest) = text.split_at_checked(3)?;
+    if !code.chars().all(|c| c.is_ascii_digit()) || !rest.starts_with('?') {
+        return None;
+    }
+    let mut subfields: BTreeMap<u32, String> = BTreeMap::new();
+    for part in rest.split('?').skip(1) {
+        let (key, value) = part.split_at_checked(2)?;
+        let key: u32 = key.parse().ok()?;
+        subfields.entry(key).or_default().push_str(value);
+    }
+    Some(subfields)
+}
+
+/// Counterparty and remittance info from the `:86:` lines.
+fn parse_information(lines: &[String]) -> (Option<String>, Option<String>) {
+    // Structured fields wrap mid-word, free text wraps at word boundaries.
+    let joined = lines.concat();
+    let Some(subfields) = structured_subfields(&joined) else {
+        let text = lines.join(" ").trim().to_string();
+        return (None, Some(text).filter(|t| !t.is_empty()));
+    };
+
+    let join = |keys: &[u32], separator: &str| {
+        let text = keys
+            .iter()
+            .filter_map(|k| subfields.get(k))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join(separator);
+        Some(text).filter(|t| !t.is_empty())
+    };
+    let remittance_keys: Vec<u32> = (20..=29).chain(60..=63).collect();
+    let counterparty = join(&[32, 33], "").or_else(|| join(&[0], ""));
+    (counterparty, join(&remittance_keys, " "))
+}
+
+/// Parses a SWIFT MT940 customer statement.
+///
+/// Each `:61:` line becomes a row: the counterparty from the `:86:` text is
+/// the product and the remittance info the description. The bank reference
+/// (or the customer reference unless it is "NONREF"), prefixed with the
+/// account from `:25:`, is the external id.
+pub fn parse_mt940(data: &[u8]) -> Result<ParsedStatement, String> {
+    let text = String::from_utf8_lossy(data);
+    let mut parsed = ParsedStatement::default();
+
+    for statement_line in collect_lines(&text)? {
+        let line = statement_line.line;
+        match mt940_row(statement_line) {
+            Ok(row) => parsed.rows.push(row),
+            Err(message) => parsed.errors.push(ImportRowError { line, message }),
+        }
+    }
+    Ok(parsed)
+}
+
+fn mt940_row(statement_line: StatementLine) -> Result<ImportRow, String> {
+    let details = parse_details(&statement_line.details)?;
+    if details.amount == 0 {
+        return Err("Amount is zero".to_string());
+    }
+    let (counterparty, remittance) = parse_information(&statement_line.information);
+    let product_name = counterparty
+        .or_else(|| remittance.clone())
+        .ok_or("Missing counterparty and remittance information in :86:")?;
+    let account = statement_line.account;
+    let external_id = details.reference.map(|r| match &account {
+        Some(account) => format!("{account}:{r}"),
+        None => r,
+    });
+
+    Ok(ImportRow {
+        line: statement_line.line,
+        date: details.booking_date.and_time(NaiveTime::MIN),
+        amount: details.amount,
+        transaction_type: if details.income {
+            TransactionType::Income
+        } else {
+            TransactionType::Expense
+        },
+        product_name,
+        description: remittance,
+        tags: Vec::new(),
+        external_id,
+        account,
+        value_date: Some(details.value_date),
+        import_hash: None,
+    })
+}
@@ -0,0 +1,183 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::HashMap;
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::models::{ImportRow, ImportRowError, ParsedStatement};
+use super::services::parse_amount;
+
+/// A tag from an OFX document with the text that follows it.
+///
+/// OFX 1.x is SGML where leaf elements have no closing tag, OFX 2.x is XML;
+/// reading "tag, then text up to the next tag" handles both.
+struct Element {
+    name: String,
+    closing: bool,
+    value: Option<String>,
+}
+
+fn decode_entities(raw: &str) -> String {
+    raw.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn tokenize(text: &str) -> Vec<Element> {
+    let mut elements = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim();
+        rest = &rest[start + end + 1..];
+
+        // Processing instructions (<?xml ...?>, <?OFX ...?>) and comments.
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        let (closing, name) = match tag.strip_prefix('/') {
+            Some(name) => (true, name),
+            None => (false, tag.trim_end_matches('/')),
+        };
+        let text_end = rest.find('<').unwrap_or(rest.len());
+        let value = rest[..text_end].trim();
+        elements.push(Element {
+            name: name.trim().to_ascii_uppercase(),
+            closing,
+            value: (!value.is_empty()).then(|| decode_entities(value)),
+        });
+    }
+    elements
+}
+
+/// Parses an OFX date such as "20250103", "20250103120000" or
+/// "20250103120000.000[-5:EST]". The timezone is ignored.
+pub fn parse_ofx_date(raw: &str) -> Result<NaiveDateTime, String> {
+    let digits: String = raw.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let invalid = || format!("Invalid OFX date: {raw:?}");
+    if digits.len() < 8 {
+        return Err(invalid());
+    }
+    let date = NaiveDate::parse_from_str(&digits[..8], "%Y%m%d").map_err(|_| invalid())?;
+    let time = if digits.len() >= 14 {
+        NaiveTime::parse_from_str(&digits[8..14], "%H%M%S").map_err(|_| invalid())?
+    } else {
+        NaiveTime::MIN
+    };
+    Ok(date.and_time(time))
+}
+
+/// Parses an OFX or QFX statement (OFX 1.x SGML or 2.x XML).
+///
+/// Every `<STMTTRN>` becomes a row: NAME (the payee) is the product, MEMO the
+/// description, and FITID, prefixed with the account id, the external id.
+pub fn parse_ofx(data: &[u8]) -> Result<ParsedStatement, String> {
+    let text = String::from_utf8_lossy(data);
+    let elements = tokenize(&text);
+    if !elements.iter().any(|e| e.name == "OFX" && !e.closing) {
+        return Err("Not an OFX file: missing <OFX> element".to_string());
+    }
+
+    let mut parsed = ParsedStatement::default();
+    let mut account: Option<String> = None;
+    let mut current: Option<HashMap<String, String>> = None;
+    let mut entry = 0;
+
+    for element in elements {
+        match (element.closing, element.name.as_str()) {
+            (false, "STMTTRN") => {
+                if let Some(fields) = current.take() {
+                    push_entry(&mut parsed, &fields, entry, account.as_deref());
+                }
+                entry += 1;
+                current = Some(HashMap::new());
+            }
+            (true, "STMTTRN") => {
+                if let Some(fields) = current.take() {
+                    push_entry(&mut parsed, &fields, entry, account.as_deref());
+                }
+            }
+            (false, "ACCTID") if current.is_none() => account = element.value,
+            (false, name) => {
+                if let (Some(fields), Some(value)) = (current.as_mut(), element.value) {
+                    // The first NAME wins over a nested PAYEE/NAME.
+                    fields.entry(name.to_string()).or_insert(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(fields) = current.take() {
+        push_entry(&mut parsed, &fields, entry, account.as_deref());
+    }
+
+    Ok(parsed)
+}
+
+fn push_entry(
+    parsed: &mut ParsedStatement,
+    fields: &HashMap<String, String>,
+    entry: usize,
+    account: Option<&str>,
+) {
+    match ofx_row(fields, account) {
+        Ok(mut row) => {
+            row.line = entry;
+            parsed.rows.push(row);
+        }
+        Err(message) => parsed.errors.push(ImportRowError {
+            line: entry,
+            message,
+        }),
+    }
+}
+
+fn ofx_row(fields: &HashMap<String, String>, account: Option<&str>) -> Result<ImportRow, String> {
+    let date = fields
+        .get("DTPOSTED")
+        .or_else(|| fields.get("DTUSER"))
+        .ok_or_else(|| "Missing DTPOSTED".to_string())
+        .and_then(|raw| parse_ofx_date(raw))?;
+    let amount = parse_amount(
+        fields
+            .get("TRNAMT")
+            .ok_or_else(|| "Missing TRNAMT".to_string())?,
+        '.',
+    )?;
+    if amount == 0 {
+        return Err("Amount is zero".to_string());
+    }
+
+    let memo = fields.get("MEMO").cloned();
+    let product_name = fields
+        .get("NAME")
+        .cloned()
+        .or_else(|| memo.clone())
+        .ok_or_else(|| "Missing NAME and MEMO".to_string())?;
+    let external_id = fields.get("FITID").map(|fitid| match account {
+        Some(account) => format!("{account}:{fitid}"),
+        None => fitid.clone(),
+    });
+
+    Ok(ImportRow {
+        line: 0,
+        date,
+        amount: amount.abs(),
+        transaction_type: if amount > 0 {
+            TransactionType::Income
+        } else {
+            TransactionType::Expense
+        },
+        product_name,
+        description: memo,
+        tags: Vec::new(),
+        external_id,
+        account: account.map(str::to_string),
+        value_date: None,
+        import_hash: None,
+    })
+}
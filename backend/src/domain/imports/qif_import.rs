@@ -0,0 +1,158 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::domain::transactions::models::TransactionType;
+
+use super::csv_import::parse_date;
+use super::models::{ImportRow, ImportRowError, ParsedStatement};
+use super::services::parse_amount;
+
+/// QIF account types whose records are plain cash transactions.
+const TRANSACTION_TYPES: [&str; 5] = ["bank", "cash", "ccard", "oth a", "oth l"];
+
+/// Parses a Quicken date such as "1/3'25", "01/03/2025" or "2025-01-03".
+///
+/// Without an explicit `format`, slash dates are month/day/year; two-digit
+/// years after an apostrophe are 20xx, otherwise 19xx from 50 on.
+pub fn parse_qif_date(raw: &str, format: Option<&str>) -> Result<NaiveDateTime, String> {
+    let compact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(format) = format {
+        return parse_date(&compact.replace('\'', "/"), format);
+    }
+
+    let invalid = || format!("Invalid QIF date: {raw:?}");
+    let parts: Vec<&str> = compact.split(['/', '\'']).collect();
+    let date = if let [month, day, year] = parts[..] {
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+        let day: u32 = day.parse().map_err(|_| invalid())?;
+        let mut year: i32 = year.parse().map_err(|_| invalid())?;
+        if year < 100 {
+            year += if compact.contains('\'') || year < 50 {
+                2000
+            } else {
+                1900
+            };
+        }
+        NaiveDate::from_ymd_opt(year, month, day).ok_or_else(invalid)?
+    } else {
+        NaiveDate::parse_from_str(&compact, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(&compact, "%d.%m.%Y"))
+            .map_err(|_| invalid())?
+    };
+    Ok(date.and_time(NaiveTime::MIN))
+}
+
+/// One record between `^` separators, with the line it started on.
+#[derive(Default)]
+struct QifRecord {
+    line: usize,
+    date: Option<String>,
+    amount: Option<String>,
+    payee: Option<String>,
+    memo: Option<String>,
+}
+
+/// Parses a QIF export of a bank, cash or credit card account.
+///
+/// The payee (P) is the product and the memo (M) the description. QIF has no
+/// transaction ids, so rows carry no external id. Sections of other types
+/// (investments, category lists, ...) are skipped.
+pub fn parse_qif(
+    data: &[u8],
+    date_format: Option<&str>,
+    decimal_separator: char,
+) -> Result<ParsedStatement, String> {
+    let text = String::from_utf8_lossy(data);
+    let mut parsed = ParsedStatement::default();
+    let mut in_transactions = false;
+    let mut seen_header = false;
+    let mut record = QifRecord::default();
+
+    for (idx, raw_line) in text.lines().enumerate() {
+        let line = raw_line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('!') {
+            seen_header = true;
+            if let Some(kind) = header.strip_prefix("Type:") {
+                in_transactions = TRANSACTION_TYPES.contains(&kind.trim().to_lowercase().as_str());
+            } else if header.starts_with("Account") {
+                in_transactions = false;
+            }
+            record = QifRecord::default();
+            continue;
+        }
+        if !in_transactions {
+            continue;
+        }
+
+        // The code is one character, which need not be ASCII in a broken file.
+        let (code, value) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+        let value = value.trim();
+        if record.line == 0 {
+            record.line = idx + 1;
+        }
+        match code {
+            "D" => record.date = Some(value.to_string()),
+            "T" => record.amount = Some(value.to_string()),
+            "U" if record.amount.is_none() => record.amount = Some(value.to_string()),
+            "P" => record.payee = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "M" => record.memo = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "^" => {
+                let finished = std::mem::take(&mut record);
+                let line = finished.line;
+                match qif_row(finished, date_format, decimal_separator) {
+                    Ok(row) => parsed.rows.push(row),
+                    Err(message) => parsed.errors.push(ImportRowError { line, message }),
+                }
+            }
+            // Number, cleared status, category, splits, addresses: not imported.
+            _ => {}
+        }
+    }
+
+    if !seen_header {
+        return Err("Not a QIF file: missing !Type header".to_string());
+    }
+    Ok(parsed)
+}
+
+fn qif_row(
+    record: QifRecord,
+    date_format: Option<&str>,
+    decimal_separator: char,
+) -> Result<ImportRow, String> {
+    let date = parse_qif_date(
+        record.date.as_deref().ok_or("Missing date (D)")?,
+        date_format,
+    )?;
+    let amount = parse_amount(
+        record.amount.as_deref().ok_or("Missing amount (T)")?,
+        decimal_separator,
+    )?;
+    if amount == 0 {
+        return Err("Amount is zero".to_string());
+    }
+    let product_name = record
+        .payee
+        .or_else(|| record.memo.clone())
+        .ok_or("Missing payee (P) and memo (M)")?;
+
+    Ok(ImportRow {
+        line: record.line,
+        date,
+        amount: amount.abs(),
+        transaction_type: if amount > 0 {
+            TransactionType::Income
+        } else {
+            TransactionType::Expense
+        },
+        product_name,
+        description: record.memo,
+        tags: Vec::new(),
+        external_id: None,
+        account: None,
+        value_date: None,
+        import_hash: None,
+    })
+}
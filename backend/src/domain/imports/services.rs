@@ -0,0 +1,218 @@
+use diesel::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::tags::models::TagReference;
+use crate::domain::transactions::models::TransactionPayload;
+use crate::domain::transactions::services::insert_transaction;
+
+use super::models::{ImportRow, ImportRowError, ParsedStatement};
+
+/// Parses a money amount such as "-1.234,56" or "1,234.56 EUR" into cents.
+///
+/// `decimal_separator` picks which of "." and "," separates the cents; the
+/// other one is treated as a thousands separator and ignored, as are spaces,
+/// apostrophes and currency symbols.
+pub fn parse_amount(raw: &str, decimal_separator: char) -> Result<i64, String> {
+    let mut negative = false;
+    let mut units = String::new();
+    let mut fraction: Option<String> = None;
+
+    let trimmed = raw.trim();
+    // Accounting notation: (12.50) means -12.50.
+    let (trimmed, parenthesized) = match trimmed.strip_prefix('(').and_then(|s| s.strip_suffix(')'))
+    {
+        Some(inner) => (inner, true),
+        None => (trimmed, false),
+    };
+
+    for c in trimmed.chars() {
+        match c {
+            '0'..='9' => match fraction.as_mut() {
+                Some(f) => f.push(c),
+                None => units.push(c),
+            },
+            '-' | '\u{2212}' => negative = true,
+            c if c == decimal_separator => {
+                if fraction.is_some() {
+                    return Err(format!("Invalid amount: {raw}"));
+                }
+                fraction = Some(String::new());
+            }
+            '.' | ',' | ' ' | '\'' | '+' | '\u{a0}' => {}
+            c if c.is_alphabetic() || "$€£¥".contains(c) => {}
+            _ => return Err(format!("Invalid amount: {raw}")),
+        }
+    }
+
+    if units.is_empty() && fraction.as_deref().unwrap_or("").is_empty() {
+        return Err(format!("Invalid amount: {raw:?}"));
+    }
+    let fraction = fraction.unwrap_or_default();
+    if fraction.len() > 2 {
+        return Err(format!("Too many decimal places: {raw}"));
+    }
+
+    let units: i64 = if units.is_empty() {
+        0
+    } else {
+        units
+            .parse()
+            .map_err(|_| format!("Invalid amount: {raw}"))?
+    };
+    let cents: i64 = format!("{fraction:0<2}")
+        .parse()
+        .map_err(|_| format!("Invalid amount: {raw}"))?;
+
+    let value = units
+        .checked_mul(100)
+        .and_then(|v| v.checked_add(cents))
+        .ok_or_else(|| format!("Amount out of range: {raw}"))?;
+    Ok(if negative || parenthesized {
+        -value
+    } else {
+        value
+    })
+}
+
+/// Turns rows whose amount a price cannot hold (zero, or above
+/// 21,474,836.47) into errors, so they are never stored clipped.
+pub fn reject_out_of_range_amounts(parsed: &mut ParsedStatement) {
+    let (rows, out_of_range): (Vec<ImportRow>, Vec<ImportRow>) = std::mem::take(&mut parsed.rows)
+        .into_iter()
+        .partition(|row| (1..=i32::MAX as i64).contains(&row.amount));
+    parsed.rows = rows;
+    parsed
+        .errors
+        .extend(out_of_range.into_iter().map(|row| ImportRowError {
+            line: row.line,
+            message: format!("Amount out of range: {:.2}", row.amount as f64 / 100.0),
+        }));
+    parsed.errors.sort_by_key(|e| e.line);
+}
+
+/// Creates one transaction per row through the regular transaction write path.
+///
+/// Must be called inside a Diesel transaction so a failing row rolls back the
+/// whole import. Returns the ids of the created transactions.
+pub fn commit_rows(
+    txn_conn: &mut PgConnection,
+    household: ActiveHousehold,
+    rows: &[ImportRow],
+) -> QueryResult<Vec<i32>> {
+    let mut ids = Vec::with_capacity(rows.len());
+    for row in rows {
+        let payload = TransactionPayload {
+            product_id: None,
+            product_name: Some(row.product_name.clone()),
+            product_price_id: None,
+            price: Some(row.amount as f64 / 100.0),
+            transaction_type: row.transaction_type,
+            description: row.description.clone(),
+            date: row.date,
+            tags: Some(
+                row.tags
+                    .iter()
+                    .map(|t| TagReference::Name(t.clone()))
+                    .collect(),
+            ),
+            external_id: row.external_id.clone(),
+            account: row.account.clone(),
+            value_date: row.value_date,
+            import_hash: row.import_hash.clone(),
+        };
+        let created = insert_transaction(txn_conn, household, &payload)?;
+        ids.push(created.transaction.id);
+    }
+    Ok(ids)
+}
+
+/// Gives every row without a bank id a stable hash of its content.
+///
+/// Identical lines within one statement (two coffees on the same day) are
+/// told apart by how often the line occurred before, so importing the same
+/// file again yields the same hashes.
+pub fn assign_import_hashes(rows: &mut [ImportRow]) {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for row in rows.iter_mut().filter(|r| r.external_id.is_none()) {
+        let content = format!(
+            "{}|{}|{}|{:?}|{}|{}",
+            row.account.as_deref().unwrap_or(""),
+            row.date,
+            row.amount,
+            row.transaction_type,
+            row.product_name,
+            row.description.as_deref().unwrap_or(""),
+        );
+        let occurrence = occurrences.entry(content.clone()).or_default();
+        *occurrence += 1;
+        let digest = Sha256::digest(format!("{content}|{occurrence}").as_bytes());
+        row.import_hash = Some(digest.iter().map(|b| format!("{b:02x}")).collect());
+    }
+}
+
+/// Flags rows that were already imported: their external id or import hash
+/// belongs to an existing transaction or to a merged duplicate, or the same
+/// external id appears earlier in the statement. Returns one flag per row.
+pub fn find_duplicates(
+    conn: &mut PgConnection,
+    household_id: i32,
+    rows: &[ImportRow],
+) -> QueryResult<Vec<bool>> {
+    use crate::schema::duplicate_reviews::dsl as dr;
+    use crate::schema::transactions::dsl as tx;
+
+    let ids: Vec<&str> = rows
+        .iter()
+        .filter_map(|r| r.external_id.as_deref())
+        .collect();
+    let hashes: Vec<&str> = rows
+        .iter()
+        .filter_map(|r| r.import_hash.as_deref())
+        .collect();
+
+    let mut seen_ids: HashSet<String> = HashSet::new();
+    let mut seen_hashes: HashSet<String> = HashSet::new();
+    if !ids.is_empty() {
+        seen_ids.extend(
+            tx::transactions
+                .filter(tx::household_id.eq(household_id))
+                .filter(tx::external_id.eq_any(&ids))
+                .select(tx::external_id.assume_not_null())
+                .load::<String>(conn)?,
+        );
+        seen_ids.extend(
+            dr::duplicate_reviews
+                .filter(dr::household_id.eq(household_id))
+                .filter(dr::external_id.eq_any(&ids))
+                .select(dr::external_id.assume_not_null())
+                .load::<String>(conn)?,
+        );
+    }
+    if !hashes.is_empty() {
+        seen_hashes.extend(
+            tx::transactions
+                .filter(tx::household_id.eq(household_id))
+                .filter(tx::import_hash.eq_any(&hashes))
+                .select(tx::import_hash.assume_not_null())
+                .load::<String>(conn)?,
+        );
+        seen_hashes.extend(
+            dr::duplicate_reviews
+                .filter(dr::household_id.eq(household_id))
+                .filter(dr::import_hash.eq_any(&hashes))
+                .select(dr::import_hash.assume_not_null())
+                .load::<String>(conn)?,
+        );
+    }
+
+    Ok(rows
+        .iter()
+        .map(|row| match (&row.external_id, &row.import_hash) {
+            (Some(id), _) => !seen_ids.insert(id.clone()),
+            (None, Some(hash)) => !seen_hashes.insert(hash.clone()),
+            (None, None) => false,
+        })
+        .collect())
+}
@@ -0,0 +1,164 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::sync::Arc;
+
+use crate::domain::analytics::handlers::load_monthly_totals;
+use crate::domain::analytics::models::ReportRangeQuery;
+use crate::domain::analytics::services::month_start;
+use crate::domain::households::models::ActiveHousehold;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{
+    NetWorthPoint, NetWorthSnapshot, NetWorthSnapshotDto, NetWorthSnapshotPayload,
+    NewNetWorthSnapshot,
+};
+use super::services::net_worth_history;
+
+/// Handler for POST /net-worth-snapshots.
+/// Records the value of an asset or liability on a given date.
+#[debug_handler]
+pub async fn create_net_worth_snapshot(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(payload): Json<NetWorthSnapshotPayload>,
+) -> JsonResult<NetWorthSnapshotDto> {
+    use crate::schema::net_worth_snapshots::dsl;
+
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(error_response("Snapshot name must not be empty"));
+    }
+    if payload.value < 0.0 {
+        return Err(error_response(
+            "Snapshot value must not be negative; use kind \"Liability\" for debts",
+        ));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let new_snapshot = NewNetWorthSnapshot {
+        household_id: household.id,
+        name: name.to_string(),
+        kind: payload.kind,
+        value: (payload.value * 100.0).round() as i64,
+        snapshot_date: payload.snapshot_date,
+    };
+
+    let inserted = diesel::insert_into(dsl::net_worth_snapshots)
+        .values(&new_snapshot)
+        .get_result::<NetWorthSnapshot>(&mut conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                error_response("A snapshot with that name already exists for that date")
+            }
+            e => error_response(format!("Failed to create snapshot: {e}")),
+        })?;
+
+    Ok(Json(inserted.into()))
+}
+
+/// Handler for GET /net-worth-snapshots.
+#[debug_handler]
+pub async fn list_net_worth_snapshots(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> JsonResult<Vec<NetWorthSnapshotDto>> {
+    use crate::schema::net_worth_snapshots::dsl::*;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let items = net_worth_snapshots
+        .filter(household_id.eq(household.id))
+        .order((snapshot_date.asc(), name.asc()))
+        .load::<NetWorthSnapshot>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading snapshots: {e}")))?;
+
+    Ok(Json(
+        items.into_iter().map(NetWorthSnapshotDto::from).collect(),
+    ))
+}
+
+/// Handler for DELETE /net-worth-snapshots/{id}.
+#[debug_handler]
+pub async fn delete_net_worth_snapshot(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(snapshot_id): Path<i32>,
+) -> JsonResult<NetWorthSnapshotDto> {
+    use crate::schema::net_worth_snapshots::dsl::*;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let deleted = diesel::delete(
+        net_worth_snapshots
+            .filter(id.eq(snapshot_id))
+            .filter(household_id.eq(household.id)),
+    )
+    .get_result::<NetWorthSnapshot>(&mut conn)
+    .optional()
+    .map_err(|e| error_response(format!("Failed to delete snapshot: {e}")))?;
+
+    match deleted {
+        Some(snapshot) => Ok(Json(snapshot.into())),
+        None => Err(error_response("Snapshot not found")),
+    }
+}
+
+/// Handler for GET /net-worth-history.
+/// Monthly net worth from the running cash balance plus asset/liability snapshots.
+#[debug_handler]
+pub async fn get_net_worth_history(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<ReportRangeQuery>,
+) -> JsonResult<Vec<NetWorthPoint>> {
+    use crate::schema::net_worth_snapshots::dsl as nw;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let totals = load_monthly_totals(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Query error: {e}")))?;
+
+    let snapshots = nw::net_worth_snapshots
+        .filter(nw::household_id.eq(household.id))
+        .load::<NetWorthSnapshot>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading snapshots: {e}")))?;
+
+    // Start at the first month with any data, unless asked to start later.
+    let first_data = totals
+        .keys()
+        .next()
+        .copied()
+        .into_iter()
+        .chain(snapshots.iter().map(|s| month_start(s.snapshot_date, 0)))
+        .min();
+    let Some(first_data) = first_data else {
+        return Ok(Json(Vec::new()));
+    };
+    let from = query
+        .from
+        .map(|d| month_start(d, 0))
+        .unwrap_or(first_data)
+        .max(first_data);
+    let to = month_start(query.to.unwrap_or_else(|| Utc::now().date_naive()), 0);
+
+    Ok(Json(net_worth_history(&totals, &snapshots, from, to)))
+}
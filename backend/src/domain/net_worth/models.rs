@@ -0,0 +1,106 @@
+use crate::schema::net_worth_snapshots;
+use chrono::NaiveDate;
+use diesel::pg::Pg;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::{AsExpression, FromSqlRow, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+// Whether a snapshot adds to or subtracts from net worth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = Text)]
+pub enum SnapshotKind {
+    Asset,
+    Liability,
+}
+
+impl ToSql<Text, Pg> for SnapshotKind {
+    fn to_sql(&self, out: &mut Output<'_, '_, Pg>) -> serialize::Result {
+        match self {
+            SnapshotKind::Asset => out.write_all(b"asset")?,
+            SnapshotKind::Liability => out.write_all(b"liability")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl diesel::deserialize::FromSql<Text, Pg> for SnapshotKind {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        let s = <String as diesel::deserialize::FromSql<Text, Pg>>::from_sql(bytes)?;
+        match s.as_str() {
+            "asset" => Ok(SnapshotKind::Asset),
+            "liability" => Ok(SnapshotKind::Liability),
+            _ => Err(format!("Invalid snapshot kind: {}", s).into()),
+        }
+    }
+}
+
+/// A manually entered asset or liability value, mapped to `net_worth_snapshots`.
+#[derive(Selectable, Queryable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = net_worth_snapshots)]
+pub struct NetWorthSnapshot {
+    pub id: i32,
+    pub household_id: i32,
+    pub name: String,
+    pub kind: SnapshotKind,
+    pub value: i64, // Stored in cents.
+    pub snapshot_date: NaiveDate,
+}
+
+/// For inserting a new snapshot.
+#[derive(Insertable)]
+#[diesel(table_name = net_worth_snapshots)]
+pub struct NewNetWorthSnapshot {
+    pub household_id: i32,
+    pub name: String,
+    pub kind: SnapshotKind,
+    pub value: i64,
+    pub snapshot_date: NaiveDate,
+}
+
+/// Payload received from the client when recording a snapshot.
+#[derive(Deserialize)]
+pub struct NetWorthSnapshotPayload {
+    pub name: String,
+    pub kind: SnapshotKind,
+    pub value: f64, // in dollars
+    pub snapshot_date: NaiveDate,
+}
+
+/// DTO for returning a snapshot with a float value.
+#[derive(Serialize)]
+pub struct NetWorthSnapshotDto {
+    pub id: i32,
+    pub name: String,
+    pub kind: SnapshotKind,
+    /// Value as a float (dollars).
+    pub value: f64,
+    pub snapshot_date: NaiveDate,
+}
+
+impl From<NetWorthSnapshot> for NetWorthSnapshotDto {
+    fn from(snapshot: NetWorthSnapshot) -> Self {
+        Self {
+            id: snapshot.id,
+            name: snapshot.name,
+            kind: snapshot.kind,
+            value: snapshot.value as f64 / 100.0,
+            snapshot_date: snapshot.snapshot_date,
+        }
+    }
+}
+
+/// One point of the net worth line chart.
+#[derive(Debug, Serialize)]
+pub struct NetWorthPoint {
+    /// Month in "YYYY-MM" format
+    pub month: String,
+    /// Running total of income minus expenses.
+    pub cash_balance: f64,
+    pub assets: f64,
+    pub liabilities: f64,
+    pub net_worth: f64,
+}
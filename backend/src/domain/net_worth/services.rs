@@ -0,0 +1,56 @@
+use chrono::{Months, NaiveDate};
+use std::collections::BTreeMap;
+
+use crate::domain::analytics::services::{cents_to_dollars, month_end, MonthlyTotals};
+
+use super::models::{NetWorthPoint, NetWorthSnapshot, SnapshotKind};
+
+/// Builds one net worth point per month in `[from, to]`.
+///
+/// The cash balance is the running total of income minus expenses; assets and
+/// liabilities use the latest snapshot of each named item at the end of the month.
+pub fn net_worth_history(
+    totals: &MonthlyTotals,
+    snapshots: &[NetWorthSnapshot],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<NetWorthPoint> {
+    let mut points = Vec::new();
+    let mut month = from;
+    while month <= to {
+        let end = month_end(month);
+
+        let cash: i64 = totals
+            .range(..=end)
+            .map(|(_, (income, expense))| income - expense)
+            .sum();
+
+        let mut latest: BTreeMap<&str, &NetWorthSnapshot> = BTreeMap::new();
+        for snap in snapshots.iter().filter(|s| s.snapshot_date <= end) {
+            let current = latest.entry(snap.name.as_str()).or_insert(snap);
+            if snap.snapshot_date > current.snapshot_date {
+                *current = snap;
+            }
+        }
+        let sum_kind = |kind: SnapshotKind| -> i64 {
+            latest
+                .values()
+                .filter(|s| s.kind == kind)
+                .map(|s| s.value)
+                .sum()
+        };
+        let assets = sum_kind(SnapshotKind::Asset);
+        let liabilities = sum_kind(SnapshotKind::Liability);
+
+        points.push(NetWorthPoint {
+            month: month.format("%Y-%m").to_string(),
+            cash_balance: cents_to_dollars(cash as f64),
+            assets: cents_to_dollars(assets as f64),
+            liabilities: cents_to_dollars(liabilities as f64),
+            net_worth: cents_to_dollars((cash + assets - liabilities) as f64),
+        });
+
+        month = month + Months::new(1);
+    }
+    points
+}
@@ -0,0 +1,144 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{PriceAlert, PriceAlertDto, PriceAlertQuery, PriceAlertSettings};
+
+/// Handler for GET /price-alerts.
+/// Lists the household's price alerts, newest first. Dismissed alerts are hidden
+/// unless `include_dismissed=true` is passed.
+#[debug_handler]
+pub async fn list_price_alerts(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<PriceAlertQuery>,
+) -> JsonResult<Vec<PriceAlertDto>> {
+    use crate::schema::price_alerts::dsl as pa;
+    use crate::schema::products::dsl as pr;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let mut alerts_query = pa::price_alerts
+        .inner_join(pr::products.on(pr::id.eq(pa::product_id)))
+        .filter(pa::household_id.eq(household.id))
+        .into_boxed();
+    if !query.include_dismissed {
+        alerts_query = alerts_query.filter(pa::dismissed.eq(false));
+    }
+
+    let rows = alerts_query
+        .order((pa::created_at.desc(), pa::id.desc()))
+        .select((PriceAlert::as_select(), pr::name))
+        .load::<(PriceAlert, String)>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading price alerts: {e}")))?;
+
+    let dtos = rows
+        .into_iter()
+        .map(|(alert, product_name)| PriceAlertDto::from_alert(alert, product_name))
+        .collect();
+
+    Ok(Json(dtos))
+}
+
+/// Handler for POST /price-alerts/{id}/dismiss.
+/// Marks a single alert as dismissed.
+#[debug_handler]
+pub async fn dismiss_price_alert(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(alert_id): Path<i32>,
+) -> JsonResult<PriceAlertDto> {
+    use crate::schema::price_alerts::dsl as pa;
+    use crate::schema::products::dsl as pr;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let updated = diesel::update(
+        pa::price_alerts
+            .filter(pa::id.eq(alert_id))
+            .filter(pa::household_id.eq(household.id)),
+    )
+    .set(pa::dismissed.eq(true))
+    .get_result::<PriceAlert>(&mut conn)
+    .optional()
+    .map_err(|e| error_response(format!("Failed to dismiss price alert: {e}")))?;
+
+    let Some(alert) = updated else {
+        return Err(error_response("Price alert not found"));
+    };
+
+    let product_name = pr::products
+        .filter(pr::id.eq(alert.product_id))
+        .select(pr::name)
+        .first::<String>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading product: {e}")))?;
+
+    Ok(Json(PriceAlertDto::from_alert(alert, product_name)))
+}
+
+/// Handler for GET /price-alerts/settings.
+#[debug_handler]
+pub async fn get_price_alert_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+) -> JsonResult<PriceAlertSettings> {
+    use crate::schema::users::dsl as u;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let threshold = u::users
+        .filter(u::id.eq(logged_in_user_id))
+        .select(u::price_alert_threshold)
+        .first::<i32>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading settings: {e}")))?;
+
+    Ok(Json(PriceAlertSettings {
+        threshold_percent: threshold,
+    }))
+}
+
+/// Handler for PUT /price-alerts/settings.
+/// Updates the percentage change that triggers a price alert.
+#[debug_handler]
+pub async fn update_price_alert_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<PriceAlertSettings>,
+) -> JsonResult<PriceAlertSettings> {
+    use crate::schema::users::dsl as u;
+
+    if payload.threshold_percent <= 0 {
+        return Err(error_response("Threshold must be a positive percentage"));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let threshold = diesel::update(u::users.filter(u::id.eq(logged_in_user_id)))
+        .set(u::price_alert_threshold.eq(payload.threshold_percent))
+        .returning(u::price_alert_threshold)
+        .get_result::<i32>(&mut conn)
+        .map_err(|e| error_response(format!("Failed to update settings: {e}")))?;
+
+    Ok(Json(PriceAlertSettings {
+        threshold_percent: threshold,
+    }))
+}
@@ -0,0 +1,78 @@
+use crate::schema::price_alerts;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A recorded price alert, mapped to the `price_alerts` table.
+#[derive(Selectable, Queryable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = price_alerts)]
+pub struct PriceAlert {
+    pub id: i32,
+    pub household_id: i32,
+    pub product_id: i32,
+    pub product_price_id: i32,
+    pub median_price: i32, // Stored in cents.
+    pub new_price: i32,    // Stored in cents.
+    pub change_percent: f64,
+    pub dismissed: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// For inserting a new price alert.
+#[derive(Insertable)]
+#[diesel(table_name = price_alerts)]
+pub struct NewPriceAlert {
+    pub household_id: i32,
+    pub product_id: i32,
+    pub product_price_id: i32,
+    pub median_price: i32,
+    pub new_price: i32,
+    pub change_percent: f64,
+}
+
+/// DTO for returning a price alert with float prices.
+#[derive(Serialize, Debug)]
+pub struct PriceAlertDto {
+    pub id: i32,
+    pub product_id: i32,
+    pub product_name: String,
+    pub product_price_id: i32,
+    /// Rolling median before the change, as a float (dollars).
+    pub median_price: f64,
+    /// The new price, as a float (dollars).
+    pub new_price: f64,
+    pub change_percent: f64,
+    pub dismissed: bool,
+    pub created_at: NaiveDateTime,
+}
+
+impl PriceAlertDto {
+    pub fn from_alert(alert: PriceAlert, product_name: String) -> Self {
+        Self {
+            id: alert.id,
+            product_id: alert.product_id,
+            product_name,
+            product_price_id: alert.product_price_id,
+            median_price: alert.median_price as f64 / 100.0,
+            new_price: alert.new_price as f64 / 100.0,
+            change_percent: (alert.change_percent * 100.0).round() / 100.0,
+            dismissed: alert.dismissed,
+            created_at: alert.created_at,
+        }
+    }
+}
+
+/// Query parameters for GET /price-alerts.
+#[derive(Deserialize, Debug)]
+pub struct PriceAlertQuery {
+    /// Also return alerts that were already dismissed.
+    #[serde(default)]
+    pub include_dismissed: bool,
+}
+
+/// The user's price alert settings.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PriceAlertSettings {
+    /// Minimum change from the rolling median (in percent) that raises an alert.
+    pub threshold_percent: i32,
+}
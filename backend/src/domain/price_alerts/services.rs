@@ -0,0 +1,74 @@
+use diesel::prelude::*;
+
+use crate::domain::analytics::services::median;
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::product_prices::models::ProductPrice;
+
+use super::models::{NewPriceAlert, PriceAlert};
+
+/// How many of the most recent earlier prices form the rolling window.
+pub const ROLLING_WINDOW: i64 = 10;
+
+/// Alerts are only raised once a product has at least this many earlier prices,
+/// so the first few purchases don't trigger noise.
+pub const MIN_HISTORY: usize = 3;
+
+/// Compares a freshly inserted price against the product's rolling median and
+/// records a price alert for the household if the change exceeds the
+/// threshold of the member who entered the price.
+///
+/// Must be called inside the same Diesel transaction that inserted `new_price`.
+pub fn detect_price_change(
+    conn: &mut PgConnection,
+    household: ActiveHousehold,
+    new_price: &ProductPrice,
+) -> QueryResult<Option<PriceAlert>> {
+    use crate::schema::price_alerts::dsl as pa;
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::users::dsl as u;
+
+    let history = pp::product_prices
+        .filter(pp::product_id.eq(new_price.product_id))
+        .filter(pp::id.ne(new_price.id))
+        .filter(pp::created_at.le(new_price.created_at))
+        .order(pp::created_at.desc())
+        .limit(ROLLING_WINDOW)
+        .select(pp::price)
+        .load::<i32>(conn)?;
+
+    if history.len() < MIN_HISTORY {
+        return Ok(None);
+    }
+
+    let values: Vec<f64> = history.iter().map(|p| *p as f64).collect();
+    let Some(median_price) = median(&values) else {
+        return Ok(None);
+    };
+    if median_price <= 0.0 {
+        return Ok(None);
+    }
+
+    let change_percent = (new_price.price as f64 - median_price) / median_price * 100.0;
+
+    let threshold = u::users
+        .filter(u::id.eq(household.user_id))
+        .select(u::price_alert_threshold)
+        .first::<i32>(conn)?;
+    if change_percent.abs() < threshold as f64 {
+        return Ok(None);
+    }
+
+    let new_alert = NewPriceAlert {
+        household_id: household.id,
+        product_id: new_price.product_id,
+        product_price_id: new_price.id,
+        median_price: median_price.round() as i32,
+        new_price: new_price.price,
+        change_percent,
+    };
+    let inserted = diesel::insert_into(pa::price_alerts)
+        .values(&new_alert)
+        .get_result::<PriceAlert>(conn)?;
+
+    Ok(Some(inserted))
+}
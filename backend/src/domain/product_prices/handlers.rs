@@ -4,8 +4,12 @@ use diesel::prelude::*;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use std::sync::Arc;
 
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::price_alerts::models::PriceAlertDto;
+use crate::domain::price_alerts::services::detect_price_change;
 use crate::domain::product_prices::models::{CreateProductPriceResponse, ProductPricePayload};
-use crate::domain::products::models::{NewProduct, Product};
+use crate::domain::products::models::Product;
+use crate::domain::products::services::find_or_create_product;
 use crate::{
     error_response, // Ensure this helper is defined in your crate root or shared module.
     AppState,
@@ -19,7 +23,7 @@ use super::models::{NewProductPrice, ProductPrice, ProductPriceDto};
 #[debug_handler]
 pub async fn create_product_price(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
     Json(payload): Json<ProductPricePayload>,
 ) -> JsonResult<CreateProductPriceResponse> {
     use crate::schema::product_prices::dsl;
@@ -37,25 +41,7 @@ pub async fn create_product_price(
             if trimmed.is_empty() {
                 return Err(DieselError::RollbackTransaction);
             }
-            use crate::schema::products::dsl as prod_dsl;
-            let existing_product: Option<Product> = prod_dsl::products
-                .filter(prod_dsl::name.eq(trimmed))
-                .filter(prod_dsl::user_id.eq(logged_in_user_id))
-                .first::<Product>(txn_conn)
-                .optional()?;
-            if let Some(prod) = existing_product {
-                prod.id
-            } else {
-                let new_prod = NewProduct {
-                    user_id: logged_in_user_id,
-                    category_id: None, // or add similar logic for category if needed
-                    name: trimmed.to_string(),
-                };
-                diesel::insert_into(prod_dsl::products)
-                    .values(&new_prod)
-                    .returning(prod_dsl::id)
-                    .get_result::<i32>(txn_conn)?
-            }
+            find_or_create_product(txn_conn, household.id, trimmed)?
         } else {
             return Err(DieselError::RollbackTransaction);
         };
@@ -72,6 +58,9 @@ pub async fn create_product_price(
             .values(&new_price)
             .get_result::<ProductPrice>(txn_conn)?;
 
+        // Compare against the rolling median and record an alert if needed.
+        let price_alert = detect_price_change(txn_conn, household, &inserted)?;
+
         // conver to DTO
         let inserted_dto = ProductPriceDto {
             id: inserted.id,
@@ -86,9 +75,12 @@ pub async fn create_product_price(
             .filter(prod_dsl::id.eq(final_product_id))
             .first::<Product>(txn_conn)?;
 
+        let price_alert =
+            price_alert.map(|alert| PriceAlertDto::from_alert(alert, prod.name.clone()));
         Ok(CreateProductPriceResponse {
             product_price: inserted_dto,
             product: prod,
+            price_alert,
         })
     });
 
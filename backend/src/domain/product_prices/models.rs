@@ -1,4 +1,7 @@
-use crate::{domain::products::models::Product, schema::product_prices};
+use crate::{
+    domain::{price_alerts::models::PriceAlertDto, products::models::Product},
+    schema::product_prices,
+};
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -44,4 +47,6 @@ pub struct ProductPricePayload {
 pub struct CreateProductPriceResponse {
     pub product_price: ProductPriceDto,
     pub product: Product,
+    /// Set when the new price deviates enough from the rolling median.
+    pub price_alert: Option<PriceAlertDto>,
 }
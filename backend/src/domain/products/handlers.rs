@@ -1,6 +1,7 @@
 use crate::domain::categories::models::Category;
 use crate::domain::categories::models::CategoryDto;
 use crate::domain::categories::models::NewCategory;
+use crate::domain::households::models::ActiveHousehold;
 use crate::domain::products::models::CreateProductResponse;
 use crate::domain::products::models::NewProduct;
 use crate::domain::products::models::ProductDto;
@@ -17,7 +18,7 @@ use crate::{error_response, schema, AppState, JsonResult};
 #[debug_handler]
 pub async fn create_product(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
     Json(payload): Json<ProductPayload>,
 ) -> JsonResult<CreateProductResponse> {
     let mut conn = state
@@ -37,7 +38,7 @@ pub async fn create_product(
                 use crate::schema::categories::dsl as cat_dsl;
                 let existing_category: Option<Category> = cat_dsl::categories
                     .filter(cat_dsl::name.eq(trimmed))
-                    .filter(cat_dsl::user_id.eq(logged_in_user_id))
+                    .filter(cat_dsl::household_id.eq(household.id))
                     .first::<Category>(txn_conn)
                     .optional()?;
                 let cat_id = if let Some(category) = existing_category {
@@ -46,7 +47,7 @@ pub async fn create_product(
                     let new_category = NewCategory {
                         name: trimmed.to_string(),
                         parent_category_id: None,
-                        user_id: logged_in_user_id,
+                        household_id: household.id,
                     };
                     diesel::insert_into(cat_dsl::categories)
                         .values(&new_category)
@@ -61,7 +62,7 @@ pub async fn create_product(
 
         // 2) Build the new product record with the final category id.
         let new_prod = NewProduct {
-            user_id: logged_in_user_id,
+            household_id: household.id,
             category_id: final_category_id,
             name: payload.name.clone(),
         };
@@ -105,7 +106,7 @@ pub async fn create_product(
 #[debug_handler]
 pub async fn list_products(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
 ) -> JsonResult<Vec<ProductDto>> {
     use schema::products::dsl::*;
     let mut conn = state
@@ -114,11 +115,11 @@ pub async fn list_products(
         .map_err(|_| error_response("Failed to fetch connection from pool"))?;
 
     let items = products
-        .filter(user_id.eq(logged_in_user_id))
+        .filter(household_id.eq(household.id))
         .load::<Product>(&mut conn)
         .map_err(|e| error_response(format!("Error loading products: {e}")))?;
 
-    // Convert each Product into a ProductDto (which omits the user_id).
+    // Convert each Product into a ProductDto (which omits the household_id).
     let product_dtos: Vec<ProductDto> = items.into_iter().map(ProductDto::from).collect();
 
     Ok(Json(product_dtos))
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 #[diesel(table_name = products)]
 pub struct Product {
     pub id: i32,
-    pub user_id: i32,
+    pub household_id: i32,
     pub category_id: Option<i32>,
     pub name: String,
 }
@@ -14,7 +14,7 @@ pub struct Product {
 #[derive(diesel::Insertable)]
 #[diesel(table_name = products)]
 pub struct NewProduct {
-    pub user_id: i32,
+    pub household_id: i32,
     pub category_id: Option<i32>,
     pub name: String,
 }
@@ -34,7 +34,7 @@ pub struct CreateProductResponse {
     pub category: Option<CategoryDto>,
 }
 
-/// A Data Transfer Object for exposing products without the user_id.
+/// A Data Transfer Object for exposing products without the household_id.
 #[derive(Serialize, Debug)]
 pub struct ProductDto {
     pub id: i32,
@@ -0,0 +1,31 @@
+use diesel::prelude::*;
+
+use super::models::{NewProduct, Product};
+
+/// Returns the id of the household's product called `name`, creating it if needed.
+pub fn find_or_create_product(
+    conn: &mut PgConnection,
+    household_id: i32,
+    name: &str,
+) -> QueryResult<i32> {
+    use crate::schema::products::dsl as prod_dsl;
+
+    let existing_product: Option<Product> = prod_dsl::products
+        .filter(prod_dsl::name.eq(name))
+        .filter(prod_dsl::household_id.eq(household_id))
+        .first::<Product>(conn)
+        .optional()?;
+    if let Some(prod) = existing_product {
+        return Ok(prod.id);
+    }
+
+    let new_prod = NewProduct {
+        household_id,
+        category_id: None,
+        name: name.to_string(),
+    };
+    diesel::insert_into(prod_dsl::products)
+        .values(&new_prod)
+        .returning(prod_dsl::id)
+        .get_result::<i32>(conn)
+}
@@ -0,0 +1,129 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{NewRecurringRule, RecurringRule, RecurringRuleDto, RecurringRulePayload};
+
+/// Inserts `new_rule`, mapping the unique constraint to a readable error.
+pub fn insert_recurring_rule(
+    conn: &mut PgConnection,
+    new_rule: &NewRecurringRule,
+) -> JsonResult<RecurringRuleDto> {
+    use crate::schema::recurring_rules::dsl;
+
+    match diesel::insert_into(dsl::recurring_rules)
+        .values(new_rule)
+        .get_result::<RecurringRule>(conn)
+    {
+        Ok(rule) => Ok(Json(rule.into())),
+        Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            Err(error_response(
+                "A recurring rule with that cadence and description already exists for this product",
+            ))
+        }
+        Err(e) => Err(error_response(format!(
+            "Failed to create recurring rule: {e}"
+        ))),
+    }
+}
+
+/// Handler for POST /recurring-rules.
+#[debug_handler]
+pub async fn create_recurring_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(payload): Json<RecurringRulePayload>,
+) -> JsonResult<RecurringRuleDto> {
+    use crate::schema::products::dsl as pr;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    // The product must belong to the caller.
+    let owns_product = pr::products
+        .filter(pr::id.eq(payload.product_id))
+        .filter(pr::household_id.eq(household.id))
+        .select(pr::id)
+        .first::<i32>(&mut conn)
+        .optional()
+        .map_err(|e| error_response(format!("Error loading product: {e}")))?
+        .is_some();
+    if !owns_product {
+        return Err(error_response("Product not found"));
+    }
+
+    let new_rule = NewRecurringRule {
+        household_id: household.id,
+        product_id: payload.product_id,
+        description: payload.description,
+        amount: (payload.amount * 100.0).round() as i32,
+        transaction_type: payload.transaction_type,
+        cadence: payload.cadence,
+        next_due_date: payload.next_due_date,
+    };
+
+    insert_recurring_rule(&mut conn, &new_rule)
+}
+
+/// Handler for GET /recurring-rules.
+#[debug_handler]
+pub async fn list_recurring_rules(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> JsonResult<Vec<RecurringRuleDto>> {
+    use crate::schema::recurring_rules::dsl::*;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let items = recurring_rules
+        .filter(household_id.eq(household.id))
+        .order(next_due_date.asc())
+        .load::<RecurringRule>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading recurring rules: {e}")))?;
+
+    Ok(Json(
+        items.into_iter().map(RecurringRuleDto::from).collect(),
+    ))
+}
+
+/// Handler for DELETE /recurring-rules/{id}.
+#[debug_handler]
+pub async fn delete_recurring_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(rule_id): Path<i32>,
+) -> JsonResult<RecurringRuleDto> {
+    use crate::schema::recurring_rules::dsl::*;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let deleted = diesel::delete(
+        recurring_rules
+            .filter(id.eq(rule_id))
+            .filter(household_id.eq(household.id)),
+    )
+    .get_result::<RecurringRule>(&mut conn)
+    .optional()
+    .map_err(|e| error_response(format!("Failed to delete recurring rule: {e}")))?;
+
+    match deleted {
+        Some(rule) => Ok(Json(rule.into())),
+        None => Err(error_response("Recurring rule not found")),
+    }
+}
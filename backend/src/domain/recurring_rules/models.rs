@@ -0,0 +1,132 @@
+use crate::domain::transactions::models::TransactionType;
+use crate::schema::recurring_rules;
+use chrono::{Duration, Months, NaiveDate, NaiveDateTime};
+use diesel::pg::Pg;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::{AsExpression, FromSqlRow, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+// How often a recurring rule repeats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = Text)]
+pub enum Cadence {
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Cadence {
+    /// The date one period after `date`.
+    pub fn advance(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Cadence::Weekly => date + Duration::days(7),
+            Cadence::Monthly => date + Months::new(1),
+            Cadence::Yearly => date + Months::new(12),
+        }
+    }
+
+    /// How many periods fit in a year.
+    pub fn periods_per_year(self) -> f64 {
+        match self {
+            Cadence::Weekly => 52.0,
+            Cadence::Monthly => 12.0,
+            Cadence::Yearly => 1.0,
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for Cadence {
+    fn to_sql(&self, out: &mut Output<'_, '_, Pg>) -> serialize::Result {
+        match self {
+            Cadence::Weekly => out.write_all(b"weekly")?,
+            Cadence::Monthly => out.write_all(b"monthly")?,
+            Cadence::Yearly => out.write_all(b"yearly")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl diesel::deserialize::FromSql<Text, Pg> for Cadence {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        let s = <String as diesel::deserialize::FromSql<Text, Pg>>::from_sql(bytes)?;
+        match s.as_str() {
+            "weekly" => Ok(Cadence::Weekly),
+            "monthly" => Ok(Cadence::Monthly),
+            "yearly" => Ok(Cadence::Yearly),
+            _ => Err(format!("Invalid cadence: {}", s).into()),
+        }
+    }
+}
+
+/// The main recurring rule record.
+#[derive(Selectable, Queryable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = recurring_rules)]
+pub struct RecurringRule {
+    pub id: i32,
+    pub household_id: i32,
+    pub product_id: i32,
+    pub description: Option<String>,
+    pub amount: i32, // Stored in cents.
+    pub transaction_type: TransactionType,
+    pub cadence: Cadence,
+    pub next_due_date: NaiveDate,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used for inserting a new recurring rule.
+#[derive(Insertable)]
+#[diesel(table_name = recurring_rules)]
+pub struct NewRecurringRule {
+    pub household_id: i32,
+    pub product_id: i32,
+    pub description: Option<String>,
+    pub amount: i32,
+    pub transaction_type: TransactionType,
+    pub cadence: Cadence,
+    pub next_due_date: NaiveDate,
+}
+
+/// The payload that the client sends when creating a recurring rule.
+#[derive(Deserialize)]
+pub struct RecurringRulePayload {
+    pub product_id: i32,
+    pub description: Option<String>,
+    pub amount: f64, // in dollars
+    pub transaction_type: TransactionType,
+    pub cadence: Cadence,
+    pub next_due_date: NaiveDate,
+}
+
+/// DTO for returning a recurring rule with a float amount.
+#[derive(Serialize)]
+pub struct RecurringRuleDto {
+    pub id: i32,
+    pub product_id: i32,
+    pub description: Option<String>,
+    /// Amount as a float (dollars).
+    pub amount: f64,
+    pub transaction_type: TransactionType,
+    pub cadence: Cadence,
+    pub next_due_date: NaiveDate,
+    pub active: bool,
+}
+
+impl From<RecurringRule> for RecurringRuleDto {
+    fn from(rule: RecurringRule) -> Self {
+        Self {
+            id: rule.id,
+            product_id: rule.product_id,
+            description: rule.description,
+            amount: rule.amount as f64 / 100.0,
+            transaction_type: rule.transaction_type,
+            cadence: rule.cadence,
+            next_due_date: rule.next_due_date,
+            active: rule.active,
+        }
+    }
+}
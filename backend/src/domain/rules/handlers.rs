@@ -0,0 +1,286 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::tags::models::{TagDto, TagReference};
+use crate::domain::tags::services::resolve_tag_references;
+use crate::domain::transactions::models::TransactionFilter;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{ApplyRulesPayload, ApplyRulesResult, NewRule, Rule, RuleDto, RulePayload};
+use super::services::{execute_rule_changes, load_rule_tags, plan_rule_changes, validate_rule};
+
+/// Checks that the category and tags referenced by `payload` belong to the household.
+fn check_references(
+    conn: &mut PgConnection,
+    household_id: i32,
+    new_rule: &NewRule,
+    payload: &RulePayload,
+) -> Result<(), String> {
+    use crate::schema::categories::dsl as cat;
+    use crate::schema::tags::dsl as tg;
+
+    if let Some(category_id) = new_rule.set_category_id {
+        let owned = cat::categories
+            .filter(cat::id.eq(category_id))
+            .filter(cat::household_id.eq(household_id))
+            .select(cat::id)
+            .first::<i32>(conn)
+            .optional()
+            .map_err(|e| format!("Error loading category: {e}"))?;
+        if owned.is_none() {
+            return Err("Category not found".to_string());
+        }
+    }
+
+    let tag_ids: Vec<i32> = payload
+        .actions
+        .add_tags
+        .iter()
+        .filter_map(|t| match t {
+            TagReference::Id(id) => Some(*id),
+            TagReference::Name(_) => None,
+        })
+        .collect();
+    if !tag_ids.is_empty() {
+        let owned = tg::tags
+            .filter(tg::id.eq_any(&tag_ids))
+            .filter(tg::household_id.eq(household_id))
+            .count()
+            .get_result::<i64>(conn)
+            .map_err(|e| format!("Error loading tags: {e}"))?;
+        let mut distinct = tag_ids.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if owned as usize != distinct.len() {
+            return Err("Tag not found".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the tags a rule adds.
+fn replace_rule_tags(
+    txn_conn: &mut PgConnection,
+    household_id: i32,
+    rule_id: i32,
+    tag_refs: &[TagReference],
+) -> QueryResult<()> {
+    use crate::schema::rule_tags::dsl as rt;
+
+    diesel::delete(rt::rule_tags.filter(rt::rule_id.eq(rule_id))).execute(txn_conn)?;
+    for tag_id in resolve_tag_references(txn_conn, household_id, tag_refs)? {
+        diesel::insert_into(rt::rule_tags)
+            .values((rt::rule_id.eq(rule_id), rt::tag_id.eq(tag_id)))
+            .on_conflict_do_nothing()
+            .execute(txn_conn)?;
+    }
+    Ok(())
+}
+
+/// Builds the DTO for `rule`, loading the tags it adds.
+fn rule_dto(conn: &mut PgConnection, rule: Rule) -> QueryResult<RuleDto> {
+    let tags = load_rule_tags(conn, &[rule.id])?
+        .remove(&rule.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| TagDto {
+            id: t.id,
+            name: t.name,
+        })
+        .collect();
+    Ok(RuleDto::new(rule, tags))
+}
+
+fn save_error(action: &str, e: DieselError) -> String {
+    match e {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            "A rule with that name already exists".to_string()
+        }
+        e => format!("Failed to {action} rule: {e}"),
+    }
+}
+
+/// Handler for POST /rules.
+#[debug_handler]
+pub async fn create_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(payload): Json<RulePayload>,
+) -> JsonResult<RuleDto> {
+    use crate::schema::rules::dsl as r;
+
+    let new_rule = validate_rule(household.id, &payload).map_err(error_response)?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+    check_references(&mut conn, household.id, &new_rule, &payload).map_err(error_response)?;
+
+    let rule = conn
+        .transaction::<RuleDto, DieselError, _>(|txn_conn| {
+            let rule = diesel::insert_into(r::rules)
+                .values(&new_rule)
+                .get_result::<Rule>(txn_conn)?;
+            replace_rule_tags(txn_conn, household.id, rule.id, &payload.actions.add_tags)?;
+            rule_dto(txn_conn, rule)
+        })
+        .map_err(|e| error_response(save_error("create", e)))?;
+
+    Ok(Json(rule))
+}
+
+/// Handler for GET /rules.
+/// Lists the household's rules in the order they run.
+#[debug_handler]
+pub async fn list_rules(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> JsonResult<Vec<RuleDto>> {
+    use crate::schema::rules::dsl as r;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let rules = r::rules
+        .filter(r::household_id.eq(household.id))
+        .order((r::priority.asc(), r::id.asc()))
+        .load::<Rule>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading rules: {e}")))?;
+    let ids: Vec<i32> = rules.iter().map(|r| r.id).collect();
+    let mut tags = load_rule_tags(&mut conn, &ids)
+        .map_err(|e| error_response(format!("Error loading rule tags: {e}")))?;
+
+    Ok(Json(
+        rules
+            .into_iter()
+            .map(|rule| {
+                let rule_tags = tags
+                    .remove(&rule.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|t| TagDto {
+                        id: t.id,
+                        name: t.name,
+                    })
+                    .collect();
+                RuleDto::new(rule, rule_tags)
+            })
+            .collect(),
+    ))
+}
+
+/// Handler for PUT /rules/{id}.
+/// Replaces the rule's conditions and actions.
+#[debug_handler]
+pub async fn update_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(rule_id): Path<i32>,
+    Json(payload): Json<RulePayload>,
+) -> JsonResult<RuleDto> {
+    use crate::schema::rules::dsl as r;
+
+    let new_rule = validate_rule(household.id, &payload).map_err(error_response)?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+    check_references(&mut conn, household.id, &new_rule, &payload).map_err(error_response)?;
+
+    let rule = conn
+        .transaction::<Option<RuleDto>, DieselError, _>(|txn_conn| {
+            let Some(rule) = diesel::update(
+                r::rules
+                    .filter(r::id.eq(rule_id))
+                    .filter(r::household_id.eq(household.id)),
+            )
+            .set(&new_rule)
+            .get_result::<Rule>(txn_conn)
+            .optional()?
+            else {
+                return Ok(None);
+            };
+            replace_rule_tags(txn_conn, household.id, rule.id, &payload.actions.add_tags)?;
+            rule_dto(txn_conn, rule).map(Some)
+        })
+        .map_err(|e| error_response(save_error("update", e)))?;
+
+    rule.map(Json)
+        .ok_or_else(|| error_response("Rule not found"))
+}
+
+/// Handler for DELETE /rules/{id}.
+#[debug_handler]
+pub async fn delete_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(rule_id): Path<i32>,
+) -> JsonResult<RuleDto> {
+    use crate::schema::rules::dsl as r;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    // Load the tags first; the delete cascades to rule_tags.
+    let rule = r::rules
+        .filter(r::id.eq(rule_id))
+        .filter(r::household_id.eq(household.id))
+        .first::<Rule>(&mut conn)
+        .optional()
+        .map_err(|e| error_response(format!("Error loading rule: {e}")))?
+        .ok_or_else(|| error_response("Rule not found"))?;
+    let dto = rule_dto(&mut conn, rule)
+        .map_err(|e| error_response(format!("Error loading rule tags: {e}")))?;
+
+    diesel::delete(r::rules.filter(r::id.eq(rule_id)))
+        .execute(&mut conn)
+        .map_err(|e| error_response(format!("Failed to delete rule: {e}")))?;
+
+    Ok(Json(dto))
+}
+
+/// Handler for POST /rules/apply.
+/// Runs the active rules over existing transactions, narrowed down by the
+/// usual transaction filters. Only reports the changes unless `dry_run` is
+/// false.
+#[debug_handler]
+pub async fn apply_rules(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(filter): Query<TransactionFilter>,
+    Json(payload): Json<ApplyRulesPayload>,
+) -> JsonResult<ApplyRulesResult> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let (checked, changes) = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            let (checked, changes) = plan_rule_changes(txn_conn, household.id, &filter)?;
+            if !payload.dry_run {
+                execute_rule_changes(txn_conn, household.id, &changes)?;
+            }
+            Ok((checked, changes))
+        })
+        .map_err(|e| error_response(format!("Failed to apply rules: {e}")))?;
+
+    Ok(Json(ApplyRulesResult {
+        dry_run: payload.dry_run,
+        checked,
+        changes,
+    }))
+}
@@ -0,0 +1,163 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::tags::models::{TagDto, TagReference};
+use crate::schema::rules;
+
+/// A categorization rule, mapped to the `rules` table.
+#[derive(Selectable, Queryable, Serialize, Debug, Clone)]
+#[diesel(table_name = rules)]
+pub struct Rule {
+    pub id: i32,
+    pub household_id: i32,
+    pub name: String,
+    /// Lower runs first.
+    pub priority: i32,
+    pub active: bool,
+    pub description_pattern: Option<String>,
+    pub product_name: Option<String>,
+    pub merchant: Option<String>,
+    pub account: Option<String>,
+    pub min_amount: Option<i32>, // In cents.
+    pub max_amount: Option<i32>, // In cents.
+    pub set_category_id: Option<i32>,
+    pub set_description: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used for inserting or replacing a rule.
+#[derive(Insertable, diesel::AsChangeset)]
+#[diesel(table_name = rules, treat_none_as_null = true)]
+pub struct NewRule {
+    pub household_id: i32,
+    pub name: String,
+    pub priority: i32,
+    pub active: bool,
+    pub description_pattern: Option<String>,
+    pub product_name: Option<String>,
+    pub merchant: Option<String>,
+    pub account: Option<String>,
+    pub min_amount: Option<i32>,
+    pub max_amount: Option<i32>,
+    pub set_category_id: Option<i32>,
+    pub set_description: Option<String>,
+}
+
+/// When a rule matches. Every condition that is set must hold.
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct RuleConditions {
+    /// Regular expression searched for in the description.
+    pub description_pattern: Option<String>,
+    /// Exact product name, ignoring case.
+    pub product_name: Option<String>,
+    /// Text contained in the product name or description, ignoring case.
+    pub merchant: Option<String>,
+    /// Bank account the transaction was booked on, ignoring case.
+    pub account: Option<String>,
+    /// Smallest matching amount in dollars, inclusive.
+    pub min_amount: Option<f64>,
+    /// Largest matching amount in dollars, inclusive.
+    pub max_amount: Option<f64>,
+}
+
+/// What a matching rule changes.
+#[derive(Deserialize, Default)]
+pub struct RuleActions {
+    /// Category given to the transaction's product.
+    pub set_category_id: Option<i32>,
+    #[serde(default)]
+    pub add_tags: Vec<TagReference>,
+    pub set_description: Option<String>,
+}
+
+/// The payload that the client sends when creating or replacing a rule.
+#[derive(Deserialize)]
+pub struct RulePayload {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "default_active")]
+    pub active: bool,
+    pub conditions: RuleConditions,
+    pub actions: RuleActions,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+pub struct RuleActionsDto {
+    pub set_category_id: Option<i32>,
+    pub add_tags: Vec<TagDto>,
+    pub set_description: Option<String>,
+}
+
+/// DTO for returning a rule with float amounts and its tags.
+#[derive(Serialize)]
+pub struct RuleDto {
+    pub id: i32,
+    pub name: String,
+    pub priority: i32,
+    pub active: bool,
+    pub conditions: RuleConditions,
+    pub actions: RuleActionsDto,
+}
+
+impl RuleDto {
+    pub fn new(rule: Rule, tags: Vec<TagDto>) -> Self {
+        Self {
+            id: rule.id,
+            name: rule.name,
+            priority: rule.priority,
+            active: rule.active,
+            conditions: RuleConditions {
+                description_pattern: rule.description_pattern,
+                product_name: rule.product_name,
+                merchant: rule.merchant,
+                account: rule.account,
+                min_amount: rule.min_amount.map(|a| a as f64 / 100.0),
+                max_amount: rule.max_amount.map(|a| a as f64 / 100.0),
+            },
+            actions: RuleActionsDto {
+                set_category_id: rule.set_category_id,
+                add_tags: tags,
+                set_description: rule.set_description,
+            },
+        }
+    }
+}
+
+/// Body of POST /rules/apply.
+#[derive(Deserialize)]
+pub struct ApplyRulesPayload {
+    /// Only report what would change; true unless `false` is sent explicitly.
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+/// What the rules change on one transaction. Only effective changes are listed.
+#[derive(Serialize, Debug)]
+pub struct RuleChange {
+    pub transaction_id: i32,
+    pub product_id: i32,
+    /// Matching rules, in the order they ran.
+    pub rule_ids: Vec<i32>,
+    pub set_category_id: Option<i32>,
+    pub add_tags: Vec<String>,
+    pub set_description: Option<String>,
+}
+
+/// Outcome of POST /rules/apply.
+#[derive(Serialize, Debug)]
+pub struct ApplyRulesResult {
+    pub dry_run: bool,
+    /// Transactions the rules were run against.
+    pub checked: usize,
+    pub changes: Vec<RuleChange>,
+}
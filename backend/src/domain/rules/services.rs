@@ -0,0 +1,429 @@
+use diesel::prelude::*;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::tags::models::Tag;
+use crate::domain::transactions::models::{Transaction, TransactionFilter};
+use crate::domain::transactions::services::filter_transactions;
+
+use super::models::{NewRule, Rule, RuleChange, RulePayload};
+
+/// Upper bound on the compiled size of a description pattern.
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+pub fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid description pattern: {e}"))
+}
+
+fn trimmed(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn to_cents(dollars: Option<f64>) -> Result<Option<i32>, String> {
+    match dollars {
+        Some(d) if d < 0.0 => Err("Amounts must not be negative".to_string()),
+        Some(d) => Ok(Some((d * 100.0).round() as i32)),
+        None => Ok(None),
+    }
+}
+
+/// Checks a rule payload and turns it into a row. Tags are resolved separately.
+pub fn validate_rule(household_id: i32, payload: &RulePayload) -> Result<NewRule, String> {
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Rule name must not be empty".to_string());
+    }
+
+    let conditions = &payload.conditions;
+    if let Some(pattern) = &conditions.description_pattern {
+        compile_pattern(pattern)?;
+    }
+    let min_amount = to_cents(conditions.min_amount)?;
+    let max_amount = to_cents(conditions.max_amount)?;
+    if let (Some(min), Some(max)) = (min_amount, max_amount) {
+        if min > max {
+            return Err("min_amount must not be larger than max_amount".to_string());
+        }
+    }
+    let rule = NewRule {
+        household_id,
+        name,
+        priority: payload.priority,
+        active: payload.active,
+        description_pattern: conditions
+            .description_pattern
+            .clone()
+            .filter(|p| !p.is_empty()),
+        product_name: trimmed(&conditions.product_name),
+        merchant: trimmed(&conditions.merchant),
+        account: trimmed(&conditions.account),
+        min_amount,
+        max_amount,
+        set_category_id: payload.actions.set_category_id,
+        set_description: trimmed(&payload.actions.set_description),
+    };
+
+    let has_condition = rule.description_pattern.is_some()
+        || rule.product_name.is_some()
+        || rule.merchant.is_some()
+        || rule.account.is_some()
+        || rule.min_amount.is_some()
+        || rule.max_amount.is_some();
+    if !has_condition {
+        return Err("A rule needs at least one condition".to_string());
+    }
+    let has_action = rule.set_category_id.is_some()
+        || rule.set_description.is_some()
+        || !payload.actions.add_tags.is_empty();
+    if !has_action {
+        return Err("A rule needs at least one action".to_string());
+    }
+    Ok(rule)
+}
+
+/// A rule ready to be matched, with its description pattern compiled.
+pub struct CompiledRule {
+    pub rule: Rule,
+    pub tags: Vec<Tag>,
+    /// `Err` if the stored pattern no longer compiles; such a rule never matches.
+    pattern: Option<Result<Regex, String>>,
+}
+
+impl CompiledRule {
+    pub fn new(rule: Rule, tags: Vec<Tag>) -> Self {
+        let pattern = rule.description_pattern.as_deref().map(compile_pattern);
+        Self {
+            rule,
+            tags,
+            pattern,
+        }
+    }
+
+    pub fn matches(&self, subject: &RuleSubject) -> bool {
+        let rule = &self.rule;
+        let description = subject.description.unwrap_or("");
+        let pattern_ok = match &self.pattern {
+            Some(Ok(pattern)) => pattern.is_match(description),
+            Some(Err(_)) => false,
+            None => true,
+        };
+        let product_ok = rule
+            .product_name
+            .as_ref()
+            .is_none_or(|name| name.to_lowercase() == subject.product_name.to_lowercase());
+        let merchant_ok = rule.merchant.as_ref().is_none_or(|merchant| {
+            let merchant = merchant.to_lowercase();
+            subject.product_name.to_lowercase().contains(&merchant)
+                || description.to_lowercase().contains(&merchant)
+        });
+        let account_ok = rule.account.as_ref().is_none_or(|account| {
+            subject
+                .account
+                .is_some_and(|a| a.to_lowercase() == account.to_lowercase())
+        });
+        let amount_ok = rule.min_amount.is_none_or(|min| subject.amount >= min)
+            && rule.max_amount.is_none_or(|max| subject.amount <= max);
+        pattern_ok && product_ok && merchant_ok && account_ok && amount_ok
+    }
+}
+
+/// The fields of a transaction that rules look at.
+pub struct RuleSubject<'a> {
+    pub product_name: &'a str,
+    pub description: Option<&'a str>,
+    /// In cents.
+    pub amount: i32,
+    pub account: Option<&'a str>,
+}
+
+/// The combined effect of all rules matching one transaction.
+#[derive(Default, Debug)]
+pub struct RuleOutcome {
+    pub rule_ids: Vec<i32>,
+    pub category_id: Option<i32>,
+    pub tags: Vec<Tag>,
+    pub description: Option<String>,
+}
+
+/// Runs `rules` (already in priority order) against a transaction.
+///
+/// Conditions see the transaction as it was, not as earlier rules left it.
+/// For the category and description the first matching rule that sets them
+/// wins; tags from every matching rule are added.
+pub fn evaluate_rules(rules: &[CompiledRule], subject: &RuleSubject) -> RuleOutcome {
+    let mut outcome = RuleOutcome::default();
+    for compiled in rules.iter().filter(|r| r.matches(subject)) {
+        let rule = &compiled.rule;
+        outcome.rule_ids.push(rule.id);
+        if outcome.category_id.is_none() {
+            outcome.category_id = rule.set_category_id;
+        }
+        if outcome.description.is_none() {
+            outcome.description = rule.set_description.clone();
+        }
+        for tag in &compiled.tags {
+            if !outcome.tags.iter().any(|t| t.id == tag.id) {
+                outcome.tags.push(Tag {
+                    id: tag.id,
+                    name: tag.name.clone(),
+                    household_id: tag.household_id,
+                });
+            }
+        }
+    }
+    outcome
+}
+
+/// Loads the tags each rule adds, keyed by rule id.
+pub fn load_rule_tags(
+    conn: &mut PgConnection,
+    rule_ids: &[i32],
+) -> QueryResult<HashMap<i32, Vec<Tag>>> {
+    use crate::schema::rule_tags::dsl as rt;
+    use crate::schema::tags::dsl as tg;
+
+    let mut by_rule: HashMap<i32, Vec<Tag>> = HashMap::new();
+    for (rule_id, tag) in rt::rule_tags
+        .inner_join(tg::tags.on(tg::id.eq(rt::tag_id)))
+        .filter(rt::rule_id.eq_any(rule_ids))
+        .order(tg::name.asc())
+        .select((rt::rule_id, Tag::as_select()))
+        .load::<(i32, Tag)>(conn)?
+    {
+        by_rule.entry(rule_id).or_default().push(tag);
+    }
+    Ok(by_rule)
+}
+
+/// The user's active rules in the order they run.
+pub fn load_active_rules(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> QueryResult<Vec<CompiledRule>> {
+    use crate::schema::rules::dsl as r;
+
+    let rules = r::rules
+        .filter(r::household_id.eq(household_id))
+        .filter(r::active.eq(true))
+        .order((r::priority.asc(), r::id.asc()))
+        .load::<Rule>(conn)?;
+    let ids: Vec<i32> = rules.iter().map(|r| r.id).collect();
+    let mut tags = load_rule_tags(conn, &ids)?;
+    Ok(rules
+        .into_iter()
+        .map(|rule| {
+            let rule_tags = tags.remove(&rule.id).unwrap_or_default();
+            CompiledRule::new(rule, rule_tags)
+        })
+        .collect())
+}
+
+/// Writes the effect of matching rules to a transaction and its product.
+fn write_changes(
+    txn_conn: &mut PgConnection,
+    transaction_id: i32,
+    product_id: i32,
+    category_id: Option<i32>,
+    tag_ids: &[i32],
+    description: Option<&str>,
+) -> QueryResult<()> {
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+
+    if let Some(category_id) = category_id {
+        diesel::update(pr::products.filter(pr::id.eq(product_id)))
+            .set(pr::category_id.eq(category_id))
+            .execute(txn_conn)?;
+    }
+    if let Some(description) = description {
+        diesel::update(tx::transactions.filter(tx::id.eq(transaction_id)))
+            .set(tx::description.eq(description))
+            .execute(txn_conn)?;
+    }
+    for tag_id in tag_ids {
+        diesel::insert_into(tt::transaction_tags)
+            .values((tt::transaction_id.eq(transaction_id), tt::tag_id.eq(tag_id)))
+            .on_conflict_do_nothing()
+            .execute(txn_conn)?;
+    }
+    Ok(())
+}
+
+/// Applies the household's rules to a transaction that was just inserted.
+///
+/// Called from `insert_transaction`, so rules run for manual entries and
+/// every importer alike. Returns the transaction as it is after the rules.
+pub fn apply_rules_to_new_transaction(
+    txn_conn: &mut PgConnection,
+    household_id: i32,
+    mut transaction: Transaction,
+) -> QueryResult<Transaction> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+
+    let rules = load_active_rules(txn_conn, household_id)?;
+    if rules.is_empty() {
+        return Ok(transaction);
+    }
+    let product_name = pr::products
+        .filter(pr::id.eq(transaction.product_id))
+        .select(pr::name)
+        .first::<String>(txn_conn)?;
+    let amount = pp::product_prices
+        .filter(pp::id.eq(transaction.product_price_id))
+        .select(pp::price)
+        .first::<i32>(txn_conn)?;
+
+    let outcome = evaluate_rules(
+        &rules,
+        &RuleSubject {
+            product_name: &product_name,
+            description: transaction.description.as_deref(),
+            amount,
+            account: transaction.account.as_deref(),
+        },
+    );
+    let tag_ids: Vec<i32> = outcome.tags.iter().map(|t| t.id).collect();
+    write_changes(
+        txn_conn,
+        transaction.id,
+        transaction.product_id,
+        outcome.category_id,
+        &tag_ids,
+        outcome.description.as_deref(),
+    )?;
+    if outcome.description.is_some() {
+        transaction.description = outcome.description;
+    }
+    Ok(transaction)
+}
+
+/// Works out what the active rules would change on the household's existing
+/// transactions (narrowed down by `filter`). Returns the number of
+/// transactions checked and the effective changes, oldest first.
+///
+/// A product gets the category from the first of its transactions whose
+/// rules set one, so later transactions of the same product cannot flip it.
+pub fn plan_rule_changes(
+    conn: &mut PgConnection,
+    household_id: i32,
+    filter: &TransactionFilter,
+) -> QueryResult<(usize, Vec<RuleChange>)> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transaction_tags::dsl as tt;
+
+    let rules = load_active_rules(conn, household_id)?;
+    let transactions = filter_transactions(household_id, filter).load::<Transaction>(conn)?;
+    if rules.is_empty() {
+        return Ok((transactions.len(), Vec::new()));
+    }
+
+    let mut products: HashMap<i32, (String, Option<i32>)> = pr::products
+        .filter(pr::household_id.eq(household_id))
+        .select((pr::id, pr::name, pr::category_id))
+        .load::<(i32, String, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, name, category_id)| (id, (name, category_id)))
+        .collect();
+    let price_ids: Vec<i32> = transactions.iter().map(|t| t.product_price_id).collect();
+    let prices: HashMap<i32, i32> = pp::product_prices
+        .filter(pp::id.eq_any(&price_ids))
+        .select((pp::id, pp::price))
+        .load::<(i32, i32)>(conn)?
+        .into_iter()
+        .collect();
+    let transaction_ids: Vec<i32> = transactions.iter().map(|t| t.id).collect();
+    let mut current_tags: HashSet<(i32, i32)> = tt::transaction_tags
+        .filter(tt::transaction_id.eq_any(&transaction_ids))
+        .select((tt::transaction_id, tt::tag_id))
+        .load::<(i32, i32)>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut changes = Vec::new();
+    for transaction in &transactions {
+        let Some((product_name, category_id)) = products.get(&transaction.product_id).cloned()
+        else {
+            continue;
+        };
+        let outcome = evaluate_rules(
+            &rules,
+            &RuleSubject {
+                product_name: &product_name,
+                description: transaction.description.as_deref(),
+                amount: prices
+                    .get(&transaction.product_price_id)
+                    .copied()
+                    .unwrap_or(0),
+                account: transaction.account.as_deref(),
+            },
+        );
+
+        let set_category_id = outcome.category_id.filter(|c| Some(*c) != category_id);
+        if let Some(new_category) = set_category_id {
+            products.insert(transaction.product_id, (product_name, Some(new_category)));
+        }
+        let add_tags: Vec<String> = outcome
+            .tags
+            .into_iter()
+            .filter(|t| current_tags.insert((transaction.id, t.id)))
+            .map(|t| t.name)
+            .collect();
+        let set_description = outcome
+            .description
+            .filter(|d| transaction.description.as_deref() != Some(d.as_str()));
+
+        if set_category_id.is_some() || !add_tags.is_empty() || set_description.is_some() {
+            changes.push(RuleChange {
+                transaction_id: transaction.id,
+                product_id: transaction.product_id,
+                rule_ids: outcome.rule_ids,
+                set_category_id,
+                add_tags,
+                set_description,
+            });
+        }
+    }
+    Ok((transactions.len(), changes))
+}
+
+/// Writes planned changes. Must be called inside a Diesel transaction.
+pub fn execute_rule_changes(
+    txn_conn: &mut PgConnection,
+    household_id: i32,
+    changes: &[RuleChange],
+) -> QueryResult<()> {
+    use crate::schema::tags::dsl as tg;
+
+    let tag_ids: HashMap<String, i32> = tg::tags
+        .filter(tg::household_id.eq(household_id))
+        .select((tg::name, tg::id))
+        .load::<(String, i32)>(txn_conn)?
+        .into_iter()
+        .collect();
+    for change in changes {
+        let ids: Vec<i32> = change
+            .add_tags
+            .iter()
+            .filter_map(|name| tag_ids.get(name).copied())
+            .collect();
+        write_changes(
+            txn_conn,
+            change.transaction_id,
+            change.product_id,
+            change.set_category_id,
+            &ids,
+            change.set_description.as_deref(),
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::auth::CurrentSession;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{RefreshRequest, SessionDto, TokenResponse};
+use super::services::{active_sessions, revoke_session, rotate_session};
+
+/// Handler for POST /refresh.
+/// Trades a refresh token for a new access token and refresh token.
+#[debug_handler]
+pub async fn refresh(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RefreshRequest>,
+) -> JsonResult<TokenResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    rotate_session(&mut conn, &state.jwt, &payload.refresh_token)
+        .map_err(|e| error_response(format!("Failed to refresh session: {e}")))?
+        .map(Json)
+        .ok_or_else(|| error_response("Invalid or expired refresh token"))
+}
+
+/// Handler for POST /logout.
+/// Ends the session the request was made with.
+#[debug_handler]
+pub async fn logout(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+) -> JsonResult<SessionDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    revoke_session(&mut conn, logged_in_user_id, session_id)
+        .map_err(|e| error_response(format!("Failed to log out: {e}")))?
+        .map(|s| Json(SessionDto::new(s, session_id)))
+        .ok_or_else(|| error_response("Session not found"))
+}
+
+/// Handler for GET /sessions.
+#[debug_handler]
+pub async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+) -> JsonResult<Vec<SessionDto>> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let sessions = active_sessions(&mut conn, logged_in_user_id)
+        .map_err(|e| error_response(format!("Error loading sessions: {e}")))?;
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|s| SessionDto::new(s, session_id))
+            .collect(),
+    ))
+}
+
+/// Handler for DELETE /sessions/{id}.
+/// Revokes a session; its access tokens stop working right away.
+#[debug_handler]
+pub async fn delete_session(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Extension(CurrentSession(current_session_id)): Extension<CurrentSession>,
+    Path(session_id): Path<i32>,
+) -> JsonResult<SessionDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    revoke_session(&mut conn, logged_in_user_id, session_id)
+        .map_err(|e| error_response(format!("Failed to revoke session: {e}")))?
+        .map(|s| Json(SessionDto::new(s, current_session_id)))
+        .ok_or_else(|| error_response("Session not found"))
+}
@@ -0,0 +1,69 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::sessions;
+
+/// A login session from the `sessions` table, without its token hashes.
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = sessions)]
+pub struct Session {
+    pub id: i32,
+    pub user_id: i32,
+    pub device_label: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Used when inserting a new session.
+#[derive(Insertable)]
+#[diesel(table_name = sessions)]
+pub struct NewSession {
+    pub user_id: i32,
+    pub refresh_token_hash: String,
+    pub device_label: Option<String>,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Returned by POST /login and POST /refresh.
+#[derive(Serialize, Deserialize)]
+pub struct TokenResponse {
+    /// Short-lived access token for the `Authorization` header.
+    pub token: String,
+    /// Single-use token for POST /refresh; replaced by every refresh.
+    pub refresh_token: String,
+    /// Seconds until `token` expires.
+    pub expires_in: i64,
+}
+
+/// Body of POST /refresh.
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// DTO for listing active sessions.
+#[derive(Serialize)]
+pub struct SessionDto {
+    pub id: i32,
+    pub device_label: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
+
+impl SessionDto {
+    pub fn new(session: Session, current_session_id: i32) -> Self {
+        Self {
+            id: session.id,
+            device_label: session.device_label,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+            current: session.id == current_session_id,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::auth::{generate_jwt, hash_token, random_token};
+use crate::config::JwtConfig;
+
+use super::models::{NewSession, Session, TokenResponse};
+
+/// `last_used_at` is only written when it is older than this, so ordinary
+/// requests do not all turn into writes.
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+fn tokens(jwt: &JwtConfig, user_id: i32, session_id: i32, refresh_token: String) -> TokenResponse {
+    TokenResponse {
+        token: generate_jwt(jwt, user_id, session_id),
+        refresh_token,
+        expires_in: jwt.access_token_minutes * 60,
+    }
+}
+
+/// Opens a session for a user who just proved who they are.
+pub fn start_session(
+    conn: &mut PgConnection,
+    jwt: &JwtConfig,
+    user_id: i32,
+    device_label: Option<String>,
+) -> QueryResult<TokenResponse> {
+    use crate::schema::sessions::dsl as s;
+
+    let refresh_token = random_token();
+    let session_id = diesel::insert_into(s::sessions)
+        .values(&NewSession {
+            user_id,
+            refresh_token_hash: hash_token(&refresh_token),
+            device_label,
+            expires_at: now() + Duration::days(jwt.session_days),
+        })
+        .returning(s::id)
+        .get_result::<i32>(conn)?;
+    Ok(tokens(jwt, user_id, session_id, refresh_token))
+}
+
+/// Exchanges a refresh token for new tokens, replacing the refresh token and
+/// extending the session. Returns `None` if the token is unknown, expired or
+/// revoked.
+///
+/// A refresh token that was already replaced means two parties hold it, so
+/// its session is revoked.
+pub fn rotate_session(
+    conn: &mut PgConnection,
+    jwt: &JwtConfig,
+    refresh_token: &str,
+) -> QueryResult<Option<TokenResponse>> {
+    use crate::schema::sessions::dsl as s;
+
+    let token_hash = hash_token(refresh_token);
+    conn.transaction(|txn_conn| {
+        let session = s::sessions
+            .filter(s::refresh_token_hash.eq(&token_hash))
+            .filter(s::revoked_at.is_null())
+            .filter(s::expires_at.gt(now()))
+            .select(Session::as_select())
+            .for_update()
+            .first(txn_conn)
+            .optional()?;
+        let Some(session) = session else {
+            diesel::update(
+                s::sessions
+                    .filter(s::previous_token_hash.eq(&token_hash))
+                    .filter(s::revoked_at.is_null()),
+            )
+            .set(s::revoked_at.eq(now()))
+            .execute(txn_conn)?;
+            return Ok(None);
+        };
+
+        let new_token = random_token();
+        diesel::update(s::sessions.filter(s::id.eq(session.id)))
+            .set((
+                s::refresh_token_hash.eq(hash_token(&new_token)),
+                s::previous_token_hash.eq(&token_hash),
+                s::last_used_at.eq(now()),
+                s::expires_at.eq(now() + Duration::days(jwt.session_days)),
+            ))
+            .execute(txn_conn)?;
+        Ok(Some(tokens(jwt, session.user_id, session.id, new_token)))
+    })
+}
+
+/// Whether the session is still active, recording that it was just used.
+pub fn touch_session(conn: &mut PgConnection, session_id: i32, user_id: i32) -> QueryResult<bool> {
+    use crate::schema::sessions::dsl as s;
+
+    let last_used = s::sessions
+        .filter(s::id.eq(session_id))
+        .filter(s::user_id.eq(user_id))
+        .filter(s::revoked_at.is_null())
+        .filter(s::expires_at.gt(now()))
+        .select(s::last_used_at)
+        .first::<NaiveDateTime>(conn)
+        .optional()?;
+    let Some(last_used) = last_used else {
+        return Ok(false);
+    };
+    if last_used < now() - Duration::seconds(TOUCH_INTERVAL_SECONDS) {
+        diesel::update(s::sessions.filter(s::id.eq(session_id)))
+            .set(s::last_used_at.eq(now()))
+            .execute(conn)?;
+    }
+    Ok(true)
+}
+
+/// Revokes one of the user's sessions. Returns `None` if there is no such
+/// active session.
+pub fn revoke_session(
+    conn: &mut PgConnection,
+    user_id: i32,
+    session_id: i32,
+) -> QueryResult<Option<Session>> {
+    use crate::schema::sessions::dsl as s;
+
+    diesel::update(
+        s::sessions
+            .filter(s::id.eq(session_id))
+            .filter(s::user_id.eq(user_id))
+            .filter(s::revoked_at.is_null()),
+    )
+    .set(s::revoked_at.eq(now()))
+    .returning(Session::as_returning())
+    .get_result(conn)
+    .optional()
+}
+
+/// The user's sessions that can still be used, most recently used first.
+pub fn active_sessions(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Session>> {
+    use crate::schema::sessions::dsl as s;
+
+    s::sessions
+        .filter(s::user_id.eq(user_id))
+        .filter(s::revoked_at.is_null())
+        .filter(s::expires_at.gt(now()))
+        .order((s::last_used_at.desc(), s::id.desc()))
+        .select(Session::as_select())
+        .load(conn)
+}
+
+/// Revokes all of the user's sessions except `keep`, e.g. after the password
+/// changed.
+pub fn revoke_other_sessions(
+    conn: &mut PgConnection,
+    user_id: i32,
+    keep: Option<i32>,
+) -> QueryResult<usize> {
+    use crate::schema::sessions::dsl as s;
+
+    diesel::update(
+        s::sessions
+            .filter(s::user_id.eq(user_id))
+            .filter(s::id.ne(keep.unwrap_or(0)))
+            .filter(s::revoked_at.is_null()),
+    )
+    .set(s::revoked_at.eq(now()))
+    .execute(conn)
+}
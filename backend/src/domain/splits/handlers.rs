@@ -0,0 +1,293 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use backend::ErrorResponse;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::households::services::{member_role, members};
+use crate::domain::transactions::models::{TransactionPayload, TransactionType};
+use crate::domain::transactions::services::insert_transaction;
+use crate::domain::users::handlers::MessageResponse;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{
+    BalancesResponse, DebtDto, MemberBalanceDto, SettlementRequest, SettlementResponse, SplitDto,
+    SplitMethod, SplitRequest, SplitShare, SplitShareDto, TransactionSplit,
+};
+use super::services::{
+    allocate_split, find_transaction, household_shares, load_split, member_balances, pair_debts,
+    save_split, settle_up, SETTLEMENT_PRODUCT,
+};
+
+type HandlerError = (StatusCode, Json<ErrorResponse>);
+
+/// Fails unless every user is a member of the household.
+fn require_members(
+    conn: &mut PgConnection,
+    household_id: i32,
+    user_ids: impl IntoIterator<Item = i32>,
+) -> Result<(), HandlerError> {
+    for user_id in user_ids {
+        let role = member_role(conn, household_id, user_id)
+            .map_err(|e| error_response(format!("Error loading household: {e}")))?;
+        if role.is_none() {
+            return Err(error_response(format!(
+                "User {user_id} is not a member of this household"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn split_dto(
+    split: TransactionSplit,
+    shares: Vec<SplitShare>,
+    total: i64,
+) -> Result<SplitDto, HandlerError> {
+    let method = SplitMethod::parse(&split.method)
+        .ok_or_else(|| error_response(format!("Unknown split method: {}", split.method)))?;
+    Ok(SplitDto {
+        transaction_id: split.transaction_id,
+        paid_by: split.paid_by,
+        method,
+        amount: total as f64 / 100.0,
+        shares: shares
+            .into_iter()
+            .map(|share| SplitShareDto {
+                user_id: share.user_id,
+                amount: share.amount as f64 / 100.0,
+            })
+            .collect(),
+        created_at: split.created_at,
+    })
+}
+
+/// What each pair of members owes, and each member's net balance with zero
+/// for members without any.
+fn household_balances(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> Result<(Vec<DebtDto>, BTreeMap<i32, i64>), HandlerError> {
+    let shares = household_shares(conn, household_id)
+        .map_err(|e| error_response(format!("Error loading splits: {e}")))?;
+    let debts = pair_debts(&shares);
+    let mut balances = member_balances(&debts);
+    let current = members(conn, household_id)
+        .map_err(|e| error_response(format!("Error loading members: {e}")))?;
+    for (user_id, ..) in current {
+        balances.entry(user_id).or_default();
+    }
+    Ok((debts.into_iter().map(DebtDto::from).collect(), balances))
+}
+
+/// Handler for PUT /transactions/{id}/split.
+/// Splits an expense among household members, replacing an earlier split.
+#[debug_handler]
+pub async fn put_split(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(transaction_id): Path<i32>,
+    Json(payload): Json<SplitRequest>,
+) -> JsonResult<SplitDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let (transaction, total) = find_transaction(&mut conn, household.id, transaction_id)
+        .map_err(|e| error_response(format!("Error loading transaction: {e}")))?
+        .ok_or_else(|| error_response("Transaction not found"))?;
+    if transaction.transaction_type != TransactionType::Expense {
+        return Err(error_response("Only expenses can be split"));
+    }
+    let paid_by = payload
+        .paid_by
+        .or(transaction.user_id)
+        .unwrap_or(household.user_id);
+    let participants: Vec<(i32, Option<f64>)> = payload
+        .participants
+        .iter()
+        .map(|p| (p.user_id, p.share))
+        .collect();
+    require_members(
+        &mut conn,
+        household.id,
+        std::iter::once(paid_by).chain(participants.iter().map(|&(user_id, _)| user_id)),
+    )?;
+    let amounts = allocate_split(payload.method, total, &participants).map_err(error_response)?;
+
+    save_split(&mut conn, transaction_id, paid_by, payload.method, &amounts)
+        .map_err(|e| error_response(format!("Failed to save split: {e}")))?;
+    let (split, shares) = load_split(&mut conn, transaction_id)
+        .map_err(|e| error_response(format!("Error loading split: {e}")))?
+        .ok_or_else(|| error_response("Transaction is not split"))?;
+    split_dto(split, shares, total).map(Json)
+}
+
+/// Handler for GET /transactions/{id}/split.
+#[debug_handler]
+pub async fn get_split(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(transaction_id): Path<i32>,
+) -> JsonResult<SplitDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let (_, total) = find_transaction(&mut conn, household.id, transaction_id)
+        .map_err(|e| error_response(format!("Error loading transaction: {e}")))?
+        .ok_or_else(|| error_response("Transaction not found"))?;
+    let (split, shares) = load_split(&mut conn, transaction_id)
+        .map_err(|e| error_response(format!("Error loading split: {e}")))?
+        .ok_or_else(|| error_response("Transaction is not split"))?;
+    split_dto(split, shares, total).map(Json)
+}
+
+/// Handler for DELETE /transactions/{id}/split.
+/// The transaction stays; it just no longer counts towards the balances.
+#[debug_handler]
+pub async fn delete_split(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(transaction_id): Path<i32>,
+) -> JsonResult<MessageResponse> {
+    use crate::schema::transaction_splits::dsl as ts;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    find_transaction(&mut conn, household.id, transaction_id)
+        .map_err(|e| error_response(format!("Error loading transaction: {e}")))?
+        .ok_or_else(|| error_response("Transaction not found"))?;
+    let deleted = diesel::delete(ts::transaction_splits.find(transaction_id))
+        .execute(&mut conn)
+        .map_err(|e| error_response(format!("Failed to delete split: {e}")))?;
+    if deleted == 0 {
+        return Err(error_response("Transaction is not split"));
+    }
+
+    Ok(Json(MessageResponse {
+        message: "Split removed".to_string(),
+    }))
+}
+
+/// Handler for GET /splits/balances.
+/// Each member's net balance and what each pair of members owes.
+#[debug_handler]
+pub async fn get_balances(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> JsonResult<BalancesResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let (debts, balances) = household_balances(&mut conn, household.id)?;
+    Ok(Json(BalancesResponse {
+        members: balances
+            .into_iter()
+            .map(|(user_id, balance)| MemberBalanceDto {
+                user_id,
+                balance: balance as f64 / 100.0,
+            })
+            .collect(),
+        debts,
+    }))
+}
+
+/// Handler for GET /splits/settle-up.
+/// The fewest payments that settle every balance in the household.
+#[debug_handler]
+pub async fn get_settle_up(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+) -> JsonResult<Vec<DebtDto>> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let (_, balances) = household_balances(&mut conn, household.id)?;
+    Ok(Json(
+        settle_up(&balances)
+            .into_iter()
+            .map(DebtDto::from)
+            .collect(),
+    ))
+}
+
+/// Handler for POST /splits/settlements.
+/// Records a payment between two members as a transfer, which the receiver
+/// owes in full so that it cancels out the debt it pays.
+#[debug_handler]
+pub async fn create_settlement(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Json(payload): Json<SettlementRequest>,
+) -> JsonResult<SettlementResponse> {
+    let from = payload.from_user_id.unwrap_or(household.user_id);
+    let to = payload.to_user_id;
+    if from == to {
+        return Err(error_response("A settlement needs two different members"));
+    }
+    let cents = (payload.amount * 100.0).round();
+    if !cents.is_finite() || cents < 1.0 || cents > i32::MAX as f64 {
+        return Err(error_response("Settlement amount must be positive"));
+    }
+    let cents = cents as i64;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    require_members(&mut conn, household.id, [from, to])?;
+    let transfer = TransactionPayload {
+        product_id: None,
+        product_name: Some(SETTLEMENT_PRODUCT.to_string()),
+        product_price_id: None,
+        price: Some(cents as f64 / 100.0),
+        transaction_type: TransactionType::Transfer,
+        description: None,
+        date: payload.date.unwrap_or_else(|| Utc::now().naive_utc()),
+        tags: None,
+        external_id: None,
+        account: None,
+        value_date: None,
+        import_hash: None,
+    };
+    let transaction = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            let created = insert_transaction(txn_conn, household, &transfer)?;
+            save_split(
+                txn_conn,
+                created.transaction.id,
+                from,
+                SplitMethod::Exact,
+                &[(to, cents)],
+            )?;
+            Ok(created.transaction)
+        })
+        .map_err(|e| error_response(format!("Failed to record settlement: {e}")))?;
+
+    let (split, shares) = load_split(&mut conn, transaction.id)
+        .map_err(|e| error_response(format!("Error loading split: {e}")))?
+        .ok_or_else(|| error_response("Transaction is not split"))?;
+    Ok(Json(SettlementResponse {
+        transaction,
+        split: split_dto(split, shares, cents)?,
+    }))
+}
@@ -0,0 +1,172 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::transactions::models::Transaction;
+use crate::schema::{split_shares, transaction_splits};
+
+/// How a transaction's amount is divided among its participants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitMethod {
+    /// Everyone pays the same; leftover cents go to the first participants.
+    Equal,
+    /// In proportion to each participant's `share`.
+    Shares,
+    /// Each `share` is an amount; together they make up the transaction.
+    Exact,
+    /// Each `share` is a percentage; together they make 100.
+    Percentages,
+}
+
+impl SplitMethod {
+    pub fn parse(raw: &str) -> Option<SplitMethod> {
+        match raw {
+            "equal" => Some(SplitMethod::Equal),
+            "shares" => Some(SplitMethod::Shares),
+            "exact" => Some(SplitMethod::Exact),
+            "percentages" => Some(SplitMethod::Percentages),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SplitMethod::Equal => "equal",
+            SplitMethod::Shares => "shares",
+            SplitMethod::Exact => "exact",
+            SplitMethod::Percentages => "percentages",
+        }
+    }
+}
+
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = transaction_splits)]
+pub struct TransactionSplit {
+    pub transaction_id: i32,
+    /// `None` once the member who paid has deleted their account.
+    pub paid_by: Option<i32>,
+    pub method: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = transaction_splits)]
+pub struct NewTransactionSplit {
+    pub transaction_id: i32,
+    pub paid_by: i32,
+    pub method: String,
+}
+
+/// What one participant owes of a split transaction, in cents.
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = split_shares)]
+pub struct SplitShare {
+    /// `None` once the participant has deleted their account.
+    pub user_id: Option<i32>,
+    pub amount: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = split_shares)]
+pub struct NewSplitShare {
+    pub transaction_id: i32,
+    pub user_id: i32,
+    pub amount: i32,
+}
+
+#[derive(Deserialize)]
+pub struct SplitParticipant {
+    pub user_id: i32,
+    /// A weight, amount or percentage depending on the method; ignored for
+    /// equal splits.
+    #[serde(default)]
+    pub share: Option<f64>,
+}
+
+/// Body of PUT /transactions/{id}/split.
+#[derive(Deserialize)]
+pub struct SplitRequest {
+    /// Defaults to the member who entered the transaction.
+    pub paid_by: Option<i32>,
+    pub method: SplitMethod,
+    pub participants: Vec<SplitParticipant>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SplitShareDto {
+    pub user_id: Option<i32>,
+    /// In dollars.
+    pub amount: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SplitDto {
+    pub transaction_id: i32,
+    pub paid_by: Option<i32>,
+    pub method: SplitMethod,
+    /// The transaction's amount, in dollars.
+    pub amount: f64,
+    pub shares: Vec<SplitShareDto>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Money one member owes another, in cents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Debt {
+    pub from_user_id: i32,
+    pub to_user_id: i32,
+    pub amount: i64,
+}
+
+#[derive(Serialize)]
+pub struct DebtDto {
+    pub from_user_id: i32,
+    pub to_user_id: i32,
+    /// In dollars.
+    pub amount: f64,
+}
+
+impl From<Debt> for DebtDto {
+    fn from(debt: Debt) -> Self {
+        DebtDto {
+            from_user_id: debt.from_user_id,
+            to_user_id: debt.to_user_id,
+            amount: debt.amount as f64 / 100.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MemberBalanceDto {
+    pub user_id: i32,
+    /// Positive when the others owe this member, in dollars.
+    pub balance: f64,
+}
+
+/// Response of GET /splits/balances.
+#[derive(Serialize)]
+pub struct BalancesResponse {
+    pub members: Vec<MemberBalanceDto>,
+    /// What each pair of members owes after netting both directions.
+    pub debts: Vec<DebtDto>,
+}
+
+/// Body of POST /splits/settlements.
+#[derive(Deserialize)]
+pub struct SettlementRequest {
+    /// Defaults to the caller.
+    pub from_user_id: Option<i32>,
+    pub to_user_id: i32,
+    /// In dollars.
+    pub amount: f64,
+    /// Defaults to now.
+    pub date: Option<NaiveDateTime>,
+}
+
+/// Response of POST /splits/settlements.
+#[derive(Serialize)]
+pub struct SettlementResponse {
+    pub transaction: Transaction,
+    pub split: SplitDto,
+}
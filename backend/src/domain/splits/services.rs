@@ -0,0 +1,386 @@
+use diesel::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::domain::transactions::models::Transaction;
+
+use super::models::{
+    Debt, NewSplitShare, NewTransactionSplit, SplitMethod, SplitShare, TransactionSplit,
+};
+
+/// Product name settlements are recorded under.
+pub const SETTLEMENT_PRODUCT: &str = "Settlement";
+
+/// Settle-up searches for the fewest payments among up to this many members
+/// with an open balance, and pays greedily beyond that.
+pub const MAX_EXACT_SETTLE_UP: usize = 16;
+
+/// Shares and percentages are weighed in units of 1/10000.
+const WEIGHT_SCALE: f64 = 10_000.0;
+
+/// Largest share accepted, whichever the method; keeps weights and amounts
+/// in cents far from overflowing.
+const MAX_SHARE: f64 = 1_000_000_000.0;
+
+/// Divides `total` cents among `weights`, handing leftover cents to the
+/// largest remainders and, on ties, to the earlier participants.
+fn largest_remainder(total: i64, weights: &[i64]) -> Vec<i64> {
+    let sum: i128 = weights.iter().map(|&w| w as i128).sum();
+    let mut amounts: Vec<i64> = Vec::with_capacity(weights.len());
+    let mut remainders: Vec<(i128, usize)> = Vec::with_capacity(weights.len());
+    for (i, &weight) in weights.iter().enumerate() {
+        let exact = total as i128 * weight as i128;
+        amounts.push((exact / sum) as i64);
+        remainders.push((exact % sum, i));
+    }
+    let leftover = total - amounts.iter().sum::<i64>();
+    remainders.sort_by_key(|&(remainder, i)| (Reverse(remainder), i));
+    for &(_, i) in remainders.iter().take(leftover as usize) {
+        amounts[i] += 1;
+    }
+    amounts
+}
+
+/// Works out what each participant owes of `total` cents.
+///
+/// `participants` pairs a user id with its share: a weight for
+/// [`SplitMethod::Shares`], an amount in dollars for [`SplitMethod::Exact`]
+/// and a percentage for [`SplitMethod::Percentages`]. Returns the amounts in
+/// cents, in participant order, always adding up to `total`.
+pub fn allocate_split(
+    method: SplitMethod,
+    total: i64,
+    participants: &[(i32, Option<f64>)],
+) -> Result<Vec<(i32, i64)>, String> {
+    if participants.is_empty() {
+        return Err("A split needs at least one participant".to_string());
+    }
+    let mut seen = HashSet::new();
+    for (user_id, _) in participants {
+        if !seen.insert(user_id) {
+            return Err(format!("User {user_id} is listed twice"));
+        }
+    }
+    let shares = || -> Result<Vec<f64>, String> {
+        participants
+            .iter()
+            .map(|&(user_id, share)| match share {
+                Some(share) if share > MAX_SHARE => {
+                    Err(format!("The share of user {user_id} is too large"))
+                }
+                Some(share) if share.is_finite() && share >= 0.0 => Ok(share),
+                Some(_) => Err(format!("The share of user {user_id} must not be negative")),
+                None => Err(format!("User {user_id} needs a share")),
+            })
+            .collect()
+    };
+    let weights = |shares: &[f64]| -> Vec<i64> {
+        shares
+            .iter()
+            .map(|share| (share * WEIGHT_SCALE).round() as i64)
+            .collect()
+    };
+
+    let amounts = match method {
+        SplitMethod::Equal => largest_remainder(total, &vec![1; participants.len()]),
+        SplitMethod::Shares => {
+            let weights = weights(&shares()?);
+            if weights.iter().all(|&weight| weight == 0) {
+                return Err("Shares must add up to more than zero".to_string());
+            }
+            largest_remainder(total, &weights)
+        }
+        SplitMethod::Percentages => {
+            let shares = shares()?;
+            if (shares.iter().sum::<f64>() - 100.0).abs() > 0.01 {
+                return Err("Percentages must add up to 100".to_string());
+            }
+            largest_remainder(total, &weights(&shares))
+        }
+        SplitMethod::Exact => {
+            let amounts: Vec<i64> = shares()?
+                .iter()
+                .map(|share| (share * 100.0).round() as i64)
+                .collect();
+            let sum: i128 = amounts.iter().map(|&amount| amount as i128).sum();
+            if sum != total as i128 {
+                return Err(format!(
+                    "Amounts add up to {:.2}, not {:.2}",
+                    sum as f64 / 100.0,
+                    total as f64 / 100.0
+                ));
+            }
+            amounts
+        }
+    };
+    Ok(participants
+        .iter()
+        .map(|&(user_id, _)| user_id)
+        .zip(amounts)
+        .collect())
+}
+
+/// Nets `(paid_by, user_id, amount)` shares into what each pair of members
+/// owes, one debt per pair, ordered by the pair's user ids.
+pub fn pair_debts(shares: &[(i32, i32, i64)]) -> Vec<Debt> {
+    // What the lower user id owes the higher one; negative the other way.
+    let mut net: BTreeMap<(i32, i32), i64> = BTreeMap::new();
+    for &(paid_by, user_id, amount) in shares {
+        if user_id < paid_by {
+            *net.entry((user_id, paid_by)).or_default() += amount;
+        } else if user_id > paid_by {
+            *net.entry((paid_by, user_id)).or_default() -= amount;
+        }
+    }
+    net.into_iter()
+        .filter(|&(_, amount)| amount != 0)
+        .map(|((low, high), amount)| {
+            if amount > 0 {
+                Debt {
+                    from_user_id: low,
+                    to_user_id: high,
+                    amount,
+                }
+            } else {
+                Debt {
+                    from_user_id: high,
+                    to_user_id: low,
+                    amount: -amount,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Each member's net balance: positive when the others owe them.
+pub fn member_balances(debts: &[Debt]) -> BTreeMap<i32, i64> {
+    let mut balances = BTreeMap::new();
+    for debt in debts {
+        *balances.entry(debt.to_user_id).or_default() += debt.amount;
+        *balances.entry(debt.from_user_id).or_default() -= debt.amount;
+    }
+    balances
+}
+
+/// Repeatedly has the largest debtor pay the largest creditor. Settles `k`
+/// members whose balances add up to zero in at most `k - 1` payments.
+fn greedy_payments(members: &[(i32, i64)]) -> Vec<Debt> {
+    let mut balances = members.to_vec();
+    let mut payments = Vec::new();
+    while let Some(creditor) = (0..balances.len()).max_by_key(|&i| (balances[i].1, Reverse(i))) {
+        let debtor = (0..balances.len())
+            .min_by_key(|&i| (balances[i].1, i))
+            .unwrap_or(creditor);
+        if balances[creditor].1 <= 0 || balances[debtor].1 >= 0 {
+            break;
+        }
+        let amount = balances[creditor].1.min(-balances[debtor].1);
+        payments.push(Debt {
+            from_user_id: balances[debtor].0,
+            to_user_id: balances[creditor].0,
+            amount,
+        });
+        balances[creditor].1 -= amount;
+        balances[debtor].1 += amount;
+    }
+    payments
+}
+
+/// The fewest payments that bring every balance to zero.
+///
+/// A group of members whose balances add up to zero can settle among
+/// themselves in one payment fewer than its size, so the payments are
+/// minimal when the members split into as many such groups as possible. Up
+/// to [`MAX_EXACT_SETTLE_UP`] members the best split is found over all
+/// subsets; larger households are settled greedily.
+pub fn settle_up(balances: &BTreeMap<i32, i64>) -> Vec<Debt> {
+    let members: Vec<(i32, i64)> = balances
+        .iter()
+        .filter(|&(_, &balance)| balance != 0)
+        .map(|(&user_id, &balance)| (user_id, balance))
+        .collect();
+    let n = members.len();
+    if n > MAX_EXACT_SETTLE_UP {
+        return greedy_payments(&members);
+    }
+
+    let full = (1usize << n) - 1;
+    let mut sums = vec![0i64; full + 1];
+    for mask in 1..=full {
+        let lowest = mask.trailing_zeros() as usize;
+        sums[mask] = sums[mask & (mask - 1)] + members[lowest].1;
+    }
+    // groups[mask]: most zero-sum sets along a chain of subsets of `mask`
+    // that removes one member at a time.
+    let mut groups = vec![0u32; full + 1];
+    for mask in 1..=full {
+        let best = (0..n)
+            .filter(|&i| mask & (1 << i) != 0)
+            .map(|i| groups[mask ^ (1 << i)])
+            .max()
+            .unwrap_or(0);
+        groups[mask] = best + u32::from(sums[mask] == 0);
+    }
+
+    // Walk the best chain back down, closing a group each time the members
+    // left over add up to zero again.
+    let mut payments = Vec::new();
+    let mut group = Vec::new();
+    let mut mask = full;
+    while mask != 0 {
+        let zero = u32::from(sums[mask] == 0);
+        let Some(i) = (0..n)
+            .find(|&i| mask & (1 << i) != 0 && groups[mask ^ (1 << i)] + zero == groups[mask])
+        else {
+            break;
+        };
+        group.push(members[i]);
+        mask ^= 1 << i;
+        if sums[mask] == 0 {
+            payments.extend(greedy_payments(&group));
+            group.clear();
+        }
+    }
+    payments.sort_by_key(|p| (p.from_user_id, p.to_user_id));
+    payments
+}
+
+/// A transaction of the household with its amount in cents.
+pub fn find_transaction(
+    conn: &mut PgConnection,
+    household_id: i32,
+    transaction_id: i32,
+) -> QueryResult<Option<(Transaction, i64)>> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::transactions::dsl as tx;
+
+    tx::transactions
+        .inner_join(pp::product_prices)
+        .filter(tx::id.eq(transaction_id))
+        .filter(tx::household_id.eq(household_id))
+        .select((Transaction::as_select(), pp::price))
+        .first::<(Transaction, i32)>(conn)
+        .optional()
+        .map(|found| found.map(|(transaction, price)| (transaction, price as i64)))
+}
+
+/// Stores how a transaction is split, replacing any earlier split.
+pub fn save_split(
+    conn: &mut PgConnection,
+    transaction_id: i32,
+    paid_by: i32,
+    method: SplitMethod,
+    amounts: &[(i32, i64)],
+) -> QueryResult<()> {
+    use crate::schema::split_shares::dsl as ss;
+    use crate::schema::transaction_splits::dsl as ts;
+
+    conn.transaction(|conn| {
+        diesel::delete(ts::transaction_splits.find(transaction_id)).execute(conn)?;
+        diesel::insert_into(ts::transaction_splits)
+            .values(&NewTransactionSplit {
+                transaction_id,
+                paid_by,
+                method: method.name().to_string(),
+            })
+            .execute(conn)?;
+        let shares: Vec<NewSplitShare> = amounts
+            .iter()
+            .map(|&(user_id, amount)| NewSplitShare {
+                transaction_id,
+                user_id,
+                amount: amount as i32,
+            })
+            .collect();
+        diesel::insert_into(ss::split_shares)
+            .values(&shares)
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// The split of a transaction with its shares, ordered by user id.
+pub fn load_split(
+    conn: &mut PgConnection,
+    transaction_id: i32,
+) -> QueryResult<Option<(TransactionSplit, Vec<SplitShare>)>> {
+    use crate::schema::split_shares::dsl as ss;
+    use crate::schema::transaction_splits::dsl as ts;
+
+    let Some(split) = ts::transaction_splits
+        .find(transaction_id)
+        .select(TransactionSplit::as_select())
+        .first(conn)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let shares = ss::split_shares
+        .filter(ss::transaction_id.eq(transaction_id))
+        .order(ss::user_id.asc())
+        .select(SplitShare::as_select())
+        .load(conn)?;
+    Ok(Some((split, shares)))
+}
+
+/// Every share of the household's split transactions as
+/// `(paid_by, user_id, amount)`.
+///
+/// Shares of deleted accounts are left out: an account can only be deleted
+/// once its debts are settled, and without it the other pairs net the same.
+pub fn household_shares(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> QueryResult<Vec<(i32, i32, i64)>> {
+    use crate::schema::split_shares::dsl as ss;
+    use crate::schema::transaction_splits::dsl as ts;
+    use crate::schema::transactions::dsl as tx;
+
+    let rows = ss::split_shares
+        .inner_join(ts::transaction_splits.inner_join(tx::transactions))
+        .filter(tx::household_id.eq(household_id))
+        .filter(ts::paid_by.is_not_null())
+        .filter(ss::user_id.is_not_null())
+        .select((
+            ts::paid_by.assume_not_null(),
+            ss::user_id.assume_not_null(),
+            ss::amount,
+        ))
+        .load::<(i32, i32, i32)>(conn)?;
+    Ok(rows
+        .into_iter()
+        .map(|(paid_by, user_id, amount)| (paid_by, user_id, amount as i64))
+        .collect())
+}
+
+/// Whether the user owes or is owed money in any household, including ones
+/// they have left.
+pub fn has_open_debts(conn: &mut PgConnection, user_id: i32) -> QueryResult<bool> {
+    use crate::schema::split_shares::dsl as ss;
+    use crate::schema::transaction_splits::dsl as ts;
+    use crate::schema::transactions::dsl as tx;
+
+    let rows = ss::split_shares
+        .inner_join(ts::transaction_splits.inner_join(tx::transactions))
+        .filter(ts::paid_by.eq(user_id).or(ss::user_id.eq(user_id)))
+        .filter(ts::paid_by.is_not_null())
+        .filter(ss::user_id.is_not_null())
+        .select((
+            tx::household_id,
+            ts::paid_by.assume_not_null(),
+            ss::user_id.assume_not_null(),
+            ss::amount,
+        ))
+        .load::<(i32, i32, i32, i32)>(conn)?;
+    // Every pair here includes the user, so any debt left is theirs.
+    let mut by_household: BTreeMap<i32, Vec<(i32, i32, i64)>> = BTreeMap::new();
+    for (household_id, paid_by, user_id, amount) in rows {
+        by_household
+            .entry(household_id)
+            .or_default()
+            .push((paid_by, user_id, amount as i64));
+    }
+    Ok(by_household
+        .values()
+        .any(|shares| !pair_debts(shares).is_empty()))
+}
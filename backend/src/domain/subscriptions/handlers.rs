@@ -0,0 +1,144 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Path, Query, State},
+    Json,
+};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::recurring_rules::handlers::insert_recurring_rule;
+use crate::domain::recurring_rules::models::{Cadence, NewRecurringRule, RecurringRuleDto};
+use crate::domain::transactions::models::TransactionType;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{ConvertQuery, DetectedSubscription, SubscriptionQuery};
+use super::services::{detect_subscriptions, normalize_description, PaymentRow};
+
+/// Loads the household's expenses as detector input, optionally for one product only.
+fn load_payments(
+    conn: &mut PgConnection,
+    household_id: i32,
+    only_product: Option<i32>,
+) -> QueryResult<Vec<PaymentRow>> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transactions::dsl as tx;
+
+    let mut query = tx::transactions
+        .filter(tx::household_id.eq(household_id))
+        .filter(tx::transaction_type.eq(TransactionType::Expense))
+        .inner_join(pr::products.on(pr::id.eq(tx::product_id)))
+        .inner_join(pp::product_prices.on(pp::id.eq(tx::product_price_id)))
+        .select((pr::id, pr::name, tx::description, tx::date, pp::price))
+        .into_boxed();
+    if let Some(pid) = only_product {
+        query = query.filter(tx::product_id.eq(pid));
+    }
+
+    let rows = query.load::<(i32, String, Option<String>, NaiveDateTime, i32)>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(product_id, product_name, description, date, price)| PaymentRow {
+                product_id,
+                product_name,
+                description,
+                date: date.date(),
+                amount: price as i64,
+            },
+        )
+        .collect())
+}
+
+/// Handler for GET /subscriptions.
+/// Lists expenses that repeat weekly, monthly or yearly with a stable amount.
+#[debug_handler]
+pub async fn list_subscriptions(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<SubscriptionQuery>,
+) -> JsonResult<Vec<DetectedSubscription>> {
+    use crate::schema::recurring_rules::dsl as rr;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let payments = load_payments(&mut conn, household.id, None)
+        .map_err(|e| error_response(format!("Error loading transactions: {e}")))?;
+
+    let rules = rr::recurring_rules
+        .filter(rr::household_id.eq(household.id))
+        .select((rr::id, rr::product_id, rr::cadence, rr::description))
+        .load::<(i32, i32, Cadence, Option<String>)>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading recurring rules: {e}")))?;
+
+    let today = Utc::now().date_naive();
+    let mut detected = detect_subscriptions(&payments, today);
+    detected.retain(|s| query.include_inactive || s.active);
+    for sub in detected.iter_mut() {
+        let description = normalize_description(sub.description.as_deref());
+        sub.recurring_rule_id = rules
+            .iter()
+            .find(|(_, pid, cadence, rule_description)| {
+                *pid == sub.product_id
+                    && *cadence == sub.cadence
+                    && normalize_description(rule_description.as_deref()) == description
+            })
+            .map(|(id, ..)| *id);
+    }
+    detected.sort_by(|a, b| b.annualized_cost.total_cmp(&a.annualized_cost));
+
+    Ok(Json(detected))
+}
+
+/// Handler for POST /subscriptions/{product_id}/convert.
+/// Turns a detected subscription into a recurring rule in one step.
+#[debug_handler]
+pub async fn convert_subscription(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Path(product_id): Path<i32>,
+    Query(query): Query<ConvertQuery>,
+) -> JsonResult<RecurringRuleDto> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let payments = load_payments(&mut conn, household.id, Some(product_id))
+        .map_err(|e| error_response(format!("Error loading transactions: {e}")))?;
+
+    let wanted = query
+        .description
+        .as_deref()
+        .map(|d| normalize_description(Some(d)));
+    let Some(sub) = detect_subscriptions(&payments, Utc::now().date_naive())
+        .into_iter()
+        .find(|sub| {
+            wanted
+                .as_ref()
+                .is_none_or(|w| *w == normalize_description(sub.description.as_deref()))
+        })
+    else {
+        return Err(error_response(
+            "No subscription pattern detected for this product",
+        ));
+    };
+
+    let new_rule = NewRecurringRule {
+        household_id: household.id,
+        product_id: sub.product_id,
+        description: sub.description,
+        amount: (sub.average_amount * 100.0).round() as i32,
+        transaction_type: TransactionType::Expense,
+        cadence: sub.cadence,
+        next_due_date: sub.next_expected_date,
+    };
+
+    insert_recurring_rule(&mut conn, &new_rule)
+}
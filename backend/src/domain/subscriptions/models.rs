@@ -0,0 +1,39 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::recurring_rules::models::Cadence;
+
+/// A subscription-like pattern found in the household's expenses.
+#[derive(Debug, Serialize)]
+pub struct DetectedSubscription {
+    pub product_id: i32,
+    pub product_name: String,
+    /// The most common description used for these payments.
+    pub description: Option<String>,
+    pub cadence: Cadence,
+    pub occurrences: usize,
+    pub average_amount: f64,
+    pub last_date: NaiveDate,
+    pub next_expected_date: NaiveDate,
+    pub annualized_cost: f64,
+    /// False once a payment is overdue by more than half a period.
+    pub active: bool,
+    /// Set when the subscription was already converted into a recurring rule.
+    pub recurring_rule_id: Option<i32>,
+}
+
+/// Query parameters for GET /subscriptions.
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionQuery {
+    /// Also list subscriptions that appear to have been cancelled.
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// Query parameters for POST /subscriptions/{product_id}/convert.
+#[derive(Debug, Deserialize)]
+pub struct ConvertQuery {
+    /// Picks among several subscriptions of the product by description;
+    /// dates and numbers in it do not matter.
+    pub description: Option<String>,
+}
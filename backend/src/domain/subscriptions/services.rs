@@ -0,0 +1,143 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::analytics::services::{cents_to_dollars, median};
+use crate::domain::recurring_rules::models::Cadence;
+
+use super::models::DetectedSubscription;
+
+/// Largest relative deviation from the median amount that still counts as "stable".
+pub const AMOUNT_TOLERANCE: f64 = 0.1;
+
+/// Share of intervals that must match the cadence.
+pub const REGULAR_SHARE: f64 = 0.75;
+
+/// One payment, as fed into the detector.
+pub struct PaymentRow {
+    pub product_id: i32,
+    pub product_name: String,
+    pub description: Option<String>,
+    pub date: NaiveDate,
+    /// Amount in cents.
+    pub amount: i64,
+}
+
+/// Maps a gap between payments (in days) to a cadence, if it looks like one.
+pub fn classify_interval(days: i64) -> Option<Cadence> {
+    match days {
+        6..=8 => Some(Cadence::Weekly),
+        27..=33 => Some(Cadence::Monthly),
+        355..=375 => Some(Cadence::Yearly),
+        _ => None,
+    }
+}
+
+/// Fewest payments needed before a cadence is trusted.
+fn min_occurrences(cadence: Cadence) -> usize {
+    match cadence {
+        Cadence::Weekly => 4,
+        Cadence::Monthly => 3,
+        Cadence::Yearly => 2,
+    }
+}
+
+/// Lowercase words of a description without the ones holding digits, such
+/// as dates and invoice numbers that change from payment to payment.
+pub fn normalize_description(description: Option<&str>) -> String {
+    description
+        .unwrap_or("")
+        .split_whitespace()
+        .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Scans payments grouped by product and normalized description for regular
+/// intervals with stable amounts, so that a generic product such as a bank
+/// transfer can carry several subscriptions.
+pub fn detect_subscriptions(
+    payments: &[PaymentRow],
+    today: NaiveDate,
+) -> Vec<DetectedSubscription> {
+    let mut by_product: BTreeMap<(i32, String), Vec<&PaymentRow>> = BTreeMap::new();
+    for p in payments {
+        let key = (
+            p.product_id,
+            normalize_description(p.description.as_deref()),
+        );
+        by_product.entry(key).or_default().push(p);
+    }
+
+    by_product
+        .into_values()
+        .filter_map(|mut group| {
+            group.sort_by_key(|p| p.date);
+            detect_one(&group, today)
+        })
+        .collect()
+}
+
+fn detect_one(group: &[&PaymentRow], today: NaiveDate) -> Option<DetectedSubscription> {
+    if group.len() < 2 {
+        return None;
+    }
+
+    let intervals: Vec<i64> = group
+        .windows(2)
+        .map(|w| (w[1].date - w[0].date).num_days())
+        .collect();
+    let interval_values: Vec<f64> = intervals.iter().map(|d| *d as f64).collect();
+    let cadence = classify_interval(median(&interval_values)?.round() as i64)?;
+    if group.len() < min_occurrences(cadence) {
+        return None;
+    }
+
+    let regular = intervals
+        .iter()
+        .filter(|d| classify_interval(**d) == Some(cadence))
+        .count();
+    if (regular as f64) < REGULAR_SHARE * intervals.len() as f64 {
+        return None;
+    }
+
+    let amounts: Vec<f64> = group.iter().map(|p| p.amount as f64).collect();
+    let typical = median(&amounts)?;
+    if typical <= 0.0
+        || amounts
+            .iter()
+            .any(|a| (a - typical).abs() / typical > AMOUNT_TOLERANCE)
+    {
+        return None;
+    }
+    let average = amounts.iter().sum::<f64>() / amounts.len() as f64;
+
+    let last = group.last()?;
+    let next_expected_date = cadence.advance(last.date);
+    // Overdue by more than half a period means it was probably cancelled.
+    let grace = (cadence.advance(next_expected_date) - next_expected_date) / 2;
+    let active = today <= next_expected_date + grace;
+
+    let mut description_counts: HashMap<&str, usize> = HashMap::new();
+    for desc in group.iter().filter_map(|p| p.description.as_deref()) {
+        *description_counts.entry(desc).or_default() += 1;
+    }
+    let description = description_counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+        .map(|(d, _)| d.to_string());
+
+    Some(DetectedSubscription {
+        product_id: last.product_id,
+        product_name: last.product_name.clone(),
+        description,
+        cadence,
+        occurrences: group.len(),
+        average_amount: cents_to_dollars(average),
+        last_date: last.date,
+        next_expected_date,
+        annualized_cost: cents_to_dollars(average * cadence.periods_per_year()),
+        active,
+        recurring_rule_id: None,
+    })
+}
@@ -0,0 +1,96 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::{error_response, AppState, JsonResult};
+
+use super::models::{CategorySuggestion, SuggestQuery, SuggestionResult, TagSuggestion};
+use super::services::{load_training_examples, tokenize, Classifier};
+
+/// Categories returned when the client does not ask for a number.
+const DEFAULT_LIMIT: usize = 3;
+
+/// Handler for GET /transactions/suggest.
+/// Suggests a category and tags for a product name and/or description,
+/// learned from the household's own history.
+#[debug_handler]
+pub async fn suggest(
+    State(state): State<Arc<AppState>>,
+    Extension(household): Extension<ActiveHousehold>,
+    Query(query): Query<SuggestQuery>,
+) -> JsonResult<SuggestionResult> {
+    use crate::schema::categories::dsl as cat;
+    use crate::schema::tags::dsl as tg;
+
+    let text = [query.product_name.as_deref(), query.description.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.trim().is_empty() {
+        return Err(error_response(
+            "Provide a product_name or description to get suggestions",
+        ));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let examples = load_training_examples(&mut conn, household.id)
+        .map_err(|e| error_response(format!("Error loading history: {e}")))?;
+    let classifier = Classifier::train(&examples);
+    let known_tokens = classifier.known_tokens(&tokenize(&text));
+
+    let category_names: HashMap<i32, String> = cat::categories
+        .filter(cat::household_id.eq(household.id))
+        .select((cat::id, cat::name))
+        .load::<(i32, String)>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading categories: {e}")))?
+        .into_iter()
+        .collect();
+    let tag_names: HashMap<i32, String> = tg::tags
+        .filter(tg::household_id.eq(household.id))
+        .select((tg::id, tg::name))
+        .load::<(i32, String)>(&mut conn)
+        .map_err(|e| error_response(format!("Error loading tags: {e}")))?
+        .into_iter()
+        .collect();
+
+    let categories = classifier
+        .categories(&known_tokens)
+        .into_iter()
+        .filter_map(|(category_id, confidence)| {
+            Some(CategorySuggestion {
+                category_id,
+                name: category_names.get(&category_id)?.clone(),
+                confidence,
+            })
+        })
+        .take(query.limit.unwrap_or(DEFAULT_LIMIT))
+        .collect();
+    let tags = classifier
+        .tags(&known_tokens)
+        .into_iter()
+        .filter_map(|(tag_id, confidence)| {
+            Some(TagSuggestion {
+                tag_id,
+                name: tag_names.get(&tag_id)?.clone(),
+                confidence,
+            })
+        })
+        .collect();
+
+    Ok(Json(SuggestionResult {
+        known_tokens,
+        categories,
+        tags,
+    }))
+}
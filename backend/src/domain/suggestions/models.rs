@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Query parameters for GET /transactions/suggest. At least one is required.
+#[derive(Deserialize)]
+pub struct SuggestQuery {
+    pub product_name: Option<String>,
+    pub description: Option<String>,
+    /// How many categories to return; defaults to 3.
+    pub limit: Option<usize>,
+}
+
+/// One past transaction, or a product never used in one, to learn from.
+pub struct TrainingExample {
+    pub tokens: Vec<String>,
+    pub category_id: Option<i32>,
+    pub tag_ids: Vec<i32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CategorySuggestion {
+    pub category_id: i32,
+    pub name: String,
+    /// Posterior probability between 0 and 1.
+    pub confidence: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TagSuggestion {
+    pub tag_id: i32,
+    pub name: String,
+    /// Probability between 0 and 1 that the tag applies.
+    pub confidence: f64,
+}
+
+/// Response of GET /transactions/suggest, most likely first.
+#[derive(Serialize, Debug)]
+pub struct SuggestionResult {
+    /// Tokens of the input that occur in the household's history.
+    pub known_tokens: Vec<String>,
+    pub categories: Vec<CategorySuggestion>,
+    pub tags: Vec<TagSuggestion>,
+}
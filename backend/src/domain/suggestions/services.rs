@@ -0,0 +1,220 @@
+use diesel::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use super::models::TrainingExample;
+
+/// Splits text into lowercase words, dropping single characters and bare
+/// numbers, which say little about a category.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() > 1 && !t.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Token counts over the examples belonging to one class.
+#[derive(Default)]
+struct ClassCounts {
+    examples: usize,
+    tokens: HashMap<String, usize>,
+    total_tokens: usize,
+}
+
+impl ClassCounts {
+    fn add(&mut self, tokens: &[String]) {
+        self.examples += 1;
+        for token in tokens {
+            *self.tokens.entry(token.clone()).or_default() += 1;
+        }
+        self.total_tokens += tokens.len();
+    }
+
+    /// Laplace-smoothed log P(token | class).
+    fn log_likelihood(&self, token: &str, vocabulary: usize) -> f64 {
+        let count = self.tokens.get(token).copied().unwrap_or(0);
+        ((count + 1) as f64 / (self.total_tokens + vocabulary) as f64).ln()
+    }
+}
+
+/// Multinomial naive Bayes over the words of product names and descriptions.
+///
+/// Categories are one multi-class model; every tag is its own yes/no model,
+/// since a transaction can carry any number of tags.
+#[derive(Default)]
+pub struct Classifier {
+    vocabulary: HashSet<String>,
+    all: ClassCounts,
+    categorized: usize,
+    categories: HashMap<i32, ClassCounts>,
+    tags: HashMap<i32, ClassCounts>,
+}
+
+impl Classifier {
+    pub fn train(examples: &[TrainingExample]) -> Self {
+        let mut classifier = Classifier::default();
+        for example in examples.iter().filter(|e| !e.tokens.is_empty()) {
+            classifier.vocabulary.extend(example.tokens.iter().cloned());
+            classifier.all.add(&example.tokens);
+            if let Some(category_id) = example.category_id {
+                classifier.categorized += 1;
+                classifier
+                    .categories
+                    .entry(category_id)
+                    .or_default()
+                    .add(&example.tokens);
+            }
+            for tag_id in &example.tag_ids {
+                classifier
+                    .tags
+                    .entry(*tag_id)
+                    .or_default()
+                    .add(&example.tokens);
+            }
+        }
+        classifier
+    }
+
+    /// The distinct input tokens seen during training; the rest carry no
+    /// information.
+    pub fn known_tokens(&self, tokens: &[String]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        tokens
+            .iter()
+            .filter(|t| self.vocabulary.contains(*t) && seen.insert(t.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Posterior probability of each category, most likely first.
+    pub fn categories(&self, known_tokens: &[String]) -> Vec<(i32, f64)> {
+        if known_tokens.is_empty() || self.categorized == 0 {
+            return Vec::new();
+        }
+        let vocabulary = self.vocabulary.len();
+        let scores: Vec<(i32, f64)> = self
+            .categories
+            .iter()
+            .map(|(category_id, counts)| {
+                let prior = (counts.examples as f64 / self.categorized as f64).ln();
+                let likelihood: f64 = known_tokens
+                    .iter()
+                    .map(|t| counts.log_likelihood(t, vocabulary))
+                    .sum();
+                (*category_id, prior + likelihood)
+            })
+            .collect();
+
+        // Softmax, shifted by the maximum so exp() cannot underflow to zero.
+        let max = scores
+            .iter()
+            .map(|(_, s)| *s)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        let mut probabilities: Vec<(i32, f64)> = scores
+            .into_iter()
+            .map(|(id, s)| (id, (s - max).exp() / total))
+            .collect();
+        probabilities.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        probabilities
+    }
+
+    /// Probability of each tag applying, keeping those above one half, most
+    /// likely first.
+    pub fn tags(&self, known_tokens: &[String]) -> Vec<(i32, f64)> {
+        if known_tokens.is_empty() {
+            return Vec::new();
+        }
+        let vocabulary = self.vocabulary.len();
+        let mut probabilities: Vec<(i32, f64)> = self
+            .tags
+            .iter()
+            .map(|(tag_id, with)| {
+                // Everything not tagged with it, derived from the totals.
+                let mut without = ClassCounts {
+                    examples: self.all.examples - with.examples,
+                    tokens: HashMap::new(),
+                    total_tokens: self.all.total_tokens - with.total_tokens,
+                };
+                for token in known_tokens {
+                    let count = self.all.tokens.get(token).copied().unwrap_or(0)
+                        - with.tokens.get(token).copied().unwrap_or(0);
+                    without.tokens.insert(token.clone(), count);
+                }
+                let prior = ((with.examples + 1) as f64 / (without.examples + 1) as f64).ln();
+                let log_odds: f64 = prior
+                    + known_tokens
+                        .iter()
+                        .map(|t| {
+                            with.log_likelihood(t, vocabulary)
+                                - without.log_likelihood(t, vocabulary)
+                        })
+                        .sum::<f64>();
+                (*tag_id, 1.0 / (1.0 + (-log_odds).exp()))
+            })
+            .filter(|(_, p)| *p > 0.5)
+            .collect();
+        probabilities.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        probabilities
+    }
+}
+
+/// Builds the training set from the household's history: one example per
+/// transaction, labelled with its product's category and its tags, plus one
+/// per product that no transaction uses yet.
+pub fn load_training_examples(
+    conn: &mut PgConnection,
+    household_id: i32,
+) -> QueryResult<Vec<TrainingExample>> {
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+
+    let rows = tx::transactions
+        .inner_join(pr::products.on(pr::id.eq(tx::product_id)))
+        .filter(tx::household_id.eq(household_id))
+        .select((tx::id, pr::id, pr::name, pr::category_id, tx::description))
+        .load::<(i32, i32, String, Option<i32>, Option<String>)>(conn)?;
+
+    let mut tags_by_transaction: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (transaction_id, tag_id) in tt::transaction_tags
+        .inner_join(tx::transactions.on(tx::id.eq(tt::transaction_id)))
+        .filter(tx::household_id.eq(household_id))
+        .select((tt::transaction_id, tt::tag_id))
+        .load::<(i32, i32)>(conn)?
+    {
+        tags_by_transaction
+            .entry(transaction_id)
+            .or_default()
+            .push(tag_id);
+    }
+
+    let mut used_products = HashSet::new();
+    let mut examples = Vec::with_capacity(rows.len());
+    for (transaction_id, product_id, name, category_id, description) in rows {
+        used_products.insert(product_id);
+        let mut tokens = tokenize(&name);
+        tokens.extend(tokenize(description.as_deref().unwrap_or("")));
+        examples.push(TrainingExample {
+            tokens,
+            category_id,
+            tag_ids: tags_by_transaction
+                .remove(&transaction_id)
+                .unwrap_or_default(),
+        });
+    }
+
+    for (product_id, name, category_id) in pr::products
+        .filter(pr::household_id.eq(household_id))
+        .select((pr::id, pr::name, pr::category_id))
+        .load::<(i32, String, Option<i32>)>(conn)?
+    {
+        if !used_products.contains(&product_id) {
+            examples.push(TrainingExample {
+                tokens: tokenize(&name),
+                category_id,
+                tag_ids: Vec::new(),
+            });
+        }
+    }
+    Ok(examples)
+}
@@ -1,3 +1,4 @@
+use crate::domain::households::models::ActiveHousehold;
 use crate::schema::tags::dsl;
 use crate::{error_response, AppState, JsonResult};
 use axum::{
@@ -15,7 +16,7 @@ use super::models::{NewTag, Tag, TagPayload};
 #[debug_handler]
 pub async fn create_tag(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
     Json(payload): Json<TagPayload>,
 ) -> JsonResult<Tag> {
     let mut conn = state
@@ -25,7 +26,7 @@ pub async fn create_tag(
 
     let new_tag = NewTag {
         name: payload.name,
-        user_id: logged_in_user_id,
+        household_id: household.id,
     };
 
     let inserted = diesel::insert_into(dsl::tags)
@@ -41,7 +42,7 @@ pub async fn create_tag(
 #[debug_handler]
 pub async fn list_tags(
     State(state): State<Arc<AppState>>,
-    Extension(logged_in_user_id): Extension<i32>,
+    Extension(household): Extension<ActiveHousehold>,
 ) -> JsonResult<Vec<Tag>> {
     let mut conn = state
         .pool
@@ -49,7 +50,7 @@ pub async fn list_tags(
         .map_err(|_| error_response("Failed to fetch connection from pool"))?;
 
     let items = dsl::tags
-        .filter(dsl::user_id.eq(logged_in_user_id))
+        .filter(dsl::household_id.eq(household.id))
         .load::<Tag>(&mut conn)
         .map_err(|e| error_response(format!("Error loading tags: {e}")))?;
 
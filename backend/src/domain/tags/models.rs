@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize}; // Ensure your tags table is defined in sch
 pub struct Tag {
     pub id: i32,
     pub name: String,
-    pub user_id: i32,
+    pub household_id: i32,
 }
 
 /// Data Transfer Object for Tag.
@@ -24,7 +24,7 @@ pub struct TagDto {
 #[diesel(table_name = tags)]
 pub struct NewTag {
     pub name: String,
-    pub user_id: i32,
+    pub household_id: i32,
 }
 
 /// Payload received from the client when creating a tag.
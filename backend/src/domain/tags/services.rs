@@ -0,0 +1,55 @@
+use diesel::prelude::*;
+
+use super::models::{NewTag, Tag, TagReference};
+
+/// Returns the id of the household's tag called `name`, creating it if needed.
+pub fn find_or_create_tag(
+    conn: &mut PgConnection,
+    household_id: i32,
+    name: &str,
+) -> QueryResult<i32> {
+    use crate::schema::tags::dsl as tags_dsl;
+
+    let existing_tag: Option<Tag> = tags_dsl::tags
+        .filter(tags_dsl::name.eq(name))
+        .filter(tags_dsl::household_id.eq(household_id))
+        .first::<Tag>(conn)
+        .optional()?;
+    if let Some(tag) = existing_tag {
+        return Ok(tag.id);
+    }
+
+    let new_tag = NewTag {
+        name: name.to_string(),
+        household_id,
+    };
+    diesel::insert_into(tags_dsl::tags)
+        .values(&new_tag)
+        .returning(tags_dsl::id)
+        .get_result::<i32>(conn)
+}
+
+/// Resolves tag references to ids, creating tags referenced by an unknown name.
+/// A tag id of another household fails with `NotFound`.
+pub fn resolve_tag_references(
+    conn: &mut PgConnection,
+    household_id: i32,
+    tag_refs: &[TagReference],
+) -> QueryResult<Vec<i32>> {
+    use crate::schema::tags::dsl as tags_dsl;
+
+    let mut ids = Vec::new();
+    for tag_ref in tag_refs {
+        match tag_ref {
+            TagReference::Id(tid) => ids.push(
+                tags_dsl::tags
+                    .filter(tags_dsl::id.eq(tid))
+                    .filter(tags_dsl::household_id.eq(household_id))
+                    .select(tags_dsl::id)
+                    .first::<i32>(conn)?,
+            ),
+            TagReference::Name(name) => ids.push(find_or_create_tag(conn, household_id, name)?),
+        }
+    }
+    Ok(ids)
+}
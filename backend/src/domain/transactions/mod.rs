@@ -1,2 +1,4 @@
 pub mod handlers;
 pub mod models;
+pub mod quick_add;
+pub mod services;
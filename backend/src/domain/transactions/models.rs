@@ -1,19 +1,24 @@
+use crate::domain::price_alerts::models::PriceAlertDto;
 use crate::domain::product_prices::models::ProductPriceDto; // From product_prices module.
 use crate::domain::products::models::Product; // Assuming Product lives here.
 use crate::domain::tags::models::TagDto;
 use crate::domain::tags::models::TagReference;
 use crate::schema::transactions;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::sql_types::Text;
 use diesel::{AsExpression, FromSqlRow, Insertable, Queryable};
 use serde::{Deserialize, Serialize}; // Assuming tags are defined in a shared models file.
 
 // Transaction type enum.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, AsExpression, FromSqlRow, Serialize, Deserialize,
+)]
 #[diesel(sql_type = Text)]
 pub enum TransactionType {
     Expense,
     Income,
+    /// Money moving between household members, such as a settlement.
+    Transfer,
 }
 
 // Implement ToSql and FromSql for TransactionType.
@@ -26,6 +31,7 @@ impl ToSql<Text, Pg> for TransactionType {
         match self {
             TransactionType::Expense => out.write_all(b"expense")?,
             TransactionType::Income => out.write_all(b"income")?,
+            TransactionType::Transfer => out.write_all(b"transfer")?,
         }
         Ok(IsNull::No)
     }
@@ -39,6 +45,7 @@ impl diesel::deserialize::FromSql<Text, Pg> for TransactionType {
         match s.as_str() {
             "expense" => Ok(TransactionType::Expense),
             "income" => Ok(TransactionType::Income),
+            "transfer" => Ok(TransactionType::Transfer),
             _ => Err(format!("Invalid transaction_type: {}", s).into()),
         }
     }
@@ -49,24 +56,40 @@ impl diesel::deserialize::FromSql<Text, Pg> for TransactionType {
 #[diesel(table_name = transactions)]
 pub struct Transaction {
     pub id: i32,
-    pub user_id: i32,
+    /// The member who entered the transaction; `None` once they deleted
+    /// their account.
+    pub user_id: Option<i32>,
     pub product_id: i32,
     pub product_price_id: i32,
     pub transaction_type: TransactionType,
     pub description: Option<String>,
     pub date: NaiveDateTime,
+    /// The bank's id for this transaction, set by statement imports.
+    pub external_id: Option<String>,
+    /// Bank account the transaction was booked on, if known.
+    pub account: Option<String>,
+    /// Value date from the bank statement; `date` is the booking date.
+    pub value_date: Option<NaiveDate>,
+    /// Hash identifying the statement line this was imported from.
+    pub import_hash: Option<String>,
+    pub household_id: i32,
 }
 
 /// Used for inserting a new transaction.
 #[derive(Insertable, Deserialize)]
 #[diesel(table_name = transactions)]
 pub struct NewTransaction {
-    pub user_id: i32,
+    pub household_id: i32,
+    pub user_id: Option<i32>,
     pub product_id: i32,
     pub product_price_id: i32,
     pub transaction_type: TransactionType,
     pub description: Option<String>,
     pub date: NaiveDateTime,
+    pub external_id: Option<String>,
+    pub account: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    pub import_hash: Option<String>,
 }
 
 /// The payload that the client sends when creating a transaction.
@@ -80,6 +103,14 @@ pub struct TransactionPayload {
     pub description: Option<String>,
     pub date: NaiveDateTime,
     pub tags: Option<Vec<TagReference>>, // tag references (either id or name)
+    #[serde(default)]
+    pub external_id: Option<String>, // the bank's id, if the transaction came from a statement
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(default)]
+    pub value_date: Option<NaiveDate>,
+    #[serde(skip)]
+    pub import_hash: Option<String>, // set by statement imports only
 }
 
 /// The response after creating a transaction.
@@ -89,13 +120,30 @@ pub struct CreateTransactionResponse {
     pub product: Product,
     pub product_price: ProductPriceDto,
     pub tags: Vec<TagDto>,
+    /// Set when a newly created price deviates enough from the rolling median.
+    pub price_alert: Option<PriceAlertDto>,
+}
+
+/// Query parameters shared by the transaction list and the exports.
+#[derive(Deserialize, Default, Debug)]
+pub struct TransactionFilter {
+    /// First day to include.
+    pub from: Option<NaiveDate>,
+    /// Last day to include.
+    pub to: Option<NaiveDate>,
+    pub transaction_type: Option<TransactionType>,
+    pub product_id: Option<i32>,
+    /// Only transactions whose product is directly in this category.
+    pub category_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub account: Option<String>,
 }
 
 /// DTO for listing transactions.
 #[derive(Serialize)]
 pub struct TransactionDto {
     pub id: i32,
-    pub user_id: i32,
+    pub user_id: Option<i32>,
     pub product_id: i32,
     pub product_price_id: i32,
     pub transaction_type: TransactionType,
@@ -103,3 +151,36 @@ pub struct TransactionDto {
     pub date: NaiveDateTime,
     pub tags: Vec<i32>, // List of tag IDs.
 }
+
+/// The payload for POST /transactions/quick.
+#[derive(Deserialize)]
+pub struct QuickAddPayload {
+    /// A one-line entry such as `coffee 3.50 @starbucks #work yesterday`.
+    pub text: String,
+    /// Only parse and return the result without creating anything.
+    #[serde(default)]
+    pub preview: bool,
+    /// The day relative dates count from; defaults to today in UTC.
+    pub today: Option<NaiveDate>,
+}
+
+/// What a quick-add line was read as.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct QuickEntry {
+    pub product_name: String,
+    /// In dollars, always positive; the type carries the sign.
+    pub amount: f64,
+    pub transaction_type: TransactionType,
+    /// Stored as the description.
+    pub merchant: Option<String>,
+    pub tags: Vec<String>,
+    pub date: NaiveDate,
+}
+
+/// The response of POST /transactions/quick.
+#[derive(Serialize)]
+pub struct QuickAddResponse {
+    pub parsed: QuickEntry,
+    /// `None` in preview mode.
+    pub created: Option<CreateTransactionResponse>,
+}
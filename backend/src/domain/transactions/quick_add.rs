@@ -0,0 +1,177 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+use super::models::{QuickEntry, TransactionType};
+
+/// Parses a one-line entry such as `coffee 3.50 @starbucks #work yesterday`.
+///
+/// Words are read as follows; whatever is left over names the product:
+/// - the amount, optionally signed and with a currency symbol; `+` makes it
+///   income, anything else an expense
+/// - `@merchant` (underscores become spaces) and `#tag`
+/// - a date: `today`, `yesterday`, `tomorrow`, a weekday (optionally after
+///   `last`), `N days ago`, an ordinal like `1st` or an ISO date
+///
+/// Relative dates count back from `today`. Weekdays and ordinals mean the
+/// most recent such day, including today.
+pub fn parse_quick_add(text: &str, today: NaiveDate) -> Result<QuickEntry, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut product_words = Vec::new();
+    let mut amount = None;
+    let mut merchant = None;
+    let mut tags = Vec::new();
+    let mut date = None;
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let lower = word.to_lowercase();
+        let next = |offset: usize| words.get(i + offset).map(|w| w.to_lowercase());
+
+        if let Some(tag) = word.strip_prefix('#') {
+            if tag.is_empty() {
+                return Err("Empty tag after '#'".to_string());
+            }
+            tags.push(tag.to_string());
+        } else if let Some(name) = word.strip_prefix('@') {
+            if name.is_empty() {
+                return Err("Empty merchant after '@'".to_string());
+            }
+            if merchant.replace(name.replace('_', " ")).is_some() {
+                return Err("More than one merchant given".to_string());
+            }
+        } else if let Some(day) = relative_day(&lower, today) {
+            set_date(&mut date, day, word)?;
+        } else if let Some(day) = next(1)
+            .filter(|_| lower == "last")
+            .as_deref()
+            .and_then(weekday)
+        {
+            set_date(&mut date, most_recent(day, today), word)?;
+            i += 1;
+        } else if let Some(day) = weekday(&lower) {
+            set_date(&mut date, most_recent(day, today), word)?;
+        } else if let (Ok(n), Some("day" | "days"), Some("ago")) =
+            (lower.parse::<u64>(), next(1).as_deref(), next(2).as_deref())
+        {
+            let day = today
+                .checked_sub_days(Days::new(n))
+                .ok_or_else(|| format!("'{n} days ago' is too far back"))?;
+            set_date(&mut date, day, word)?;
+            i += 2;
+        } else if let Some(day_of_month) = ordinal(&lower) {
+            set_date(&mut date, day_in_recent_month(day_of_month, today)?, word)?;
+        } else if let Ok(day) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+            set_date(&mut date, day, word)?;
+        } else if let Some(value) = parse_signed_amount(word) {
+            if amount.replace(value).is_some() {
+                return Err(format!("More than one amount given (at '{word}')"));
+            }
+        } else {
+            product_words.push(word);
+        }
+        i += 1;
+    }
+
+    let (cents, transaction_type) = amount.ok_or("No amount found")?;
+    let product_name = if product_words.is_empty() {
+        merchant.clone().ok_or("No product or merchant found")?
+    } else {
+        product_words.join(" ")
+    };
+    Ok(QuickEntry {
+        product_name,
+        amount: cents as f64 / 100.0,
+        transaction_type,
+        merchant,
+        tags,
+        date: date.unwrap_or(today),
+    })
+}
+
+fn set_date(slot: &mut Option<NaiveDate>, value: NaiveDate, word: &str) -> Result<(), String> {
+    if slot.replace(value).is_some() {
+        return Err(format!("More than one date given (at '{word}')"));
+    }
+    Ok(())
+}
+
+fn relative_day(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        "tomorrow" => today.succ_opt(),
+        _ => None,
+    }
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn most_recent(day: Weekday, today: NaiveDate) -> NaiveDate {
+    let back = (today.weekday().num_days_from_monday() + 7 - day.num_days_from_monday()) % 7;
+    today - Days::new(back as u64)
+}
+
+/// `1st`, `2nd`, `3rd`, `4th` … `31st`.
+fn ordinal(word: &str) -> Option<u32> {
+    let digits = word
+        .strip_suffix("st")
+        .or_else(|| word.strip_suffix("nd"))
+        .or_else(|| word.strip_suffix("rd"))
+        .or_else(|| word.strip_suffix("th"))?;
+    let day = digits.parse::<u32>().ok()?;
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    (word == format!("{day}{suffix}") && (1..=31).contains(&day)).then_some(day)
+}
+
+/// The most recent date on or before `today` that falls on `day` of a month.
+fn day_in_recent_month(day: u32, today: NaiveDate) -> Result<NaiveDate, String> {
+    let this_month = today.with_day(1).unwrap();
+    (0..12)
+        .filter_map(|back| this_month.checked_sub_months(Months::new(back)))
+        .filter_map(|month| month.with_day(day))
+        .find(|date| *date <= today)
+        .ok_or_else(|| format!("No recent month has a day {day}"))
+}
+
+/// Reads `3.50`, `+2500`, `-12,99`, `$4` or `4€` as cents and a type.
+fn parse_signed_amount(word: &str) -> Option<(i32, TransactionType)> {
+    let (transaction_type, rest) = match word.strip_prefix('+') {
+        Some(rest) => (TransactionType::Income, rest),
+        None => (
+            TransactionType::Expense,
+            word.strip_prefix('-').unwrap_or(word),
+        ),
+    };
+    let rest = rest
+        .trim_start_matches(['$', '€', '£'])
+        .trim_end_matches(['$', '€', '£']);
+    let normalized = rest.replacen(',', ".", 1);
+    let (whole, fraction) = normalized.split_once('.').unwrap_or((&normalized, ""));
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !all_digits(whole) || fraction.len() > 2 || !all_digits(fraction) {
+        return None;
+    }
+    let cents = whole
+        .parse::<i32>()
+        .ok()?
+        .checked_mul(100)?
+        .checked_add(format!("{fraction:0<2}").parse::<i32>().ok()?)?;
+    (cents > 0).then_some((cents, transaction_type))
+}
@@ -0,0 +1,224 @@
+use chrono::NaiveTime;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+
+use crate::domain::households::models::ActiveHousehold;
+use crate::domain::price_alerts::models::PriceAlertDto;
+use crate::domain::price_alerts::services::detect_price_change;
+use crate::domain::product_prices::models::{NewProductPrice, ProductPrice, ProductPriceDto};
+use crate::domain::products::models::Product;
+use crate::domain::products::services::find_or_create_product;
+use crate::domain::rules::services::apply_rules_to_new_transaction;
+use crate::domain::tags::models::{Tag, TagDto};
+use crate::domain::tags::services::resolve_tag_references;
+
+use super::models::{
+    CreateTransactionResponse, NewTransaction, Transaction, TransactionFilter, TransactionPayload,
+    TransactionType,
+};
+
+/// Creates a transaction from `payload`, resolving or creating its product,
+/// price and tags along the way.
+///
+/// This is the single write path for transactions: the HTTP handler and the
+/// importers all go through it. Must be called inside a Diesel transaction.
+///
+/// A `product_name` resolves to the household's product of that (trimmed)
+/// name before a new one is created, and a `price` is rounded to the nearest
+/// cent and reuses the product's price row with the same amount and date, so
+/// repeating a purchase does not pile up duplicate products and prices.
+pub fn insert_transaction(
+    txn_conn: &mut PgConnection,
+    household: ActiveHousehold,
+    payload: &TransactionPayload,
+) -> QueryResult<CreateTransactionResponse> {
+    use crate::schema::product_prices::dsl as pp;
+    use crate::schema::products::dsl as pr;
+    use crate::schema::tags::dsl as tags_dsl;
+    use crate::schema::transaction_tags::dsl as tt_dsl;
+    use crate::schema::transactions::dsl as tx;
+
+    // 1) Determine final product ID. Products and prices given by id must
+    // belong to the household, or the lookup fails with `NotFound`.
+    let final_product_id = match payload.product_id {
+        Some(pid) => pr::products
+            .filter(pr::id.eq(pid))
+            .filter(pr::household_id.eq(household.id))
+            .select(pr::id)
+            .first::<i32>(txn_conn)?,
+        None => {
+            let name = payload
+                .product_name
+                .as_ref()
+                .ok_or(DieselError::RollbackTransaction)?
+                .trim();
+            if name.is_empty() {
+                return Err(DieselError::RollbackTransaction);
+            }
+            find_or_create_product(txn_conn, household.id, name)?
+        }
+    };
+
+    // 2) Determine final product price ID, reusing an identical price row.
+    let mut created_new_price = false;
+    let final_price_id = if let Some(pp_id) = payload.product_price_id {
+        pp::product_prices
+            .filter(pp::id.eq(pp_id))
+            .filter(pp::product_id.eq(final_product_id))
+            .select(pp::id)
+            .first::<i32>(txn_conn)?
+    } else {
+        let cents = (payload.price.unwrap_or(0.0) * 100.0).round() as i32;
+        let existing = pp::product_prices
+            .filter(pp::product_id.eq(final_product_id))
+            .filter(pp::price.eq(cents))
+            .filter(pp::created_at.eq(payload.date))
+            .select(pp::id)
+            .first::<i32>(txn_conn)
+            .optional()?;
+        match existing {
+            Some(id) => id,
+            None => {
+                created_new_price = true;
+                let new_price = NewProductPrice {
+                    product_id: final_product_id,
+                    price: cents,
+                    created_at: payload.date,
+                };
+                diesel::insert_into(pp::product_prices)
+                    .values(&new_price)
+                    .returning(pp::id)
+                    .get_result::<i32>(txn_conn)?
+            }
+        }
+    };
+
+    // 3) Insert the transaction.
+    let new_tx = NewTransaction {
+        household_id: household.id,
+        user_id: Some(household.user_id),
+        product_id: final_product_id,
+        product_price_id: final_price_id,
+        transaction_type: payload.transaction_type,
+        description: payload.description.clone(),
+        date: payload.date,
+        external_id: payload.external_id.clone(),
+        account: payload.account.clone(),
+        value_date: payload.value_date,
+        import_hash: payload.import_hash.clone(),
+    };
+    let inserted_tx = diesel::insert_into(tx::transactions)
+        .values(&new_tx)
+        .get_result::<Transaction>(txn_conn)?;
+    let inserted_tx = apply_rules_to_new_transaction(txn_conn, household.id, inserted_tx)?;
+
+    // 4) Fetch the product.
+    let fetched_product = pr::products
+        .filter(pr::id.eq(final_product_id))
+        .first::<Product>(txn_conn)?;
+
+    // 5) Fetch the raw product price.
+    let fetched_price = pp::product_prices
+        .filter(pp::id.eq(final_price_id))
+        .first::<ProductPrice>(txn_conn)?;
+
+    // Only freshly inserted prices are checked against the rolling median;
+    // transfers have no price to watch.
+    let price_alert = if created_new_price && payload.transaction_type != TransactionType::Transfer
+    {
+        detect_price_change(txn_conn, household, &fetched_price)?
+            .map(|alert| PriceAlertDto::from_alert(alert, fetched_product.name.clone()))
+    } else {
+        None
+    };
+
+    let price_dto = ProductPriceDto {
+        id: fetched_price.id,
+        product_id: fetched_price.product_id,
+        price: fetched_price.price as f64 / 100.0,
+        created_at: fetched_price.created_at,
+    };
+
+    // 6) Handle tags.
+    if let Some(tag_refs) = &payload.tags {
+        let tag_ids = resolve_tag_references(txn_conn, household.id, tag_refs)?;
+        for tag_id in tag_ids {
+            diesel::insert_into(tt_dsl::transaction_tags)
+                .values((
+                    tt_dsl::transaction_id.eq(inserted_tx.id),
+                    tt_dsl::tag_id.eq(tag_id),
+                ))
+                .on_conflict_do_nothing()
+                .execute(txn_conn)?;
+        }
+    }
+
+    // 7) Fetch associated tags.
+    let associated_tags = tt_dsl::transaction_tags
+        .inner_join(tags_dsl::tags.on(tt_dsl::tag_id.eq(tags_dsl::id)))
+        .filter(tt_dsl::transaction_id.eq(inserted_tx.id))
+        .select(tags_dsl::tags::all_columns())
+        .load::<Tag>(txn_conn)?;
+
+    let response_tags = associated_tags
+        .into_iter()
+        .map(|tag| TagDto {
+            id: tag.id,
+            name: tag.name,
+        })
+        .collect();
+
+    Ok(CreateTransactionResponse {
+        transaction: inserted_tx,
+        product: fetched_product,
+        product_price: price_dto,
+        tags: response_tags,
+        price_alert,
+    })
+}
+
+/// The user's transactions narrowed down by `filter`, ordered by id.
+pub fn filter_transactions(
+    household_id: i32,
+    filter: &TransactionFilter,
+) -> crate::schema::transactions::BoxedQuery<'_, Pg> {
+    use crate::schema::products::dsl as pr;
+    use crate::schema::transaction_tags::dsl as tt;
+    use crate::schema::transactions::dsl as tx;
+
+    let mut query = tx::transactions
+        .filter(tx::household_id.eq(household_id))
+        .order(tx::id.asc())
+        .into_boxed();
+    if let Some(from) = filter.from {
+        query = query.filter(tx::date.ge(from.and_time(NaiveTime::MIN)));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(tx::date.lt(to.succ_opt().unwrap_or(to).and_time(NaiveTime::MIN)));
+    }
+    if let Some(kind) = filter.transaction_type {
+        query = query.filter(tx::transaction_type.eq(kind));
+    }
+    if let Some(pid) = filter.product_id {
+        query = query.filter(tx::product_id.eq(pid));
+    }
+    if let Some(cid) = filter.category_id {
+        query = query.filter(
+            tx::product_id.eq_any(pr::products.filter(pr::category_id.eq(cid)).select(pr::id)),
+        );
+    }
+    if let Some(tid) = filter.tag_id {
+        query = query.filter(
+            tx::id.eq_any(
+                tt::transaction_tags
+                    .filter(tt::tag_id.eq(tid))
+                    .select(tt::transaction_id),
+            ),
+        );
+    }
+    if let Some(account) = &filter.account {
+        query = query.filter(tx::account.eq(account));
+    }
+    query
+}
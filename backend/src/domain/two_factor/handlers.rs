@@ -0,0 +1,243 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use backend::ErrorResponse;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use std::sync::Arc;
+
+use crate::{
+    auth::CurrentSession,
+    domain::{
+        sessions::services::{revoke_other_sessions, start_session},
+        users::{
+            handlers::{verify_password, MessageResponse},
+            models::User,
+            throttle::{
+                account_key, clear_failures, locked_for, lockout_response, record_failure,
+                ACCOUNT_FREE_FAILURES,
+            },
+        },
+    },
+    error_response, schema, AppState, JsonResult,
+};
+
+use super::models::{
+    CodeRequest, DisableRequest, EnrollRequest, EnrollResponse, RecoveryCodesResponse,
+    TwoFactorLoginRequest,
+};
+use super::services::{
+    check_second_factor, check_totp_code, claim_challenge_attempt, delete_challenge,
+    disable_two_factor, enable_two_factor, generate_secret, load_credential,
+    replace_recovery_codes, save_pending_secret, totp_for,
+};
+
+fn load_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<User> {
+    use schema::users::dsl as u;
+
+    u::users.filter(u::id.eq(user_id)).first::<User>(conn)
+}
+
+/// POST /users/me/2fa/enroll
+/// Generates a TOTP secret. 2FA is only switched on once a code from it was
+/// confirmed with POST /users/me/2fa/verify.
+#[debug_handler]
+pub async fn enroll_two_factor(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<EnrollRequest>,
+) -> JsonResult<EnrollResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = verify_password(&mut conn, logged_in_user_id, &payload.password)?;
+    let existing = load_credential(&mut conn, user.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?;
+    if existing.is_some_and(|c| c.enabled_at.is_some()) {
+        return Err(error_response(
+            "Two-factor authentication is already enabled",
+        ));
+    }
+
+    let secret = generate_secret();
+    save_pending_secret(&mut conn, user.id, &secret)
+        .map_err(|e| error_response(format!("Failed to save two-factor secret: {e}")))?;
+
+    Ok(Json(EnrollResponse {
+        otpauth_uri: totp_for(&secret, &user.email).get_url(),
+        secret,
+    }))
+}
+
+/// POST /users/me/2fa/verify
+/// Confirms the enrollment with a code, switches 2FA on and signs out every
+/// other session.
+#[debug_handler]
+pub async fn verify_two_factor(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+    Json(payload): Json<CodeRequest>,
+) -> JsonResult<RecoveryCodesResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = load_user(&mut conn, logged_in_user_id)
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+    let credential = load_credential(&mut conn, user.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?
+        .ok_or_else(|| error_response("Start the enrollment first"))?;
+    if credential.enabled_at.is_some() {
+        return Err(error_response(
+            "Two-factor authentication is already enabled",
+        ));
+    }
+
+    let recovery_codes = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            if !check_totp_code(txn_conn, &credential, &user.email, &payload.code)? {
+                return Ok(None);
+            }
+            let codes = enable_two_factor(txn_conn, user.id)?;
+            revoke_other_sessions(txn_conn, user.id, Some(session_id))?;
+            Ok(Some(codes))
+        })
+        .map_err(|e| error_response(format!("Failed to enable two-factor authentication: {e}")))?
+        .ok_or_else(|| error_response("Invalid two-factor code"))?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// POST /users/me/2fa/recovery-codes
+/// Replaces all recovery codes, e.g. after some were used up.
+#[debug_handler]
+pub async fn regenerate_recovery_codes(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<CodeRequest>,
+) -> JsonResult<RecoveryCodesResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = load_user(&mut conn, logged_in_user_id)
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+    let credential = load_credential(&mut conn, user.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?
+        .filter(|c| c.enabled_at.is_some())
+        .ok_or_else(|| error_response("Two-factor authentication is not enabled"))?;
+
+    let recovery_codes = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            if !check_totp_code(txn_conn, &credential, &user.email, &payload.code)? {
+                return Ok(None);
+            }
+            replace_recovery_codes(txn_conn, user.id).map(Some)
+        })
+        .map_err(|e| error_response(format!("Failed to replace recovery codes: {e}")))?
+        .ok_or_else(|| error_response("Invalid two-factor code"))?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// DELETE /users/me/2fa
+/// Switches 2FA off; takes the password and a code or recovery code.
+#[debug_handler]
+pub async fn disable_two_factor_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<DisableRequest>,
+) -> JsonResult<MessageResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = verify_password(&mut conn, logged_in_user_id, &payload.password)?;
+    let credential = load_credential(&mut conn, user.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?
+        .filter(|c| c.enabled_at.is_some())
+        .ok_or_else(|| error_response("Two-factor authentication is not enabled"))?;
+
+    let disabled = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            if !check_second_factor(txn_conn, &credential, &user.email, &payload.code)? {
+                return Ok(false);
+            }
+            disable_two_factor(txn_conn, user.id)?;
+            Ok(true)
+        })
+        .map_err(|e| error_response(format!("Failed to disable two-factor authentication: {e}")))?;
+    if !disabled {
+        return Err(error_response("Invalid two-factor code"));
+    }
+
+    Ok(Json(MessageResponse {
+        message: "Two-factor authentication disabled".to_string(),
+    }))
+}
+
+/// POST /login/2fa
+/// Second login step: trades the challenge from POST /login and a code for a
+/// session. Wrong codes count towards the account's login lock like wrong
+/// passwords, so opening new challenges does not buy more guesses.
+#[debug_handler]
+pub async fn login_two_factor(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let challenge = claim_challenge_attempt(&mut conn, &payload.challenge_token)
+        .map_err(|e| error_response(format!("Error querying login challenge: {e}")))?
+        .ok_or_else(|| error_response("Invalid or expired login challenge"))?;
+    let user = load_user(&mut conn, challenge.user_id)
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+    let credential = load_credential(&mut conn, user.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?
+        .ok_or_else(|| error_response("Invalid or expired login challenge"))?;
+
+    let account = account_key(&user.email);
+    let locked = locked_for(&mut conn, std::slice::from_ref(&account))
+        .map_err(|e| error_response(format!("Error checking login attempts: {e}")))?;
+    if let Some(seconds) = locked {
+        return Ok(lockout_response(seconds));
+    }
+
+    let tokens = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            if !check_second_factor(txn_conn, &credential, &user.email, &payload.code)? {
+                return Ok(None);
+            }
+            delete_challenge(txn_conn, challenge.id)?;
+            start_session(
+                txn_conn,
+                &state.jwt,
+                user.id,
+                challenge.device_label.clone(),
+            )
+            .map(Some)
+        })
+        .map_err(|e| error_response(format!("Failed to start session: {e}")))?;
+    let Some(tokens) = tokens else {
+        record_failure(&mut conn, &account, ACCOUNT_FREE_FAILURES)
+            .map_err(|e| error_response(format!("Error recording login attempt: {e}")))?;
+        return Err(error_response("Invalid two-factor code"));
+    };
+    clear_failures(&mut conn, &account)
+        .map_err(|e| error_response(format!("Error recording login attempt: {e}")))?;
+
+    Ok(Json(tokens).into_response())
+}
@@ -0,0 +1,108 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{login_challenges, recovery_codes, totp_credentials};
+
+/// A TOTP secret from `totp_credentials`.
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = totp_credentials)]
+pub struct TotpCredential {
+    pub user_id: i32,
+    /// Base32, as shown to the user during enrollment.
+    pub secret: String,
+    /// `None` until the enrollment was confirmed with a code.
+    pub enabled_at: Option<NaiveDateTime>,
+    /// The last time step a code was accepted for.
+    pub last_used_step: Option<i64>,
+}
+
+/// Used when starting an enrollment.
+#[derive(Insertable)]
+#[diesel(table_name = totp_credentials)]
+pub struct NewTotpCredential {
+    pub user_id: i32,
+    pub secret: String,
+}
+
+/// Used when inserting recovery codes.
+#[derive(Insertable)]
+#[diesel(table_name = recovery_codes)]
+pub struct NewRecoveryCode {
+    pub user_id: i32,
+    pub code_hash: String,
+}
+
+/// A pending second login step from `login_challenges`.
+#[derive(Selectable, Queryable, Debug)]
+#[diesel(table_name = login_challenges)]
+pub struct LoginChallenge {
+    pub id: i32,
+    pub user_id: i32,
+    pub device_label: Option<String>,
+}
+
+/// Used when inserting a login challenge.
+#[derive(Insertable)]
+#[diesel(table_name = login_challenges)]
+pub struct NewLoginChallenge {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub device_label: Option<String>,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Body of POST /users/me/2fa/enroll; the password confirms the change.
+#[derive(Deserialize)]
+pub struct EnrollRequest {
+    pub password: String,
+}
+
+/// Returned by POST /users/me/2fa/enroll.
+#[derive(Serialize)]
+pub struct EnrollResponse {
+    /// Base32 secret for typing into an authenticator app.
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code.
+    pub otpauth_uri: String,
+}
+
+/// Body of POST /users/me/2fa/verify and POST /users/me/2fa/recovery-codes.
+#[derive(Deserialize)]
+pub struct CodeRequest {
+    /// A code from the authenticator app.
+    pub code: String,
+}
+
+/// Returned when 2FA gets enabled or the recovery codes are replaced. The
+/// codes are shown only this once.
+#[derive(Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Body of DELETE /users/me/2fa.
+#[derive(Deserialize)]
+pub struct DisableRequest {
+    pub password: String,
+    /// A code from the authenticator app or an unused recovery code.
+    pub code: String,
+}
+
+/// Returned by POST /login instead of tokens when the account has 2FA.
+#[derive(Serialize, Deserialize)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    /// Passed to POST /login/2fa together with a code.
+    pub challenge_token: String,
+    /// Seconds until `challenge_token` expires.
+    pub expires_in: i64,
+}
+
+/// Body of POST /login/2fa.
+#[derive(Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    /// A code from the authenticator app or an unused recovery code.
+    pub code: String,
+}
@@ -0,0 +1,294 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use rand::RngCore;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::auth::{hash_token, random_token};
+
+use super::models::{
+    LoginChallenge, NewLoginChallenge, NewRecoveryCode, NewTotpCredential, TotpCredential,
+    TwoFactorChallenge,
+};
+
+/// Name shown for the account in authenticator apps.
+const ISSUER: &str = "Rusty Fin";
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECONDS: u64 = 30;
+
+/// Codes from this many steps before or after the current one are accepted,
+/// to allow for clock drift.
+const TOTP_SKEW_STEPS: i64 = 1;
+
+/// Number of recovery codes handed out at a time.
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// How long the second login step can take.
+pub const CHALLENGE_MINUTES: i64 = 5;
+
+/// Wrong codes allowed per challenge before it stops working.
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// A fresh 160-bit secret, base32 encoded.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    Secret::Raw(bytes.to_vec()).to_encoded().to_string()
+}
+
+/// The TOTP generator for a stored secret. Skew is handled by
+/// `matching_step`, so the generator only ever checks a single step.
+pub fn totp_for(secret: &str, email: &str) -> TOTP {
+    let bytes = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .expect("stored TOTP secrets are valid base32");
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        0,
+        TOTP_STEP_SECONDS,
+        bytes,
+        Some(ISSUER.to_string()),
+        email.replace(':', ""),
+    )
+    .expect("generated TOTP secrets are long enough")
+}
+
+/// The time step `code` was generated for, if it is within the allowed skew
+/// of `unix_time` and later than `last_used_step`.
+pub fn matching_step(
+    totp: &TOTP,
+    code: &str,
+    unix_time: i64,
+    last_used_step: Option<i64>,
+) -> Option<i64> {
+    let current = unix_time / TOTP_STEP_SECONDS as i64;
+    ((current - TOTP_SKEW_STEPS)..=(current + TOTP_SKEW_STEPS))
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| totp.check(code, *step as u64 * TOTP_STEP_SECONDS))
+}
+
+pub fn load_credential(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> QueryResult<Option<TotpCredential>> {
+    use crate::schema::totp_credentials::dsl as t;
+
+    t::totp_credentials
+        .filter(t::user_id.eq(user_id))
+        .select(TotpCredential::as_select())
+        .first(conn)
+        .optional()
+}
+
+/// Whether the user has to give a code when logging in.
+pub fn two_factor_enabled(conn: &mut PgConnection, user_id: i32) -> QueryResult<bool> {
+    Ok(load_credential(conn, user_id)?.is_some_and(|c| c.enabled_at.is_some()))
+}
+
+/// Stores a secret that still needs to be confirmed, replacing an earlier
+/// unconfirmed one.
+pub fn save_pending_secret(conn: &mut PgConnection, user_id: i32, secret: &str) -> QueryResult<()> {
+    use crate::schema::totp_credentials::dsl as t;
+
+    diesel::insert_into(t::totp_credentials)
+        .values(&NewTotpCredential {
+            user_id,
+            secret: secret.to_string(),
+        })
+        .on_conflict(t::user_id)
+        .do_update()
+        .set((
+            t::secret.eq(secret),
+            t::enabled_at.eq(None::<NaiveDateTime>),
+            t::last_used_step.eq(None::<i64>),
+            t::created_at.eq(now()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Records that a code for `step` was used. Returns false if that step or a
+/// later one was used already, i.e. the code is being replayed.
+pub fn record_step(conn: &mut PgConnection, user_id: i32, step: i64) -> QueryResult<bool> {
+    use crate::schema::totp_credentials::dsl as t;
+
+    let updated = diesel::update(
+        t::totp_credentials
+            .filter(t::user_id.eq(user_id))
+            .filter(t::last_used_step.is_null().or(t::last_used_step.lt(step))),
+    )
+    .set(t::last_used_step.eq(step))
+    .execute(conn)?;
+    Ok(updated == 1)
+}
+
+/// Marks the credential as confirmed and hands out recovery codes.
+pub fn enable_two_factor(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<String>> {
+    use crate::schema::totp_credentials::dsl as t;
+
+    diesel::update(t::totp_credentials.filter(t::user_id.eq(user_id)))
+        .set(t::enabled_at.eq(now()))
+        .execute(conn)?;
+    replace_recovery_codes(conn, user_id)
+}
+
+pub fn disable_two_factor(conn: &mut PgConnection, user_id: i32) -> QueryResult<()> {
+    use crate::schema::recovery_codes::dsl as r;
+    use crate::schema::totp_credentials::dsl as t;
+
+    diesel::delete(r::recovery_codes.filter(r::user_id.eq(user_id))).execute(conn)?;
+    diesel::delete(t::totp_credentials.filter(t::user_id.eq(user_id))).execute(conn)?;
+    Ok(())
+}
+
+/// Recovery codes are compared without dashes, spaces or case.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Throws away the user's recovery codes and returns new ones, formatted as
+/// `xxxx-xxxx-xxxx-xxxx`. Only their hashes are stored.
+pub fn replace_recovery_codes(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<String>> {
+    use crate::schema::recovery_codes::dsl as r;
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let hex = random_token();
+            (0..4)
+                .map(|i| &hex[i * 4..i * 4 + 4])
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect();
+    let rows: Vec<NewRecoveryCode> = codes
+        .iter()
+        .map(|code| NewRecoveryCode {
+            user_id,
+            code_hash: hash_token(&normalize_recovery_code(code)),
+        })
+        .collect();
+
+    diesel::delete(r::recovery_codes.filter(r::user_id.eq(user_id))).execute(conn)?;
+    diesel::insert_into(r::recovery_codes)
+        .values(&rows)
+        .execute(conn)?;
+    Ok(codes)
+}
+
+/// Uses up one of the user's recovery codes. Returns false if it is unknown
+/// or was used before.
+pub fn redeem_recovery_code(
+    conn: &mut PgConnection,
+    user_id: i32,
+    code: &str,
+) -> QueryResult<bool> {
+    use crate::schema::recovery_codes::dsl as r;
+
+    let updated = diesel::update(
+        r::recovery_codes
+            .filter(r::user_id.eq(user_id))
+            .filter(r::code_hash.eq(hash_token(&normalize_recovery_code(code))))
+            .filter(r::used_at.is_null()),
+    )
+    .set(r::used_at.eq(now()))
+    .execute(conn)?;
+    Ok(updated == 1)
+}
+
+/// Checks an authenticator code against the credential and records its step.
+pub fn check_totp_code(
+    conn: &mut PgConnection,
+    credential: &TotpCredential,
+    email: &str,
+    code: &str,
+) -> QueryResult<bool> {
+    let totp = totp_for(&credential.secret, email);
+    let step = matching_step(
+        &totp,
+        code.trim(),
+        Utc::now().timestamp(),
+        credential.last_used_step,
+    );
+    match step {
+        Some(step) => record_step(conn, credential.user_id, step),
+        None => Ok(false),
+    }
+}
+
+/// Checks a second factor: a six digit code from the authenticator app, or
+/// else one of the recovery codes.
+pub fn check_second_factor(
+    conn: &mut PgConnection,
+    credential: &TotpCredential,
+    email: &str,
+    code: &str,
+) -> QueryResult<bool> {
+    let code = code.trim();
+    if code.len() == TOTP_DIGITS && code.chars().all(|c| c.is_ascii_digit()) {
+        check_totp_code(conn, credential, email, code)
+    } else {
+        redeem_recovery_code(conn, credential.user_id, code)
+    }
+}
+
+/// Starts the second login step for a user whose password was correct.
+pub fn create_challenge(
+    conn: &mut PgConnection,
+    user_id: i32,
+    device_label: Option<String>,
+) -> QueryResult<TwoFactorChallenge> {
+    use crate::schema::login_challenges::dsl as l;
+
+    // Challenges nobody finished are of no use to anyone.
+    diesel::delete(l::login_challenges.filter(l::expires_at.lt(now()))).execute(conn)?;
+
+    let token = random_token();
+    diesel::insert_into(l::login_challenges)
+        .values(&NewLoginChallenge {
+            user_id,
+            token_hash: hash_token(&token),
+            device_label,
+            expires_at: now() + Duration::minutes(CHALLENGE_MINUTES),
+        })
+        .execute(conn)?;
+    Ok(TwoFactorChallenge {
+        two_factor_required: true,
+        challenge_token: token,
+        expires_in: CHALLENGE_MINUTES * 60,
+    })
+}
+
+/// Counts an attempt against the challenge and returns it, or `None` if it
+/// is unknown, expired or out of attempts.
+pub fn claim_challenge_attempt(
+    conn: &mut PgConnection,
+    token: &str,
+) -> QueryResult<Option<LoginChallenge>> {
+    use crate::schema::login_challenges::dsl as l;
+
+    diesel::update(
+        l::login_challenges
+            .filter(l::token_hash.eq(hash_token(token)))
+            .filter(l::expires_at.gt(now()))
+            .filter(l::attempts.lt(MAX_CHALLENGE_ATTEMPTS)),
+    )
+    .set(l::attempts.eq(l::attempts + 1))
+    .returning(LoginChallenge::as_returning())
+    .get_result(conn)
+    .optional()
+}
+
+pub fn delete_challenge(conn: &mut PgConnection, challenge_id: i32) -> QueryResult<()> {
+    use crate::schema::login_challenges::dsl as l;
+
+    diesel::delete(l::login_challenges.filter(l::id.eq(challenge_id))).execute(conn)?;
+    Ok(())
+}
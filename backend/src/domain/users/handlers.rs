@@ -1,19 +1,46 @@
-use axum::{debug_handler, extract::State, Json};
+use axum::{
+    debug_handler,
+    extract::{Extension, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use diesel::prelude::*;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use serde::Serialize;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
 use crate::{
-    auth::generate_jwt,
+    auth::CurrentSession,
+    domain::households::services::{
+        create_household, release_owned_households, PERSONAL_HOUSEHOLD_NAME,
+    },
+    domain::sessions::services::{revoke_other_sessions, start_session},
+    domain::splits::services::has_open_debts,
+    domain::two_factor::services::{create_challenge, two_factor_enabled},
     error_response, // Some function in your main or a shared module
+    mailer::Email,
+    rate_limit::ClientIp,
     schema,
     AppState,
     JsonResult,
 };
 
-use super::models::{LoginRequest, NewUser, User};
+use backend::ErrorResponse;
+
+use super::models::{
+    AccountDto, ChangePasswordRequest, DeleteAccountRequest, LoginRequest, LoginResponse, NewUser,
+    PasswordResetConfirm, PasswordResetRequest, User, VerifyEmailRequest,
+};
+use super::services::{
+    create_reset_token, hash_new_password, normalize_email, redeem_reset_token,
+    redeem_verification_token, send_verification_email, set_password_hash, RESET_TOKEN_MINUTES,
+};
+use super::throttle::{
+    account_key, clear_failures, ip_key, locked_for, lockout_response, record_failure,
+    ACCOUNT_FREE_FAILURES, IP_FREE_FAILURES,
+};
 
 /// A minimal struct to return after sign-up
 #[derive(Serialize)]
@@ -22,18 +49,39 @@ pub struct PublicUser {
     pub email: String,
 }
 
-/// A minimal struct to return after login
+/// A confirmation without further data
 #[derive(Serialize)]
-pub struct TokenResponse {
-    pub token: String,
+pub struct MessageResponse {
+    pub message: String,
+}
+
+/// Loads the logged-in user and checks `password` against theirs.
+pub fn verify_password(
+    conn: &mut PgConnection,
+    user_id: i32,
+    password: &str,
+) -> Result<User, (StatusCode, Json<ErrorResponse>)> {
+    use schema::users::dsl as u;
+
+    let user = u::users
+        .filter(u::id.eq(user_id))
+        .first::<User>(conn)
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+    if !verify(password, &user.password_hash).unwrap_or(false) {
+        return Err(error_response("Current password is incorrect"));
+    }
+    Ok(user)
 }
 
 /// POST /users
+/// Creates the account and emails a verification token to its address.
 #[debug_handler] // <--- Fixes the "Handler not implemented" error in separate file
 pub async fn sign_up(
     State(state): State<Arc<AppState>>,
     Json(mut new_user): Json<NewUser>,
 ) -> JsonResult<PublicUser> {
+    new_user.email = normalize_email(&new_user.email).map_err(error_response)?;
+
     let mut conn = state
         .pool
         .get()
@@ -44,10 +92,15 @@ pub async fn sign_up(
         hash(&new_user.password_hash, DEFAULT_COST).map_err(|e| error_response(format!("{e}")))?;
     new_user.password_hash = hashed;
 
-    // Insert user
-    let inserted: User = diesel::insert_into(schema::users::table)
-        .values(&new_user)
-        .get_result(&mut conn)
+    // Insert the user along with their personal household
+    let inserted = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            let inserted: User = diesel::insert_into(schema::users::table)
+                .values(&new_user)
+                .get_result(txn_conn)?;
+            create_household(txn_conn, inserted.id, PERSONAL_HOUSEHOLD_NAME)?;
+            Ok(inserted)
+        })
         .map_err(|e| {
             if let DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) = e {
                 error_response("A user with that email already exists")
@@ -56,39 +109,319 @@ pub async fn sign_up(
             }
         })?;
 
+    // The account exists either way; the user can ask for another email.
+    if let Err(e) = send_verification_email(
+        &mut conn,
+        state.mailer.as_ref(),
+        inserted.id,
+        &inserted.email,
+    ) {
+        println!("Failed to send verification email: {e}");
+    }
+
     Ok(Json(PublicUser {
         id: inserted.id,
         email: inserted.email,
     }))
 }
 
+/// Compared against when the email is unknown.
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash("not a real password", DEFAULT_COST).expect("bcrypt hash"));
+
 /// POST /login
+/// Starts a session labelled with `device_label`, or the client's user agent.
+/// With 2FA enabled it returns a challenge for POST /login/2fa instead.
+///
+/// Repeated failures lock the account and the client address for a while,
+/// doubling with every further failure.
 #[debug_handler]
 pub async fn login(
     State(state): State<Arc<AppState>>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> JsonResult<TokenResponse> {
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
     use schema::users::dsl::*;
     let mut conn = state
         .pool
         .get()
         .map_err(|_| error_response("Failed to fetch connection from pool"))?;
 
+    // Accounts are stored under their normalized address.
+    let login_email = payload.email.trim().to_lowercase();
+    let account = account_key(&login_email);
+    let client = ip_key(client_ip);
+
+    let locked = locked_for(&mut conn, &[account.clone(), client.clone()])
+        .map_err(|e| error_response(format!("Error checking login attempts: {e}")))?;
+    if let Some(seconds) = locked {
+        return Ok(lockout_response(seconds));
+    }
+
     let maybe_user = users
-        .filter(email.eq(&payload.email))
+        .filter(email.eq(&login_email))
         .first::<User>(&mut conn)
         .optional()
         .map_err(|e| error_response(format!("Error querying user: {e}")))?;
 
-    let Some(u) = maybe_user else {
+    // Unknown emails are checked against a dummy hash so they take as long
+    // as wrong passwords.
+    let stored_hash = maybe_user
+        .as_ref()
+        .map_or(DUMMY_PASSWORD_HASH.as_str(), |u| u.password_hash.as_str());
+    let matches = verify(&payload.password_hash, stored_hash).unwrap_or(false);
+    let Some(u) = maybe_user.filter(|_| matches) else {
+        record_failure(&mut conn, &account, ACCOUNT_FREE_FAILURES)
+            .and_then(|_| record_failure(&mut conn, &client, IP_FREE_FAILURES))
+            .map_err(|e| error_response(format!("Error recording login attempt: {e}")))?;
         return Err(error_response("Invalid email or password"));
     };
+    clear_failures(&mut conn, &account)
+        .map_err(|e| error_response(format!("Error recording login attempt: {e}")))?;
 
-    let matches = verify(&payload.password_hash, &u.password_hash).unwrap_or(false);
-    if !matches {
-        return Err(error_response("Invalid email or password"));
+    let device_label = payload.device_label.or_else(|| {
+        headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    });
+    let needs_code = two_factor_enabled(&mut conn, u.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?;
+    if needs_code {
+        let challenge = create_challenge(&mut conn, u.id, device_label)
+            .map_err(|e| error_response(format!("Failed to start login challenge: {e}")))?;
+        return Ok(Json(LoginResponse::TwoFactor(challenge)).into_response());
+    }
+
+    let tokens = start_session(&mut conn, &state.jwt, u.id, device_label)
+        .map_err(|e| error_response(format!("Failed to start session: {e}")))?;
+    Ok(Json(LoginResponse::Tokens(tokens)).into_response())
+}
+
+/// PUT /users/me/password
+/// Changes the password and signs out every other session.
+#[debug_handler]
+pub async fn change_password(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> JsonResult<MessageResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    verify_password(&mut conn, logged_in_user_id, &payload.current_password)?;
+    let new_hash = hash_new_password(&payload.new_password).map_err(error_response)?;
+
+    conn.transaction::<_, DieselError, _>(|txn_conn| {
+        set_password_hash(txn_conn, logged_in_user_id, &new_hash)?;
+        revoke_other_sessions(txn_conn, logged_in_user_id, Some(session_id))
+    })
+    .map_err(|e| error_response(format!("Failed to change password: {e}")))?;
+
+    Ok(Json(MessageResponse {
+        message: "Password changed".to_string(),
+    }))
+}
+
+/// POST /password-reset
+/// Emails a reset token if the address belongs to an account. The answer is
+/// the same either way, so it cannot be used to probe for accounts.
+#[debug_handler]
+pub async fn request_password_reset(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PasswordResetRequest>,
+) -> JsonResult<MessageResponse> {
+    use schema::users::dsl as u;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = u::users
+        .filter(u::email.eq(payload.email.trim().to_lowercase()))
+        .first::<User>(&mut conn)
+        .optional()
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+
+    if let Some(user) = user {
+        let token = create_reset_token(&mut conn, user.id)
+            .map_err(|e| error_response(format!("Failed to create reset token: {e}")))?;
+        let email = Email {
+            to: user.email,
+            subject: "Reset your Rusty Fin password".to_string(),
+            body: format!(
+                "Someone asked to reset the password of your Rusty Fin account.\n\n\
+                 Reset token: {token}\n\n\
+                 The token works once and expires in {RESET_TOKEN_MINUTES} minutes. \
+                 If this wasn't you, ignore this email."
+            ),
+        };
+        // A failed delivery must not reveal that the account exists.
+        if let Err(e) = state.mailer.send(&email) {
+            println!("Failed to send password reset email: {e}");
+        }
+    }
+
+    Ok(Json(MessageResponse {
+        message: "If that email belongs to an account, a reset token is on its way".to_string(),
+    }))
+}
+
+/// POST /password-reset/confirm
+/// Sets a new password with a reset token and signs out every session.
+#[debug_handler]
+pub async fn confirm_password_reset(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PasswordResetConfirm>,
+) -> JsonResult<MessageResponse> {
+    let new_hash = hash_new_password(&payload.new_password).map_err(error_response)?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let reset = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            let Some(user_id) = redeem_reset_token(txn_conn, &payload.token)? else {
+                return Ok(false);
+            };
+            set_password_hash(txn_conn, user_id, &new_hash)?;
+            revoke_other_sessions(txn_conn, user_id, None)?;
+            Ok(true)
+        })
+        .map_err(|e| error_response(format!("Failed to reset password: {e}")))?;
+    if !reset {
+        return Err(error_response("Invalid or expired reset token"));
     }
 
-    let token_str = generate_jwt(u.id);
-    Ok(Json(TokenResponse { token: token_str }))
+    Ok(Json(MessageResponse {
+        message: "Password changed".to_string(),
+    }))
+}
+
+/// DELETE /users/me
+/// Deletes the account and the households nobody else belongs to, with all
+/// their data. Shared households it solely owned pass to the member who
+/// joined next; transactions it entered there stay. Refused while the user
+/// still owes or is owed money from split expenses.
+#[debug_handler]
+pub async fn delete_account(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> JsonResult<PublicUser> {
+    use schema::users::dsl as u;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    verify_password(&mut conn, logged_in_user_id, &payload.password)?;
+    let open_debts = has_open_debts(&mut conn, logged_in_user_id)
+        .map_err(|e| error_response(format!("Error loading splits: {e}")))?;
+    if open_debts {
+        return Err(error_response(
+            "Settle up your shared expenses before deleting the account",
+        ));
+    }
+    let deleted = conn
+        .transaction::<_, DieselError, _>(|txn_conn| {
+            release_owned_households(txn_conn, logged_in_user_id)?;
+            diesel::delete(u::users.filter(u::id.eq(logged_in_user_id)))
+                .get_result::<User>(txn_conn)
+        })
+        .map_err(|e| error_response(format!("Failed to delete account: {e}")))?;
+
+    Ok(Json(PublicUser {
+        id: deleted.id,
+        email: deleted.email,
+    }))
+}
+
+/// GET /users/me
+#[debug_handler]
+pub async fn get_account(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+) -> JsonResult<AccountDto> {
+    use schema::users::dsl as u;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = u::users
+        .filter(u::id.eq(logged_in_user_id))
+        .first::<User>(&mut conn)
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+
+    let two_factor_enabled = two_factor_enabled(&mut conn, user.id)
+        .map_err(|e| error_response(format!("Error querying two-factor settings: {e}")))?;
+
+    Ok(Json(AccountDto {
+        id: user.id,
+        email: user.email,
+        email_verified: user.email_verified,
+        two_factor_enabled,
+    }))
+}
+
+/// POST /verify-email
+/// Confirms the email address with the token from the verification email.
+#[debug_handler]
+pub async fn verify_email(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> JsonResult<MessageResponse> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let verified = redeem_verification_token(&mut conn, &payload.token)
+        .map_err(|e| error_response(format!("Failed to verify email: {e}")))?;
+    if verified.is_none() {
+        return Err(error_response("Invalid or expired verification token"));
+    }
+
+    Ok(Json(MessageResponse {
+        message: "Email address verified".to_string(),
+    }))
+}
+
+/// POST /verify-email/resend
+/// Sends a fresh verification token, invalidating the previous one.
+#[debug_handler]
+pub async fn resend_verification(
+    State(state): State<Arc<AppState>>,
+    Extension(logged_in_user_id): Extension<i32>,
+) -> JsonResult<MessageResponse> {
+    use schema::users::dsl as u;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|_| error_response("Failed to fetch connection from pool"))?;
+
+    let user = u::users
+        .filter(u::id.eq(logged_in_user_id))
+        .first::<User>(&mut conn)
+        .map_err(|e| error_response(format!("Error querying user: {e}")))?;
+    if user.email_verified {
+        return Err(error_response("Email address is already verified"));
+    }
+    send_verification_email(&mut conn, state.mailer.as_ref(), user.id, &user.email)
+        .map_err(error_response)?;
+
+    Ok(Json(MessageResponse {
+        message: format!("Verification email sent to {}", user.email),
+    }))
 }
@@ -1,2 +1,4 @@
 pub mod handlers;
 pub mod models;
+pub mod services;
+pub mod throttle;
@@ -1,7 +1,9 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::users;
+use crate::domain::sessions::models::TokenResponse;
+use crate::domain::two_factor::models::TwoFactorChallenge;
+use crate::schema::{email_verification_tokens, password_reset_tokens, users};
 
 /// The main user record, mapped to the `users` table.
 #[derive(Selectable, Queryable, Serialize, Deserialize, Debug)]
@@ -10,6 +12,10 @@ pub struct User {
     pub id: i32,
     pub email: String,
     pub password_hash: String,
+    /// Percentage change from the rolling median that raises a price alert.
+    pub price_alert_threshold: i32,
+    /// Set once the user followed the link in the verification email.
+    pub email_verified: bool,
 }
 
 /// Used when inserting a new user into `users`.
@@ -25,4 +31,76 @@ pub struct NewUser {
 pub struct LoginRequest {
     pub email: String,
     pub password_hash: String,
+    /// Shown in the session list, e.g. "Work laptop".
+    #[serde(default)]
+    pub device_label: Option<String>,
+}
+
+/// Returned by POST /login: tokens, or a challenge if the account has 2FA.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Tokens(TokenResponse),
+    TwoFactor(TwoFactorChallenge),
+}
+
+/// Body of PUT /users/me/password.
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Body of POST /password-reset.
+#[derive(Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+/// Body of POST /password-reset/confirm.
+#[derive(Deserialize)]
+pub struct PasswordResetConfirm {
+    /// The token from the reset email.
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Body of POST /verify-email.
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    /// The token from the verification email.
+    pub token: String,
+}
+
+/// Returned by GET /users/me.
+#[derive(Serialize)]
+pub struct AccountDto {
+    pub id: i32,
+    pub email: String,
+    pub email_verified: bool,
+    pub two_factor_enabled: bool,
+}
+
+/// Body of DELETE /users/me; the password confirms the deletion.
+#[derive(Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+/// Used when inserting a password reset token.
+#[derive(Insertable)]
+#[diesel(table_name = password_reset_tokens)]
+pub struct NewPasswordResetToken {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+/// Used when inserting an email verification token.
+#[derive(Insertable)]
+#[diesel(table_name = email_verification_tokens)]
+pub struct NewEmailVerificationToken {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub expires_at: chrono::NaiveDateTime,
 }
@@ -0,0 +1,199 @@
+use bcrypt::{hash, DEFAULT_COST};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+
+use crate::auth::{hash_token, random_token};
+use crate::mailer::{Email, Mailer};
+
+use super::models::{NewEmailVerificationToken, NewPasswordResetToken};
+
+/// Shortest password accepted when setting a new one.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// How long a password reset token can be used.
+pub const RESET_TOKEN_MINUTES: i64 = 60;
+
+/// Checks and hashes a new password.
+pub fn hash_new_password(password: &str) -> Result<String, String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!(
+            "Password must be at least {MIN_PASSWORD_LENGTH} characters long"
+        ));
+    }
+    hash(password, DEFAULT_COST).map_err(|e| format!("Failed to hash password: {e}"))
+}
+
+pub fn set_password_hash(
+    conn: &mut PgConnection,
+    user_id: i32,
+    password_hash: &str,
+) -> QueryResult<()> {
+    use crate::schema::users::dsl as u;
+
+    diesel::update(u::users.filter(u::id.eq(user_id)))
+        .set(u::password_hash.eq(password_hash))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Issues a reset token for the user, replacing any unused one, and returns
+/// it in the clear for the email. Only its hash is stored.
+pub fn create_reset_token(conn: &mut PgConnection, user_id: i32) -> QueryResult<String> {
+    use crate::schema::password_reset_tokens::dsl as prt;
+
+    let token = random_token();
+    conn.transaction(|txn_conn| {
+        diesel::delete(
+            prt::password_reset_tokens
+                .filter(prt::user_id.eq(user_id))
+                .filter(prt::used_at.is_null()),
+        )
+        .execute(txn_conn)?;
+        diesel::insert_into(prt::password_reset_tokens)
+            .values(&NewPasswordResetToken {
+                user_id,
+                token_hash: hash_token(&token),
+                expires_at: Utc::now().naive_utc() + Duration::minutes(RESET_TOKEN_MINUTES),
+            })
+            .execute(txn_conn)
+    })?;
+    Ok(token)
+}
+
+/// Marks a reset token as used and returns its user, or `None` if the token
+/// is unknown, expired or already used. A single statement, so two
+/// concurrent requests cannot both redeem it.
+pub fn redeem_reset_token(conn: &mut PgConnection, token: &str) -> QueryResult<Option<i32>> {
+    use crate::schema::password_reset_tokens::dsl as prt;
+
+    let now = Utc::now().naive_utc();
+    diesel::update(
+        prt::password_reset_tokens
+            .filter(prt::token_hash.eq(hash_token(token)))
+            .filter(prt::used_at.is_null())
+            .filter(prt::expires_at.gt(now)),
+    )
+    .set(prt::used_at.eq(now))
+    .returning(prt::user_id)
+    .get_result::<i32>(conn)
+    .optional()
+}
+
+/// How long an email verification token can be used.
+pub const VERIFICATION_TOKEN_HOURS: i64 = 48;
+
+/// Characters allowed in the part before the `@`, besides letters and digits.
+const LOCAL_PART_SYMBOLS: &str = ".!#$%&'*+/=?^_`{|}~-";
+
+/// Trims and lowercases an email address and checks its syntax: a dot-atom
+/// local part and a domain of at least two labels with an alphabetic TLD.
+/// Quoted local parts and IP literals are not accepted.
+pub fn normalize_email(raw: &str) -> Result<String, String> {
+    let email = raw.trim().to_lowercase();
+    let invalid = || Err(format!("'{}' is not a valid email address", raw.trim()));
+    if email.len() > 254 {
+        return invalid();
+    }
+    let Some((local, domain)) = email.split_once('@') else {
+        return invalid();
+    };
+
+    let local_ok = !local.is_empty()
+        && local.len() <= 64
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || LOCAL_PART_SYMBOLS.contains(c))
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..");
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let domain_ok = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+
+    if local_ok && domain_ok {
+        Ok(email)
+    } else {
+        invalid()
+    }
+}
+
+/// Issues a verification token for the user, replacing any unused one, and
+/// returns it in the clear for the email.
+pub fn create_verification_token(conn: &mut PgConnection, user_id: i32) -> QueryResult<String> {
+    use crate::schema::email_verification_tokens::dsl as evt;
+
+    let token = random_token();
+    conn.transaction(|txn_conn| {
+        diesel::delete(
+            evt::email_verification_tokens
+                .filter(evt::user_id.eq(user_id))
+                .filter(evt::used_at.is_null()),
+        )
+        .execute(txn_conn)?;
+        diesel::insert_into(evt::email_verification_tokens)
+            .values(&NewEmailVerificationToken {
+                user_id,
+                token_hash: hash_token(&token),
+                expires_at: Utc::now().naive_utc() + Duration::hours(VERIFICATION_TOKEN_HOURS),
+            })
+            .execute(txn_conn)
+    })?;
+    Ok(token)
+}
+
+/// Redeems a verification token and marks its user's email as verified.
+/// Returns the user, or `None` if the token is unknown, expired or used.
+pub fn redeem_verification_token(conn: &mut PgConnection, token: &str) -> QueryResult<Option<i32>> {
+    use crate::schema::email_verification_tokens::dsl as evt;
+    use crate::schema::users::dsl as u;
+
+    let now = Utc::now().naive_utc();
+    conn.transaction(|txn_conn| {
+        let user_id = diesel::update(
+            evt::email_verification_tokens
+                .filter(evt::token_hash.eq(hash_token(token)))
+                .filter(evt::used_at.is_null())
+                .filter(evt::expires_at.gt(now)),
+        )
+        .set(evt::used_at.eq(now))
+        .returning(evt::user_id)
+        .get_result::<i32>(txn_conn)
+        .optional()?;
+        if let Some(user_id) = user_id {
+            diesel::update(u::users.filter(u::id.eq(user_id)))
+                .set(u::email_verified.eq(true))
+                .execute(txn_conn)?;
+        }
+        Ok(user_id)
+    })
+}
+
+/// Creates a verification token and emails it to `email`.
+pub fn send_verification_email(
+    conn: &mut PgConnection,
+    mailer: &dyn Mailer,
+    user_id: i32,
+    email: &str,
+) -> Result<(), String> {
+    let token = create_verification_token(conn, user_id)
+        .map_err(|e| format!("Failed to create verification token: {e}"))?;
+    mailer.send(&Email {
+        to: email.to_string(),
+        subject: "Confirm your Rusty Fin email address".to_string(),
+        body: format!(
+            "Welcome to Rusty Fin! Confirm your email address to unlock exports and backups.\n\n\
+             Verification token: {token}\n\n\
+             The token expires in {VERIFICATION_TOKEN_HOURS} hours."
+        ),
+    })
+}
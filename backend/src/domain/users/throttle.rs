@@ -0,0 +1,105 @@
+use axum::response::Response;
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use std::net::IpAddr;
+
+use crate::rate_limit::{client_network, too_many_requests};
+
+/// Failed logins allowed per account before it gets locked.
+pub const ACCOUNT_FREE_FAILURES: i32 = 5;
+
+/// Failed logins allowed per client address, across all accounts.
+pub const IP_FREE_FAILURES: i32 = 20;
+
+/// The first lock lasts this long; every further failure doubles it.
+const BASE_LOCK_SECONDS: i64 = 30;
+const MAX_LOCK_SECONDS: i64 = 60 * 60;
+
+/// Counters start over after this long without a failure.
+const FORGET_AFTER_HOURS: i64 = 24;
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+pub fn account_key(email: &str) -> String {
+    format!("account:{email}")
+}
+
+pub fn ip_key(ip: IpAddr) -> String {
+    format!("ip:{}", client_network(ip))
+}
+
+/// How long to lock after `failures` failures, once past `free_failures`.
+pub fn lock_seconds(failures: i32, free_failures: i32) -> Option<i64> {
+    let excess = failures - free_failures;
+    (excess >= 0).then(|| {
+        BASE_LOCK_SECONDS
+            .saturating_mul(1 << excess.min(20))
+            .min(MAX_LOCK_SECONDS)
+    })
+}
+
+/// Seconds until the longest of the locks on `keys` ends, if any is locked.
+pub fn locked_for(conn: &mut PgConnection, keys: &[String]) -> QueryResult<Option<i64>> {
+    use crate::schema::login_throttles::dsl as lt;
+
+    let until = lt::login_throttles
+        .filter(lt::key.eq_any(keys))
+        .filter(lt::locked_until.gt(now()))
+        .select(diesel::dsl::max(lt::locked_until))
+        .first::<Option<NaiveDateTime>>(conn)?;
+    Ok(until.map(|until| (until - now()).num_seconds().max(1)))
+}
+
+/// The 429 for a login refused while a lock lasts `seconds` more.
+pub fn lockout_response(seconds: i64) -> Response {
+    too_many_requests(
+        format!("Too many failed login attempts; try again in {seconds} seconds"),
+        seconds as u64,
+    )
+}
+
+/// Counts a failed login against `key`, locking it once it is past
+/// `free_failures`.
+pub fn record_failure(conn: &mut PgConnection, key: &str, free_failures: i32) -> QueryResult<()> {
+    use crate::schema::login_throttles::dsl as lt;
+
+    conn.transaction(|txn_conn| {
+        let previous = lt::login_throttles
+            .filter(lt::key.eq(key))
+            .filter(lt::last_failure_at.gt(now() - Duration::hours(FORGET_AFTER_HOURS)))
+            .select(lt::failures)
+            .for_update()
+            .first::<i32>(txn_conn)
+            .optional()?;
+        let failures = previous.unwrap_or(0) + 1;
+        let locked_until =
+            lock_seconds(failures, free_failures).map(|s| now() + Duration::seconds(s));
+
+        diesel::insert_into(lt::login_throttles)
+            .values((
+                lt::key.eq(key),
+                lt::failures.eq(failures),
+                lt::last_failure_at.eq(now()),
+                lt::locked_until.eq(locked_until),
+            ))
+            .on_conflict(lt::key)
+            .do_update()
+            .set((
+                lt::failures.eq(failures),
+                lt::last_failure_at.eq(now()),
+                lt::locked_until.eq(locked_until),
+            ))
+            .execute(txn_conn)?;
+        Ok(())
+    })
+}
+
+/// Forgets the failures counted against `key`, after a successful login.
+pub fn clear_failures(conn: &mut PgConnection, key: &str) -> QueryResult<()> {
+    use crate::schema::login_throttles::dsl as lt;
+
+    diesel::delete(lt::login_throttles.filter(lt::key.eq(key))).execute(conn)?;
+    Ok(())
+}
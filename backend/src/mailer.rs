@@ -0,0 +1,60 @@
+use chrono::Utc;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A plain-text email.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers emails. Kept behind a trait so deployments can plug in SMTP or
+/// an API without touching the handlers.
+pub trait Mailer: Send + Sync {
+    fn send(&self, email: &Email) -> Result<(), String>;
+}
+
+/// Prints emails to stdout instead of sending them. The bodies hold live
+/// tokens, so this is only used in development mode.
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send(&self, email: &Email) -> Result<(), String> {
+        println!("mail to {} | {}\n{}", email.to, email.subject, email.body);
+        Ok(())
+    }
+}
+
+/// Drops every email as a `.eml` file into a directory, for local use.
+pub struct FileMailer {
+    dir: PathBuf,
+    counter: AtomicUsize,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            counter: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, email: &Email) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create mail dir: {e}"))?;
+        let name = format!(
+            "{}-{:04}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            self.counter.fetch_add(1, Ordering::SeqCst)
+        );
+        let content = format!(
+            "To: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            email.to, email.subject, email.body
+        );
+        fs::write(self.dir.join(name), content).map_err(|e| format!("Failed to write mail: {e}"))
+    }
+}
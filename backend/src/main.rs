@@ -1,25 +1,58 @@
 mod auth;
 mod config;
 mod db;
+mod mailer;
+mod rate_limit;
 mod schema;
 
 mod domain {
+    pub mod access_tokens;
     pub mod analytics;
+    pub mod backups;
     pub mod categories;
+    pub mod duplicates;
+    pub mod exports;
+    pub mod households;
+    pub mod imports;
+    pub mod net_worth;
+    pub mod price_alerts;
     pub mod product_prices;
     pub mod products;
+    pub mod recurring_rules;
+    pub mod rules;
+    pub mod sessions;
+    pub mod splits;
+    pub mod subscriptions;
+    pub mod suggestions;
     pub mod tags;
     pub mod transactions;
+    pub mod two_factor;
     pub mod users;
 }
 
 mod routes {
+    pub mod access_token_routes;
+    pub mod account_routes;
     pub mod analytics_routes;
+    pub mod backup_routes;
     pub mod category_routes;
+    pub mod duplicate_routes;
+    pub mod export_routes;
+    pub mod household_routes;
+    pub mod import_routes;
+    pub mod net_worth_routes;
+    pub mod price_alert_routes;
     pub mod product_price_routes;
     pub mod product_routes;
+    pub mod recurring_rule_routes;
+    pub mod rule_routes;
+    pub mod session_routes;
+    pub mod split_routes;
+    pub mod subscription_routes;
+    pub mod suggestion_routes;
     pub mod tag_routes;
     pub mod transaction_routes;
+    pub mod two_factor_routes;
     pub mod user_routes;
 }
 
@@ -27,20 +60,33 @@ mod routes {
 use axum::Router;
 use backend::{error_response, JsonResult};
 use routes::product_price_routes::product_price_routes;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
 // Local modules
-use crate::auth::require_auth;
-use crate::config::AppConfig;
+use crate::auth::{
+    require_auth, require_household, require_scope, require_session, require_verified_email,
+};
+use crate::config::{AppConfig, JwtConfig};
 use crate::db::{init_pool, PgPool};
+use crate::domain::access_tokens::models::ApiArea;
+use crate::mailer::{FileMailer, LogMailer, Mailer};
+use crate::rate_limit::{rate_limit, RateLimiter};
 
 use crate::routes::{
-    analytics_routes::analytics_routes, category_routes::category_routes,
-    product_routes::product_routes, tag_routes::tag_routes, transaction_routes::transaction_routes,
-    user_routes::user_routes,
+    access_token_routes::access_token_routes, account_routes::account_routes,
+    analytics_routes::analytics_routes, backup_routes::backup_routes,
+    category_routes::category_routes, duplicate_routes::duplicate_routes,
+    export_routes::export_routes, household_routes::household_routes, import_routes::import_routes,
+    net_worth_routes::net_worth_routes, price_alert_routes::price_alert_routes,
+    product_routes::product_routes, recurring_rule_routes::recurring_rule_routes,
+    rule_routes::rule_routes, session_routes::session_routes, split_routes::split_routes,
+    subscription_routes::subscription_routes, suggestion_routes::suggestion_routes,
+    tag_routes::tag_routes, transaction_routes::transaction_routes,
+    two_factor_routes::two_factor_routes, user_routes::user_routes,
 };
 
 #[cfg(test)]
@@ -51,6 +97,14 @@ mod tests;
 pub struct AppState {
     /// Our r2d2 Postgres connection pool
     pub pool: PgPool,
+    /// Delivers verification, password reset and other account emails
+    pub mailer: Arc<dyn Mailer>,
+    /// Per-client request limit for the whole API, if configured
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Whether client addresses come from `X-Forwarded-For`
+    pub trust_proxy_headers: bool,
+    /// Signing keys and lifetimes for access tokens
+    pub jwt: Arc<JwtConfig>,
 }
 
 // ==================================
@@ -58,22 +112,73 @@ pub struct AppState {
 // ==================================
 
 pub fn main_router(shared_state: Arc<AppState>) -> Router {
-    let protected_routes = Router::new()
-        // category routes
+    // Every protected route either names the API area whose scope a personal
+    // access token needs, or is reserved for login sessions.
+    let scoped = |area: ApiArea, routes: Router<Arc<AppState>>| {
+        routes.route_layer(axum::middleware::from_fn_with_state(area, require_scope))
+    };
+    // Features that stay locked until the account's email is confirmed.
+    let verified = |routes: Router<Arc<AppState>>| {
+        routes.route_layer(axum::middleware::from_fn_with_state(
+            shared_state.clone(),
+            require_verified_email,
+        ))
+    };
+
+    let transaction_api = Router::new()
+        .merge(transaction_routes())
+        .merge(tag_routes())
+        .merge(recurring_rule_routes())
+        .merge(import_routes())
+        .merge(duplicate_routes())
+        .merge(rule_routes())
+        .merge(suggestion_routes())
+        .merge(split_routes());
+    let catalog_api = Router::new()
         .merge(category_routes())
         .merge(product_routes())
         .merge(product_price_routes())
-        .merge(transaction_routes())
-        .merge(tag_routes())
+        .merge(price_alert_routes());
+    let analytics_api = Router::new()
         .merge(analytics_routes())
-        .layer(axum::middleware::from_fn(require_auth));
+        .merge(subscription_routes())
+        .merge(net_worth_routes());
+    let account_api = Router::new()
+        .merge(session_routes())
+        .merge(account_routes())
+        .merge(two_factor_routes())
+        .merge(access_token_routes())
+        .merge(household_routes())
+        .route_layer(axum::middleware::from_fn(require_session));
+
+    // Financial data belongs to the household picked for the request.
+    let household_api = Router::new()
+        .merge(scoped(ApiArea::Transactions, transaction_api))
+        .merge(scoped(ApiArea::Catalog, catalog_api))
+        .merge(scoped(ApiArea::Analytics, analytics_api))
+        .merge(verified(scoped(ApiArea::Exports, export_routes())))
+        .merge(verified(scoped(ApiArea::Backups, backup_routes())))
+        .route_layer(axum::middleware::from_fn_with_state(
+            shared_state.clone(),
+            require_household,
+        ));
+
+    let protected_routes = Router::new().merge(household_api).merge(account_api).layer(
+        axum::middleware::from_fn_with_state(shared_state.clone(), require_auth),
+    );
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let api_routes = Router::new().merge(user_routes()).merge(protected_routes);
+    let api_routes = Router::new()
+        .merge(user_routes())
+        .merge(protected_routes)
+        .layer(axum::middleware::from_fn_with_state(
+            shared_state.clone(),
+            rate_limit,
+        ));
 
     Router::new()
         .nest("/rusty-fin/api", api_routes)
@@ -86,11 +191,24 @@ pub fn main_router(shared_state: Arc<AppState>) -> Router {
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     // 1) Load .env + environment vars
-    let config = AppConfig::from_env().expect("Failed to load AppConfig");
+    let config = AppConfig::from_env().unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {e}");
+        std::process::exit(1);
+    });
 
     // 2) Create the pool & store in AppState
     let pool = init_pool(&config.database_url);
-    let shared_state = AppState { pool };
+    let mailer: Arc<dyn Mailer> = match &config.mail_dir {
+        Some(dir) => Arc::new(FileMailer::new(dir)),
+        None => Arc::new(LogMailer),
+    };
+    let shared_state = AppState {
+        pool,
+        mailer,
+        rate_limiter: config.rate_limit.map(|c| Arc::new(RateLimiter::new(c))),
+        trust_proxy_headers: config.trust_proxy_headers,
+        jwt: Arc::new(config.jwt.clone()),
+    };
 
     // 3) Bind to the address from config
     let listener = TcpListener::bind(&config.address)
@@ -98,10 +216,18 @@ async fn main() {
         .expect("Failed to bind to address");
 
     println!("Server running on http://{}", config.address);
+    if config.dev_mode {
+        println!("Development mode: insecure defaults are allowed");
+    }
 
     // 4) Build Axum router
     let app = main_router(Arc::new(shared_state));
 
-    // 5) Serve
-    axum::serve(listener, app).await.unwrap();
+    // 5) Serve, keeping the peer address for rate limiting
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
@@ -0,0 +1,183 @@
+use axum::extract::{ConnectInfo, FromRequestParts, Request, State};
+use axum::http::{header, request::Parts, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use backend::ErrorResponse;
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+
+/// Most clients tracked at once; past it the least recently seen client is
+/// forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// The address a client is limited by: IPv6 clients by their /64, which a
+/// single host usually has to itself, and IPv4-mapped addresses as IPv4.
+pub fn client_network(ip: IpAddr) -> IpAddr {
+    match ip.to_canonical() {
+        IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & !(u64::MAX as u128))),
+        v4 => v4,
+    }
+}
+
+/// How many requests a client may make per window.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    pub requests: u32,
+    pub window_seconds: u64,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The buckets with an index by when each client was last seen.
+#[derive(Default)]
+struct Buckets {
+    by_client: HashMap<IpAddr, Bucket>,
+    by_age: BTreeSet<(Instant, IpAddr)>,
+}
+
+/// Token buckets per client network (see [`client_network`]). A client can
+/// spend the whole window's allowance at once, after which it refills evenly.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    max_clients: usize,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            max_clients: MAX_TRACKED_CLIENTS,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Tracks at most `max_clients` clients instead of the default.
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients.max(1);
+        self
+    }
+
+    fn refill_per_second(&self) -> f64 {
+        self.config.requests as f64 / self.config.window_seconds.max(1) as f64
+    }
+
+    /// Takes a token for `client`. On refusal returns the seconds until the
+    /// next one is available.
+    ///
+    /// Clients idle for a whole window have a full bucket again and are
+    /// dropped, oldest first; beyond `max_clients` the least recently seen
+    /// client goes too, so each call costs O(log n) however many addresses
+    /// show up.
+    pub fn check(&self, client: IpAddr) -> Result<(), u64> {
+        let client = client_network(client);
+        let capacity = self.config.requests as f64;
+        let rate = self.refill_per_second();
+        let now = Instant::now();
+        let refilled = Duration::from_secs(self.config.window_seconds.max(1));
+        let mut guard = self.buckets.lock().unwrap();
+        let Buckets { by_client, by_age } = &mut *guard;
+
+        while let Some(&(seen, oldest)) = by_age.first() {
+            let full = now.duration_since(seen) >= refilled;
+            let over = by_client.len() >= self.max_clients && !by_client.contains_key(&client);
+            if !full && !over {
+                break;
+            }
+            by_age.pop_first();
+            by_client.remove(&oldest);
+        }
+
+        let bucket = by_client.entry(client).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        by_age.remove(&(bucket.updated, client));
+        by_age.insert((now, client));
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+        }
+    }
+}
+
+/// The address of the client making the request. Behind a trusted reverse
+/// proxy it is the last `X-Forwarded-For` entry, the one the proxy appended;
+/// the entries before it come from the client and can be made up.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    fn from_parts(parts: &Parts, trust_proxy_headers: bool) -> Self {
+        let forwarded = trust_proxy_headers
+            .then(|| parts.headers.get("x-forwarded-for"))
+            .flatten()
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next_back())
+            .and_then(|ip| ip.trim().parse().ok());
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        ClientIp(
+            forwarded
+                .or(peer)
+                .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        )
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts, state.trust_proxy_headers))
+    }
+}
+
+/// A 429 with `Retry-After`.
+pub fn too_many_requests(message: impl ToString, retry_after_seconds: u64) -> Response {
+    let body = ErrorResponse {
+        error: message.to_string(),
+    };
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after_seconds.to_string())],
+        Json(body),
+    )
+        .into_response()
+}
+
+/// Refuses requests from clients that used up their allowance.
+pub async fn rate_limit(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    let Some(limiter) = &state.rate_limiter else {
+        return next.run(req).await;
+    };
+    let (parts, body) = req.into_parts();
+    let ClientIp(client) = ClientIp::from_parts(&parts, state.trust_proxy_headers);
+    if let Err(wait) = limiter.check(client) {
+        return too_many_requests(
+            format!("Too many requests; try again in {wait} seconds"),
+            wait,
+        );
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
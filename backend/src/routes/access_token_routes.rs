@@ -0,0 +1,20 @@
+use axum::{
+    routing::{delete, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::access_tokens::handlers::{
+    create_access_token_handler, delete_access_token, list_access_tokens,
+};
+use crate::AppState;
+
+/// Returns a sub-router for managing personal access tokens.
+pub fn access_token_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/access-tokens",
+            post(create_access_token_handler).get(list_access_tokens),
+        )
+        .route("/access-tokens/{id}", delete(delete_access_token))
+}
@@ -0,0 +1,18 @@
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::users::handlers::{
+    change_password, delete_account, get_account, resend_verification,
+};
+use crate::AppState;
+
+/// Returns a sub-router for managing the logged-in user's own account.
+pub fn account_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/me", get(get_account).delete(delete_account))
+        .route("/users/me/password", put(change_password))
+        .route("/verify-email/resend", post(resend_verification))
+}
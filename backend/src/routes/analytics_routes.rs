@@ -2,7 +2,8 @@ use axum::{routing::get, Router};
 use std::sync::Arc;
 
 use crate::domain::analytics::handlers::{
-    category_spending, product_price_data, spending_time_series,
+    category_spending, product_price_data, savings_report, spending_anomalies, spending_forecast,
+    spending_heatmap, spending_time_series,
 };
 use crate::AppState;
 
@@ -12,4 +13,8 @@ pub fn analytics_routes() -> Router<Arc<AppState>> {
         .route("/spending-time-series", get(spending_time_series))
         .route("/category-spending", get(category_spending))
         .route("/product-price-data", get(product_price_data))
+        .route("/spending-anomalies", get(spending_anomalies))
+        .route("/spending-forecast", get(spending_forecast))
+        .route("/savings-report", get(savings_report))
+        .route("/spending-heatmap", get(spending_heatmap))
 }
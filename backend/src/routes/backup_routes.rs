@@ -0,0 +1,20 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::backups::handlers::{download_backup, restore_backup};
+use crate::AppState;
+
+/// Largest archive POST /restore accepts; the default 2 MB is far too small.
+const RESTORE_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Returns a sub-router for account backup and restore.
+pub fn backup_routes() -> Router<Arc<AppState>> {
+    Router::new().route("/backup", get(download_backup)).route(
+        "/restore",
+        post(restore_backup).layer(DefaultBodyLimit::max(RESTORE_BODY_LIMIT)),
+    )
+}
@@ -0,0 +1,15 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::duplicates::handlers::{list_duplicates, review_duplicate};
+use crate::AppState;
+
+/// Returns a sub-router for the duplicate finder and its review endpoint.
+pub fn duplicate_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/transactions/duplicates", get(list_duplicates))
+        .route("/transactions/duplicates/review", post(review_duplicate))
+}
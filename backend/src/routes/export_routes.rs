@@ -0,0 +1,17 @@
+use axum::{routing::get, Router};
+use std::sync::Arc;
+
+use crate::domain::exports::handlers::{
+    export_categories, export_journal, export_prices, export_products, export_transactions,
+};
+use crate::AppState;
+
+/// Returns a sub-router for the CSV, JSON Lines, XLSX and journal exports.
+pub fn export_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/export/transactions", get(export_transactions))
+        .route("/export/products", get(export_products))
+        .route("/export/prices", get(export_prices))
+        .route("/export/categories", get(export_categories))
+        .route("/export/journal", get(export_journal))
+}
@@ -0,0 +1,30 @@
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::households::handlers::{
+    create_household_handler, create_invitation_handler, delete_member, join_household,
+    list_households, list_members, update_member,
+};
+use crate::AppState;
+
+/// Returns a sub-router for households, their members and invitations.
+pub fn household_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/households",
+            get(list_households).post(create_household_handler),
+        )
+        .route("/households/join", post(join_household))
+        .route("/households/{id}/members", get(list_members))
+        .route(
+            "/households/{id}/members/{user_id}",
+            put(update_member).delete(delete_member),
+        )
+        .route(
+            "/households/{id}/invitations",
+            post(create_invitation_handler),
+        )
+}
@@ -0,0 +1,27 @@
+use axum::{
+    routing::{delete, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::imports::handlers::{
+    create_import_profile, delete_import_profile, import_beancount, import_camt053, import_csv,
+    import_mt940, import_ofx, import_qif, list_import_profiles,
+};
+use crate::AppState;
+
+/// Returns a sub-router for statement import endpoints.
+pub fn import_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/import/csv", post(import_csv))
+        .route("/import/ofx", post(import_ofx))
+        .route("/import/qif", post(import_qif))
+        .route("/import/camt053", post(import_camt053))
+        .route("/import/mt940", post(import_mt940))
+        .route("/import/beancount", post(import_beancount))
+        .route(
+            "/import-profiles",
+            post(create_import_profile).get(list_import_profiles),
+        )
+        .route("/import-profiles/{id}", delete(delete_import_profile))
+}
@@ -0,0 +1,25 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::net_worth::handlers::{
+    create_net_worth_snapshot, delete_net_worth_snapshot, get_net_worth_history,
+    list_net_worth_snapshots,
+};
+use crate::AppState;
+
+/// Returns a sub-router for net worth endpoints.
+pub fn net_worth_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/net-worth-snapshots",
+            post(create_net_worth_snapshot).get(list_net_worth_snapshots),
+        )
+        .route(
+            "/net-worth-snapshots/{id}",
+            delete(delete_net_worth_snapshot),
+        )
+        .route("/net-worth-history", get(get_net_worth_history))
+}
@@ -0,0 +1,21 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::price_alerts::handlers::{
+    dismiss_price_alert, get_price_alert_settings, list_price_alerts, update_price_alert_settings,
+};
+use crate::AppState;
+
+/// Returns a sub-router for price alert endpoints.
+pub fn price_alert_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/price-alerts", get(list_price_alerts))
+        .route(
+            "/price-alerts/settings",
+            get(get_price_alert_settings).put(update_price_alert_settings),
+        )
+        .route("/price-alerts/{id}/dismiss", post(dismiss_price_alert))
+}
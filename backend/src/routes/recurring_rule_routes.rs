@@ -0,0 +1,20 @@
+use axum::{
+    routing::{delete, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::recurring_rules::handlers::{
+    create_recurring_rule, delete_recurring_rule, list_recurring_rules,
+};
+use crate::AppState;
+
+/// Returns a sub-router for recurring rule endpoints.
+pub fn recurring_rule_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/recurring-rules",
+            post(create_recurring_rule).get(list_recurring_rules),
+        )
+        .route("/recurring-rules/{id}", delete(delete_recurring_rule))
+}
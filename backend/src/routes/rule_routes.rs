@@ -0,0 +1,18 @@
+use axum::{
+    routing::{post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::rules::handlers::{
+    apply_rules, create_rule, delete_rule, list_rules, update_rule,
+};
+use crate::AppState;
+
+/// Returns a sub-router for categorization rule endpoints.
+pub fn rule_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/rules", post(create_rule).get(list_rules))
+        .route("/rules/apply", post(apply_rules))
+        .route("/rules/{id}", put(update_rule).delete(delete_rule))
+}
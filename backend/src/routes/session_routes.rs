@@ -0,0 +1,17 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::sessions::handlers::{delete_session, list_sessions, logout};
+use crate::AppState;
+
+/// Returns a sub-router for managing the caller's sessions. POST /refresh is
+/// public and lives with the user routes.
+pub fn session_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/logout", post(logout))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{id}", delete(delete_session))
+}
@@ -0,0 +1,22 @@
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::splits::handlers::{
+    create_settlement, delete_split, get_balances, get_settle_up, get_split, put_split,
+};
+use crate::AppState;
+
+/// Returns a sub-router for splitting expenses and settling up.
+pub fn split_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/transactions/{id}/split",
+            put(put_split).get(get_split).delete(delete_split),
+        )
+        .route("/splits/balances", get(get_balances))
+        .route("/splits/settle-up", get(get_settle_up))
+        .route("/splits/settlements", post(create_settlement))
+}
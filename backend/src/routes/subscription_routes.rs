@@ -0,0 +1,18 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::subscriptions::handlers::{convert_subscription, list_subscriptions};
+use crate::AppState;
+
+/// Returns a sub-router for subscription detection endpoints.
+pub fn subscription_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/subscriptions", get(list_subscriptions))
+        .route(
+            "/subscriptions/{product_id}/convert",
+            post(convert_subscription),
+        )
+}
@@ -0,0 +1,10 @@
+use axum::{routing::get, Router};
+use std::sync::Arc;
+
+use crate::domain::suggestions::handlers::suggest;
+use crate::AppState;
+
+/// Returns a sub-router for learned category and tag suggestions.
+pub fn suggestion_routes() -> Router<Arc<AppState>> {
+    Router::new().route("/transactions/suggest", get(suggest))
+}
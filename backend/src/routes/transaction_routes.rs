@@ -1,11 +1,15 @@
-use crate::domain::transactions::handlers::{create_transaction, list_transactions};
+use crate::domain::transactions::handlers::{
+    create_transaction, list_transactions, quick_add_transaction,
+};
 use crate::AppState;
 use axum::{routing::post, Router};
 use std::sync::Arc;
 
 pub fn transaction_routes() -> Router<Arc<AppState>> {
-    Router::new().route(
-        "/transactions",
-        post(create_transaction).get(list_transactions),
-    )
+    Router::new()
+        .route(
+            "/transactions",
+            post(create_transaction).get(list_transactions),
+        )
+        .route("/transactions/quick", post(quick_add_transaction))
 }
@@ -0,0 +1,22 @@
+use axum::{
+    routing::{delete, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::domain::two_factor::handlers::{
+    disable_two_factor_handler, enroll_two_factor, regenerate_recovery_codes, verify_two_factor,
+};
+use crate::AppState;
+
+/// Returns a sub-router for setting up TOTP two-factor authentication.
+pub fn two_factor_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/me/2fa", delete(disable_two_factor_handler))
+        .route("/users/me/2fa/enroll", post(enroll_two_factor))
+        .route("/users/me/2fa/verify", post(verify_two_factor))
+        .route(
+            "/users/me/2fa/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+}
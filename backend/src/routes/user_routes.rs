@@ -1,12 +1,22 @@
 use axum::{routing::post, Router};
 use std::sync::Arc;
 
-use crate::domain::users::handlers::{login, sign_up};
+use crate::domain::sessions::handlers::refresh;
+use crate::domain::two_factor::handlers::login_two_factor;
+use crate::domain::users::handlers::{
+    confirm_password_reset, login, request_password_reset, sign_up, verify_email,
+};
 use crate::AppState;
 
-/// Provides routes for user sign-up and login
+/// Provides the public routes for user sign-up, email verification, login
+/// (including its two-factor step), token refresh and password reset
 pub fn user_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/users", post(sign_up))
+        .route("/verify-email", post(verify_email))
         .route("/login", post(login))
+        .route("/login/2fa", post(login_two_factor))
+        .route("/refresh", post(refresh))
+        .route("/password-reset", post(request_password_reset))
+        .route("/password-reset/confirm", post(confirm_password_reset))
 }
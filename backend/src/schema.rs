@@ -5,7 +5,154 @@ diesel::table! {
         id -> Int4,
         parent_category_id -> Nullable<Int4>,
         name -> Text,
+        household_id -> Int4,
+    }
+}
+
+diesel::table! {
+    duplicate_reviews (id) {
+        id -> Int4,
+        household_id -> Int4,
+        transaction_id -> Int4,
+        duplicate_id -> Int4,
+        action -> Text,
+        external_id -> Nullable<Text>,
+        import_hash -> Nullable<Text>,
+        reviewed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    email_verification_tokens (id) {
+        id -> Int4,
         user_id -> Int4,
+        token_hash -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    household_invitations (id) {
+        id -> Int4,
+        household_id -> Int4,
+        role -> Text,
+        token_hash -> Text,
+        created_by -> Int4,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    household_members (household_id, user_id) {
+        household_id -> Int4,
+        user_id -> Int4,
+        role -> Text,
+        joined_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    households (id) {
+        id -> Int4,
+        name -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    import_profiles (id) {
+        id -> Int4,
+        household_id -> Int4,
+        name -> Text,
+        delimiter -> Text,
+        has_header -> Bool,
+        skip_rows -> Int4,
+        date_column -> Text,
+        date_format -> Text,
+        decimal_separator -> Text,
+        amount_column -> Text,
+        sign_column -> Nullable<Text>,
+        income_indicator -> Nullable<Text>,
+        invert_sign -> Bool,
+        description_column -> Nullable<Text>,
+        product_column -> Nullable<Text>,
+        tags_column -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    login_challenges (id) {
+        id -> Int4,
+        user_id -> Int4,
+        token_hash -> Text,
+        device_label -> Nullable<Text>,
+        attempts -> Int4,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    login_throttles (key) {
+        key -> Text,
+        failures -> Int4,
+        last_failure_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    net_worth_snapshots (id) {
+        id -> Int4,
+        household_id -> Int4,
+        name -> Text,
+        kind -> Text,
+        value -> Int8,
+        snapshot_date -> Date,
+    }
+}
+
+diesel::table! {
+    password_reset_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        token_hash -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    personal_access_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Text,
+        token_prefix -> Text,
+        token_hash -> Text,
+        scopes -> Array<Text>,
+        created_at -> Timestamp,
+        last_used_at -> Nullable<Timestamp>,
+        expires_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    price_alerts (id) {
+        id -> Int4,
+        household_id -> Int4,
+        product_id -> Int4,
+        product_price_id -> Int4,
+        median_price -> Int4,
+        new_price -> Int4,
+        change_percent -> Float8,
+        dismissed -> Bool,
+        created_at -> Timestamp,
     }
 }
 
@@ -21,17 +168,109 @@ diesel::table! {
 diesel::table! {
     products (id) {
         id -> Int4,
-        user_id -> Int4,
+        household_id -> Int4,
         category_id -> Nullable<Int4>,
         name -> Text,
     }
 }
 
+diesel::table! {
+    recovery_codes (id) {
+        id -> Int4,
+        user_id -> Int4,
+        code_hash -> Text,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    recurring_rules (id) {
+        id -> Int4,
+        household_id -> Int4,
+        product_id -> Int4,
+        description -> Nullable<Text>,
+        amount -> Int4,
+        transaction_type -> Text,
+        cadence -> Text,
+        next_due_date -> Date,
+        active -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    rule_tags (rule_id, tag_id) {
+        rule_id -> Int4,
+        tag_id -> Int4,
+    }
+}
+
+diesel::table! {
+    rules (id) {
+        id -> Int4,
+        household_id -> Int4,
+        name -> Text,
+        priority -> Int4,
+        active -> Bool,
+        description_pattern -> Nullable<Text>,
+        product_name -> Nullable<Text>,
+        merchant -> Nullable<Text>,
+        account -> Nullable<Text>,
+        min_amount -> Nullable<Int4>,
+        max_amount -> Nullable<Int4>,
+        set_category_id -> Nullable<Int4>,
+        set_description -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    sessions (id) {
+        id -> Int4,
+        user_id -> Int4,
+        refresh_token_hash -> Text,
+        previous_token_hash -> Nullable<Text>,
+        device_label -> Nullable<Text>,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    split_shares (id) {
+        id -> Int4,
+        transaction_id -> Int4,
+        user_id -> Nullable<Int4>,
+        amount -> Int4,
+    }
+}
+
 diesel::table! {
     tags (id) {
         id -> Int4,
         name -> Text,
+        household_id -> Int4,
+    }
+}
+
+diesel::table! {
+    totp_credentials (user_id) {
         user_id -> Int4,
+        secret -> Text,
+        enabled_at -> Nullable<Timestamp>,
+        last_used_step -> Nullable<Int8>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_splits (transaction_id) {
+        transaction_id -> Int4,
+        paid_by -> Nullable<Int4>,
+        method -> Text,
+        created_at -> Timestamp,
     }
 }
 
@@ -45,12 +284,17 @@ diesel::table! {
 diesel::table! {
     transactions (id) {
         id -> Int4,
-        user_id -> Int4,
+        user_id -> Nullable<Int4>,
         product_id -> Int4,
         product_price_id -> Int4,
         transaction_type -> Text,
         description -> Nullable<Text>,
         date -> Timestamp,
+        external_id -> Nullable<Text>,
+        account -> Nullable<Text>,
+        value_date -> Nullable<Date>,
+        import_hash -> Nullable<Text>,
+        household_id -> Int4,
     }
 }
 
@@ -59,25 +303,76 @@ diesel::table! {
         id -> Int4,
         email -> Text,
         password_hash -> Text,
+        price_alert_threshold -> Int4,
+        email_verified -> Bool,
     }
 }
 
-diesel::joinable!(categories -> users (user_id));
+diesel::joinable!(categories -> households (household_id));
+diesel::joinable!(duplicate_reviews -> households (household_id));
+diesel::joinable!(duplicate_reviews -> transactions (transaction_id));
+diesel::joinable!(email_verification_tokens -> users (user_id));
+diesel::joinable!(household_invitations -> households (household_id));
+diesel::joinable!(household_invitations -> users (created_by));
+diesel::joinable!(household_members -> households (household_id));
+diesel::joinable!(household_members -> users (user_id));
+diesel::joinable!(import_profiles -> households (household_id));
+diesel::joinable!(login_challenges -> users (user_id));
+diesel::joinable!(net_worth_snapshots -> households (household_id));
+diesel::joinable!(password_reset_tokens -> users (user_id));
+diesel::joinable!(personal_access_tokens -> users (user_id));
+diesel::joinable!(price_alerts -> households (household_id));
+diesel::joinable!(price_alerts -> product_prices (product_price_id));
+diesel::joinable!(price_alerts -> products (product_id));
 diesel::joinable!(product_prices -> products (product_id));
 diesel::joinable!(products -> categories (category_id));
-diesel::joinable!(products -> users (user_id));
-diesel::joinable!(tags -> users (user_id));
+diesel::joinable!(products -> households (household_id));
+diesel::joinable!(recovery_codes -> users (user_id));
+diesel::joinable!(recurring_rules -> households (household_id));
+diesel::joinable!(recurring_rules -> products (product_id));
+diesel::joinable!(rule_tags -> rules (rule_id));
+diesel::joinable!(rule_tags -> tags (tag_id));
+diesel::joinable!(rules -> categories (set_category_id));
+diesel::joinable!(rules -> households (household_id));
+diesel::joinable!(sessions -> users (user_id));
+diesel::joinable!(split_shares -> transaction_splits (transaction_id));
+diesel::joinable!(split_shares -> users (user_id));
+diesel::joinable!(tags -> households (household_id));
+diesel::joinable!(totp_credentials -> users (user_id));
+diesel::joinable!(transaction_splits -> transactions (transaction_id));
+diesel::joinable!(transaction_splits -> users (paid_by));
 diesel::joinable!(transaction_tags -> tags (tag_id));
 diesel::joinable!(transaction_tags -> transactions (transaction_id));
+diesel::joinable!(transactions -> households (household_id));
 diesel::joinable!(transactions -> product_prices (product_price_id));
 diesel::joinable!(transactions -> products (product_id));
 diesel::joinable!(transactions -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     categories,
+    duplicate_reviews,
+    email_verification_tokens,
+    household_invitations,
+    household_members,
+    households,
+    import_profiles,
+    login_challenges,
+    login_throttles,
+    net_worth_snapshots,
+    password_reset_tokens,
+    personal_access_tokens,
+    price_alerts,
     product_prices,
     products,
+    recovery_codes,
+    recurring_rules,
+    rule_tags,
+    rules,
+    sessions,
+    split_shares,
     tags,
+    totp_credentials,
+    transaction_splits,
     transaction_tags,
     transactions,
     users,
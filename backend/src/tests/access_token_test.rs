@@ -0,0 +1,188 @@
+// tests/access_token_test.rs
+
+use reqwest::StatusCode;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::access_tokens::models::{Access, ApiArea, Scope};
+
+#[test]
+fn test_scopes() {
+    let read = Scope::parse("read:transactions").unwrap();
+    let write = Scope::parse("write:transactions").unwrap();
+    assert_eq!(
+        write,
+        Scope {
+            access: Access::Write,
+            area: ApiArea::Transactions
+        }
+    );
+    assert_eq!(read.to_string(), "read:transactions");
+    assert!(write.covers(read));
+    assert!(!read.covers(write));
+    assert!(!write.covers(Scope::parse("read:analytics").unwrap()));
+    for invalid in [
+        "read",
+        "delete:transactions",
+        "read:everything",
+        "READ:exports",
+    ] {
+        assert!(Scope::parse(invalid).is_none());
+    }
+    assert_eq!(Scope::all().len(), 10);
+}
+
+#[tokio::test]
+async fn test_access_tokens() {
+    let (base_url, client) = spawn_app().await;
+    let session = sign_up_and_login(&base_url, &client, "ada@example.com").await;
+
+    let create = |body: serde_json::Value| {
+        let request = client
+            .post(format!("{}/access-tokens", base_url))
+            .bearer_auth(&session)
+            .json(&body);
+        async move {
+            request
+                .send()
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        }
+    };
+    let body = create(serde_json::json!({ "name": "Sync", "scopes": ["read:everything"] })).await;
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Unknown scope 'read:everything'; expected one of read:transactions"));
+    let body = create(serde_json::json!({ "name": "Sync", "scopes": [] })).await;
+    assert_eq!(body["error"], "A token needs at least one scope");
+    let body = create(serde_json::json!({
+        "name": "Sync",
+        "scopes": ["read:transactions"],
+        "expires_in_days": 0
+    }))
+    .await;
+    assert_eq!(body["error"], "expires_in_days must be between 1 and 366");
+
+    let reader = create(serde_json::json!({
+        "name": "Nightly report",
+        "scopes": ["read:transactions", "read:transactions", "read:analytics"],
+        "expires_in_days": 30
+    }))
+    .await;
+    let reader_token = reader["token"].as_str().unwrap().to_string();
+    assert!(reader_token.starts_with("rfpat_"));
+    assert_eq!(
+        reader["scopes"],
+        serde_json::json!(["read:transactions", "read:analytics"])
+    );
+    assert!(reader_token.starts_with(reader["token_prefix"].as_str().unwrap()));
+    assert!(reader["expires_at"].is_string());
+    let writer = create(serde_json::json!({
+        "name": "Importer",
+        "scopes": ["write:transactions"]
+    }))
+    .await;
+    let writer_token = writer["token"].as_str().unwrap().to_string();
+    assert!(writer["expires_at"].is_null());
+
+    let get = |path: &'static str, token: String| {
+        let request = client
+            .get(format!("{}{}", base_url, path))
+            .bearer_auth(token);
+        async move { request.send().await.unwrap() }
+    };
+    let add_transaction = |token: String| {
+        let request = client
+            .post(format!("{}/transactions", base_url))
+            .bearer_auth(token)
+            .json(&serde_json::json!({
+                "product_name": "Coffee",
+                "price": 3.2,
+                "transaction_type": "Expense",
+                "description": null,
+                "date": "2025-05-01T08:00:00",
+                "tags": []
+            }));
+        async move { request.send().await.unwrap() }
+    };
+
+    // Reads are allowed, writes need a write scope.
+    assert!(get("/transactions", reader_token.clone())
+        .await
+        .status()
+        .is_success());
+    assert!(get("/category-spending", reader_token.clone())
+        .await
+        .status()
+        .is_success());
+    let resp = add_transaction(reader_token.clone()).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(
+        body["error"],
+        "This token lacks the 'write:transactions' scope"
+    );
+    assert!(add_transaction(writer_token.clone())
+        .await
+        .status()
+        .is_success());
+    // Write access includes reading, but only within its area.
+    assert!(get("/transactions", writer_token.clone())
+        .await
+        .status()
+        .is_success());
+    assert_eq!(
+        get("/categories", writer_token.clone()).await.status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get("/export/transactions", reader_token.clone())
+            .await
+            .status(),
+        StatusCode::FORBIDDEN
+    );
+
+    // Tokens cannot manage the account, sessions or other tokens.
+    for path in ["/users/me", "/sessions", "/access-tokens"] {
+        let resp = get(path, writer_token.clone()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body = resp.json::<serde_json::Value>().await.unwrap();
+        assert_eq!(body["error"], "Personal access tokens cannot be used here");
+    }
+
+    // Listing shows when each token was last used, never the secret.
+    let tokens = get("/access-tokens", session.clone())
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let tokens = tokens.as_array().unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0]["name"], "Importer");
+    assert!(tokens[0]["last_used_at"].is_string());
+    assert!(tokens[0]["token"].is_null());
+
+    // Revoked and made-up tokens are turned away.
+    let revoke = |id: i64| {
+        let request = client
+            .delete(format!("{}/access-tokens/{}", base_url, id))
+            .bearer_auth(&session);
+        async move { request.send().await.unwrap() }
+    };
+    let reader_id = reader["id"].as_i64().unwrap();
+    assert!(revoke(reader_id).await.status().is_success());
+    assert!(revoke(reader_id).await.status().is_client_error());
+    assert_eq!(
+        get("/transactions", reader_token).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        get("/transactions", format!("rfpat_{}", "0".repeat(64)))
+            .await
+            .status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
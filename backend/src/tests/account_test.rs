@@ -0,0 +1,200 @@
+// tests/account_test.rs
+
+use reqwest::StatusCode;
+
+use super::workflow_test::{sent_emails, sign_up_and_login, spawn_app, token_from_email};
+
+#[tokio::test]
+async fn test_password_change_reset_and_account_deletion() {
+    let (base_url, client) = spawn_app().await;
+    let reset_token_from_mail =
+        || token_from_email(&base_url, "pat@example.com", "Reset token: ").unwrap();
+    let token = sign_up_and_login(&base_url, &client, "pat@example.com").await;
+
+    let login = |password: &'static str| {
+        let request = client
+            .post(format!("{}/login", base_url))
+            .json(&serde_json::json!({
+                "email": "pat@example.com",
+                "password_hash": password
+            }));
+        async move { request.send().await.unwrap() }
+    };
+    let status_with = |token: String| {
+        let request = client
+            .get(format!("{}/transactions", base_url))
+            .bearer_auth(token);
+        async move { request.send().await.unwrap().status() }
+    };
+    let token_of = |resp: reqwest::Response| async move {
+        let body = resp.json::<serde_json::Value>().await.unwrap();
+        body["token"].as_str().unwrap().to_string()
+    };
+
+    // Change: the current password is required, the other session ends.
+    let other = token_of(login("secret123").await).await;
+    let change = |current: &'static str, new: &'static str| {
+        let request = client
+            .put(format!("{}/users/me/password", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({ "current_password": current, "new_password": new }));
+        async move { request.send().await.unwrap() }
+    };
+    let resp = change("wrong", "better-secret").await;
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "Current password is incorrect");
+    let body = change("secret123", "short")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(body["error"], "Password must be at least 8 characters long");
+    assert!(change("secret123", "better-secret")
+        .await
+        .status()
+        .is_success());
+    assert!(status_with(token.clone()).await.is_success());
+    assert_eq!(status_with(other).await, StatusCode::UNAUTHORIZED);
+    assert!(login("secret123").await.status().is_client_error());
+    assert!(login("better-secret").await.status().is_success());
+
+    // Reset: the answer does not tell whether the account exists.
+    let request_reset = |email: &'static str| {
+        let request = client
+            .post(format!("{}/password-reset", base_url))
+            .json(&serde_json::json!({ "email": email }));
+        async move {
+            request
+                .send()
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        }
+    };
+    let unknown = request_reset("nobody@example.com").await;
+    assert!(sent_emails(&base_url)
+        .iter()
+        .all(|email| email.to != "nobody@example.com"));
+    let known = request_reset(" Pat@Example.com").await;
+    assert_eq!(known, unknown);
+    let stale = reset_token_from_mail();
+    request_reset("pat@example.com").await;
+    let reset_token = reset_token_from_mail();
+    assert_ne!(stale, reset_token);
+
+    let confirm = |token: String, password: &'static str| {
+        let request = client
+            .post(format!("{}/password-reset/confirm", base_url))
+            .json(&serde_json::json!({ "token": token, "new_password": password }));
+        async move { request.send().await.unwrap() }
+    };
+    // A newer request replaces the older token.
+    assert!(confirm(stale, "reset-secret")
+        .await
+        .status()
+        .is_client_error());
+    assert!(confirm(reset_token.clone(), "tiny")
+        .await
+        .status()
+        .is_client_error());
+    assert!(confirm(reset_token.clone(), "reset-secret")
+        .await
+        .status()
+        .is_success());
+    let body = confirm(reset_token, "another-secret")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(body["error"], "Invalid or expired reset token");
+    assert_eq!(status_with(token).await, StatusCode::UNAUTHORIZED);
+    let token = token_of(login("reset-secret").await).await;
+
+    // Deletion removes the user along with everything they own.
+    let post = |path: &'static str, body: serde_json::Value| {
+        client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    for (path, body) in [
+        (
+            "/categories",
+            serde_json::json!({ "parent_category_id": null, "name": "Food" }),
+        ),
+        (
+            "/categories",
+            serde_json::json!({ "parent_category_id": 1, "name": "Groceries" }),
+        ),
+        (
+            "/products",
+            serde_json::json!({ "category_id": 2, "name": "Milk" }),
+        ),
+        (
+            "/transactions",
+            serde_json::json!({
+                "product_name": "Milk",
+                "price": 1.19,
+                "transaction_type": "Expense",
+                "description": null,
+                "date": "2025-04-01T08:00:00",
+                "tags": ["dairy"]
+            }),
+        ),
+        (
+            "/rules",
+            serde_json::json!({
+                "name": "Milk",
+                "conditions": { "product_name": "milk" },
+                "actions": { "add_tags": ["dairy"] }
+            }),
+        ),
+        (
+            "/recurring-rules",
+            serde_json::json!({
+                "product_id": 1,
+                "description": null,
+                "amount": 1.19,
+                "transaction_type": "Expense",
+                "cadence": "Weekly",
+                "next_due_date": "2025-04-08"
+            }),
+        ),
+    ] {
+        assert!(post(path, body).await.unwrap().status().is_success());
+    }
+    request_reset("pat@example.com").await;
+
+    let delete = |password: &'static str| {
+        let request = client
+            .delete(format!("{}/users/me", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({ "password": password }));
+        async move { request.send().await.unwrap() }
+    };
+    assert!(delete("secret123").await.status().is_client_error());
+    let deleted = delete("reset-secret")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(deleted["email"], "pat@example.com");
+    assert_eq!(status_with(token.clone()).await, StatusCode::UNAUTHORIZED);
+    assert!(login("reset-secret").await.status().is_client_error());
+
+    // The address is free again and the new account starts empty.
+    let token = sign_up_and_login(&base_url, &client, "pat@example.com").await;
+    let categories = client
+        .get(format!("{}/categories", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert!(categories.as_array().unwrap().is_empty());
+}
@@ -0,0 +1,61 @@
+// tests/anomaly_test.rs
+
+use chrono::NaiveDate;
+
+use crate::domain::analytics::services::{
+    find_day_anomalies, find_transaction_anomalies, median_and_mad, ExpenseRow,
+};
+
+fn day(d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, d).unwrap()
+}
+
+fn expense(id: i32, d: u32, product_id: i32, amount: i64) -> ExpenseRow {
+    ExpenseRow {
+        transaction_id: id,
+        date: day(d),
+        product_id,
+        product_name: format!("Product {product_id}"),
+        category_name: Some("Groceries".to_string()),
+        amount,
+    }
+}
+
+#[test]
+fn test_median_and_mad() {
+    assert_eq!(
+        median_and_mad(&[1.0, 2.0, 3.0, 4.0, 100.0]),
+        Some((3.0, 1.0))
+    );
+    assert_eq!(median_and_mad(&[]), None);
+}
+
+#[test]
+fn test_flags_expensive_transaction() {
+    let mut rows: Vec<ExpenseRow> = (1..=6)
+        .map(|i| expense(i, i as u32, 1, 300 + (i as i64 % 3) * 10))
+        .collect();
+    rows.push(expense(7, 7, 1, 2500));
+
+    let anomalies = find_transaction_anomalies(&rows, 3.0, 5);
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].transaction_id, 7);
+    assert_eq!(anomalies[0].typical_amount, 3.1);
+    assert!(anomalies[0].reason.contains("\"Product 1\""));
+
+    // Too little history: nothing is judged.
+    assert!(find_transaction_anomalies(&rows[..4], 3.0, 5).is_empty());
+}
+
+#[test]
+fn test_flags_unusual_days() {
+    let mut totals: Vec<(NaiveDate, i64)> = (1..=10)
+        .map(|d| (day(d), 2000 + (d as i64 % 4) * 100))
+        .collect();
+    totals.push((day(11), 15000));
+
+    let anomalies = find_day_anomalies(&totals, 3.0, 5);
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].date, "2025-01-11");
+    assert!(anomalies[0].reason.contains("above the typical"));
+}
@@ -0,0 +1,205 @@
+// tests/backup_test.rs
+
+use std::collections::HashMap;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::backups::models::BackupArchive;
+
+/// Replaces database ids with positions so archives from different accounts
+/// compare equal when they hold the same data.
+fn normalize(mut archive: BackupArchive) -> BackupArchive {
+    fn positions(ids: impl Iterator<Item = i32>) -> HashMap<i32, i32> {
+        ids.enumerate().map(|(idx, id)| (id, idx as i32)).collect()
+    }
+    let categories = positions(archive.categories.iter().map(|c| c.id));
+    let products = positions(archive.products.iter().map(|p| p.id));
+    let prices = positions(archive.product_prices.iter().map(|p| p.id));
+    let transactions = positions(archive.transactions.iter().map(|t| t.id));
+    let tags = positions(archive.tags.iter().map(|t| t.id));
+
+    for c in &mut archive.categories {
+        c.id = categories[&c.id];
+        c.parent_category_id = c.parent_category_id.map(|p| categories[&p]);
+    }
+    for p in &mut archive.products {
+        p.id = products[&p.id];
+        p.category_id = p.category_id.map(|c| categories[&c]);
+    }
+    for p in &mut archive.product_prices {
+        p.id = prices[&p.id];
+        p.product_id = products[&p.product_id];
+    }
+    for t in &mut archive.transactions {
+        t.id = transactions[&t.id];
+        t.product_id = products[&t.product_id];
+        t.product_price_id = prices[&t.product_price_id];
+    }
+    for t in &mut archive.tags {
+        t.id = tags[&t.id];
+    }
+    for link in &mut archive.transaction_tags {
+        link.transaction_id = transactions[&link.transaction_id];
+        link.tag_id = tags[&link.tag_id];
+    }
+    archive.created_at = chrono::NaiveDateTime::default();
+    archive.user.email = String::new();
+    archive
+}
+
+#[tokio::test]
+async fn test_backup_round_trip() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "mia@example.com").await;
+
+    let post = |path: &'static str, body: serde_json::Value| {
+        client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    // Groceries is created after Rent, so its parent is not the previous row.
+    for body in [
+        serde_json::json!({ "parent_category_id": null, "name": "Food" }),
+        serde_json::json!({ "parent_category_id": null, "name": "Rent" }),
+        serde_json::json!({ "parent_category_id": 1, "name": "Groceries" }),
+    ] {
+        assert!(post("/categories", body)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+    }
+    assert!(post(
+        "/products",
+        serde_json::json!({ "category_id": 3, "name": "Milk" })
+    )
+    .await
+    .unwrap()
+    .status()
+    .is_success());
+    assert!(post(
+        "/product_prices",
+        serde_json::json!({ "product_id": 1, "price": 1.19, "created_at": "2025-01-02T08:00:00" }),
+    )
+    .await
+    .unwrap()
+    .status()
+    .is_success());
+    for body in [
+        serde_json::json!({
+            "product_id": 1,
+            "product_price_id": 1,
+            "transaction_type": "Expense",
+            "description": "Weekly shop",
+            "date": "2025-01-02T08:00:00",
+            "tags": ["groceries", "weekly"]
+        }),
+        serde_json::json!({
+            "product_name": "Coffee",
+            "price": 3.5,
+            "transaction_type": "Expense",
+            "description": null,
+            "date": "2025-01-03T09:00:00",
+            "tags": ["weekly"]
+        }),
+        serde_json::json!({
+            "product_name": "Coffee",
+            "price": 3.5,
+            "transaction_type": "Expense",
+            "description": null,
+            "date": "2025-01-03T09:00:00",
+            "tags": []
+        }),
+        serde_json::json!({
+            "product_name": "Salary",
+            "price": 2500.0,
+            "transaction_type": "Income",
+            "description": "January",
+            "date": "2025-01-31T00:00:00",
+            "tags": [],
+            "external_id": "DE89:2025-01-31",
+            "account": "DE89370400440532013000",
+            "value_date": "2025-02-01"
+        }),
+    ] {
+        let resp = post("/transactions", body).await.unwrap();
+        assert!(resp.status().is_success());
+    }
+    assert!(client
+        .put(format!("{}/price-alerts/settings", base_url))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "threshold_percent": 25 }))
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+
+    let backup = |token: String| {
+        let request = client
+            .get(format!("{}/backup", base_url))
+            .bearer_auth(token);
+        async move {
+            let resp = request.send().await.unwrap();
+            assert!(resp.status().is_success());
+            resp.json::<BackupArchive>().await.unwrap()
+        }
+    };
+    let restore = |token: String, archive: &BackupArchive| {
+        let request = client
+            .post(format!("{}/restore", base_url))
+            .bearer_auth(token)
+            .json(archive);
+        async move { request.send().await.unwrap() }
+    };
+
+    let original = backup(token.clone()).await;
+    assert_eq!(original.version, 1);
+    assert_eq!(original.user.email, "mia@example.com");
+    assert_eq!(original.user.price_alert_threshold, 25);
+    assert_eq!(original.categories.len(), 3);
+    assert_eq!(original.transactions.len(), 4);
+    assert_eq!(original.transaction_tags.len(), 3);
+
+    // Into an empty account: everything is created with new ids.
+    let other = sign_up_and_login(&base_url, &client, "noah@example.com").await;
+    let summary = restore(other.clone(), &original)
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(summary["transactions"]["created"], 4);
+    assert_eq!(summary["product_prices"]["created"], 3);
+
+    let restored = backup(other.clone()).await;
+    assert_ne!(restored.transactions[0].id, original.transactions[0].id);
+    assert_eq!(restored.user.email, "noah@example.com");
+    let original = normalize(original);
+    assert_eq!(normalize(restored), original);
+
+    // Restoring again matches every row instead of duplicating it.
+    let again = backup(other.clone()).await;
+    let summary = restore(other.clone(), &again)
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(summary["transactions"]["created"], 0);
+    assert_eq!(summary["transactions"]["existing"], 4);
+    assert_eq!(summary["transaction_tags"]["created"], 0);
+    assert_eq!(normalize(backup(other.clone()).await), original);
+
+    // Broken archives are refused before anything is written.
+    let mut broken = again;
+    broken.transaction_tags[0].tag_id = 9999;
+    let resp = restore(other.clone(), &broken).await;
+    assert!(resp.status().is_client_error());
+    broken.version = 2;
+    let resp = restore(other, &broken).await;
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(
+        body["error"],
+        "Unsupported backup version 2 (this server reads up to 1)"
+    );
+}
@@ -0,0 +1,123 @@
+// tests/camt_mt940_import_test.rs
+
+use reqwest::multipart::{Form, Part};
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::imports::camt_import::parse_camt053;
+use crate::domain::imports::mt940_import::parse_mt940;
+use crate::domain::transactions::models::TransactionType;
+
+const CAMT053: &[u8] = include_bytes!("fixtures/camt053_statement.xml");
+const MT940: &[u8] = include_bytes!("fixtures/mt940_statement.sta");
+
+#[test]
+fn test_parse_camt053_fixture() {
+    let parsed = parse_camt053(CAMT053).unwrap();
+    // The pending entry is skipped, the one without a booking date fails.
+    assert_eq!(parsed.rows.len(), 2);
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].line, 4);
+
+    let rent = &parsed.rows[0];
+    assert_eq!(rent.amount, 85000);
+    assert_eq!(rent.transaction_type, TransactionType::Expense);
+    assert_eq!(rent.product_name, "Hausverwaltung Schmidt & Co");
+    assert_eq!(
+        rent.description.as_deref(),
+        Some("Miete Januar 2025 Wohnung 3B")
+    );
+    assert_eq!(rent.account.as_deref(), Some("DE89370400440532013000"));
+    assert_eq!(rent.date.to_string(), "2025-01-02 00:00:00");
+    assert_eq!(rent.value_date.unwrap().to_string(), "2025-01-03");
+    assert_eq!(
+        rent.external_id.as_deref(),
+        Some("DE89370400440532013000:2025010200001")
+    );
+
+    let salary = &parsed.rows[1];
+    assert_eq!(salary.transaction_type, TransactionType::Income);
+    assert_eq!(salary.product_name, "ACME GmbH");
+    assert_eq!(salary.description.as_deref(), Some("Gehalt 01/2025"));
+
+    assert!(parse_camt053(b"<OFX></OFX>").is_err());
+}
+
+#[test]
+fn test_parse_mt940_fixture() {
+    let parsed = parse_mt940(MT940).unwrap();
+    assert_eq!(parsed.rows.len(), 3);
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].line, 14);
+
+    let rent = &parsed.rows[0];
+    assert_eq!(rent.amount, 85000);
+    assert_eq!(rent.transaction_type, TransactionType::Expense);
+    assert_eq!(rent.product_name, "Hausverwaltung Schmidt & Co");
+    assert_eq!(
+        rent.description.as_deref(),
+        Some("Miete Januar 2025 Wohnung 3B")
+    );
+    assert_eq!(rent.account.as_deref(), Some("37040044/0532013000"));
+    assert_eq!(
+        rent.external_id.as_deref(),
+        Some("37040044/0532013000:B5A02XYZ0001")
+    );
+
+    let salary = &parsed.rows[1];
+    assert_eq!(salary.transaction_type, TransactionType::Income);
+    assert_eq!(salary.amount, 310000);
+    assert_eq!(salary.product_name, "ACME GmbH");
+
+    // Booked on Dec 31st for a Jan 2nd value date; NONREF gives no external id.
+    let fee = &parsed.rows[2];
+    assert_eq!(fee.date.to_string(), "2024-12-31 00:00:00");
+    assert_eq!(fee.value_date.unwrap().to_string(), "2025-01-02");
+    assert_eq!(fee.product_name, "Kontofuehrungsgebuehr Dezember");
+    assert!(fee.external_id.is_none());
+
+    assert!(parse_mt940(b"date,amount\n").is_err());
+}
+
+#[tokio::test]
+async fn test_camt053_reimport_deduplicates_by_entry_reference() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "heidi@example.com").await;
+
+    // Drop the broken entry so the statement can be committed.
+    let statement = String::from_utf8_lossy(CAMT053).replace(
+        "<AcctSvcrRef>2025013100002</AcctSvcrRef>",
+        "<BookgDt><Dt>2025-01-31</Dt></BookgDt><AcctSvcrRef>2025013100002</AcctSvcrRef>",
+    );
+    let import = || {
+        client
+            .post(format!("{}/import/camt053", base_url))
+            .bearer_auth(&token)
+            .multipart(
+                Form::new()
+                    .part(
+                        "file",
+                        Part::bytes(statement.as_bytes().to_vec()).file_name("camt053.xml"),
+                    )
+                    .text("dry_run", "false"),
+            )
+            .send()
+    };
+
+    let first = import()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(first["imported"], 3);
+    assert_eq!(first["rows"][0]["value_date"], "2025-01-03");
+
+    let second = import()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(second["imported"], 0);
+    assert_eq!(second["skipped"], 3);
+}
@@ -0,0 +1,168 @@
+// tests/csv_import_test.rs
+
+use reqwest::multipart::{Form, Part};
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::imports::csv_import::parse_csv;
+use crate::domain::imports::models::CsvMapping;
+use crate::domain::imports::services::{parse_amount, reject_out_of_range_amounts};
+use crate::domain::transactions::models::TransactionType;
+
+const STATEMENT: &str = "\
+Export for account 1234
+Datum;Betrag;Empfaenger;Tags
+03.01.2025;-1.234,50;Landlord;rent|home
+04.01.2025;2.500,00;Employer;
+05.01.2025;abc;Broken;
+";
+
+fn german_mapping() -> CsvMapping {
+    serde_json::from_value(serde_json::json!({
+        "delimiter": ";",
+        "skip_rows": 1,
+        "date_column": "Datum",
+        "date_format": "%d.%m.%Y",
+        "decimal_separator": ",",
+        "amount_column": "Betrag",
+        "description_column": "Empfaenger",
+        "tags_column": "Tags"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_parse_amount_formats() {
+    assert_eq!(parse_amount("12.34", '.'), Ok(1234));
+    assert_eq!(parse_amount("-1,234.5", '.'), Ok(-123450));
+    assert_eq!(parse_amount("1.234,56", ','), Ok(123456));
+    assert_eq!(parse_amount("(7.00)", '.'), Ok(-700));
+    assert!(parse_amount("", '.').is_err());
+    assert!(parse_amount("1,2,3", ',').is_err());
+}
+
+#[test]
+fn test_parse_csv_with_mapping() {
+    let parsed = parse_csv(STATEMENT.as_bytes(), &german_mapping()).unwrap();
+    assert_eq!(parsed.rows.len(), 2);
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].line, 5);
+
+    let rent = &parsed.rows[0];
+    assert_eq!(rent.amount, 123450);
+    assert_eq!(rent.transaction_type, TransactionType::Expense);
+    assert_eq!(rent.product_name, "Landlord");
+    assert_eq!(rent.tags, vec!["rent", "home"]);
+    assert_eq!(parsed.rows[1].transaction_type, TransactionType::Income);
+
+    let mut mapping = german_mapping();
+    mapping.amount_column = "Amount".to_string();
+    assert!(parse_csv(STATEMENT.as_bytes(), &mapping).is_err());
+}
+
+#[test]
+fn test_amounts_too_large_for_a_price_are_rejected() {
+    let statement = "\
+Export for account 1234
+Datum;Betrag;Empfaenger;Tags
+03.01.2025;-21.474.836,47;Largest;
+04.01.2025;21.474.836,48;Too large;
+";
+    let mut parsed = parse_csv(statement.as_bytes(), &german_mapping()).unwrap();
+    assert_eq!(parsed.rows.len(), 2);
+    reject_out_of_range_amounts(&mut parsed);
+    assert_eq!(parsed.rows.len(), 1);
+    assert_eq!(parsed.rows[0].amount, i32::MAX as i64);
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].line, 4);
+    assert_eq!(parsed.errors[0].message, "Amount out of range: 21474836.48");
+}
+
+#[tokio::test]
+async fn test_csv_import_dry_run_and_commit() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "frank@example.com").await;
+
+    let mut profile = serde_json::to_value(german_mapping()).unwrap();
+    profile["name"] = serde_json::json!("Sparkasse");
+    let resp = client
+        .post(format!("{}/import-profiles", base_url))
+        .bearer_auth(&token)
+        .json(&profile)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let profile_id = resp.json::<serde_json::Value>().await.unwrap()["id"].clone();
+
+    let upload = |body: &str, dry_run: &'static str| {
+        Form::new()
+            .part(
+                "file",
+                Part::bytes(body.as_bytes().to_vec()).file_name("statement.csv"),
+            )
+            .text("profile_id", profile_id.to_string())
+            .text("dry_run", dry_run)
+    };
+
+    // A dry run previews rows and reports the broken line without writing.
+    let preview = client
+        .post(format!("{}/import/csv", base_url))
+        .bearer_auth(&token)
+        .multipart(upload(STATEMENT, "true"))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["imported"], 0);
+    assert_eq!(preview["rows"].as_array().unwrap().len(), 2);
+    assert_eq!(preview["rows"][0]["amount"], 1234.5);
+    assert_eq!(preview["errors"][0]["line"], 5);
+
+    // Committing is refused while any line fails to parse.
+    let resp = client
+        .post(format!("{}/import/csv", base_url))
+        .bearer_auth(&token)
+        .multipart(upload(STATEMENT, "false"))
+        .send()
+        .await
+        .unwrap();
+    assert!(!resp.status().is_success());
+
+    let clean = STATEMENT.rsplit_once("05.01.2025").unwrap().0;
+    let result = client
+        .post(format!("{}/import/csv", base_url))
+        .bearer_auth(&token)
+        .multipart(upload(clean, "false"))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(result["imported"], 2);
+
+    let transactions = client
+        .get(format!("{}/transactions", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(transactions.as_array().unwrap().len(), 2);
+
+    let tags = client
+        .get(format!("{}/tags", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(tags.as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,204 @@
+// tests/duplicate_test.rs
+
+use chrono::NaiveDate;
+use reqwest::multipart::{Form, Part};
+use std::collections::HashSet;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::duplicates::models::DuplicateTransaction;
+use crate::domain::duplicates::services::find_duplicate_candidates;
+use crate::domain::transactions::models::TransactionType;
+
+const STATEMENT: &str = "\
+date,amount,payee
+2025-01-04,-42.10,Grocery Mart
+2025-01-04,-3.50,Coffee Corner
+2025-01-04,-3.50,Coffee Corner
+";
+
+fn side(id: i32, name: &str, day: u32, external_id: Option<&str>) -> DuplicateTransaction {
+    DuplicateTransaction {
+        id,
+        product_name: name.to_string(),
+        description: None,
+        amount: 42.1,
+        transaction_type: TransactionType::Expense,
+        date: NaiveDate::from_ymd_opt(2025, 1, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+        external_id: external_id.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_candidate_rules() {
+    let transactions = vec![
+        side(1, "Grocery Mart", 3, None),
+        side(2, "GROCERY MART Berlin", 4, Some("A1")),
+        side(3, "Grocery Mart", 10, None),
+        side(4, "Grocery Mart", 4, Some("A2")),
+    ];
+    let found = find_duplicate_candidates(&transactions, 3, 0.5, &HashSet::new());
+    let pairs: Vec<(i32, i32)> = found
+        .iter()
+        .map(|c| (c.transaction.id, c.duplicate.id))
+        .collect();
+    // 2 and 4 carry different bank ids, 3 is a week later.
+    assert_eq!(pairs, vec![(1, 2), (1, 4)]);
+    assert_eq!(found[0].similarity, 0.67);
+
+    let dismissed = HashSet::from([(1, 2)]);
+    let found = find_duplicate_candidates(&transactions, 3, 0.5, &dismissed);
+    assert_eq!(found.len(), 1);
+}
+
+#[tokio::test]
+async fn test_same_day_purchases_and_duplicate_review() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "ivan@example.com").await;
+
+    let create = |name: &'static str, price: f64, tags: Vec<&'static str>| {
+        client
+            .post(format!("{}/transactions", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "product_name": name,
+                "price": price,
+                "transaction_type": "Expense",
+                "description": null,
+                "date": "2025-01-03T00:00:00",
+                "tags": tags
+            }))
+            .send()
+    };
+    // The same product twice on the same day is no longer rejected.
+    assert!(create("Coffee", 3.0, vec![])
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+    assert!(create("Coffee", 3.0, vec![])
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+    assert!(create("Grocery Mart", 42.1, vec!["food"])
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+
+    let mapping = serde_json::json!({
+        "date_column": "date",
+        "amount_column": "amount",
+        "description_column": "payee"
+    });
+    let import = || {
+        client
+            .post(format!("{}/import/csv", base_url))
+            .bearer_auth(&token)
+            .multipart(
+                Form::new()
+                    .part(
+                        "file",
+                        Part::bytes(STATEMENT.as_bytes().to_vec()).file_name("statement.csv"),
+                    )
+                    .text("mapping", mapping.to_string())
+                    .text("dry_run", "false"),
+            )
+            .send()
+    };
+
+    // Identical lines in one file are both imported, and only once.
+    let first = import()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(first["imported"], 3);
+    let second = import()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(second["imported"], 0);
+    assert_eq!(second["skipped"], 3);
+
+    let candidates = client
+        .get(format!("{}/transactions/duplicates", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let pairs: Vec<(i64, i64)> = candidates
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["transaction"]["id"].as_i64().unwrap(),
+                c["duplicate"]["id"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    // Manual coffees, manual vs imported groceries, imported coffees.
+    assert_eq!(pairs, vec![(1, 2), (3, 4), (5, 6)]);
+
+    let review = |transaction_id: i64, duplicate_id: i64, action: &'static str| {
+        client
+            .post(format!("{}/transactions/duplicates/review", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "transaction_id": transaction_id,
+                "duplicate_id": duplicate_id,
+                "action": action
+            }))
+            .send()
+    };
+    assert!(review(1, 2, "Dismiss").await.unwrap().status().is_success());
+    assert!(review(5, 6, "Dismiss").await.unwrap().status().is_success());
+    assert!(!review(1, 2, "Dismiss").await.unwrap().status().is_success());
+    assert!(review(3, 4, "Merge").await.unwrap().status().is_success());
+
+    let candidates = client
+        .get(format!("{}/transactions/duplicates", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(candidates, serde_json::json!([]));
+
+    // The merged grocery keeps its tag and takes over the statement line,
+    // so re-importing still skips everything.
+    let transactions = client
+        .get(format!("{}/transactions", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let transactions = transactions.as_array().unwrap();
+    assert_eq!(transactions.len(), 5);
+    let grocery = transactions.iter().find(|t| t["id"] == 3).unwrap();
+    assert_eq!(grocery["tags"], serde_json::json!([1]));
+
+    let third = import()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(third["imported"], 0);
+    assert_eq!(third["skipped"], 3);
+}
@@ -0,0 +1,179 @@
+// tests/email_test.rs
+
+use reqwest::StatusCode;
+
+use super::workflow_test::{sent_emails, spawn_app, token_from_email};
+use crate::domain::users::services::normalize_email;
+use crate::mailer::{Email, FileMailer, Mailer};
+
+#[test]
+fn test_normalize_email() {
+    assert_eq!(
+        normalize_email("  Alice.Smith+fin@Example.COM "),
+        Ok("alice.smith+fin@example.com".to_string())
+    );
+    assert_eq!(
+        normalize_email("o'brien@mail.example-host.co.uk"),
+        Ok("o'brien@mail.example-host.co.uk".to_string())
+    );
+    for invalid in [
+        "",
+        "alice",
+        "alice@",
+        "@example.com",
+        "alice@@example.com",
+        "alice@example",
+        "alice@example.c",
+        "alice@example.123",
+        "alice@-example.com",
+        "alice@example..com",
+        ".alice@example.com",
+        "alice..smith@example.com",
+        "alice smith@example.com",
+        "\"alice\"@example.com",
+    ] {
+        assert!(
+            normalize_email(invalid).is_err(),
+            "{invalid:?} was accepted"
+        );
+    }
+    let long_local = format!("{}@example.com", "a".repeat(65));
+    assert!(normalize_email(&long_local).is_err());
+}
+
+#[test]
+fn test_file_mailer() {
+    let dir = tempfile::tempdir().unwrap();
+    let mailer = FileMailer::new(dir.path().join("outbox"));
+    for subject in ["First", "Second"] {
+        let email = Email {
+            to: "bob@example.com".to_string(),
+            subject: subject.to_string(),
+            body: "Hello".to_string(),
+        };
+        mailer.send(&email).unwrap();
+    }
+
+    let mut files: Vec<_> = std::fs::read_dir(dir.path().join("outbox"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    files.sort();
+    assert_eq!(files.len(), 2);
+    let mail = std::fs::read_to_string(&files[1]).unwrap();
+    assert!(mail.starts_with("To: bob@example.com\r\nSubject: Second\r\n"));
+    assert!(mail.ends_with("\r\n\r\nHello\r\n"));
+}
+
+#[tokio::test]
+async fn test_email_verification() {
+    let (base_url, client) = spawn_app().await;
+
+    let sign_up = |email: &'static str| {
+        let request = client
+            .post(format!("{}/users", base_url))
+            .json(&serde_json::json!({ "email": email, "password_hash": "secret123" }));
+        async move { request.send().await.unwrap() }
+    };
+    let body = sign_up("not-an-email")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(body["error"], "'not-an-email' is not a valid email address");
+
+    let body = sign_up("  Vera@Example.com ")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(body["email"], "vera@example.com");
+    let body = sign_up("VERA@example.com")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(body["error"], "A user with that email already exists");
+
+    // Login works before verification, with any casing of the address.
+    let resp = client
+        .post(format!("{}/login", base_url))
+        .json(&serde_json::json!({ "email": "Vera@EXAMPLE.com", "password_hash": "secret123" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let get = |path: &'static str| {
+        let request = client
+            .get(format!("{}{}", base_url, path))
+            .bearer_auth(&token);
+        async move { request.send().await.unwrap() }
+    };
+    let account = get("/users/me")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(
+        account,
+        serde_json::json!({
+            "id": 1,
+            "email": "vera@example.com",
+            "email_verified": false,
+            "two_factor_enabled": false
+        })
+    );
+
+    // Exports and backups stay locked, everything else works.
+    assert!(get("/transactions").await.status().is_success());
+    for path in ["/export/transactions", "/backup"] {
+        let resp = get(path).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body = resp.json::<serde_json::Value>().await.unwrap();
+        assert_eq!(
+            body["error"],
+            "Verify your email address to use this feature"
+        );
+    }
+
+    // Resending replaces the first token.
+    let stale = token_from_email(&base_url, "vera@example.com", "Verification token: ").unwrap();
+    let resend = || {
+        let request = client
+            .post(format!("{}/verify-email/resend", base_url))
+            .bearer_auth(&token);
+        async move { request.send().await.unwrap() }
+    };
+    assert!(resend().await.status().is_success());
+    assert_eq!(sent_emails(&base_url).len(), 2);
+    let fresh = token_from_email(&base_url, "vera@example.com", "Verification token: ").unwrap();
+
+    let verify = |token: String| {
+        let request = client
+            .post(format!("{}/verify-email", base_url))
+            .json(&serde_json::json!({ "token": token }));
+        async move { request.send().await.unwrap() }
+    };
+    let body = verify(stale)
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(body["error"], "Invalid or expired verification token");
+    assert!(verify(fresh.clone()).await.status().is_success());
+    assert!(verify(fresh).await.status().is_client_error());
+
+    let account = get("/users/me")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(account["email_verified"], true);
+    assert!(get("/export/transactions").await.status().is_success());
+    assert!(get("/backup").await.status().is_success());
+    let body = resend().await.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "Email address is already verified");
+}
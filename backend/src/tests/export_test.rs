@@ -0,0 +1,127 @@
+// tests/export_test.rs
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+
+#[tokio::test]
+async fn test_exports() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "judy@example.com").await;
+
+    let post = |path: &'static str, body: serde_json::Value| {
+        client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    for body in [
+        serde_json::json!({ "parent_category_id": null, "name": "Food" }),
+        serde_json::json!({ "parent_category_id": 1, "name": "Groceries" }),
+    ] {
+        assert!(post("/categories", body)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+    }
+    let resp = post(
+        "/products",
+        serde_json::json!({ "category_id": 2, "name": "Milk, whole" }),
+    )
+    .await
+    .unwrap();
+    assert!(resp.status().is_success());
+
+    for (name, price, date, tags) in [
+        (
+            "Milk, whole",
+            2.99,
+            "2025-01-08T12:00:00",
+            vec!["dairy", "weekly"],
+        ),
+        ("Rent", 900.0, "2025-02-01T00:00:00", vec![]),
+        ("Milk, whole", 3.09, "2025-02-08T12:00:00", vec!["dairy"]),
+    ] {
+        let resp = post(
+            "/transactions",
+            serde_json::json!({
+                "product_name": name,
+                "price": price,
+                "transaction_type": "Expense",
+                "description": null,
+                "date": date,
+                "tags": tags
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let export = |path: &str| {
+        client
+            .get(format!("{}/export/{}", base_url, path))
+            .bearer_auth(&token)
+            .send()
+    };
+
+    let resp = export("transactions").await.unwrap();
+    assert_eq!(resp.headers()["content-type"], "text/csv; charset=utf-8");
+    assert_eq!(
+        resp.headers()["content-disposition"],
+        "attachment; filename=\"transactions.csv\""
+    );
+    let csv = resp.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,date,transaction_type,product_id,product,category_path,price,description,tags,account,value_date,external_id"
+    );
+    assert_eq!(
+        lines[1],
+        "1,2025-01-08T12:00:00,Expense,1,\"Milk, whole\",Food > Groceries,2.99,,dairy|weekly,,,"
+    );
+    assert_eq!(
+        lines[2],
+        "2,2025-02-01T00:00:00,Expense,2,Rent,,900.00,,,,,"
+    );
+    assert_eq!(lines.len(), 4);
+
+    // Exports honor the transaction list filters.
+    let jsonl = export("transactions?format=jsonl&tag_id=1&from=2025-02-01")
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let rows: Vec<serde_json::Value> = jsonl
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["id"], 3);
+    assert_eq!(rows[0]["price"], 3.09);
+    assert_eq!(rows[0]["category_path"], "Food > Groceries");
+    assert_eq!(rows[0]["tags"], serde_json::json!(["dairy"]));
+
+    let categories = export("categories").await.unwrap().text().await.unwrap();
+    assert_eq!(
+        categories,
+        "id,name,parent_category_id,path\n1,Food,,Food\n2,Groceries,1,Food > Groceries\n"
+    );
+    let prices = export("prices?format=jsonl")
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(prices.lines().count(), 3);
+
+    let resp = export("products?format=xlsx").await.unwrap();
+    assert!(resp.status().is_success());
+    let xlsx = resp.bytes().await.unwrap();
+    assert!(xlsx.starts_with(b"PK"));
+
+    let resp = export("products?format=pdf").await.unwrap();
+    assert!(resp.status().is_client_error());
+}
@@ -0,0 +1,57 @@
+// tests/forecast_test.rs
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::domain::analytics::services::{forecast_month, ExpenseRow};
+
+fn row(product_id: i32, name: &str, category: &str, date: NaiveDate, amount: i64) -> ExpenseRow {
+    ExpenseRow {
+        transaction_id: 0,
+        date,
+        product_id,
+        product_name: name.to_string(),
+        category_name: Some(category.to_string()),
+        amount,
+    }
+}
+
+#[test]
+fn test_forecast_uses_weekdays_and_recurring_items() {
+    let mut expenses = Vec::new();
+
+    // Coffee every Monday since December, including this month.
+    let mut day = NaiveDate::from_ymd_opt(2024, 12, 2).unwrap();
+    while day <= NaiveDate::from_ymd_opt(2025, 6, 9).unwrap() {
+        assert_eq!(day.weekday(), Weekday::Mon);
+        expenses.push(row(1, "Coffee", "Drinks", day, 500));
+        day += Duration::days(7);
+    }
+
+    // Rent on the 1st of each of the last three months; not yet paid in June.
+    for month in 3..=5 {
+        let date = NaiveDate::from_ymd_opt(2025, month, 1).unwrap();
+        expenses.push(row(2, "Rent", "Housing", date, 100_000));
+    }
+
+    let as_of = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+    let forecast = forecast_month(&expenses, as_of);
+
+    assert_eq!(forecast.month, "2025-06");
+    assert_eq!(forecast.days_remaining, 20);
+
+    let drinks = &forecast.categories[0];
+    assert_eq!(drinks.category_name, "Drinks");
+    assert_eq!(drinks.month_to_date, 10.0);
+    // Three Mondays left (16th, 23rd, 30th) at $5 each.
+    assert_eq!(drinks.expected_variable, 15.0);
+    assert_eq!(drinks.projected_total, 25.0);
+    assert_eq!((drinks.low, drinks.high), (25.0, 25.0));
+
+    let housing = &forecast.categories[1];
+    assert_eq!(housing.category_name, "Housing");
+    assert_eq!(housing.month_to_date, 0.0);
+    assert_eq!(housing.expected_recurring, 1000.0);
+    assert_eq!(housing.expected_variable, 0.0);
+
+    assert_eq!(forecast.total.projected_total, 1025.0);
+}
@@ -0,0 +1,64 @@
+// tests/heatmap_test.rs
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+
+#[tokio::test]
+async fn test_heatmap_buckets_and_tag_filter() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "erin@example.com").await;
+
+    // 2025-01-06 is a Monday.
+    for (name, price, date, tags) in [
+        ("Coffee", 3.5, "2025-01-06T08:15:00", vec!["work"]),
+        ("Snack", 2.0, "2025-01-06T08:45:00", vec![]),
+        ("Pizza", 12.0, "2025-01-11T22:30:00", vec!["work"]),
+    ] {
+        let resp = client
+            .post(format!("{}/transactions", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "product_name": name,
+                "price": price,
+                "transaction_type": "Expense",
+                "description": null,
+                "date": date,
+                "tags": tags
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let heatmap = client
+        .get(format!("{}/spending-heatmap", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let cells = heatmap["by_weekday_hour"].as_array().unwrap();
+    assert_eq!(cells.len(), 7 * 24);
+    // Monday 08:00
+    assert_eq!(cells[8]["total_spending"], 5.5);
+    assert_eq!(cells[8]["count"], 2);
+    // Saturday 22:00
+    assert_eq!(cells[5 * 24 + 22]["total_spending"], 12.0);
+    let days = heatmap["by_day_of_month"].as_array().unwrap();
+    assert_eq!(days.len(), 31);
+    assert_eq!(days[5]["count"], 2);
+
+    let heatmap = client
+        .get(format!("{}/spending-heatmap?tag_id=1", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(heatmap["by_weekday_hour"][8]["total_spending"], 3.5);
+    assert_eq!(heatmap["by_weekday_hour"][8]["count"], 1);
+}
@@ -0,0 +1,252 @@
+// tests/household_test.rs
+
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+use super::workflow_test::{sign_up_and_login, spawn_app, Api};
+
+#[tokio::test]
+async fn test_shared_household_roles_and_invitations() {
+    let (base_url, client) = spawn_app().await;
+    let alice = sign_up_and_login(&base_url, &client, "alice@example.com").await;
+    let bob = sign_up_and_login(&base_url, &client, "bob@example.com").await;
+    let carol = sign_up_and_login(&base_url, &client, "carol@example.com").await;
+    let api = Api { base_url, client };
+
+    // Everyone starts with a personal household that their data goes to.
+    let (_, households) = api.get("/households", &alice, None).await;
+    assert_eq!(households.as_array().unwrap().len(), 1);
+    assert_eq!(households[0]["name"], "Personal");
+    assert_eq!(households[0]["role"], "owner");
+    let category = json!({ "parent_category_id": null, "name": "Private" });
+    let (status, _) = api.post("/categories", &alice, None, category).await;
+    assert!(status.is_success());
+
+    // A shared household, with an editor and a viewer invited into it.
+    let (_, family) = api
+        .post("/households", &alice, None, json!({ "name": "Family" }))
+        .await;
+    let family_id = family["id"].as_i64().unwrap();
+    assert_eq!(family["role"], "owner");
+
+    let invitations = format!("/households/{family_id}/invitations");
+    let editor = json!({ "role": "editor" });
+    let (_, invitation) = api.post(&invitations, &alice, None, editor).await;
+    let editor_token = json!({ "token": invitation["token"] });
+    let (_, joined) = api
+        .post("/households/join", &bob, None, editor_token.clone())
+        .await;
+    assert_eq!(joined["id"], family_id);
+    assert_eq!(joined["role"], "editor");
+    // Invitations work once, and only owners hand them out.
+    let (status, body) = api
+        .post("/households/join", &carol, None, editor_token)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Invalid or expired invitation");
+    let viewer = json!({ "role": "viewer" });
+    let (status, _) = api.post(&invitations, &bob, None, viewer.clone()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, invitation) = api.post(&invitations, &alice, None, viewer).await;
+    let viewer_token = json!({ "token": invitation["token"] });
+    let (status, _) = api
+        .post("/households/join", &carol, None, viewer_token)
+        .await;
+    assert!(status.is_success());
+
+    // The header switches the household; the data stays apart.
+    let bread = json!({
+        "product_name": "Bread",
+        "price": 2.5,
+        "transaction_type": "Expense",
+        "description": null,
+        "date": "2025-05-01T08:00:00",
+        "tags": ["bakery"]
+    });
+    let (status, _) = api
+        .post("/transactions", &bob, Some(family_id), bread)
+        .await;
+    assert!(status.is_success());
+    let (_, shared) = api.get("/transactions", &alice, Some(family_id)).await;
+    assert_eq!(shared.as_array().unwrap().len(), 1);
+    assert_eq!(shared[0]["user_id"], 2);
+    assert_eq!(api.get("/transactions", &bob, None).await.1, json!([]));
+    let (_, categories) = api.get("/categories", &alice, Some(family_id)).await;
+    assert_eq!(categories, json!([]));
+    let (_, categories) = api.get("/categories", &alice, None).await;
+    assert_eq!(categories[0]["name"], "Private");
+
+    // Viewers read but do not write; strangers get nothing.
+    let (_, seen) = api.get("/transactions", &carol, Some(family_id)).await;
+    assert_eq!(seen, shared);
+    let tag = json!({ "name": "groceries" });
+    let (status, body) = api.post("/tags", &carol, Some(family_id), tag).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Viewers cannot change household data");
+    let bobs_personal = 2;
+    let (status, _) = api.get("/transactions", &alice, Some(bobs_personal)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Owners manage roles, but a household always keeps an owner.
+    let member = |user_id: i32| format!("/households/{family_id}/members/{user_id}");
+    let editor = || Some(json!({ "role": "editor" }));
+    let (status, body) = api
+        .call(Method::PUT, &member(1), &alice, None, editor())
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "A household needs at least one owner");
+    let (status, _) = api
+        .call(Method::PUT, &member(3), &bob, None, editor())
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, updated) = api
+        .call(Method::PUT, &member(3), &alice, None, editor())
+        .await;
+    assert_eq!(updated["email"], "carol@example.com");
+    assert_eq!(updated["role"], "editor");
+    let (status, _) = api
+        .call(Method::DELETE, &member(1), &alice, None, None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Members can leave; the household is closed to them afterwards.
+    let (status, _) = api
+        .call(Method::DELETE, &member(3), &carol, None, None)
+        .await;
+    assert!(status.is_success());
+    let (status, _) = api.get("/transactions", &carol, Some(family_id)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let members_path = format!("/households/{family_id}/members");
+    let (_, members) = api.get(&members_path, &alice, None).await;
+    let emails: Vec<&str> = members
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["email"].as_str().unwrap())
+        .collect();
+    assert_eq!(emails, ["alice@example.com", "bob@example.com"]);
+
+    // A deleted account leaves what it entered in shared households behind.
+    let password = Some(json!({ "password": "secret123" }));
+    let (status, _) = api
+        .call(Method::DELETE, "/users/me", &bob, None, password)
+        .await;
+    assert!(status.is_success());
+    let (_, shared) = api.get("/transactions", &alice, Some(family_id)).await;
+    assert_eq!(shared.as_array().unwrap().len(), 1);
+    assert_eq!(shared[0]["user_id"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_deleting_the_owner_hands_shared_households_over() {
+    let (base_url, client) = spawn_app().await;
+    let alice = sign_up_and_login(&base_url, &client, "alice@example.com").await;
+    let bob = sign_up_and_login(&base_url, &client, "bob@example.com").await;
+    let api = Api { base_url, client };
+
+    let (_, family) = api
+        .post("/households", &alice, None, json!({ "name": "Family" }))
+        .await;
+    let family_id = family["id"].as_i64().unwrap();
+    let invitations = format!("/households/{family_id}/invitations");
+    let viewer = json!({ "role": "viewer" });
+    let (_, invitation) = api.post(&invitations, &alice, None, viewer).await;
+    let token = json!({ "token": invitation["token"] });
+    let (status, _) = api.post("/households/join", &bob, None, token).await;
+    assert!(status.is_success());
+    let tag = json!({ "name": "groceries" });
+    let (status, _) = api.post("/tags", &alice, Some(family_id), tag).await;
+    assert!(status.is_success());
+
+    let password = Some(json!({ "password": "secret123" }));
+    let (status, _) = api
+        .call(Method::DELETE, "/users/me", &alice, None, password)
+        .await;
+    assert!(status.is_success());
+
+    // Bob now owns the household, and its data is still there; Alice's
+    // personal household went with her.
+    let (_, households) = api.get("/households", &bob, None).await;
+    let family = households
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|h| h["id"] == family_id)
+        .expect("the shared household survives");
+    assert_eq!(family["role"], "owner");
+    let (_, tags) = api.get("/tags", &bob, Some(family_id)).await;
+    assert_eq!(tags[0]["name"], "groceries");
+    let (status, _) = api.get("/transactions", &bob, Some(1)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_transactions_only_use_the_households_own_records() {
+    let (base_url, client) = spawn_app().await;
+    let alice = sign_up_and_login(&base_url, &client, "alice@example.com").await;
+    let bob = sign_up_and_login(&base_url, &client, "bob@example.com").await;
+    let api = Api { base_url, client };
+    let expense = |product: Value, price_id: Value, tags: Value| {
+        json!({
+            "product_id": product,
+            "product_name": "Tea",
+            "product_price_id": price_id,
+            "price": 3.5,
+            "transaction_type": "Expense",
+            "description": null,
+            "date": "2025-05-20T08:00:00",
+            "tags": tags
+        })
+    };
+
+    let (_, alices) = api
+        .post(
+            "/transactions",
+            &alice,
+            None,
+            expense(Value::Null, Value::Null, json!(["private"])),
+        )
+        .await;
+    let (_, bobs) = api
+        .post(
+            "/transactions",
+            &bob,
+            None,
+            expense(Value::Null, Value::Null, Value::Null),
+        )
+        .await;
+
+    // Alice's product, a price of Alice's product and Alice's tag are all
+    // out of Bob's reach.
+    for attempt in [
+        expense(alices["product"]["id"].clone(), Value::Null, Value::Null),
+        expense(
+            bobs["product"]["id"].clone(),
+            alices["product_price"]["id"].clone(),
+            Value::Null,
+        ),
+        expense(Value::Null, Value::Null, json!([alices["tags"][0]["id"]])),
+    ] {
+        let (status, body) = api.post("/transactions", &bob, None, attempt).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["error"],
+            "Product, price or tag not found in this household"
+        );
+    }
+
+    // His own product and price are fine.
+    let (status, _) = api
+        .post(
+            "/transactions",
+            &bob,
+            None,
+            expense(
+                bobs["product"]["id"].clone(),
+                bobs["product_price"]["id"].clone(),
+                Value::Null,
+            ),
+        )
+        .await;
+    assert!(status.is_success());
+}
@@ -0,0 +1,221 @@
+// tests/journal_test.rs
+
+use reqwest::multipart::{Form, Part};
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::imports::beancount_import::parse_beancount;
+use crate::domain::transactions::models::TransactionType;
+
+const JOURNAL: &str = r#"option "operating_currency" "EUR"
+
+2025-01-01 open Assets:Checking
+2025-01-01 open Expenses:Food
+
+pushtag #trip
+2025-01-03 * "Café Central" "Breakfast; two coffees" #work
+  tags: "receipts, shared"
+  external_id: "BANK:123"
+  Expenses:Food        12.40 EUR
+  Assets:Checking
+poptag #trip
+
+2025-01-04 txn "Refund"
+  Expenses:Food       -5.00 EUR ; store credit
+  Assets:Checking      5.00 EUR
+
+2025-01-05 * "Transfer to savings"
+  Assets:Savings      100.00 EUR
+  Assets:Checking
+
+2025-01-06 * "Salary"
+  Assets:Checking    2500.00 EUR
+  Income:Job        -2600.00 EUR
+"#;
+
+#[test]
+fn test_parse_beancount() {
+    let parsed = parse_beancount(JOURNAL.as_bytes()).unwrap();
+
+    let breakfast = &parsed.rows[0];
+    assert_eq!(breakfast.line, 7);
+    assert_eq!(breakfast.product_name, "Café Central");
+    assert_eq!(
+        breakfast.description.as_deref(),
+        Some("Breakfast; two coffees")
+    );
+    assert_eq!(breakfast.amount, 1240);
+    assert_eq!(breakfast.transaction_type, TransactionType::Expense);
+    assert_eq!(breakfast.tags, vec!["work", "trip", "receipts", "shared"]);
+    assert_eq!(breakfast.external_id.as_deref(), Some("BANK:123"));
+
+    // A negative expense is money coming back.
+    let refund = &parsed.rows[1];
+    assert_eq!(refund.product_name, "Refund");
+    assert_eq!(refund.transaction_type, TransactionType::Income);
+    assert_eq!(refund.amount, 500);
+    assert!(refund.tags.is_empty());
+
+    assert_eq!(parsed.rows.len(), 2);
+    let lines: Vec<usize> = parsed.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![18, 22]);
+    assert_eq!(parsed.errors[1].message, "Postings do not balance");
+
+    assert!(parse_beancount(b"just some text").is_err());
+
+    // Transfers come back from Equity:Transfers as transfers.
+    let settlement = parse_beancount(
+        b"2025-01-07 * \"Settlement\"\n  Equity:Transfers    14.50 EUR\n  Assets:Cash\n",
+    )
+    .unwrap();
+    assert_eq!(
+        settlement.rows[0].transaction_type,
+        TransactionType::Transfer
+    );
+    assert_eq!(settlement.rows[0].amount, 1450);
+}
+
+#[tokio::test]
+async fn test_journal_export_and_beancount_round_trip() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "kate@example.com").await;
+
+    let post = |path: &'static str, body: serde_json::Value| {
+        client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    for body in [
+        serde_json::json!({ "parent_category_id": null, "name": "Food" }),
+        serde_json::json!({ "parent_category_id": 1, "name": "eating out" }),
+    ] {
+        assert!(post("/categories", body)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+    }
+    assert!(post(
+        "/products",
+        serde_json::json!({ "category_id": 2, "name": "Pizza \"Roma\"" }),
+    )
+    .await
+    .unwrap()
+    .status()
+    .is_success());
+    for (name, price, kind, description, tags) in [
+        (
+            "Pizza \"Roma\"",
+            18.5,
+            "Expense",
+            Some("Team lunch"),
+            vec!["work", "team lunch"],
+        ),
+        ("Salary", 2500.0, "Income", None, vec![]),
+    ] {
+        let resp = post(
+            "/transactions",
+            serde_json::json!({
+                "product_name": name,
+                "price": price,
+                "transaction_type": kind,
+                "description": description,
+                "date": "2025-03-01T00:00:00",
+                "tags": tags
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let export = |token: String, query: &'static str| {
+        let request = client
+            .get(format!("{}/export/journal?{}", base_url, query))
+            .bearer_auth(token);
+        async move { request.send().await.unwrap().text().await.unwrap() }
+    };
+
+    let beancount = export(token.clone(), "format=beancount&commodity=EUR").await;
+    assert_eq!(
+        beancount,
+        r#"option "operating_currency" "EUR"
+
+1970-01-01 open Assets:Cash
+1970-01-01 open Expenses:Food
+1970-01-01 open Expenses:Food:Eating-Out
+1970-01-01 open Expenses:Uncategorized
+1970-01-01 open Income:Food
+1970-01-01 open Income:Food:Eating-Out
+1970-01-01 open Income:Uncategorized
+
+2025-03-01 * "Pizza \"Roma\"" "Team lunch"
+  tags: "team lunch, work"
+  Expenses:Food:Eating-Out       18.50 EUR
+  Assets:Cash                   -18.50 EUR
+
+2025-03-01 * "Salary" ""
+  Assets:Cash              2500.00 EUR
+  Income:Uncategorized    -2500.00 EUR
+
+"#
+    );
+
+    let hledger = export(token.clone(), "format=hledger&transaction_type=Expense").await;
+    assert_eq!(
+        hledger,
+        "2025-03-01 * Pizza \"Roma\" | Team lunch
+    ; team-lunch:, work:
+    Expenses:Food:Eating-Out       18.50 USD
+    Assets:Cash                   -18.50 USD
+
+"
+    );
+    let ledger = export(token.clone(), "format=ledger").await;
+    assert!(ledger.contains("    ; :team-lunch:work:\n"));
+
+    let resp = client
+        .get(format!("{}/export/journal?commodity=euro", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_client_error());
+
+    // Another user reads the beancount export back in.
+    let other = sign_up_and_login(&base_url, &client, "leo@example.com").await;
+    let result = client
+        .post(format!("{}/import/beancount", base_url))
+        .bearer_auth(&other)
+        .multipart(
+            Form::new()
+                .part(
+                    "file",
+                    Part::bytes(beancount.into_bytes()).file_name("export.beancount"),
+                )
+                .text("dry_run", "false"),
+        )
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(result["imported"], 2);
+
+    let hledger = export(other, "format=hledger").await;
+    assert_eq!(
+        hledger,
+        "2025-03-01 * Pizza \"Roma\" | Team lunch
+    ; team-lunch:, work:
+    Expenses:Uncategorized       18.50 USD
+    Assets:Cash                 -18.50 USD
+
+2025-03-01 * Salary
+    Assets:Cash              2500.00 USD
+    Income:Uncategorized    -2500.00 USD
+
+"
+    );
+}
@@ -1 +1,26 @@
+pub mod access_token_test;
+pub mod account_test;
+pub mod anomaly_test;
+pub mod backup_test;
+pub mod camt_mt940_import_test;
+pub mod csv_import_test;
+pub mod duplicate_test;
+pub mod email_test;
+pub mod export_test;
+pub mod forecast_test;
+pub mod heatmap_test;
+pub mod household_test;
+pub mod journal_test;
+pub mod jwt_test;
+pub mod net_worth_test;
+pub mod ofx_qif_import_test;
+pub mod price_alert_test;
+pub mod quick_add_test;
+pub mod rate_limit_test;
+pub mod rule_test;
+pub mod session_test;
+pub mod split_test;
+pub mod subscription_test;
+pub mod suggestion_test;
+pub mod two_factor_test;
 pub mod workflow_test;
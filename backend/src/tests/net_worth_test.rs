@@ -0,0 +1,108 @@
+// tests/net_worth_test.rs
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+
+#[tokio::test]
+async fn test_savings_report_and_net_worth_history() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "dave@example.com").await;
+
+    for (name, kind, price, date) in [
+        ("Salary", "Income", 3000.0, "2025-01-25T09:00:00"),
+        ("Rent", "Expense", 1000.0, "2025-01-01T09:00:00"),
+        ("Groceries", "Expense", 500.0, "2025-02-03T09:00:00"),
+    ] {
+        let resp = client
+            .post(format!("{}/transactions", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "product_name": name,
+                "price": price,
+                "transaction_type": kind,
+                "description": null,
+                "date": date
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    for (name, kind, value, date) in [
+        ("House", "Asset", 200000.0, "2025-01-15"),
+        ("Mortgage", "Liability", 150000.0, "2025-01-15"),
+        ("House", "Asset", 210000.0, "2025-02-10"),
+    ] {
+        let resp = client
+            .post(format!("{}/net-worth-snapshots", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "name": name,
+                "kind": kind,
+                "value": value,
+                "snapshot_date": date
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let report = client
+        .get(format!("{}/savings-report", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!([
+            {
+                "month": "2025-01",
+                "income": 3000.0,
+                "expense": 1000.0,
+                "savings": 2000.0,
+                "savings_rate": 66.67
+            },
+            {
+                "month": "2025-02",
+                "income": 0.0,
+                "expense": 500.0,
+                "savings": -500.0,
+                "savings_rate": null
+            }
+        ])
+    );
+
+    let history = client
+        .get(format!("{}/net-worth-history?to=2025-02-28", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(
+        history,
+        serde_json::json!([
+            {
+                "month": "2025-01",
+                "cash_balance": 2000.0,
+                "assets": 200000.0,
+                "liabilities": 150000.0,
+                "net_worth": 52000.0
+            },
+            {
+                "month": "2025-02",
+                "cash_balance": 1500.0,
+                "assets": 210000.0,
+                "liabilities": 150000.0,
+                "net_worth": 61500.0
+            }
+        ])
+    );
+}
@@ -0,0 +1,190 @@
+// tests/ofx_qif_import_test.rs
+
+use reqwest::multipart::{Form, Part};
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::imports::ofx_import::parse_ofx;
+use crate::domain::imports::qif_import::{parse_qif, parse_qif_date};
+use crate::domain::transactions::models::TransactionType;
+
+const OFX_SGML: &str = "\
+OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<BANKACCTFROM><BANKID>123<ACCTID>987654<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20250103120000.000[-5:EST]
+<TRNAMT>-42.10
+<FITID>2025010301
+<NAME>GROCERY MART
+<MEMO>Card 1234 purchase
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20250105
+<TRNAMT>2500.00
+<FITID>2025010502
+<NAME>ACME PAYROLL
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<TRNAMT>-1.00
+<FITID>2025010503
+<NAME>NO DATE
+</STMTTRN>
+</BANKTRANLIST>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+
+const OFX_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX><CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS>
+<CCACCTFROM><ACCTID>4111</ACCTID></CCACCTFROM>
+<BANKTRANLIST>
+<STMTTRN><TRNTYPE>DEBIT</TRNTYPE><DTPOSTED>20250110</DTPOSTED><TRNAMT>-9.99</TRNAMT>
+<FITID>A1</FITID><NAME>Books &amp; More</NAME></STMTTRN>
+</BANKTRANLIST>
+</CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1></OFX>
+"#;
+
+const QIF: &str = "\
+!Type:Bank
+D1/3'25
+T-42.10
+PGrocery Mart
+MWeekly shop
+^
+D01/05/2025
+U2,500.00
+PAcme Payroll
+^
+Dnot a date
+T-1.00
+PBroken
+^
+";
+
+#[test]
+fn test_parse_ofx_sgml_and_xml() {
+    let parsed = parse_ofx(OFX_SGML.as_bytes()).unwrap();
+    assert_eq!(parsed.rows.len(), 2);
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].line, 3);
+
+    let grocery = &parsed.rows[0];
+    assert_eq!(grocery.amount, 4210);
+    assert_eq!(grocery.transaction_type, TransactionType::Expense);
+    assert_eq!(grocery.product_name, "GROCERY MART");
+    assert_eq!(grocery.description.as_deref(), Some("Card 1234 purchase"));
+    assert_eq!(grocery.external_id.as_deref(), Some("987654:2025010301"));
+    assert_eq!(grocery.date.to_string(), "2025-01-03 12:00:00");
+    assert_eq!(parsed.rows[1].transaction_type, TransactionType::Income);
+
+    let parsed = parse_ofx(OFX_XML.as_bytes()).unwrap();
+    assert_eq!(parsed.rows.len(), 1);
+    assert_eq!(parsed.rows[0].product_name, "Books & More");
+    assert_eq!(parsed.rows[0].external_id.as_deref(), Some("4111:A1"));
+
+    assert!(parse_ofx(b"date,amount\n").is_err());
+}
+
+#[test]
+fn test_parse_qif() {
+    assert_eq!(
+        parse_qif_date("1/3'25", None).unwrap().to_string(),
+        "2025-01-03 00:00:00"
+    );
+    assert_eq!(
+        parse_qif_date("12/31/98", None).unwrap().to_string(),
+        "1998-12-31 00:00:00"
+    );
+    assert_eq!(
+        parse_qif_date("03.01.2025", Some("%d.%m.%Y"))
+            .unwrap()
+            .to_string(),
+        "2025-01-03 00:00:00"
+    );
+
+    let parsed = parse_qif(QIF.as_bytes(), None, '.').unwrap();
+    assert_eq!(parsed.rows.len(), 2);
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].line, 11);
+    assert_eq!(parsed.rows[0].product_name, "Grocery Mart");
+    assert_eq!(parsed.rows[0].description.as_deref(), Some("Weekly shop"));
+    assert_eq!(parsed.rows[1].amount, 250000);
+    assert_eq!(parsed.rows[1].transaction_type, TransactionType::Income);
+    assert!(parsed.rows.iter().all(|r| r.external_id.is_none()));
+
+    // A line starting with a multibyte character is skipped, not a panic.
+    let odd = "!Type:Bank\nD1/3'25\nT-5.00\nÄrger\n€\nPKiosk\n^\n";
+    let parsed = parse_qif(odd.as_bytes(), None, '.').unwrap();
+    assert_eq!(parsed.rows.len(), 1);
+    assert_eq!(parsed.rows[0].product_name, "Kiosk");
+}
+
+#[tokio::test]
+async fn test_ofx_reimport_skips_known_fitids() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "grace@example.com").await;
+
+    let statement = OFX_SGML.replace("<TRNAMT>-1.00\n", "<DTPOSTED>20250106\n<TRNAMT>-1.00\n");
+    let import = |dry_run: &'static str| {
+        client
+            .post(format!("{}/import/ofx", base_url))
+            .bearer_auth(&token)
+            .multipart(
+                Form::new()
+                    .part(
+                        "file",
+                        Part::bytes(statement.as_bytes().to_vec()).file_name("statement.qfx"),
+                    )
+                    .text("dry_run", dry_run),
+            )
+            .send()
+    };
+
+    let first = import("false")
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(first["imported"], 3);
+    assert_eq!(first["skipped"], 0);
+
+    // Importing the same file again finds every FITID and creates nothing.
+    let preview = import("true")
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(preview["skipped"], 3);
+    assert_eq!(preview["rows"][0]["duplicate"], true);
+
+    let second = import("false")
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(second["imported"], 0);
+    assert_eq!(second["skipped"], 3);
+
+    let transactions = client
+        .get(format!("{}/transactions", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(transactions.as_array().unwrap().len(), 3);
+}
@@ -0,0 +1,106 @@
+// tests/price_alert_test.rs
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+
+#[tokio::test]
+async fn test_price_jump_raises_alert() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "bob@example.com").await;
+
+    // Build up a stable price history for "Bread".
+    for (day, price) in [(1, 2.00), (2, 2.10), (3, 1.90), (4, 2.00)] {
+        let resp = client
+            .post(format!("{}/product_prices", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "product_name": "Bread",
+                "price": price,
+                "created_at": format!("2025-01-0{day}T10:00:00")
+            }))
+            .send()
+            .await
+            .expect("Failed to create product price");
+        assert!(resp.status().is_success());
+        let body = resp.json::<serde_json::Value>().await.unwrap();
+        assert!(body["price_alert"].is_null());
+    }
+
+    // A 50% jump through create_transaction crosses the default 20% threshold.
+    let resp = client
+        .post(format!("{}/transactions", base_url))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "product_id": 1,
+            "price": 3.00,
+            "transaction_type": "Expense",
+            "description": null,
+            "date": "2025-01-05T10:00:00"
+        }))
+        .send()
+        .await
+        .expect("Failed to create transaction");
+    assert!(resp.status().is_success());
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    // In dollars with the product name, just like the alert list.
+    assert_eq!(body["price_alert"]["median_price"], 2.0);
+    assert_eq!(body["price_alert"]["new_price"], 3.0);
+    assert_eq!(body["price_alert"]["product_name"], "Bread");
+
+    // The alert is listed until dismissed.
+    let alerts = client
+        .get(format!("{}/price-alerts", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(alerts.as_array().unwrap().len(), 1);
+    assert_eq!(alerts[0], body["price_alert"]);
+    assert_eq!(alerts[0]["change_percent"], 50.0);
+    let alert_id = alerts[0]["id"].as_i64().unwrap();
+
+    let resp = client
+        .post(format!("{}/price-alerts/{}/dismiss", base_url, alert_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    let alerts = client
+        .get(format!("{}/price-alerts", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(alerts, serde_json::json!([]));
+
+    // Raising the threshold silences smaller jumps.
+    let resp = client
+        .put(format!("{}/price-alerts/settings", base_url))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "threshold_percent": 80 }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    let resp = client
+        .post(format!("{}/product_prices", base_url))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "product_id": 1,
+            "price": 3.10,
+            "created_at": "2025-01-06T10:00:00"
+        }))
+        .send()
+        .await
+        .unwrap();
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert!(body["price_alert"].is_null());
+}
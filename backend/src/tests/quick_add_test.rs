@@ -0,0 +1,139 @@
+// tests/quick_add_test.rs
+
+use chrono::NaiveDate;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::transactions::models::{QuickEntry, TransactionType};
+use crate::domain::transactions::quick_add::parse_quick_add;
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_parse_quick_add() {
+    // A Wednesday.
+    let today = day(2025, 3, 12);
+
+    assert_eq!(
+        parse_quick_add("coffee 3.50 @starbucks #work yesterday", today),
+        Ok(QuickEntry {
+            product_name: "coffee".to_string(),
+            amount: 3.5,
+            transaction_type: TransactionType::Expense,
+            merchant: Some("starbucks".to_string()),
+            tags: vec!["work".to_string()],
+            date: day(2025, 3, 11),
+        })
+    );
+
+    let salary = parse_quick_add("salary +2500 1st", today).unwrap();
+    assert_eq!(salary.transaction_type, TransactionType::Income);
+    assert_eq!(salary.amount, 2500.0);
+    assert_eq!(salary.date, day(2025, 3, 1));
+
+    // Ordinals and weekdays reach back to the most recent match.
+    let rent = parse_quick_add("Rent €1.200,00 #home 31st", today);
+    assert!(rent.is_err(), "two separators are not an amount");
+    let rent = parse_quick_add("Rent 1200,00€ 31st #home", today).unwrap();
+    assert_eq!(rent.amount, 1200.0);
+    assert_eq!(rent.date, day(2025, 1, 31));
+    let lunch = parse_quick_add("lunch -12,9 last friday @Corner_Cafe", today).unwrap();
+    assert_eq!(lunch.product_name, "lunch");
+    assert_eq!(lunch.amount, 12.9);
+    assert_eq!(lunch.merchant.as_deref(), Some("Corner Cafe"));
+    assert_eq!(lunch.date, day(2025, 3, 7));
+    assert_eq!(parse_quick_add("wed $4 tea", today).unwrap().date, today);
+    assert_eq!(
+        parse_quick_add("2 days ago 9.99 @Netflix", today).unwrap(),
+        QuickEntry {
+            product_name: "Netflix".to_string(),
+            amount: 9.99,
+            transaction_type: TransactionType::Expense,
+            merchant: Some("Netflix".to_string()),
+            tags: vec![],
+            date: day(2025, 3, 10),
+        }
+    );
+    assert_eq!(
+        parse_quick_add("Bike repair 45 2025-02-28", today)
+            .unwrap()
+            .date,
+        day(2025, 2, 28)
+    );
+
+    for (text, error) in [
+        ("coffee", "No amount found"),
+        ("3.50 #work", "No product or merchant found"),
+        ("coffee 3.50 4.00", "More than one amount given (at '4.00')"),
+        (
+            "coffee 3 today monday",
+            "More than one date given (at 'monday')",
+        ),
+        ("coffee 3 #", "Empty tag after '#'"),
+    ] {
+        assert_eq!(parse_quick_add(text, today), Err(error.to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_quick_add_endpoint() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "quinn@example.com").await;
+
+    let quick = |body: serde_json::Value| {
+        client
+            .post(format!("{}/transactions/quick", base_url))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+
+    let preview = quick(serde_json::json!({
+        "text": "coffee 3.50 @starbucks #work yesterday",
+        "today": "2025-03-12",
+        "preview": true
+    }))
+    .await
+    .unwrap()
+    .json::<serde_json::Value>()
+    .await
+    .unwrap();
+    assert_eq!(preview["parsed"]["date"], "2025-03-11");
+    assert_eq!(preview["created"], serde_json::Value::Null);
+
+    let transactions = client
+        .get(format!("{}/transactions", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert!(transactions.as_array().unwrap().is_empty());
+
+    let created = quick(serde_json::json!({
+        "text": "coffee 3.50 @starbucks #work yesterday",
+        "today": "2025-03-12"
+    }))
+    .await
+    .unwrap()
+    .json::<serde_json::Value>()
+    .await
+    .unwrap();
+    let created = &created["created"];
+    assert_eq!(created["product"]["name"], "coffee");
+    assert_eq!(created["product_price"]["price"], 3.5);
+    assert_eq!(created["transaction"]["transaction_type"], "Expense");
+    assert_eq!(created["transaction"]["description"], "starbucks");
+    assert_eq!(created["transaction"]["date"], "2025-03-11T00:00:00");
+    assert_eq!(created["tags"][0]["name"], "work");
+
+    let resp = quick(serde_json::json!({ "text": "salary" }))
+        .await
+        .unwrap();
+    assert!(resp.status().is_client_error());
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "No amount found");
+}
@@ -0,0 +1,150 @@
+// tests/rate_limit_test.rs
+
+use reqwest::StatusCode;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use super::workflow_test::{sign_up_and_login, spawn_app, spawn_app_with};
+use crate::domain::users::throttle::{lock_seconds, ACCOUNT_FREE_FAILURES};
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+
+#[test]
+fn test_lock_seconds() {
+    assert_eq!(lock_seconds(4, 5), None);
+    assert_eq!(lock_seconds(5, 5), Some(30));
+    assert_eq!(lock_seconds(6, 5), Some(60));
+    assert_eq!(lock_seconds(9, 5), Some(480));
+    assert_eq!(lock_seconds(12, 5), Some(3600));
+    assert_eq!(lock_seconds(500, 5), Some(3600));
+}
+
+#[test]
+fn test_rate_limiter() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        requests: 2,
+        window_seconds: 60,
+    });
+    let alice: IpAddr = "10.0.0.1".parse().unwrap();
+    let bob: IpAddr = "10.0.0.2".parse().unwrap();
+    assert_eq!(limiter.check(alice), Ok(()));
+    assert_eq!(limiter.check(alice), Ok(()));
+    assert_eq!(limiter.check(alice), Err(30));
+    assert_eq!(limiter.check(bob), Ok(()));
+}
+
+#[test]
+fn test_rate_limiter_keys_ipv6_by_network() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        requests: 1,
+        window_seconds: 60,
+    });
+    let check = |ip: &str| limiter.check(ip.parse().unwrap());
+    // Rotating through one /64 does not bring more requests.
+    assert_eq!(check("2001:db8:1:2::1"), Ok(()));
+    assert_eq!(check("2001:db8:1:2:ffff::9"), Err(60));
+    assert_eq!(check("2001:db8:1:3::1"), Ok(()));
+    // IPv4-mapped addresses count as the IPv4 address.
+    assert_eq!(check("10.0.0.1"), Ok(()));
+    assert_eq!(check("::ffff:10.0.0.1"), Err(60));
+}
+
+#[test]
+fn test_rate_limiter_forgets_least_recent_clients() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        requests: 1,
+        window_seconds: 60,
+    })
+    .with_max_clients(2);
+    let ip = |last: u8| IpAddr::from([10, 0, 0, last]);
+    assert_eq!(limiter.check(ip(1)), Ok(()));
+    assert_eq!(limiter.check(ip(2)), Ok(()));
+    assert_eq!(limiter.check(ip(1)), Err(60));
+    // A third client pushes out the one seen least recently, not the others.
+    assert_eq!(limiter.check(ip(3)), Ok(()));
+    assert_eq!(limiter.check(ip(1)), Err(60));
+    assert_eq!(limiter.check(ip(2)), Ok(()));
+}
+
+#[tokio::test]
+async fn test_login_lockout() {
+    let (base_url, client) = spawn_app().await;
+    sign_up_and_login(&base_url, &client, "lou@example.com").await;
+
+    let login = |email: &'static str, password: &'static str| {
+        let request = client
+            .post(format!("{}/login", base_url))
+            .json(&serde_json::json!({ "email": email, "password_hash": password }));
+        async move {
+            let resp = request.send().await.unwrap();
+            let status = resp.status();
+            (status, resp.json::<serde_json::Value>().await.unwrap())
+        }
+    };
+
+    // A success in between starts the count over.
+    for _ in 0..ACCOUNT_FREE_FAILURES - 1 {
+        login("lou@example.com", "wrong").await;
+    }
+    assert!(login("lou@example.com", "secret123").await.0.is_success());
+
+    // Unknown emails get the same answers as wrong passwords.
+    for email in ["lou@example.com", "nobody@example.com"] {
+        for _ in 0..ACCOUNT_FREE_FAILURES {
+            let (status, body) = login(email, "wrong").await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["error"], "Invalid email or password");
+        }
+        let resp = client
+            .post(format!("{}/login", base_url))
+            .json(&serde_json::json!({ "email": email, "password_hash": "secret123" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: i64 = resp.headers()["retry-after"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=30).contains(&retry_after));
+        let body = resp.json::<serde_json::Value>().await.unwrap();
+        let error = body["error"].as_str().unwrap();
+        assert!(error.starts_with("Too many failed login attempts; try again in "));
+    }
+
+    // The lock is per account.
+    sign_up_and_login(&base_url, &client, "max@example.com").await;
+}
+
+#[tokio::test]
+async fn test_rate_limit_layer() {
+    let (base_url, client) = spawn_app_with(|state| {
+        state.rate_limiter = Some(Arc::new(RateLimiter::new(RateLimitConfig {
+            requests: 3,
+            window_seconds: 60,
+        })));
+        state.trust_proxy_headers = true;
+    })
+    .await;
+
+    // The proxy appends the address it saw; whatever the client sent before
+    // it does not count.
+    let get = |forged: String, client_ip: &'static str| {
+        let request = client
+            .get(format!("{}/transactions", base_url))
+            .header("X-Forwarded-For", format!("{forged}, {client_ip}"));
+        async move { request.send().await.unwrap() }
+    };
+    for i in 0..3 {
+        let status = get(format!("10.9.9.{i}"), "203.0.113.7").await.status();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let resp = get("10.9.9.9".to_string(), "203.0.113.7").await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(resp.headers()["retry-after"], "20");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "Too many requests; try again in 20 seconds");
+
+    let resp = get("10.9.9.9".to_string(), "203.0.113.8").await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
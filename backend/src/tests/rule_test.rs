@@ -0,0 +1,324 @@
+// tests/rule_test.rs
+
+use reqwest::multipart::{Form, Part};
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::rules::models::Rule;
+use crate::domain::rules::services::{evaluate_rules, CompiledRule, RuleSubject};
+use crate::domain::tags::models::Tag;
+
+fn rule(id: i32, priority: i32) -> Rule {
+    Rule {
+        id,
+        household_id: 1,
+        name: format!("rule {id}"),
+        priority,
+        active: true,
+        description_pattern: None,
+        product_name: None,
+        merchant: None,
+        account: None,
+        min_amount: None,
+        max_amount: None,
+        set_category_id: None,
+        set_description: None,
+        created_at: chrono::NaiveDateTime::default(),
+    }
+}
+
+fn tag(id: i32, name: &str) -> Tag {
+    Tag {
+        id,
+        name: name.to_string(),
+        household_id: 1,
+    }
+}
+
+#[test]
+fn test_evaluate_rules() {
+    let coffee = Rule {
+        merchant: Some("STARBUCKS".to_string()),
+        set_category_id: Some(1),
+        set_description: Some("Coffee".to_string()),
+        ..rule(1, 0)
+    };
+    let small = Rule {
+        min_amount: Some(100),
+        max_amount: Some(500),
+        set_category_id: Some(2),
+        ..rule(2, 1)
+    };
+    let card = Rule {
+        description_pattern: Some(r"^CARD \d{4}".to_string()),
+        account: Some("de89".to_string()),
+        ..rule(3, 2)
+    };
+    let broken = Rule {
+        description_pattern: Some("(".to_string()),
+        ..rule(4, 3)
+    };
+    let rules = vec![
+        CompiledRule::new(coffee, vec![tag(1, "coffee")]),
+        CompiledRule::new(small, vec![tag(2, "small"), tag(1, "coffee")]),
+        CompiledRule::new(card, vec![tag(3, "card")]),
+        CompiledRule::new(broken, vec![tag(4, "never")]),
+    ];
+
+    let outcome = evaluate_rules(
+        &rules,
+        &RuleSubject {
+            product_name: "Starbucks Berlin",
+            description: Some("CARD 1234 latte"),
+            amount: 500,
+            account: Some("DE89"),
+        },
+    );
+    assert_eq!(outcome.rule_ids, vec![1, 2, 3]);
+    // The first rule setting a field wins; tags accumulate without repeats.
+    assert_eq!(outcome.category_id, Some(1));
+    assert_eq!(outcome.description.as_deref(), Some("Coffee"));
+    let tags: Vec<&str> = outcome.tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(tags, vec!["coffee", "small", "card"]);
+
+    let outcome = evaluate_rules(
+        &rules,
+        &RuleSubject {
+            product_name: "Bakery",
+            description: None,
+            amount: 501,
+            account: None,
+        },
+    );
+    assert!(outcome.rule_ids.is_empty());
+    assert_eq!(outcome.category_id, None);
+}
+
+#[tokio::test]
+async fn test_rules_on_insert_and_apply() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "rita@example.com").await;
+
+    let post = |path: &'static str, body: serde_json::Value| {
+        client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    let json = |resp: reqwest::Response| async move {
+        let success = resp.status().is_success();
+        (success, resp.json::<serde_json::Value>().await.unwrap())
+    };
+
+    for name in ["Coffee", "Snacks"] {
+        let body = serde_json::json!({ "parent_category_id": null, "name": name });
+        assert!(post("/categories", body)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+    }
+
+    let (ok, created) = json(
+        post(
+            "/rules",
+            serde_json::json!({
+                "name": "Starbucks",
+                "conditions": { "merchant": "starbucks" },
+                "actions": {
+                    "set_category_id": 1,
+                    "add_tags": ["coffee"],
+                    "set_description": "Coffee"
+                }
+            }),
+        )
+        .await
+        .unwrap(),
+    )
+    .await;
+    assert!(ok, "{created}");
+    assert_eq!(created["actions"]["add_tags"][0]["name"], "coffee");
+    let (ok, _) = json(
+        post(
+            "/rules",
+            serde_json::json!({
+                "name": "Small purchases",
+                "priority": 10,
+                "conditions": { "max_amount": 5.0 },
+                "actions": { "set_category_id": 2, "add_tags": ["small"] }
+            }),
+        )
+        .await
+        .unwrap(),
+    )
+    .await;
+    assert!(ok);
+
+    for (body, message) in [
+        (
+            serde_json::json!({ "name": "Starbucks", "conditions": { "merchant": "x" },
+                "actions": { "set_description": "x" } }),
+            "A rule with that name already exists",
+        ),
+        (
+            serde_json::json!({ "name": "Empty", "conditions": {},
+                "actions": { "set_description": "x" } }),
+            "A rule needs at least one condition",
+        ),
+        (
+            serde_json::json!({ "name": "Regex", "conditions": { "description_pattern": "(" },
+                "actions": { "set_description": "x" } }),
+            "",
+        ),
+        (
+            serde_json::json!({ "name": "Foreign", "conditions": { "merchant": "x" },
+                "actions": { "set_category_id": 99 } }),
+            "Category not found",
+        ),
+    ] {
+        let (ok, body) = json(post("/rules", body).await.unwrap()).await;
+        assert!(!ok);
+        if !message.is_empty() {
+            assert_eq!(body["error"], message);
+        }
+    }
+
+    // Both rules match; the higher priority one sets category and description.
+    let (ok, created) = json(
+        post(
+            "/transactions",
+            serde_json::json!({
+                "product_name": "Starbucks Alexanderplatz",
+                "price": 3.5,
+                "transaction_type": "Expense",
+                "description": null,
+                "date": "2025-03-01T08:00:00",
+                "tags": ["morning"]
+            }),
+        )
+        .await
+        .unwrap(),
+    )
+    .await;
+    assert!(ok);
+    assert_eq!(created["transaction"]["description"], "Coffee");
+    assert_eq!(created["product"]["category_id"], 1);
+    let mut tags: Vec<&str> = created["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    tags.sort_unstable();
+    assert_eq!(tags, vec!["coffee", "morning", "small"]);
+
+    // Importers go through the same path.
+    let journal = "2025-03-02 * \"Bakery\" \"Rolls\"\n  Expenses:Food  2.00 USD\n  Assets:Cash\n";
+    let (ok, result) = json(
+        client
+            .post(format!("{}/import/beancount", base_url))
+            .bearer_auth(&token)
+            .multipart(
+                Form::new()
+                    .part(
+                        "file",
+                        Part::bytes(journal.as_bytes().to_vec()).file_name("j.beancount"),
+                    )
+                    .text("dry_run", "false"),
+            )
+            .send()
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert!(ok, "{result}");
+    let products = client
+        .get(format!("{}/products", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let bakery = products
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == "Bakery")
+        .unwrap();
+    assert_eq!(bakery["category_id"], 2);
+
+    // A new rule only touches existing transactions when applied.
+    let (ok, _) = json(
+        post(
+            "/rules",
+            serde_json::json!({
+                "name": "Bread",
+                "conditions": { "product_name": "bakery", "description_pattern": "(?i)rolls" },
+                "actions": { "add_tags": ["bread"], "set_description": "Bread rolls" }
+            }),
+        )
+        .await
+        .unwrap(),
+    )
+    .await;
+    assert!(ok);
+
+    let apply = |dry_run: bool| post("/rules/apply", serde_json::json!({ "dry_run": dry_run }));
+    let (_, preview) = json(apply(true).await.unwrap()).await;
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["checked"], 2);
+    // The refused duplicate above used up id 3; lower priorities run first.
+    let changes = preview["changes"].as_array().unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["transaction_id"], 2);
+    assert_eq!(changes[0]["rule_ids"], serde_json::json!([4, 2]));
+    assert_eq!(changes[0]["set_category_id"], serde_json::Value::Null);
+    assert_eq!(changes[0]["add_tags"], serde_json::json!(["bread"]));
+    assert_eq!(changes[0]["set_description"], "Bread rolls");
+
+    // Nothing was written by the dry run, so the plan is unchanged.
+    let (_, again) = json(apply(true).await.unwrap()).await;
+    assert_eq!(again, preview);
+
+    let (_, applied) = json(apply(false).await.unwrap()).await;
+    assert_eq!(applied["changes"], preview["changes"]);
+    let (_, after) = json(apply(true).await.unwrap()).await;
+    assert!(after["changes"].as_array().unwrap().is_empty());
+
+    let transactions = client
+        .get(format!("{}/transactions", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(transactions[1]["description"], "Bread rolls");
+
+    let resp = client
+        .delete(format!("{}/rules/4", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let rules = client
+        .get(format!("{}/rules", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let names: Vec<&str> = rules
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Starbucks", "Small purchases"]);
+}
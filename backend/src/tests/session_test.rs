@@ -0,0 +1,134 @@
+// tests/session_test.rs
+
+use reqwest::StatusCode;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::sessions::models::TokenResponse;
+
+#[tokio::test]
+async fn test_refresh_rotation_logout_and_revocation() {
+    let (base_url, client) = spawn_app().await;
+    sign_up_and_login(&base_url, &client, "olga@example.com").await;
+
+    let login = |device_label: Option<&'static str>| {
+        let request = client
+            .post(format!("{}/login", base_url))
+            .header("User-Agent", "test-agent/1.0")
+            .json(&serde_json::json!({
+                "email": "olga@example.com",
+                "password_hash": "secret123",
+                "device_label": device_label
+            }));
+        async move {
+            let resp = request.send().await.unwrap();
+            assert!(resp.status().is_success());
+            resp.json::<TokenResponse>().await.unwrap()
+        }
+    };
+    let refresh = |refresh_token: String| {
+        let request = client
+            .post(format!("{}/refresh", base_url))
+            .json(&serde_json::json!({ "refresh_token": refresh_token }));
+        async move { request.send().await.unwrap() }
+    };
+    let sessions = |token: String| {
+        let request = client
+            .get(format!("{}/sessions", base_url))
+            .bearer_auth(token);
+        async move { request.send().await.unwrap() }
+    };
+
+    let phone = login(Some("Phone")).await;
+    assert_eq!(phone.expires_in, 15 * 60);
+    let laptop = login(None).await;
+
+    let list = sessions(phone.token.clone())
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    // The sign-up helper's login is a session too.
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 3);
+    let labels: Vec<&serde_json::Value> = list.iter().map(|s| &s["device_label"]).collect();
+    assert!(labels.contains(&&serde_json::json!("Phone")));
+    assert!(labels.contains(&&serde_json::json!("test-agent/1.0")));
+    let current: Vec<&serde_json::Value> = list.iter().filter(|s| s["current"] == true).collect();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0]["device_label"], "Phone");
+
+    // Refreshing hands out a new refresh token; the old one is spent.
+    let rotated = refresh(phone.refresh_token.clone())
+        .await
+        .json::<TokenResponse>()
+        .await
+        .unwrap();
+    assert_ne!(rotated.refresh_token, phone.refresh_token);
+    assert!(sessions(rotated.token.clone()).await.status().is_success());
+
+    // Reusing the spent token looks like theft and ends the whole session.
+    assert!(refresh(phone.refresh_token.clone())
+        .await
+        .status()
+        .is_client_error());
+    assert_eq!(
+        sessions(rotated.token.clone()).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert!(refresh(rotated.refresh_token.clone())
+        .await
+        .status()
+        .is_client_error());
+
+    // Logging out ends only the current session.
+    let resp = client
+        .post(format!("{}/logout", base_url))
+        .bearer_auth(&laptop.token)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    assert_eq!(
+        sessions(laptop.token.clone()).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Revoking another device's session from this one.
+    let tablet = login(Some("Tablet")).await;
+    let desktop = login(Some("Desktop")).await;
+    let list = sessions(desktop.token.clone())
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let tablet_id = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["device_label"] == "Tablet")
+        .unwrap()["id"]
+        .clone();
+    let revoke = |id: serde_json::Value| {
+        let request = client
+            .delete(format!("{}/sessions/{}", base_url, id))
+            .bearer_auth(&desktop.token);
+        async move { request.send().await.unwrap() }
+    };
+    let revoked = revoke(tablet_id.clone())
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(revoked["device_label"], "Tablet");
+    assert_eq!(revoked["current"], false);
+    assert!(revoke(tablet_id).await.status().is_client_error());
+    assert_eq!(
+        sessions(tablet.token).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert!(refresh(tablet.refresh_token)
+        .await
+        .status()
+        .is_client_error());
+    assert!(sessions(desktop.token.clone()).await.status().is_success());
+}
@@ -0,0 +1,267 @@
+// tests/split_test.rs
+
+use reqwest::{Method, StatusCode};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use super::workflow_test::{sign_up_and_login, spawn_app, Api};
+use crate::domain::splits::models::{Debt, SplitMethod};
+use crate::domain::splits::services::{allocate_split, pair_debts, settle_up};
+
+fn debt(from_user_id: i32, to_user_id: i32, amount: i64) -> Debt {
+    Debt {
+        from_user_id,
+        to_user_id,
+        amount,
+    }
+}
+
+#[test]
+fn test_allocation_methods() {
+    let everyone = [(1, None), (2, None), (3, None)];
+    assert_eq!(
+        allocate_split(SplitMethod::Equal, 1000, &everyone),
+        Ok(vec![(1, 334), (2, 333), (3, 333)])
+    );
+    assert_eq!(
+        allocate_split(SplitMethod::Shares, 1000, &[(1, Some(1.0)), (2, Some(2.0))]),
+        Ok(vec![(1, 333), (2, 667)])
+    );
+    let percentages = [(1, Some(50.0)), (2, Some(25.0)), (3, Some(25.0))];
+    assert_eq!(
+        allocate_split(SplitMethod::Percentages, 999, &percentages),
+        Ok(vec![(1, 499), (2, 250), (3, 250)])
+    );
+    assert_eq!(
+        allocate_split(SplitMethod::Exact, 1000, &[(1, Some(7.5)), (2, Some(2.5))]),
+        Ok(vec![(1, 750), (2, 250)])
+    );
+
+    assert_eq!(
+        allocate_split(SplitMethod::Exact, 1000, &[(1, Some(7.5)), (2, Some(2.0))]),
+        Err("Amounts add up to 9.50, not 10.00".to_string())
+    );
+    assert_eq!(
+        allocate_split(SplitMethod::Percentages, 1000, &[(1, Some(90.0))]),
+        Err("Percentages must add up to 100".to_string())
+    );
+    assert_eq!(
+        allocate_split(SplitMethod::Shares, 1000, &[(1, Some(1.0)), (2, None)]),
+        Err("User 2 needs a share".to_string())
+    );
+    assert_eq!(
+        allocate_split(SplitMethod::Equal, 1000, &[(1, None), (1, None)]),
+        Err("User 1 is listed twice".to_string())
+    );
+    assert!(allocate_split(SplitMethod::Equal, 1000, &[]).is_err());
+
+    // Huge shares are refused rather than overflowing.
+    let huge = [(1, Some(1e15)), (2, Some(1e15))];
+    for method in [SplitMethod::Shares, SplitMethod::Exact] {
+        assert_eq!(
+            allocate_split(method, 1000, &huge),
+            Err("The share of user 1 is too large".to_string())
+        );
+    }
+    assert_eq!(
+        allocate_split(SplitMethod::Shares, 1000, &[(1, Some(1e9)), (2, Some(1e9))]),
+        Ok(vec![(1, 500), (2, 500)])
+    );
+}
+
+#[test]
+fn test_pair_debts_net_both_directions() {
+    // Alice paid 10.00 for Bob, Bob paid 4.50 for Alice, and Alice's own
+    // share of her purchase is nobody's debt.
+    let shares = [(1, 2, 1000), (2, 1, 450), (1, 1, 1000), (3, 1, 200)];
+    assert_eq!(pair_debts(&shares), vec![debt(2, 1, 550), debt(1, 3, 200)]);
+}
+
+#[test]
+fn test_settle_up_minimizes_payments() {
+    // Paying the largest debt first would take four payments here; {2, 3}
+    // and {1, 4, 5} settle separately in three.
+    let balances = BTreeMap::from([(1, 600), (2, 500), (3, -500), (4, -400), (5, -200)]);
+    assert_eq!(
+        settle_up(&balances),
+        vec![debt(3, 2, 500), debt(4, 1, 400), debt(5, 1, 200)]
+    );
+
+    let settled = BTreeMap::from([(1, 0), (2, 0)]);
+    assert_eq!(settle_up(&settled), vec![]);
+}
+
+#[tokio::test]
+async fn test_split_balances_and_settlement() {
+    let (base_url, client) = spawn_app().await;
+    let alice = sign_up_and_login(&base_url, &client, "alice@example.com").await;
+    let bob = sign_up_and_login(&base_url, &client, "bob@example.com").await;
+    let carol = sign_up_and_login(&base_url, &client, "carol@example.com").await;
+    let dave = sign_up_and_login(&base_url, &client, "dave@example.com").await;
+    let api = Api { base_url, client };
+
+    let (_, flat) = api
+        .post("/households", &alice, None, json!({ "name": "Flat" }))
+        .await;
+    let flat_id = flat["id"].as_i64().unwrap();
+    for roommate in [&bob, &carol] {
+        let invitations = format!("/households/{flat_id}/invitations");
+        let (_, invitation) = api
+            .post(&invitations, &alice, None, json!({ "role": "editor" }))
+            .await;
+        let token = json!({ "token": invitation["token"] });
+        let (status, _) = api.post("/households/join", roommate, None, token).await;
+        assert!(status.is_success());
+    }
+    let flat = Some(flat_id);
+    let expense = |name: &str, price: f64| {
+        json!({
+            "product_name": name,
+            "price": price,
+            "transaction_type": "Expense",
+            "description": null,
+            "date": "2025-05-20T18:00:00",
+            "tags": null
+        })
+    };
+
+    // Alice buys groceries for the three of them.
+    let (_, groceries) = api
+        .post("/transactions", &alice, flat, expense("Groceries", 30.0))
+        .await;
+    let groceries = format!("/transactions/{}/split", groceries["transaction"]["id"]);
+    let equal = json!({
+        "method": "equal",
+        "participants": [{ "user_id": 1 }, { "user_id": 2 }, { "user_id": 3 }]
+    });
+    let (status, body) = api.put(&groceries, &alice, flat, equal).await;
+    assert!(status.is_success(), "{body}");
+    assert_eq!(body["paid_by"], 1);
+    assert_eq!(body["amount"], 30.0);
+    assert_eq!(
+        body["shares"],
+        json!([
+            { "user_id": 1, "amount": 10.0 },
+            { "user_id": 2, "amount": 10.0 },
+            { "user_id": 3, "amount": 10.0 }
+        ])
+    );
+
+    // Bob pays for the cinema; Alice and Carol owe him their tickets.
+    let (_, cinema) = api
+        .post("/transactions", &bob, flat, expense("Cinema", 9.0))
+        .await;
+    let cinema = format!("/transactions/{}/split", cinema["transaction"]["id"]);
+    let exact = |alice_owes: f64| {
+        json!({
+            "method": "exact",
+            "participants": [
+                { "user_id": 1, "share": alice_owes },
+                { "user_id": 3, "share": 4.5 }
+            ]
+        })
+    };
+    let (status, body) = api.put(&cinema, &bob, flat, exact(4.0)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Amounts add up to 8.50, not 9.00");
+    let (status, _) = api.put(&cinema, &bob, flat, exact(4.5)).await;
+    assert!(status.is_success());
+    let (_, stored) = api.get(&cinema, &carol, flat).await;
+    assert_eq!(stored["method"], "exact");
+    assert_eq!(stored["paid_by"], 2);
+
+    // Only members of the household take part.
+    let outsider = json!({
+        "method": "percentages",
+        "participants": [{ "user_id": 1, "share": 50 }, { "user_id": 4, "share": 50 }]
+    });
+    let (status, body) = api.put(&cinema, &bob, flat, outsider).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "User 4 is not a member of this household");
+    let (status, _) = api.get(&cinema, &dave, flat).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Pairs net out; settling up needs fewer payments than there are debts.
+    let (_, balances) = api.get("/splits/balances", &carol, flat).await;
+    assert_eq!(
+        balances["debts"],
+        json!([
+            { "from_user_id": 2, "to_user_id": 1, "amount": 5.5 },
+            { "from_user_id": 3, "to_user_id": 1, "amount": 10.0 },
+            { "from_user_id": 3, "to_user_id": 2, "amount": 4.5 }
+        ])
+    );
+    assert_eq!(
+        balances["members"],
+        json!([
+            { "user_id": 1, "balance": 15.5 },
+            { "user_id": 2, "balance": -1.0 },
+            { "user_id": 3, "balance": -14.5 }
+        ])
+    );
+    let (_, payments) = api.get("/splits/settle-up", &carol, flat).await;
+    assert_eq!(
+        payments,
+        json!([
+            { "from_user_id": 2, "to_user_id": 1, "amount": 1.0 },
+            { "from_user_id": 3, "to_user_id": 1, "amount": 14.5 }
+        ])
+    );
+
+    // Carol pays Alice back; the settlement is a transfer that clears her debt.
+    let (status, settlement) = api
+        .post(
+            "/splits/settlements",
+            &carol,
+            flat,
+            json!({ "to_user_id": 1, "amount": 14.5 }),
+        )
+        .await;
+    assert!(status.is_success(), "{settlement}");
+    assert_eq!(settlement["transaction"]["transaction_type"], "Transfer");
+    assert_eq!(settlement["split"]["paid_by"], 3);
+    let (_, payments) = api.get("/splits/settle-up", &alice, flat).await;
+    assert_eq!(
+        payments,
+        json!([{ "from_user_id": 2, "to_user_id": 1, "amount": 1.0 }])
+    );
+
+    // Removing a split takes it out of the balances; Carol paid Alice for
+    // the cinema tickets too, so now Alice owes her.
+    let (status, _) = api.call(Method::DELETE, &cinema, &bob, flat, None).await;
+    assert!(status.is_success());
+    let (_, balances) = api.get("/splits/balances", &alice, flat).await;
+    assert_eq!(
+        balances["debts"],
+        json!([
+            { "from_user_id": 2, "to_user_id": 1, "amount": 10.0 },
+            { "from_user_id": 1, "to_user_id": 3, "amount": 4.5 }
+        ])
+    );
+
+    // Carol can only leave for good once she is settled up, and her share
+    // of the groceries stays on record after she is gone.
+    let password = Some(json!({ "password": "secret123" }));
+    let delete_carol = || api.call(Method::DELETE, "/users/me", &carol, None, password.clone());
+    let (status, body) = delete_carol().await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "Settle up your shared expenses before deleting the account"
+    );
+    let payback = json!({ "to_user_id": 3, "amount": 4.5 });
+    let (status, _) = api.post("/splits/settlements", &alice, flat, payback).await;
+    assert!(status.is_success());
+    let (status, _) = delete_carol().await;
+    assert!(status.is_success());
+    let (_, stored) = api.get(&groceries, &alice, flat).await;
+    assert_eq!(
+        stored["shares"][2],
+        json!({ "user_id": null, "amount": 10.0 })
+    );
+    let (_, balances) = api.get("/splits/balances", &alice, flat).await;
+    assert_eq!(
+        balances["debts"],
+        json!([{ "from_user_id": 2, "to_user_id": 1, "amount": 10.0 }])
+    );
+}
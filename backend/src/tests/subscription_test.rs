@@ -0,0 +1,200 @@
+// tests/subscription_test.rs
+
+use chrono::NaiveDate;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::recurring_rules::models::Cadence;
+use crate::domain::subscriptions::services::{
+    detect_subscriptions, normalize_description, PaymentRow,
+};
+
+fn payment(product_id: i32, name: &str, date: (i32, u32, u32), amount: i64) -> PaymentRow {
+    PaymentRow {
+        product_id,
+        product_name: name.to_string(),
+        description: Some(format!("{name} payment")),
+        date: NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+        amount,
+    }
+}
+
+#[test]
+fn test_detects_monthly_and_ignores_irregular() {
+    let payments = vec![
+        payment(1, "Streaming", (2025, 1, 5), 1599),
+        payment(1, "Streaming", (2025, 2, 5), 1599),
+        payment(1, "Streaming", (2025, 3, 6), 1599),
+        payment(1, "Streaming", (2025, 4, 5), 1699),
+        // Irregular purchases with wildly different amounts.
+        payment(2, "Groceries", (2025, 1, 2), 4000),
+        payment(2, "Groceries", (2025, 1, 20), 1200),
+        payment(2, "Groceries", (2025, 3, 1), 9000),
+    ];
+
+    let today = NaiveDate::from_ymd_opt(2025, 4, 20).unwrap();
+    let detected = detect_subscriptions(&payments, today);
+
+    assert_eq!(detected.len(), 1);
+    let sub = &detected[0];
+    assert_eq!(sub.product_name, "Streaming");
+    assert_eq!(sub.cadence, Cadence::Monthly);
+    assert_eq!(sub.occurrences, 4);
+    assert_eq!(sub.average_amount, 16.24);
+    assert_eq!(
+        sub.next_expected_date,
+        NaiveDate::from_ymd_opt(2025, 5, 5).unwrap()
+    );
+    assert_eq!(sub.annualized_cost, 194.88);
+    assert!(sub.active);
+
+    // Months later without a payment, the subscription looks cancelled.
+    let later = NaiveDate::from_ymd_opt(2025, 8, 1).unwrap();
+    assert!(!detect_subscriptions(&payments, later)[0].active);
+}
+
+#[test]
+fn test_generic_product_carries_several_subscriptions() {
+    // Rent and the gym both leave as bank transfers, told apart only by
+    // their descriptions.
+    let transfer = |description: &str, date: (i32, u32, u32), amount: i64| PaymentRow {
+        description: Some(description.to_string()),
+        ..payment(7, "Bank transfer", date, amount)
+    };
+    let payments = vec![
+        transfer("Rent 2025-01", (2025, 1, 1), 90000),
+        transfer("GYM membership #1041", (2025, 1, 3), 2999),
+        transfer("Rent 2025-02", (2025, 2, 1), 90000),
+        transfer("Gym membership #1187", (2025, 2, 3), 2999),
+        transfer("Rent 2025-03", (2025, 3, 1), 90000),
+        transfer("Gym membership  #1302", (2025, 3, 3), 2999),
+    ];
+    assert_eq!(
+        normalize_description(Some("GYM membership #1041")),
+        "gym membership"
+    );
+
+    let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+    let detected = detect_subscriptions(&payments, today);
+    let found: Vec<(i32, f64)> = detected
+        .iter()
+        .map(|s| (s.product_id, s.average_amount))
+        .collect();
+    assert_eq!(found, vec![(7, 29.99), (7, 900.0)]);
+    assert!(detected.iter().all(|s| s.cadence == Cadence::Monthly));
+}
+
+#[tokio::test]
+async fn test_convert_subscription_into_rule() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "carol@example.com").await;
+
+    let resp = client
+        .post(format!("{}/products", base_url))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "name": "Gym" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    for date in ["2025-01-03", "2025-02-03", "2025-03-03"] {
+        let resp = client
+            .post(format!("{}/transactions", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "product_id": 1,
+                "price": 29.99,
+                "transaction_type": "Expense",
+                "description": "Gym membership",
+                "date": format!("{date}T08:00:00")
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let resp = client
+        .post(format!("{}/subscriptions/1/convert", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let rule = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(rule["cadence"], "Monthly");
+    assert_eq!(rule["amount"], 29.99);
+    assert_eq!(rule["next_due_date"], "2025-04-03");
+    assert_eq!(rule["description"], "Gym membership");
+
+    // Converting twice is rejected.
+    let resp = client
+        .post(format!("{}/subscriptions/1/convert", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let subs = client
+        .get(format!("{}/subscriptions?include_inactive=true", base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(subs[0]["recurring_rule_id"], rule["id"]);
+}
+
+#[tokio::test]
+async fn test_convert_two_subscriptions_of_one_product() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "dora@example.com").await;
+
+    for (description, price) in [("Rent", 900.0), ("Gym membership", 29.99)] {
+        for month in 1..=3 {
+            let resp = client
+                .post(format!("{}/transactions", base_url))
+                .bearer_auth(&token)
+                .json(&serde_json::json!({
+                    "product_name": "Bank transfer",
+                    "price": price,
+                    "transaction_type": "Expense",
+                    "description": format!("{description} #{month}"),
+                    "date": format!("2025-0{month}-03T08:00:00")
+                }))
+                .send()
+                .await
+                .unwrap();
+            assert!(resp.status().is_success());
+        }
+    }
+
+    let convert = |description: &str| {
+        let request = client
+            .post(format!("{}/subscriptions/1/convert", base_url))
+            .query(&[("description", description)])
+            .bearer_auth(&token);
+        async move {
+            let resp = request.send().await.unwrap();
+            let status = resp.status();
+            (status, resp.json::<serde_json::Value>().await.unwrap())
+        }
+    };
+    let (status, rent) = convert("rent").await;
+    assert!(status.is_success(), "{rent}");
+    assert_eq!(rent["amount"], 900.0);
+    let (status, gym) = convert("Gym membership #9").await;
+    assert!(status.is_success(), "{gym}");
+    assert_eq!(gym["amount"], 29.99);
+    assert_eq!(gym["product_id"], rent["product_id"]);
+
+    let (status, body) = convert("rent").await;
+    assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "A recurring rule with that cadence and description already exists for this product"
+    );
+}
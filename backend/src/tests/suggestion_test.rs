@@ -0,0 +1,140 @@
+// tests/suggestion_test.rs
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::suggestions::models::TrainingExample;
+use crate::domain::suggestions::services::{tokenize, Classifier};
+
+fn example(text: &str, category_id: Option<i32>, tag_ids: &[i32]) -> TrainingExample {
+    TrainingExample {
+        tokens: tokenize(text),
+        category_id,
+        tag_ids: tag_ids.to_vec(),
+    }
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(
+        tokenize("REWE Markt #1234, Bio-Milch 1L"),
+        vec!["rewe", "markt", "bio", "milch", "1l"]
+    );
+    assert!(tokenize("- 42 ").is_empty());
+}
+
+#[test]
+fn test_classifier() {
+    let classifier = Classifier::train(&[
+        example("Starbucks latte", Some(1), &[10]),
+        example("Starbucks espresso", Some(1), &[10]),
+        example("Corner cafe latte", Some(1), &[]),
+        example("Rewe groceries milk", Some(2), &[]),
+        example("Rewe bread", Some(2), &[11]),
+        example("Shell fuel", None, &[]),
+    ]);
+
+    let known = classifier.known_tokens(&tokenize("Starbucks Latte, large latte"));
+    assert_eq!(known, vec!["starbucks", "latte"]);
+    let categories = classifier.categories(&known);
+    assert_eq!(categories[0].0, 1);
+    assert!(categories[0].1 > 0.9);
+    let total: f64 = categories.iter().map(|(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+
+    let tags = classifier.tags(&known);
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].0, 10);
+
+    let known = classifier.known_tokens(&tokenize("REWE"));
+    assert_eq!(classifier.categories(&known)[0].0, 2);
+    assert!(classifier.known_tokens(&tokenize("unheard of")).is_empty());
+    assert!(classifier.categories(&[]).is_empty());
+}
+
+#[tokio::test]
+async fn test_suggest_endpoint() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "sara@example.com").await;
+
+    let post = |path: &'static str, body: serde_json::Value| {
+        client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    for name in ["Coffee", "Groceries"] {
+        let body = serde_json::json!({ "parent_category_id": null, "name": name });
+        assert!(post("/categories", body)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+    }
+    for (category_id, name) in [(1, "Starbucks"), (2, "Rewe")] {
+        let body = serde_json::json!({ "category_id": category_id, "name": name });
+        assert!(post("/products", body).await.unwrap().status().is_success());
+    }
+    for (name, description, tags) in [
+        ("Starbucks", "Latte to go", vec!["coffee"]),
+        ("Starbucks", "Flat white", vec!["coffee"]),
+        ("Rewe", "Weekly groceries", vec![]),
+        ("Rewe", "Milk and bread", vec![]),
+    ] {
+        let resp = post(
+            "/transactions",
+            serde_json::json!({
+                "product_name": name,
+                "price": 4.0,
+                "transaction_type": "Expense",
+                "description": description,
+                "date": "2025-03-01T08:00:00",
+                "tags": tags
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let suggest = |query: &'static str| {
+        let request = client
+            .get(format!("{}/transactions/suggest?{}", base_url, query))
+            .bearer_auth(&token);
+        async move { request.send().await.unwrap() }
+    };
+
+    let result = suggest("description=Iced%20latte%20(large)&limit=1")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(result["known_tokens"], serde_json::json!(["latte"]));
+    let categories = result["categories"].as_array().unwrap();
+    assert_eq!(categories.len(), 1);
+    assert_eq!(categories[0]["name"], "Coffee");
+    assert!(categories[0]["confidence"].as_f64().unwrap() > 0.5);
+
+    let result = suggest("product_name=REWE")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(result["categories"][0]["category_id"], 2);
+    assert!(result["tags"].as_array().unwrap().is_empty());
+
+    let result = suggest("product_name=Starbucks")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(result["tags"][0]["name"], "coffee");
+
+    let result = suggest("product_name=Bakery")
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert!(result["categories"].as_array().unwrap().is_empty());
+
+    assert!(suggest("description=%20").await.status().is_client_error());
+}
@@ -0,0 +1,214 @@
+// tests/two_factor_test.rs
+
+use chrono::Utc;
+use reqwest::StatusCode;
+
+use super::workflow_test::{sign_up_and_login, spawn_app};
+use crate::domain::two_factor::services::{generate_secret, matching_step, totp_for};
+
+#[test]
+fn test_matching_step() {
+    let secret = generate_secret();
+    assert_eq!(secret.len(), 32);
+    let totp = totp_for(&secret, "tess@example.com");
+    let now = 1_700_000_015;
+    let step = now / 30;
+
+    let code = totp.generate(now as u64);
+    assert_eq!(matching_step(&totp, &code, now, None), Some(step));
+    // A minute of drift either way is too much.
+    assert_eq!(matching_step(&totp, &code, now + 60, None), None);
+    assert_eq!(matching_step(&totp, &code, now - 60, None), None);
+    // One step of drift is fine.
+    assert_eq!(matching_step(&totp, &code, now + 30, None), Some(step));
+    // A code is not accepted twice.
+    assert_eq!(matching_step(&totp, &code, now, Some(step)), None);
+
+    let uri = totp.get_url();
+    assert!(uri.starts_with("otpauth://totp/Rusty%20Fin:tess%40example.com?secret="));
+    assert!(uri.contains(&secret));
+}
+
+#[tokio::test]
+async fn test_two_factor_flow() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "tess@example.com").await;
+
+    let post = |path: &'static str, token: &str, body: serde_json::Value| {
+        let request = client
+            .post(format!("{}{}", base_url, path))
+            .bearer_auth(token)
+            .json(&body);
+        async move {
+            let resp = request.send().await.unwrap();
+            let status = resp.status();
+            (status, resp.json::<serde_json::Value>().await.unwrap())
+        }
+    };
+    let login = || {
+        let request = client
+            .post(format!("{}/login", base_url))
+            .json(&serde_json::json!({
+                "email": "tess@example.com",
+                "password_hash": "secret123",
+                "device_label": "Phone"
+            }));
+        async move {
+            request
+                .send()
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        }
+    };
+    let login_2fa = |challenge: &serde_json::Value, code: &str| {
+        let request = client
+            .post(format!("{}/login/2fa", base_url))
+            .json(&serde_json::json!({
+                "challenge_token": challenge["challenge_token"],
+                "code": code
+            }));
+        async move {
+            let resp = request.send().await.unwrap();
+            let status = resp.status();
+            (status, resp.json::<serde_json::Value>().await.unwrap())
+        }
+    };
+    let other = login().await["token"].as_str().unwrap().to_string();
+
+    // Enrolling needs the password and only takes effect after a code.
+    let (status, _) = post(
+        "/users/me/2fa/enroll",
+        &token,
+        serde_json::json!({ "password": "wrong" }),
+    )
+    .await;
+    assert!(status.is_client_error());
+    let (_, enrollment) = post(
+        "/users/me/2fa/enroll",
+        &token,
+        serde_json::json!({ "password": "secret123" }),
+    )
+    .await;
+    let secret = enrollment["secret"].as_str().unwrap().to_string();
+    assert!(enrollment["otpauth_uri"]
+        .as_str()
+        .unwrap()
+        .contains(&format!("secret={secret}")));
+    assert!(login().await["token"].is_string());
+
+    let totp = totp_for(&secret, "tess@example.com");
+    let enrolled_at = Utc::now().timestamp();
+    let code_at = |offset: i64| totp.generate((enrolled_at + offset) as u64);
+    let (_, body) = post(
+        "/users/me/2fa/verify",
+        &token,
+        serde_json::json!({ "code": "12345" }),
+    )
+    .await;
+    assert_eq!(body["error"], "Invalid two-factor code");
+    let (status, body) = post(
+        "/users/me/2fa/verify",
+        &token,
+        serde_json::json!({ "code": code_at(0) }),
+    )
+    .await;
+    assert!(status.is_success());
+    let recovery_codes: Vec<String> = body["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(recovery_codes.len(), 10);
+    assert_eq!(recovery_codes[0].len(), 19);
+
+    // Other sessions were signed out; this one stays.
+    let status_with = |token: String| {
+        let request = client
+            .get(format!("{}/users/me", base_url))
+            .bearer_auth(token);
+        async move { request.send().await.unwrap() }
+    };
+    assert_eq!(status_with(other).await.status(), StatusCode::UNAUTHORIZED);
+    let account = status_with(token.clone())
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(account["two_factor_enabled"], true);
+
+    // Login now takes two steps.
+    let challenge = login().await;
+    assert_eq!(challenge["two_factor_required"], true);
+    assert!(challenge["token"].is_null());
+    let (_, body) = login_2fa(&challenge, "000000x").await;
+    assert_eq!(body["error"], "Invalid two-factor code");
+    // The code used for enrollment cannot be replayed.
+    let (status, _) = login_2fa(&challenge, &code_at(0)).await;
+    assert!(status.is_client_error());
+    let (status, tokens) = login_2fa(&challenge, &code_at(30)).await;
+    assert!(status.is_success());
+    let second = tokens["token"].as_str().unwrap().to_string();
+    assert!(status_with(second).await.status().is_success());
+    // A finished challenge is gone.
+    let (_, body) = login_2fa(&challenge, &recovery_codes[0]).await;
+    assert_eq!(body["error"], "Invalid or expired login challenge");
+
+    // Recovery codes work once, with any formatting.
+    let challenge = login().await;
+    let spaced = recovery_codes[1].replace('-', " ").to_uppercase();
+    let (status, _) = login_2fa(&challenge, &spaced).await;
+    assert!(status.is_success());
+    let challenge = login().await;
+    let (status, _) = login_2fa(&challenge, &recovery_codes[1]).await;
+    assert!(status.is_client_error());
+
+    // A challenge stops working after five wrong codes.
+    for _ in 0..4 {
+        login_2fa(&challenge, "aaaa-bbbb").await;
+    }
+    let (_, body) = login_2fa(&challenge, &recovery_codes[2]).await;
+    assert_eq!(body["error"], "Invalid or expired login challenge");
+    // Those wrong codes count against the account like wrong passwords, so a
+    // new challenge does not bring more guesses.
+    let body = login().await;
+    let error = body["error"].as_str().unwrap();
+    assert!(error.starts_with("Too many failed login attempts; try again in "));
+
+    // Disabling takes the password and a second factor.
+    let delete = |password: &str, code: &str| {
+        let request = client
+            .delete(format!("{}/users/me/2fa", base_url))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({ "password": password, "code": code }));
+        async move { request.send().await.unwrap() }
+    };
+    assert!(delete("wrong", &recovery_codes[2])
+        .await
+        .status()
+        .is_client_error());
+    assert!(delete("secret123", "not-a-code")
+        .await
+        .status()
+        .is_client_error());
+    assert!(delete("secret123", &recovery_codes[2])
+        .await
+        .status()
+        .is_success());
+    let account = status_with(token.clone())
+        .await
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(account["two_factor_enabled"], false);
+    let (_, body) = post(
+        "/users/me/2fa/recovery-codes",
+        &token,
+        serde_json::json!({ "code": code_at(0) }),
+    )
+    .await;
+    assert_eq!(body["error"], "Two-factor authentication is not enabled");
+}
@@ -1,47 +1,121 @@
 // tests/workflow_test.rs
 
-use diesel::Connection;
+use diesel::pg::PgConnection;
+use diesel::{Connection, RunQueryDsl};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
 use std::{env, sync::Arc};
 
-use reqwest::Client;
+use reqwest::{Client, Method, StatusCode};
+use serde_json::Value;
 use tokio::net::TcpListener;
 
+use crate::config::JwtConfig;
+use crate::db::init_pool;
+use crate::mailer::{Email, Mailer};
 use crate::{main_router, AppState};
-// ^ Make sure `main_router` and `AppState` are public in your src/lib.rs or src/main.rs
-//   If they're private, Rust won't let your tests import them.
 
-use diesel::sqlite::SqliteConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
-use tempfile::NamedTempFile;
 
 // 1. Embed your migrations
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
 /// 2. A helper to run migrations on the open connection
-pub fn run_migrations(conn: &mut SqliteConnection) {
+pub fn run_migrations(conn: &mut PgConnection) {
     conn.run_pending_migrations(MIGRATIONS)
         .expect("Failed to run migrations");
 }
 
-/// 3. We'll spawn the test server on an ephemeral port with an in-memory DB
-async fn spawn_app() -> (String, Client, NamedTempFile) {
-    // Create a temporary file for the database
-    let db_file = NamedTempFile::new().expect("Failed to create temp file");
-    let db_path = db_file.path().to_str().unwrap().to_string();
-    env::set_var("DATABASE_URL", &db_path);
+static DB_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-    // Build shared state
-    let shared_state = AppState {
-        db_url: env::var("DATABASE_URL").unwrap(),
-    };
+/// Creates a brand new, empty database next to the one in `DATABASE_URL`
+/// so every test starts from a clean schema with predictable ids.
+fn create_test_database() -> String {
+    dotenvy::dotenv().ok();
+    let base_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+    let (server_url, _) = base_url
+        .rsplit_once('/')
+        .expect("DATABASE_URL must include a database name");
+
+    let db_name = format!(
+        "rusty_fin_test_{}_{}",
+        std::process::id(),
+        DB_COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+
+    let mut admin = PgConnection::establish(&format!("{server_url}/postgres"))
+        .expect("Failed to connect to the postgres server");
+    diesel::sql_query(format!("DROP DATABASE IF EXISTS {db_name}"))
+        .execute(&mut admin)
+        .expect("Failed to drop old test database");
+    diesel::sql_query(format!("CREATE DATABASE {db_name}"))
+        .execute(&mut admin)
+        .expect("Failed to create test database");
+
+    format!("{server_url}/{db_name}")
+}
+
+/// Keeps every email in memory so tests can read them back.
+#[derive(Default)]
+pub struct MemoryMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+impl Mailer for MemoryMailer {
+    fn send(&self, email: &Email) -> Result<(), String> {
+        self.sent.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+/// The mailbox of every spawned app, by base url.
+static MAILBOXES: LazyLock<Mutex<HashMap<String, Arc<MemoryMailer>>>> =
+    LazyLock::new(Default::default);
+
+/// Every email the app at `base_url` sent so far, oldest first.
+pub fn sent_emails(base_url: &str) -> Vec<Email> {
+    let mailboxes = MAILBOXES.lock().unwrap();
+    let sent = mailboxes[base_url].sent.lock().unwrap();
+    sent.clone()
+}
+
+/// Reads the value of the `prefix` line, e.g. `"Reset token: "`, from the
+/// newest email to `to` that has one.
+pub fn token_from_email(base_url: &str, to: &str, prefix: &str) -> Option<String> {
+    sent_emails(base_url)
+        .iter()
+        .rev()
+        .filter(|email| email.to == to)
+        .find_map(|email| email.body.lines().find_map(|l| l.strip_prefix(prefix)))
+        .map(str::to_string)
+}
+
+/// 3. We'll spawn the test server on an ephemeral port with a fresh DB
+pub async fn spawn_app() -> (String, Client) {
+    spawn_app_with(|_| {}).await
+}
 
-    // Establish a brand new empty DB connection
-    let mut conn =
-        SqliteConnection::establish(&shared_state.db_url).expect("Failed to create in-memory DB");
+/// Like `spawn_app`, but `customize` can change the state first.
+pub async fn spawn_app_with(customize: impl FnOnce(&mut AppState)) -> (String, Client) {
+    let db_url = create_test_database();
 
     // Run migrations on that DB
+    let mut conn = PgConnection::establish(&db_url).expect("Failed to connect to test DB");
     run_migrations(&mut conn);
 
+    // Build shared state
+    let mailer = Arc::new(MemoryMailer::default());
+    let mut shared_state = AppState {
+        pool: init_pool(&db_url),
+        mailer: mailer.clone(),
+        rate_limiter: None,
+        trust_proxy_headers: false,
+        jwt: Arc::new(JwtConfig::hs256("test", "test-secret")),
+    };
+    customize(&mut shared_state);
+
     // Then build + spawn the actual Axum server
     let app = main_router(Arc::new(shared_state));
 
@@ -49,17 +123,124 @@ async fn spawn_app() -> (String, Client, NamedTempFile) {
     let addr = listener.local_addr().unwrap();
 
     tokio::spawn(async move {
-        axum::serve(listener, app).await.unwrap();
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
     });
 
-    let base_url = format!("http://{}", addr);
-    (base_url, Client::new(), db_file)
+    let base_url = format!("http://{}/rusty-fin/api", addr);
+    MAILBOXES.lock().unwrap().insert(base_url.clone(), mailer);
+    (base_url, Client::new())
+}
+
+/// Signs up and verifies `email` and returns a bearer token for it.
+pub async fn sign_up_and_login(base_url: &str, client: &Client, email: &str) -> String {
+    let resp = client
+        .post(format!("{}/users", base_url))
+        .json(&serde_json::json!({
+            "email": email,
+            "password_hash": "secret123"
+        }))
+        .send()
+        .await
+        .expect("Failed to sign up user");
+    assert!(resp.status().is_success());
+
+    let token = token_from_email(base_url, email, "Verification token: ")
+        .expect("No verification email sent");
+    let resp = client
+        .post(format!("{}/verify-email", base_url))
+        .json(&serde_json::json!({ "token": token }))
+        .send()
+        .await
+        .expect("Failed to verify email");
+    assert!(resp.status().is_success());
+
+    let resp = client
+        .post(format!("{}/login", base_url))
+        .json(&serde_json::json!({
+            "email": email,
+            "password_hash": "secret123"
+        }))
+        .send()
+        .await
+        .expect("Failed to login");
+    assert!(resp.status().is_success());
+
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    body["token"].as_str().unwrap().to_string()
+}
+
+/// Sends requests to one app, optionally in a given household.
+pub struct Api {
+    pub base_url: String,
+    pub client: Client,
+}
+
+impl Api {
+    /// Returns the status with the JSON body, or `null` without one.
+    pub async fn call(
+        &self,
+        method: Method,
+        path: &str,
+        token: &str,
+        household: Option<i64>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", self.base_url, path))
+            .bearer_auth(token);
+        if let Some(id) = household {
+            request = request.header("X-Household-Id", id.to_string());
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let resp = request.send().await.unwrap();
+        let status = resp.status();
+        (status, resp.json::<Value>().await.unwrap_or(Value::Null))
+    }
+
+    pub async fn get(
+        &self,
+        path: &str,
+        token: &str,
+        household: Option<i64>,
+    ) -> (StatusCode, Value) {
+        self.call(Method::GET, path, token, household, None).await
+    }
+
+    pub async fn post(
+        &self,
+        path: &str,
+        token: &str,
+        household: Option<i64>,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        self.call(Method::POST, path, token, household, Some(body))
+            .await
+    }
+
+    pub async fn put(
+        &self,
+        path: &str,
+        token: &str,
+        household: Option<i64>,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        self.call(Method::PUT, path, token, household, Some(body))
+            .await
+    }
 }
 
 /// 4. The integration test that exercises the entire workflow
 #[tokio::test]
 async fn test_full_workflow() {
-    let (base_url, client, _db_file) = spawn_app().await;
+    let (base_url, client) = spawn_app().await;
 
     // 1. Sign up user
     let resp = client
@@ -72,8 +253,11 @@ async fn test_full_workflow() {
         .await
         .expect("Failed to sign up user");
     assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "User signed up");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({ "id": 1, "email": "alice@example.com" })
+    );
 
     // 2. Try same email -> expect duplicate error
     let resp = client
@@ -85,9 +269,9 @@ async fn test_full_workflow() {
         .send()
         .await
         .expect("Failed to sign up user again");
-    assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "A user with that email already exists");
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "A user with that email already exists");
 
     // 3. Login -> now we expect a JSON string like: `{"token":"<JWT>"}`
     let resp = client
@@ -114,7 +298,6 @@ async fn test_full_workflow() {
         .post(format!("{}/categories", base_url))
         .bearer_auth(&token) // <--- set the token
         .json(&serde_json::json!({
-            "user_id": 1,
             "parent_category_id": null,
             "name": "Groceries"
         }))
@@ -122,15 +305,14 @@ async fn test_full_workflow() {
         .await
         .expect("Failed to create category");
     assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "Category created");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["category"]["name"], "Groceries");
 
     // 5. Create "Dairy" under Groceries -> again, set bearer auth
     let resp = client
         .post(format!("{}/categories", base_url))
         .bearer_auth(&token)
         .json(&serde_json::json!({
-            "user_id": 1,
             "parent_category_id": 1,
             "name": "Dairy"
         }))
@@ -138,23 +320,24 @@ async fn test_full_workflow() {
         .await
         .expect("Failed to create subcategory");
     assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "Category created");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["category"]["name"], "Dairy");
+    assert_eq!(body["parent"]["name"], "Groceries");
 
     // 6. Create product "Milk"
     let resp = client
         .post(format!("{}/products", base_url))
         .bearer_auth(&token)
         .json(&serde_json::json!({
-            "user_id": 1,
+            "category_id": 2,
             "name": "Milk"
         }))
         .send()
         .await
         .expect("Failed to create product");
     assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "Product created");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["product"]["name"], "Milk");
 
     // 7. Insert price
     let resp = client
@@ -162,35 +345,37 @@ async fn test_full_workflow() {
         .bearer_auth(&token)
         .json(&serde_json::json!({
             "product_id": 1,
-            "price": 299,
-            "created_at": "2025-01-08T12:00:00Z"
+            "price": 2.99,
+            "created_at": "2025-01-08T12:00:00"
         }))
         .send()
         .await
         .expect("Failed to create product price");
     assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "Product price created");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["product_price"]["price"], 2.99);
 
     // 8. Create transaction
     let resp = client
         .post(format!("{}/transactions", base_url))
         .bearer_auth(&token)
         .json(&serde_json::json!({
-            "user_id": 1,
             "product_id": 1,
-            "category_id": 2,
-            "transaction_type": "out",
-            "amount": 299,
+            "product_price_id": 1,
+            "transaction_type": "Expense",
             "description": "Bought milk at the store",
-            "date": "2025-01-08"
+            "date": "2025-01-08T12:00:00",
+            "tags": ["dairy"]
         }))
         .send()
         .await
         .expect("Failed to create transaction");
     assert!(resp.status().is_success());
-    let text = resp.text().await.unwrap();
-    assert_eq!(text, "Transaction created");
+    let body = resp.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(
+        body["tags"],
+        serde_json::json!([{ "id": 1, "name": "dairy" }])
+    );
 
     // 9. Fetch transactions
     let resp = client
@@ -209,14 +394,69 @@ async fn test_full_workflow() {
                 "id": 1,
                 "user_id": 1,
                 "product_id": 1,
-                "category_id": 2,
-                "transaction_type": "out",
-                "amount": 299,
+                "product_price_id": 1,
+                "transaction_type": "Expense",
                 "description": "Bought milk at the store",
-                "date": "2025-01-08"
+                "date": "2025-01-08T12:00:00",
+                "tags": [1]
             }
         ])
     );
 
     println!("Workflow test passed!");
 }
+
+#[tokio::test]
+async fn test_create_transaction_reuses_products_and_prices() {
+    let (base_url, client) = spawn_app().await;
+    let token = sign_up_and_login(&base_url, &client, "reuse@example.com").await;
+    let api = Api { base_url, client };
+    let coffee = |name: &str, price: f64, date: &str| {
+        serde_json::json!({
+            "product_name": name,
+            "price": price,
+            "transaction_type": "Expense",
+            "description": null,
+            "date": date,
+            "tags": null
+        })
+    };
+
+    // Prices are rounded to the nearest cent, not truncated.
+    let (_, first) = api
+        .post(
+            "/transactions",
+            &token,
+            None,
+            coffee(" Coffee ", 2.499, "2025-03-01T08:00:00"),
+        )
+        .await;
+    assert_eq!(first["product"]["name"], "Coffee");
+    assert_eq!(first["product_price"]["price"], 2.5);
+
+    // The same name finds the product, and the same amount on the same date
+    // shares its price row.
+    let (_, again) = api
+        .post(
+            "/transactions",
+            &token,
+            None,
+            coffee("Coffee", 2.5, "2025-03-01T08:00:00"),
+        )
+        .await;
+    assert_eq!(again["product"]["id"], first["product"]["id"]);
+    assert_eq!(again["product_price"]["id"], first["product_price"]["id"]);
+    assert_ne!(again["transaction"]["id"], first["transaction"]["id"]);
+
+    // Another day is another price row for the same product.
+    let (_, later) = api
+        .post(
+            "/transactions",
+            &token,
+            None,
+            coffee("Coffee", 2.5, "2025-03-02T08:00:00"),
+        )
+        .await;
+    assert_eq!(later["product"]["id"], first["product"]["id"]);
+    assert_ne!(later["product_price"]["id"], first["product_price"]["id"]);
+}